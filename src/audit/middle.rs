@@ -0,0 +1,239 @@
+use crate::audit::model::{
+    AuditRecordDo, AuditSummary, AuditUtils, AUDIT_SOURCE_CONSOLE, AUDIT_SOURCE_OPENAPI,
+};
+use crate::audit::AuditManagerReq;
+use crate::common::appdata::AppShareData;
+use crate::common::model::{TokenSession, UserSession};
+use crate::now_millis_i64;
+use actix_http::HttpMessage;
+use actix_web::body::{BodySize, BoxBody, MessageBody};
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::header::{CONTENT_LENGTH, CONTENT_TYPE};
+use actix_web::{dev, web, Error};
+use futures_util::future::LocalBoxFuture;
+use std::collections::BTreeMap;
+use std::future::{ready, Ready};
+use std::sync::Arc;
+
+/// 超过该长度的请求内容不解析
+const MAX_AUDIT_BODY_SIZE: usize = 64 * 1024;
+
+lazy_static::lazy_static! {
+    /// 不属于数据变更的写请求
+    static ref IGNORE_PATHS: Vec<&'static str> = vec![
+        "/nacos/v1/cs/configs/listener",
+        "/nacos/v1/ns/instance/beat",
+        "/rnacos/api/console/login/logout",
+        "/rnacos/api/console/v2/login/logout",
+        "/rnacos/api/console/v2/user/reset_password",
+    ];
+    static ref IGNORE_PATH_PREFIXES: Vec<&'static str> = vec![
+        "/nacos/v1/raft/",
+        "/rnacos/mcp/",
+    ];
+}
+
+///
+/// 记录控制台与openapi写请求的审计日志;需要在鉴权中间件内层,以便获取登录会话
+#[derive(Clone)]
+pub struct AuditLog {
+    app_share_data: Arc<AppShareData>,
+}
+
+impl AuditLog {
+    pub fn new(app_share_data: Arc<AppShareData>) -> Self {
+        Self { app_share_data }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for AuditLog
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = AuditLogMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(AuditLogMiddleware {
+            service: Arc::new(service),
+            app_share_data: self.app_share_data.clone(),
+        }))
+    }
+}
+
+#[derive(Clone)]
+pub struct AuditLogMiddleware<S> {
+    service: Arc<S>,
+    app_share_data: Arc<AppShareData>,
+}
+
+impl<S, B> Service<ServiceRequest> for AuditLogMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    dev::forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+        if !self.app_share_data.sys_config.audit_enable || !is_audit_request(&req) {
+            return Box::pin(async move { Ok(service.call(req).await?.map_into_boxed_body()) });
+        }
+        let app_share_data = self.app_share_data.clone();
+        Box::pin(async move {
+            let mut request = req;
+            let time = now_millis_i64();
+            let mut params = BTreeMap::new();
+            if let Ok(items) =
+                serde_urlencoded::from_str::<Vec<(String, String)>>(request.query_string())
+            {
+                params.extend(items);
+            }
+            if can_read_body(&request) {
+                if let Ok(payload) = request.extract::<web::Payload>().await {
+                    if let Ok(body) = payload.to_bytes().await {
+                        AuditUtils::parse_body_params(&body, &mut params);
+                        request.set_payload(bytes_to_payload(body));
+                    }
+                }
+            }
+            let path = request.path().to_owned();
+            let source = if path.starts_with("/rnacos/api/console") || path.contains("/console/") {
+                AUDIT_SOURCE_CONSOLE
+            } else {
+                AUDIT_SOURCE_OPENAPI
+            };
+            let actor = get_actor(&request);
+            let source_ip = request
+                .connection_info()
+                .realip_remote_addr()
+                .unwrap_or_default()
+                .to_owned();
+            let action = format!("{} {}", request.method(), &path);
+            let res = service.call(request).await?;
+            let (res, result_success) = read_result_success(res).await;
+            let summary = res
+                .request()
+                .extensions()
+                .get::<AuditSummary>()
+                .cloned()
+                .unwrap_or_default();
+            let record = AuditRecordDo {
+                time,
+                actor,
+                source_ip,
+                source: source.to_owned(),
+                action,
+                resource: AuditUtils::build_resource(&params),
+                before: summary.before.unwrap_or_default(),
+                after: summary
+                    .after
+                    .unwrap_or_else(|| AuditUtils::build_params_summary(&params)),
+                success: summary
+                    .success
+                    .or(result_success)
+                    .unwrap_or_else(|| res.status().as_u16() < 400),
+                ..Default::default()
+            };
+            app_share_data
+                .audit_manager
+                .do_send(AuditManagerReq::Record(record));
+            Ok(res)
+        })
+    }
+}
+
+fn is_audit_request(req: &ServiceRequest) -> bool {
+    let method = req.method().as_str();
+    if method == "GET" || method == "HEAD" || method == "OPTIONS" {
+        return false;
+    }
+    let path = req.path();
+    (path.starts_with("/nacos/") || path.starts_with("/rnacos/"))
+        && !path.ends_with("/login")
+        && !path.contains("/login/")
+        && !IGNORE_PATHS.contains(&path)
+        && !IGNORE_PATH_PREFIXES.iter().any(|e| path.starts_with(e))
+}
+
+///
+/// 只读取长度已知且不超过限制的json与表单请求内容
+fn can_read_body(req: &ServiceRequest) -> bool {
+    let content_length = req
+        .headers()
+        .get(CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(usize::MAX);
+    if content_length > MAX_AUDIT_BODY_SIZE {
+        return false;
+    }
+    let content_type = req
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+    content_type.contains("json") || content_type.contains("x-www-form-urlencoded")
+}
+
+///
+/// 读取长度已知且不超过限制的json响应内容,解析ApiResult中的处理结果后重新设置响应内容
+async fn read_result_success<B>(res: ServiceResponse<B>) -> (ServiceResponse<BoxBody>, Option<bool>)
+where
+    B: MessageBody + 'static,
+{
+    let is_json = res
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.contains("json"))
+        .unwrap_or_default();
+    let can_read = match res.response().body().size() {
+        BodySize::Sized(size) => size as usize <= MAX_AUDIT_BODY_SIZE,
+        _ => false,
+    };
+    if !is_json || !can_read {
+        return (res.map_into_boxed_body(), None);
+    }
+    let (req, res) = res.into_parts();
+    let (res, body) = res.into_parts();
+    match actix_web::body::to_bytes(body).await {
+        Ok(bytes) => {
+            let success = AuditUtils::parse_result_success(&bytes);
+            let res = res.set_body(BoxBody::new(bytes));
+            (ServiceResponse::new(req, res), success)
+        }
+        Err(_) => {
+            log::warn!("audit read response body error,path:{}", req.path());
+            let res = res.set_body(BoxBody::new(web::Bytes::new()));
+            (ServiceResponse::new(req, res), None)
+        }
+    }
+}
+
+fn get_actor(req: &ServiceRequest) -> String {
+    let extensions = req.extensions();
+    if let Some(session) = extensions.get::<Arc<UserSession>>() {
+        session.username.as_ref().to_owned()
+    } else if let Some(session) = extensions.get::<Arc<TokenSession>>() {
+        session.username.as_ref().to_owned()
+    } else {
+        String::new()
+    }
+}
+
+fn bytes_to_payload(buf: web::Bytes) -> dev::Payload {
+    let (_, mut pl) = actix_http::h1::Payload::create(true);
+    pl.unread_data(buf);
+    dev::Payload::from(pl)
+}
@@ -0,0 +1,267 @@
+use crate::audit::model::{AuditQueryParam, AuditRecordDo, AuditRecordDto};
+use crate::common::constant::AUDIT_LOG_TREE_NAME;
+use crate::common::AppSysConfig;
+use crate::now_millis_i64;
+use crate::raft::cluster::model::RouteAddr;
+use crate::raft::cluster::route::RaftAddrRouter;
+use crate::raft::db::route::TableRoute;
+use crate::raft::db::table::{
+    TableManager, TableManagerQueryReq, TableManagerReq, TableManagerResult,
+};
+use actix::prelude::*;
+use bean_factory::{bean, BeanFactory, FactoryData, Inject};
+use std::sync::Arc;
+use std::time::Duration;
+
+pub mod middle;
+pub mod model;
+
+/// 清理与查询时每批读取的审计日志条数
+const CLEAR_BATCH_SIZE: i64 = 1000;
+const QUERY_BATCH_SIZE: i64 = 1000;
+const CLEAR_INTERVAL: Duration = Duration::from_secs(600);
+
+///
+/// 审计日志管理;日志写入raft表,由主节点按保留天数定时清理
+#[bean(inject)]
+pub struct AuditManager {
+    enable: bool,
+    node_id: u64,
+    retention_days: u32,
+    seq: u32,
+    raft_table_route: Option<Arc<TableRoute>>,
+    table_manager: Option<Addr<TableManager>>,
+    raft_addr_route: Option<Arc<RaftAddrRouter>>,
+}
+
+impl AuditManager {
+    pub fn new(sys_config: &AppSysConfig) -> Self {
+        Self {
+            enable: sys_config.audit_enable,
+            node_id: sys_config.raft_node_id,
+            retention_days: sys_config.audit_retention_days,
+            seq: 0,
+            raft_table_route: None,
+            table_manager: None,
+            raft_addr_route: None,
+        }
+    }
+
+    fn record(&mut self, mut record: AuditRecordDo) {
+        let raft_table_route = if let Some(raft_table_route) = self.raft_table_route.clone() {
+            raft_table_route
+        } else {
+            return;
+        };
+        self.seq = (self.seq + 1) % 1_000_000;
+        if record.time == 0 {
+            record.time = now_millis_i64();
+        }
+        record.id = AuditRecordDo::build_id(record.time, self.node_id, self.seq);
+        let req = TableManagerReq::Set {
+            table_name: AUDIT_LOG_TREE_NAME.clone(),
+            key: record.id.as_bytes().to_owned(),
+            value: record.to_bytes(),
+            last_seq_id: None,
+        };
+        tokio::spawn(async move {
+            if let Err(err) = raft_table_route.request(req).await {
+                log::warn!("write audit log error: {}", err);
+            }
+        });
+    }
+
+    ///
+    /// 表key按时间有序,按时间范围从新到旧分批读取,分页填满后即停止;
+    /// 有过滤条件时返回的总数只保证能翻到下一页
+    async fn query_page(
+        table_manager: Option<Addr<TableManager>>,
+        param: AuditQueryParam,
+    ) -> anyhow::Result<(usize, Vec<AuditRecordDto>)> {
+        let table_manager = if let Some(table_manager) = table_manager {
+            table_manager
+        } else {
+            return Ok((0, vec![]));
+        };
+        let (limit, offset) = param.get_limit_info();
+        let start_key = param
+            .start_time
+            .map(|v| AuditRecordDo::build_time_bound(v).into_bytes());
+        let mut end_key = param
+            .end_time
+            .map(|v| AuditRecordDo::build_time_bound(v + 1).into_bytes());
+        let mut matched = 0;
+        let mut list = vec![];
+        let mut has_more = false;
+        let mut table_size = 0;
+        loop {
+            let query_req = TableManagerQueryReq::QueryRangeList {
+                table_name: AUDIT_LOG_TREE_NAME.clone(),
+                start_key: start_key.clone(),
+                end_key: end_key.clone(),
+                limit: Some(QUERY_BATCH_SIZE),
+                is_rev: true,
+            };
+            let items = match table_manager.send(query_req).await?? {
+                TableManagerResult::PageListResult(size, items) => {
+                    table_size = size;
+                    items
+                }
+                _ => break,
+            };
+            let batch_len = items.len();
+            for (key, v) in items {
+                end_key = Some(key);
+                let record = AuditRecordDo::from_bytes(&v)?;
+                if !param.is_match(&record) {
+                    continue;
+                }
+                if list.len() >= limit {
+                    has_more = true;
+                    break;
+                }
+                if matched >= offset {
+                    list.push(record.into());
+                }
+                matched += 1;
+            }
+            if has_more || batch_len < QUERY_BATCH_SIZE as usize {
+                break;
+            }
+        }
+        let total = if param.is_empty_filter() {
+            table_size
+        } else if has_more {
+            matched + 1
+        } else {
+            matched
+        };
+        Ok((total, list))
+    }
+
+    ///
+    /// 主节点清理超过保留天数的审计日志
+    async fn clear_expired(
+        raft_addr_route: Option<Arc<RaftAddrRouter>>,
+        raft_table_route: Option<Arc<TableRoute>>,
+        table_manager: Option<Addr<TableManager>>,
+        retention_days: u32,
+    ) -> anyhow::Result<()> {
+        let (raft_addr_route, raft_table_route, table_manager) =
+            match (raft_addr_route, raft_table_route, table_manager) {
+                (Some(a), Some(b), Some(c)) => (a, b, c),
+                _ => return Ok(()),
+            };
+        if !matches!(raft_addr_route.get_route_addr().await?, RouteAddr::Local) {
+            return Ok(());
+        }
+        let bound = AuditRecordDo::build_time_bound(
+            now_millis_i64() - retention_days as i64 * 24 * 3600 * 1000,
+        );
+        // 分批删除,直到没有早于保留时间的记录
+        loop {
+            let query_req = TableManagerQueryReq::QueryRangeList {
+                table_name: AUDIT_LOG_TREE_NAME.clone(),
+                start_key: None,
+                end_key: Some(bound.as_bytes().to_owned()),
+                limit: Some(CLEAR_BATCH_SIZE),
+                is_rev: false,
+            };
+            let items = match table_manager.send(query_req).await?? {
+                TableManagerResult::PageListResult(_, items) if !items.is_empty() => items,
+                _ => break,
+            };
+            for (key, _) in items {
+                let req = TableManagerReq::Remove {
+                    table_name: AUDIT_LOG_TREE_NAME.clone(),
+                    key,
+                };
+                raft_table_route.request(req).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Actor for AuditManager {
+    type Context = Context<Self>;
+
+    fn started(&mut self, _ctx: &mut Self::Context) {
+        log::info!("AuditManager started")
+    }
+}
+
+impl Inject for AuditManager {
+    type Context = Context<Self>;
+
+    fn inject(
+        &mut self,
+        factory_data: FactoryData,
+        _factory: BeanFactory,
+        ctx: &mut Self::Context,
+    ) {
+        self.raft_table_route = factory_data.get_bean();
+        self.table_manager = factory_data.get_actor();
+        self.raft_addr_route = factory_data.get_bean();
+        if self.retention_days == 0 {
+            return;
+        }
+        ctx.run_interval(CLEAR_INTERVAL, |act, ctx| {
+            let raft_addr_route = act.raft_addr_route.clone();
+            let raft_table_route = act.raft_table_route.clone();
+            let table_manager = act.table_manager.clone();
+            let retention_days = act.retention_days;
+            async move {
+                if let Err(err) = Self::clear_expired(
+                    raft_addr_route,
+                    raft_table_route,
+                    table_manager,
+                    retention_days,
+                )
+                .await
+                {
+                    log::warn!("clear expired audit log error: {}", err);
+                }
+            }
+            .into_actor(act)
+            .map(|_, _, _| {})
+            .spawn(ctx);
+        });
+    }
+}
+
+#[derive(Message)]
+#[rtype(result = "anyhow::Result<AuditManagerResult>")]
+pub enum AuditManagerReq {
+    Record(AuditRecordDo),
+    QueryPage(AuditQueryParam),
+}
+
+pub enum AuditManagerResult {
+    None,
+    Page(usize, Vec<AuditRecordDto>),
+}
+
+impl Handler<AuditManagerReq> for AuditManager {
+    type Result = ResponseActFuture<Self, anyhow::Result<AuditManagerResult>>;
+
+    fn handle(&mut self, msg: AuditManagerReq, _ctx: &mut Self::Context) -> Self::Result {
+        let table_manager = self.table_manager.clone();
+        let param = match msg {
+            AuditManagerReq::Record(record) => {
+                if self.enable {
+                    self.record(record);
+                }
+                return Box::pin(async { Ok(AuditManagerResult::None) }.into_actor(self));
+            }
+            AuditManagerReq::QueryPage(param) => param,
+        };
+        let fut = async move {
+            let (total, list) = Self::query_page(table_manager, param).await?;
+            Ok(AuditManagerResult::Page(total, list))
+        }
+        .into_actor(self)
+        .map(|r, _act, _ctx| r);
+        Box::pin(fut)
+    }
+}
@@ -0,0 +1,367 @@
+use crate::utils::get_md5;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+pub const AUDIT_SOURCE_CONSOLE: &str = "CONSOLE";
+pub const AUDIT_SOURCE_OPENAPI: &str = "OPENAPI";
+pub const AUDIT_SOURCE_GRPC: &str = "GRPC";
+
+/// 摘要最大长度
+const SUMMARY_MAX_LEN: usize = 1024;
+/// 参数值最大长度,超出时只记录长度
+const PARAM_VALUE_MAX_LEN: usize = 128;
+/// 不记录原值的参数
+const MASK_PARAMS: [&str; 7] = [
+    "password",
+    "oldPassword",
+    "newPassword",
+    "accessToken",
+    "clientSecret",
+    "key",
+    "encryptedDataKey",
+];
+/// 配置内容与灰度规则等载荷只记录md5与长度
+const DIGEST_PARAMS: [&str; 4] = ["content", "betaIps", "clientIps", "labels"];
+const NAMESPACE_PARAMS: [&str; 4] = ["namespaceId", "namespace", "tenant", "namespace_id"];
+const GROUP_PARAMS: [&str; 2] = ["group", "groupName"];
+const NAME_PARAMS: [&str; 7] = [
+    "dataId",
+    "serviceName",
+    "username",
+    "name",
+    "keyId",
+    "id",
+    "ip",
+];
+
+///
+/// 变更操作审计记录
+#[derive(Clone, PartialEq, prost::Message, Serialize, Deserialize)]
+pub struct AuditRecordDo {
+    #[prost(string, tag = "1")]
+    pub id: String,
+    /// 操作时间(毫秒)
+    #[prost(int64, tag = "2")]
+    pub time: i64,
+    #[prost(string, tag = "3")]
+    pub actor: String,
+    #[prost(string, tag = "4")]
+    pub source_ip: String,
+    /// 请求来源: CONSOLE,OPENAPI,GRPC
+    #[prost(string, tag = "5")]
+    pub source: String,
+    #[prost(string, tag = "6")]
+    pub action: String,
+    #[prost(string, tag = "7")]
+    pub resource: String,
+    #[prost(string, tag = "8")]
+    pub before: String,
+    #[prost(string, tag = "9")]
+    pub after: String,
+    #[prost(bool, tag = "10")]
+    pub success: bool,
+}
+
+impl AuditRecordDo {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut v = Vec::new();
+        prost::Message::encode(self, &mut v).unwrap_or_default();
+        v
+    }
+
+    pub fn from_bytes(v: &[u8]) -> anyhow::Result<Self> {
+        Ok(prost::Message::decode(v)?)
+    }
+
+    ///
+    /// 表中的key按时间排序: {毫秒时间}-{节点id}-{序号}
+    pub fn build_id(time: i64, node_id: u64, seq: u32) -> String {
+        format!("{:016}-{:04}-{:06}", time, node_id, seq)
+    }
+
+    ///
+    /// 生成指定时间之前的key上界,用于按时间清理
+    pub fn build_time_bound(time: i64) -> String {
+        format!("{:016}", time)
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditRecordDto {
+    pub id: String,
+    pub time: i64,
+    pub actor: String,
+    pub source_ip: String,
+    pub source: String,
+    pub action: String,
+    pub resource: String,
+    pub before: String,
+    pub after: String,
+    pub success: bool,
+}
+
+impl From<AuditRecordDo> for AuditRecordDto {
+    fn from(value: AuditRecordDo) -> Self {
+        Self {
+            id: value.id,
+            time: value.time,
+            actor: value.actor,
+            source_ip: value.source_ip,
+            source: value.source,
+            action: value.action,
+            resource: value.resource,
+            before: value.before,
+            after: value.after,
+            success: value.success,
+        }
+    }
+}
+
+///
+/// 接口可在请求扩展中设置变更前后摘要与处理结果,审计中间件记录时优先使用
+#[derive(Debug, Clone, Default)]
+pub struct AuditSummary {
+    pub before: Option<String>,
+    pub after: Option<String>,
+    pub success: Option<bool>,
+}
+
+impl AuditSummary {
+    ///
+    /// 变更前的内容按请求参数摘要的格式记录,敏感与过长的内容同样不记录原值
+    pub fn new_before(params: &BTreeMap<String, String>) -> Self {
+        Self {
+            before: Some(AuditUtils::build_params_summary(params)),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditQueryParam {
+    pub actor: Option<Arc<String>>,
+    pub action: Option<String>,
+    pub resource: Option<String>,
+    pub source: Option<String>,
+    pub source_ip: Option<String>,
+    pub start_time: Option<i64>,
+    pub end_time: Option<i64>,
+    pub page_no: Option<usize>,
+    pub page_size: Option<usize>,
+}
+
+impl AuditQueryParam {
+    pub fn get_limit_info(&self) -> (usize, usize) {
+        let limit = self.page_size.unwrap_or(20);
+        let offset = (self.page_no.unwrap_or(1).max(1) - 1) * limit;
+        (limit, offset)
+    }
+
+    pub fn is_empty_filter(&self) -> bool {
+        [
+            self.actor.as_ref().map(|e| e.as_str()),
+            self.action.as_deref(),
+            self.resource.as_deref(),
+            self.source.as_deref(),
+            self.source_ip.as_deref(),
+        ]
+        .iter()
+        .all(|v| v.is_none_or(|v| v.is_empty()))
+            && self.start_time.is_none()
+            && self.end_time.is_none()
+    }
+
+    pub fn is_match(&self, record: &AuditRecordDo) -> bool {
+        Self::match_eq(&self.actor.as_ref().map(|e| e.as_str()), &record.actor)
+            && Self::match_eq(&self.source.as_deref(), &record.source)
+            && Self::match_like(&self.action, &record.action)
+            && Self::match_like(&self.resource, &record.resource)
+            && Self::match_like(&self.source_ip, &record.source_ip)
+            && self.start_time.is_none_or(|v| record.time >= v)
+            && self.end_time.is_none_or(|v| record.time <= v)
+    }
+
+    fn match_eq(param: &Option<&str>, value: &str) -> bool {
+        match param {
+            Some(v) if !v.is_empty() => *v == value,
+            _ => true,
+        }
+    }
+
+    fn match_like(param: &Option<String>, value: &str) -> bool {
+        match param {
+            Some(v) if !v.is_empty() => value.contains(v.as_str()),
+            _ => true,
+        }
+    }
+}
+
+pub struct AuditUtils;
+
+impl AuditUtils {
+    ///
+    /// 请求参数摘要;敏感参数不记录原值,过长的参数值只记录长度
+    pub fn build_params_summary(params: &BTreeMap<String, String>) -> String {
+        let mut items = Vec::with_capacity(params.len());
+        for (k, v) in params {
+            if MASK_PARAMS.contains(&k.as_str()) {
+                items.push(format!("{}=***", k));
+            } else if DIGEST_PARAMS.contains(&k.as_str()) {
+                items.push(format!("{}=<md5:{},{} bytes>", k, get_md5(v), v.len()));
+            } else if v.len() > PARAM_VALUE_MAX_LEN {
+                items.push(format!("{}=<{} bytes>", k, v.len()));
+            } else {
+                items.push(format!("{}={}", k, v));
+            }
+        }
+        Self::truncate(items.join("&"))
+    }
+
+    ///
+    /// 从请求参数中提取操作对象: 命名空间/分组/名称
+    pub fn build_resource(params: &BTreeMap<String, String>) -> String {
+        let mut items = vec![];
+        for keys in [&NAMESPACE_PARAMS[..], &GROUP_PARAMS[..], &NAME_PARAMS[..]] {
+            if let Some(v) = keys
+                .iter()
+                .filter_map(|k| params.get(*k))
+                .find(|v| !v.is_empty())
+            {
+                items.push(v.as_str());
+            }
+        }
+        items.join("/")
+    }
+
+    pub fn truncate(mut value: String) -> String {
+        if value.len() > SUMMARY_MAX_LEN {
+            let mut end = SUMMARY_MAX_LEN;
+            while !value.is_char_boundary(end) {
+                end -= 1;
+            }
+            value.truncate(end);
+            value.push_str("...");
+        }
+        value
+    }
+
+    ///
+    /// 从响应内容中解析处理结果;控制台接口出错时http状态仍为200,需按ApiResult判断
+    /// 支持控制台ApiResult的success字段与openapi v2 ApiResult的code字段(0为成功)
+    pub fn parse_result_success(body: &[u8]) -> Option<bool> {
+        if let Ok(serde_json::Value::Object(map)) = serde_json::from_slice(body) {
+            if let Some(success) = map.get("success").and_then(|v| v.as_bool()) {
+                return Some(success);
+            }
+            if map.contains_key("message") && map.contains_key("data") {
+                if let Some(code) = map.get("code").and_then(|v| v.as_i64()) {
+                    return Some(code == 0);
+                }
+            }
+        }
+        None
+    }
+
+    ///
+    /// 解析json对象或表单格式的请求内容,json中的对象与数组按字符串记录
+    pub fn parse_body_params(body: &[u8], params: &mut BTreeMap<String, String>) {
+        if body.is_empty() {
+            return;
+        }
+        if let Ok(serde_json::Value::Object(map)) = serde_json::from_slice(body) {
+            for (k, v) in map {
+                let v = match v {
+                    serde_json::Value::String(s) => s,
+                    serde_json::Value::Null => continue,
+                    other => other.to_string(),
+                };
+                params.insert(k, v);
+            }
+        } else if let Ok(items) = serde_urlencoded::from_bytes::<Vec<(String, String)>>(body) {
+            params.extend(items);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn params_summary_and_resource() {
+        let mut params = BTreeMap::new();
+        AuditUtils::parse_body_params(
+            br#"{"tenant":"dev","group":"G1","dataId":"app.yaml","content":"a","password":"x"}"#,
+            &mut params,
+        );
+        assert_eq!(AuditUtils::build_resource(&params), "dev/G1/app.yaml");
+        assert_eq!(
+            AuditUtils::build_params_summary(&params),
+            format!(
+                "content=<md5:{},1 bytes>&dataId=app.yaml&group=G1&password=***&tenant=dev",
+                get_md5("a")
+            )
+        );
+        let mut params = BTreeMap::new();
+        AuditUtils::parse_body_params(b"serviceName=s1&ip=1.1.1.1", &mut params);
+        assert_eq!(AuditUtils::build_resource(&params), "s1");
+        params.insert("content".to_owned(), "x".repeat(200));
+        params.insert("betaIps".to_owned(), "1.1.1.1".to_owned());
+        params.insert("remark".to_owned(), "x".repeat(200));
+        let summary = AuditUtils::build_params_summary(&params);
+        assert!(summary.contains(",200 bytes>&"));
+        assert!(summary.contains(&format!("betaIps=<md5:{},7 bytes>", get_md5("1.1.1.1"))));
+        assert!(summary.contains("remark=<200 bytes>"));
+    }
+
+    #[test]
+    fn parse_result_success() {
+        assert_eq!(
+            AuditUtils::parse_result_success(br#"{"success":false,"code":"SYSTEM_ERROR"}"#),
+            Some(false)
+        );
+        assert_eq!(
+            AuditUtils::parse_result_success(br#"{"success":true,"data":true}"#),
+            Some(true)
+        );
+        assert_eq!(
+            AuditUtils::parse_result_success(br#"{"code":0,"message":"success","data":true}"#),
+            Some(true)
+        );
+        assert_eq!(
+            AuditUtils::parse_result_success(
+                br#"{"code":20004,"message":"resource not found","data":null}"#
+            ),
+            Some(false)
+        );
+        assert_eq!(AuditUtils::parse_result_success(b"true"), None);
+        assert_eq!(AuditUtils::parse_result_success(br#"{"code":0}"#), None);
+    }
+
+    #[test]
+    fn query_match() {
+        let record = AuditRecordDo {
+            id: AuditRecordDo::build_id(1000, 1, 1),
+            time: 1000,
+            actor: "admin".to_owned(),
+            source: AUDIT_SOURCE_CONSOLE.to_owned(),
+            action: "POST /rnacos/api/console/v2/user/update".to_owned(),
+            resource: "u1".to_owned(),
+            ..Default::default()
+        };
+        assert_eq!(record.id, "0000000000001000-0001-000001");
+        assert!(record.id.as_str() < AuditRecordDo::build_time_bound(1001).as_str());
+        let mut param = AuditQueryParam {
+            actor: Some(Arc::new("admin".to_owned())),
+            action: Some("user".to_owned()),
+            start_time: Some(900),
+            ..Default::default()
+        };
+        assert!(param.is_match(&record));
+        param.end_time = Some(999);
+        assert!(!param.is_match(&record));
+    }
+}
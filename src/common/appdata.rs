@@ -1,5 +1,9 @@
+use crate::audit::AuditManager;
 use crate::cache::core::DirectCacheManager;
 use crate::common::AppSysConfig;
+use crate::config::config_cipher::ConfigCipher;
+use crate::config::config_validator::ConfigValidatorManager;
+use crate::config::config_webhook::ConfigWebhookNotifyActor;
 use crate::config::core::ConfigActor;
 use crate::grpc::bistream_manage::BiStreamManage;
 use crate::health::core::HealthManager;
@@ -11,6 +15,7 @@ use crate::namespace::NamespaceActor;
 use crate::naming::cluster::node_manage::{InnerNodeManage, NodeManage};
 use crate::naming::cluster::route::NamingRoute;
 use crate::naming::core::NamingActor;
+use crate::naming::naming_sse::NamingSseManage;
 use crate::oauth2::core::OAuth2Manager;
 use crate::raft::cache::route::CacheRoute;
 use crate::raft::cache::CacheManager;
@@ -38,6 +43,9 @@ pub struct AppShareData {
     pub raft_store: Arc<FileStore>,
     pub sys_config: Arc<AppSysConfig>,
     pub config_route: Arc<ConfigRoute>,
+    pub config_validator: Arc<ConfigValidatorManager>,
+    pub config_cipher: Arc<ConfigCipher>,
+    pub config_webhook_notify: Addr<ConfigWebhookNotifyActor>,
     pub cluster_sender: Arc<RaftClusterRequestSender>,
     pub naming_route: Arc<NamingRoute>,
     pub naming_inner_node_manage: Addr<InnerNodeManage>,
@@ -51,6 +59,7 @@ pub struct AppShareData {
     pub direct_cache_manager: Addr<DirectCacheManager>,
     pub timezone_offset: Arc<FixedOffset>,
     pub metrics_manager: Addr<MetricsManager>,
+    pub audit_manager: Addr<AuditManager>,
     pub namespace_addr: Addr<NamespaceActor>,
     pub raft_request_route: Arc<RaftRequestRoute>,
     pub transfer_writer_manager: Addr<TransferWriterManager>,
@@ -62,5 +71,6 @@ pub struct AppShareData {
     pub sequence_manager: Addr<SequenceManager>,
     pub mcp_manager: Addr<McpManager>,
     pub sse_stream_manager: Addr<SseStreamManager>,
+    pub naming_sse_manage: Addr<NamingSseManage>,
     pub common_client: reqwest::Client,
 }
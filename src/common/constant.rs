@@ -21,8 +21,18 @@ pub const GRPC_HEAD_KEY_SUB_NAME: &str = "sub_name";
 
 lazy_static::lazy_static! {
     pub static ref CONFIG_TREE_NAME: Arc<String> =  Arc::new("T_CONFIG".to_string());
+    pub static ref CONFIG_WEBHOOK_TREE_NAME: Arc<String> =  Arc::new("T_CONFIG_WEBHOOK".to_string());
+    /// webhook推送日志,只保留最近的记录
+    pub static ref CONFIG_WEBHOOK_LOG_TREE_NAME: Arc<String> =  Arc::new("T_CONFIG_WEBHOOK_LOG".to_string());
     pub static ref SEQUENCE_TREE_NAME: Arc<String> =  Arc::new("T_SEQUENCE".to_string());
     pub static ref USER_TREE_NAME: Arc<String> =  Arc::new("T_USER".to_string());
+    pub static ref ROLE_TREE_NAME: Arc<String> =  Arc::new("T_ROLE".to_string());
+    pub static ref API_KEY_TREE_NAME: Arc<String> =  Arc::new("T_API_KEY".to_string());
+    /// API key最近使用时间,与凭证记录分开存储
+    pub static ref API_KEY_USAGE_TREE_NAME: Arc<String> =  Arc::new("T_API_KEY_USAGE".to_string());
+    /// 用户登录失败次数、锁定时间与TOTP时间步,与用户记录分开存储
+    pub static ref USER_LOGIN_STATE_TREE_NAME: Arc<String> =  Arc::new("T_USER_LOGIN_STATE".to_string());
+    pub static ref AUDIT_LOG_TREE_NAME: Arc<String> =  Arc::new("T_AUDIT_LOG".to_string());
     /// 旧缓存表
     pub static ref CACHE_TREE_NAME: Arc<String> =  Arc::new("T_CACHE".to_string());
     /// 新缓存表
@@ -34,7 +44,9 @@ lazy_static::lazy_static! {
     pub static ref SEQ_TOOL_SPEC_VERSION: Arc<String> =  Arc::new("TOOL_SPEC_VERSION".to_string());
     pub static ref SEQ_MCP_SERVER_ID: Arc<String> =  Arc::new("MCP_SERVER_ID".to_string());
     pub static ref SEQ_MCP_SERVER_VALUE_ID: Arc<String> =  Arc::new("MCP_SERVER_VALUE_ID".to_string());
+    pub static ref SEQ_CONFIG_WEBHOOK_ID: Arc<String> =  Arc::new("CONFIG_WEBHOOK_ID".to_string());
     pub static ref DEFAULT_NAMESPACE_ARC_STRING: Arc<String> = Arc::new("".to_string());
     pub static ref EMPTY_CLIENT_VERSION: Arc<ClientVersion> = Arc::new(ClientVersion::default());
     pub static ref NAMING_INSTANCE_TABLE: Arc<String> = Arc::new("T_NAMING_INSTANCE".to_string());
+    pub static ref NAMING_ROUTE_RULE_TABLE: Arc<String> = Arc::new("T_NAMING_ROUTE_RULE".to_string());
 }
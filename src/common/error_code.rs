@@ -2,3 +2,5 @@
 pub const NO_PERMISSION: &str = "NO_PERMISSION";
 
 pub const NO_NAMESPACE_PERMISSION: &str = "NO_NAMESPACE_PERMISSION";
+
+pub const NO_RESOURCE_PERMISSION: &str = "NO_RESOURCE_PERMISSION";
@@ -0,0 +1,34 @@
+use crate::common::string_utils::StringUtils;
+
+/// nacos 3.x 模糊监听表达式分隔符,格式: namespace>>group>>name
+pub const FUZZY_WATCH_PATTERN_SPLITTER: &str = ">>";
+
+pub const WATCH_TYPE_WATCH: &str = "WATCH";
+pub const WATCH_TYPE_CANCEL_WATCH: &str = "CANCEL_WATCH";
+
+pub const FUZZY_WATCH_INIT_NOTIFY: &str = "FUZZY_WATCH_INIT_NOTIFY";
+pub const FINISH_FUZZY_WATCH_INIT_NOTIFY: &str = "FINISH_FUZZY_WATCH_INIT_NOTIFY";
+pub const FUZZY_WATCH_DIFF_SYNC_NOTIFY: &str = "FUZZY_WATCH_DIFF_SYNC_NOTIFY";
+pub const FUZZY_WATCH_RESOURCE_CHANGED: &str = "FUZZY_WATCH_RESOURCE_CHANGED";
+
+/// 模糊监听同步时每批推送的key数量
+pub const FUZZY_WATCH_SYNC_BATCH_SIZE: usize = 10;
+
+pub struct FuzzyWatchUtils;
+
+impl FuzzyWatchUtils {
+    ///
+    /// 拆分模糊监听表达式,返回 (namespace, group表达式, 名称表达式)
+    pub fn split_pattern(pattern: &str) -> anyhow::Result<(&str, &str, &str)> {
+        let items: Vec<&str> = pattern.split(FUZZY_WATCH_PATTERN_SPLITTER).collect();
+        if items.len() != 3 || items[1].is_empty() || items[2].is_empty() {
+            return Err(anyhow::anyhow!("invalid fuzzy watch pattern: {}", pattern));
+        }
+        Ok((items[0], items[1], items[2]))
+    }
+
+    pub fn match_item(group_pattern: &str, name_pattern: &str, group: &str, name: &str) -> bool {
+        StringUtils::wildcard_match(group_pattern, group)
+            && StringUtils::wildcard_match(name_pattern, name)
+    }
+}
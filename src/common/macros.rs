@@ -39,9 +39,17 @@ macro_rules! merge_web_param_with_result {
 #[macro_export]
 macro_rules! user_namespace_privilege {
     ($req:expr) => {{
-        if let Some(session) = $req
-            .extensions()
-            .get::<std::sync::Arc<$crate::common::model::UserSession>>()
+        let _extensions = $req.extensions();
+        if let Some(session) =
+            _extensions.get::<std::sync::Arc<$crate::common::model::UserSession>>()
+        {
+            session
+                .namespace_privilege
+                .clone()
+                .map($crate::common::model::privilege::NamespacePrivilegeGroup::new)
+                .unwrap_or_default()
+        } else if let Some(session) =
+            _extensions.get::<std::sync::Arc<$crate::common::model::TokenSession>>()
         {
             session
                 .namespace_privilege
@@ -63,3 +71,34 @@ macro_rules! user_no_namespace_permission {
         ));
     }};
 }
+
+#[macro_export]
+macro_rules! user_resource_privilege {
+    ($req:expr) => {{
+        let _extensions = $req.extensions();
+        if let Some(session) =
+            _extensions.get::<std::sync::Arc<$crate::common::model::UserSession>>()
+        {
+            session.resource_privilege.clone()
+        } else if let Some(session) =
+            _extensions.get::<std::sync::Arc<$crate::common::model::TokenSession>>()
+        {
+            session.resource_privilege.clone()
+        } else {
+            $crate::common::model::privilege::ResourcePrivilege::default()
+        }
+    }};
+}
+
+#[macro_export]
+macro_rules! user_no_resource_permission {
+    ($namespace:expr,$group:expr,$resource:expr) => {{
+        return actix_web::HttpResponse::Ok().json($crate::common::model::ApiResult::<()>::error(
+            $crate::common::error_code::NO_RESOURCE_PERMISSION.to_string(),
+            Some(format!(
+                "user no such resource permission: {}/{}/{}",
+                $namespace, $group, $resource
+            )),
+        ));
+    }};
+}
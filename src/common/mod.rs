@@ -3,7 +3,7 @@ use crate::ldap::model::LdapConfig;
 use crate::oauth2::model::OAuth2Config;
 use crate::user::permission;
 use crate::user::permission::UserRoleHelper;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use uuid::Uuid;
 
@@ -16,6 +16,7 @@ pub mod cycle_queue;
 pub mod datetime_utils;
 pub mod delay_notify;
 pub mod error_code;
+pub mod fuzzy_watch_utils;
 pub mod hash_utils;
 pub mod limiter_utils;
 pub mod log_utils;
@@ -30,6 +31,7 @@ pub mod sled_utils;
 pub mod sqlx_utils;
 pub mod string_utils;
 pub mod tempfile;
+pub mod tls_utils;
 pub mod web_utils;
 /*
 use lazy_static::lazy_static;
@@ -110,6 +112,11 @@ pub struct AppSysConfig {
     pub ldap_user_developer_groups: Arc<HashSet<String>>,
     pub ldap_user_admin_groups: Arc<HashSet<String>>,
     pub ldap_user_default_role: Arc<String>,
+    pub ldap_bind_dn: Arc<String>,
+    pub ldap_bind_password: Arc<String>,
+    /// LDAP用户同步间隔,小于或等于0表示不开启
+    pub ldap_sync_interval_second: u64,
+    pub ldap_user_name_attribute: Arc<String>,
     pub mcp_http_timeout: u64,
     pub oauth2_enable: bool,
     pub oauth2_server_url: Arc<String>,
@@ -124,9 +131,56 @@ pub struct AppSysConfig {
     pub oauth2_nickname_claim_name: Arc<String>,
     pub oauth2_user_default_role: Arc<String>,
     pub oauth2_button: Arc<String>,
+    pub oauth2_oidc_discovery: bool,
+    pub oauth2_groups_claim_name: Arc<String>,
+    pub oauth2_user_developer_groups: Arc<HashSet<String>>,
+    pub oauth2_user_admin_groups: Arc<HashSet<String>>,
+    pub oauth2_group_namespaces: Arc<HashMap<String, HashSet<Arc<String>>>>,
     pub grpc_detection_timeout: u64,
     pub enable_grpc_detection_log: bool,
     pub naming_instance_metadata_persistence_enable: bool,
+    /// 加密配置主密钥文件,为空时从环境变量RNACOS_CONFIG_CIPHER_KEY读取
+    pub config_cipher_key_file: String,
+    /// sdk http端口tls证书与私钥(pem),为空时不开启tls
+    pub http_tls_cert_file: String,
+    pub http_tls_key_file: String,
+    /// 控制台端口tls证书与私钥(pem),为空时不开启tls
+    pub console_tls_cert_file: String,
+    pub console_tls_key_file: String,
+    /// grpc端口tls证书与私钥(pem),为空时不开启tls;集群节点间请求也使用该端口
+    pub grpc_tls_cert_file: String,
+    pub grpc_tls_key_file: String,
+    /// 集群节点间请求是否使用tls,默认与grpc端口是否开启tls一致
+    pub cluster_tls_enable: bool,
+    /// 集群节点证书的ca,为空时使用系统根证书校验
+    pub cluster_tls_ca_file: String,
+    /// 集群节点间双向tls,开启后raft与naming路由请求需要提供cluster_tls_ca_file签发的客户端证书
+    pub cluster_mutual_tls: bool,
+    /// 双向tls时节点请求使用的客户端证书,默认使用grpc端口证书
+    pub cluster_tls_client_cert_file: String,
+    pub cluster_tls_client_key_file: String,
+    /// 证书文件变更检查间隔
+    pub tls_reload_interval_second: u64,
+    /// 是否记录变更操作审计日志
+    pub audit_enable: bool,
+    /// 审计日志保留天数
+    pub audit_retention_days: u32,
+    /// 内部用户密码最小长度
+    pub password_min_length: u32,
+    /// 密码至少包含的字符类型数(小写字母、大写字母、数字、特殊字符)
+    pub password_require_char_types: u32,
+    /// 密码有效天数,0表示不过期
+    pub password_expire_days: u32,
+    /// 连续登录失败多少次后锁定账号,0表示不锁定
+    pub login_lock_fail_count: u32,
+    /// 账号锁定时长
+    pub login_lock_second: u32,
+    /// 初始化的管理员账号首次登录是否必须修改密码
+    pub init_admin_must_change_password: bool,
+    /// TOTP密钥加密使用的密钥,集群各节点需要一致
+    pub totp_secret_key: String,
+    /// 管理员角色必须开启两步验证
+    pub console_totp_require_manager: bool,
 }
 
 impl AppSysConfig {
@@ -298,6 +352,19 @@ impl AppSysConfig {
                 UserRoleHelper::get_role_by_name(&upper, permission::USER_ROLE_VISITOR.clone())
             })
             .unwrap_or(permission::USER_ROLE_VISITOR.clone());
+        let ldap_bind_dn = std::env::var("RNACOS_LDAP_BIND_DN")
+            .map(Arc::new)
+            .unwrap_or(constant::EMPTY_ARC_STRING.clone());
+        let ldap_bind_password = std::env::var("RNACOS_LDAP_BIND_PASSWORD")
+            .map(Arc::new)
+            .unwrap_or(constant::EMPTY_ARC_STRING.clone());
+        let ldap_sync_interval_second = std::env::var("RNACOS_LDAP_SYNC_INTERVAL_SECOND")
+            .unwrap_or("0".to_owned())
+            .parse()
+            .unwrap_or(0);
+        let ldap_user_name_attribute = std::env::var("RNACOS_LDAP_USER_NAME_ATTRIBUTE")
+            .map(Arc::new)
+            .unwrap_or(constant::EMPTY_ARC_STRING.clone());
         let mcp_http_timeout = std::env::var("RNACOS_MCP_HTTP_TIMEOUT_SECOND")
             .unwrap_or("30".to_owned())
             .parse()
@@ -355,6 +422,22 @@ impl AppSysConfig {
         let oauth2_button = std::env::var("RNACOS_OAUTH2_BUTTON")
             .map(Arc::new)
             .unwrap_or_else(|_| Arc::new("OAuth2.0 登录".to_string()));
+        let oauth2_oidc_discovery = std::env::var("RNACOS_OAUTH2_OIDC_DISCOVERY")
+            .unwrap_or("false".to_owned())
+            .parse()
+            .unwrap_or(false);
+        let oauth2_groups_claim_name = std::env::var("RNACOS_OAUTH2_GROUPS_CLAIM_NAME")
+            .map(Arc::new)
+            .unwrap_or_else(|_| Arc::new("groups".to_string()));
+        let oauth2_user_developer_groups = Arc::new(StringUtils::split_to_hashset(
+            &std::env::var("RNACOS_OAUTH2_USER_DEVELOPER_GROUP").unwrap_or_default(),
+        ));
+        let oauth2_user_admin_groups = Arc::new(StringUtils::split_to_hashset(
+            &std::env::var("RNACOS_OAUTH2_USER_ADMIN_GROUP").unwrap_or_default(),
+        ));
+        let oauth2_group_namespaces = Arc::new(OAuth2Config::parse_group_namespaces(
+            &std::env::var("RNACOS_OAUTH2_GROUP_NAMESPACES").unwrap_or_default(),
+        ));
         let grpc_detection_timeout = std::env::var("RNACOS_GRPC_DETECTION_TIMEOUT_SECOND")
             .unwrap_or("15".to_owned())
             .parse()
@@ -369,6 +452,72 @@ impl AppSysConfig {
                 .unwrap_or("true".to_owned())
                 .parse()
                 .unwrap_or(true);
+        let config_cipher_key_file =
+            std::env::var("RNACOS_CONFIG_CIPHER_KEY_FILE").unwrap_or_default();
+        let http_tls_cert_file = std::env::var("RNACOS_HTTP_TLS_CERT_FILE").unwrap_or_default();
+        let http_tls_key_file = std::env::var("RNACOS_HTTP_TLS_KEY_FILE").unwrap_or_default();
+        let console_tls_cert_file =
+            std::env::var("RNACOS_CONSOLE_TLS_CERT_FILE").unwrap_or_default();
+        let console_tls_key_file = std::env::var("RNACOS_CONSOLE_TLS_KEY_FILE").unwrap_or_default();
+        let grpc_tls_cert_file = std::env::var("RNACOS_GRPC_TLS_CERT_FILE").unwrap_or_default();
+        let grpc_tls_key_file = std::env::var("RNACOS_GRPC_TLS_KEY_FILE").unwrap_or_default();
+        let cluster_tls_enable = std::env::var("RNACOS_CLUSTER_TLS_ENABLE")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(!grpc_tls_cert_file.is_empty());
+        let cluster_tls_ca_file = std::env::var("RNACOS_CLUSTER_TLS_CA_FILE").unwrap_or_default();
+        let cluster_mutual_tls = std::env::var("RNACOS_CLUSTER_MUTUAL_TLS")
+            .unwrap_or("false".to_owned())
+            .parse()
+            .unwrap_or(false);
+        let cluster_tls_client_cert_file = std::env::var("RNACOS_CLUSTER_TLS_CLIENT_CERT_FILE")
+            .unwrap_or_else(|_| grpc_tls_cert_file.clone());
+        let cluster_tls_client_key_file = std::env::var("RNACOS_CLUSTER_TLS_CLIENT_KEY_FILE")
+            .unwrap_or_else(|_| grpc_tls_key_file.clone());
+        let tls_reload_interval_second = std::env::var("RNACOS_TLS_RELOAD_INTERVAL_SECOND")
+            .unwrap_or("10".to_owned())
+            .parse()
+            .unwrap_or(10);
+        let audit_enable = std::env::var("RNACOS_AUDIT_ENABLE")
+            .unwrap_or("true".to_owned())
+            .parse()
+            .unwrap_or(true);
+        let audit_retention_days = std::env::var("RNACOS_AUDIT_RETENTION_DAYS")
+            .unwrap_or("30".to_owned())
+            .parse()
+            .unwrap_or(30);
+        let password_min_length = std::env::var("RNACOS_PASSWORD_MIN_LENGTH")
+            .unwrap_or("6".to_owned())
+            .parse()
+            .unwrap_or(6);
+        let password_require_char_types = std::env::var("RNACOS_PASSWORD_REQUIRE_CHAR_TYPES")
+            .unwrap_or("1".to_owned())
+            .parse()
+            .unwrap_or(1);
+        let password_expire_days = std::env::var("RNACOS_PASSWORD_EXPIRE_DAYS")
+            .unwrap_or("0".to_owned())
+            .parse()
+            .unwrap_or(0);
+        let login_lock_fail_count = std::env::var("RNACOS_LOGIN_LOCK_FAIL_COUNT")
+            .unwrap_or("10".to_owned())
+            .parse()
+            .unwrap_or(10);
+        let login_lock_second = std::env::var("RNACOS_LOGIN_LOCK_SECOND")
+            .unwrap_or("1800".to_owned())
+            .parse()
+            .unwrap_or(1800);
+        let init_admin_must_change_password =
+            std::env::var("RNACOS_INIT_ADMIN_MUST_CHANGE_PASSWORD")
+                .unwrap_or("true".to_owned())
+                .parse()
+                .unwrap_or(true);
+        let totp_secret_key = std::env::var("RNACOS_TOTP_SECRET_KEY").unwrap_or_default();
+        // 未配置TOTP密钥材料时无法开启两步验证,不能强制管理员绑定
+        let console_totp_require_manager = std::env::var("RNACOS_CONSOLE_TOTP_REQUIRE_MANAGER")
+            .unwrap_or("false".to_owned())
+            .parse()
+            .unwrap_or(false)
+            && !(totp_secret_key.is_empty() && cluster_token.is_empty());
         Self {
             local_db_dir,
             config_db_file,
@@ -411,6 +560,10 @@ impl AppSysConfig {
             ldap_user_developer_groups,
             ldap_user_admin_groups,
             ldap_user_default_role,
+            ldap_bind_dn,
+            ldap_bind_password,
+            ldap_sync_interval_second,
+            ldap_user_name_attribute,
             mcp_http_timeout,
             oauth2_enable,
             oauth2_server_url,
@@ -425,9 +578,37 @@ impl AppSysConfig {
             oauth2_nickname_claim_name,
             oauth2_user_default_role,
             oauth2_button,
+            oauth2_oidc_discovery,
+            oauth2_groups_claim_name,
+            oauth2_user_developer_groups,
+            oauth2_user_admin_groups,
+            oauth2_group_namespaces,
             grpc_detection_timeout,
             enable_grpc_detection_log,
             naming_instance_metadata_persistence_enable,
+            config_cipher_key_file,
+            http_tls_cert_file,
+            http_tls_key_file,
+            console_tls_cert_file,
+            console_tls_key_file,
+            grpc_tls_cert_file,
+            grpc_tls_key_file,
+            cluster_tls_enable,
+            cluster_tls_ca_file,
+            cluster_mutual_tls,
+            cluster_tls_client_cert_file,
+            cluster_tls_client_key_file,
+            tls_reload_interval_second,
+            audit_enable,
+            audit_retention_days,
+            password_min_length,
+            password_require_char_types,
+            password_expire_days,
+            login_lock_fail_count,
+            login_lock_second,
+            init_admin_must_change_password,
+            totp_secret_key,
+            console_totp_require_manager,
         }
     }
 
@@ -473,6 +654,10 @@ impl AppSysConfig {
             ldap_user_developer_groups: self.ldap_user_developer_groups.clone(),
             ldap_user_admin_groups: self.ldap_user_admin_groups.clone(),
             ldap_user_default_role: self.ldap_user_default_role.clone(),
+            ldap_bind_dn: self.ldap_bind_dn.clone(),
+            ldap_bind_password: self.ldap_bind_password.clone(),
+            ldap_sync_interval_second: self.ldap_sync_interval_second,
+            ldap_user_name_attribute: self.ldap_user_name_attribute.clone(),
         })
     }
 
@@ -489,6 +674,11 @@ impl AppSysConfig {
             oauth2_username_claim_name: self.oauth2_username_claim_name.clone(),
             oauth2_nickname_claim_name: self.oauth2_nickname_claim_name.clone(),
             oauth2_user_default_role: self.oauth2_user_default_role.clone(),
+            oauth2_oidc_discovery: self.oauth2_oidc_discovery,
+            oauth2_groups_claim_name: self.oauth2_groups_claim_name.clone(),
+            oauth2_user_developer_groups: self.oauth2_user_developer_groups.clone(),
+            oauth2_user_admin_groups: self.oauth2_user_admin_groups.clone(),
+            oauth2_group_namespaces: self.oauth2_group_namespaces.clone(),
         })
     }
 }
@@ -4,7 +4,7 @@ pub mod privilege;
 use std::{collections::HashMap, sync::Arc};
 
 pub use crate::common::model::client_version::ClientVersion;
-use crate::common::model::privilege::PrivilegeGroup;
+use crate::common::model::privilege::{PrivilegeGroup, ResourcePrivilege};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Default, Deserialize, Serialize)]
@@ -94,9 +94,17 @@ pub struct UserSession {
     pub nickname: Option<String>,
     pub roles: Vec<Arc<String>>,
     pub namespace_privilege: Option<PrivilegeGroup<Arc<String>>>,
+    #[serde(default)]
+    pub resource_privilege: ResourcePrivilege,
     pub extend_infos: HashMap<String, String>,
     /// 时间戳，单位秒
     pub refresh_time: u32,
+    /// 必须先修改密码,修改前只能访问修改密码相关接口
+    #[serde(default)]
+    pub need_change_password: bool,
+    /// 必须先绑定两步验证
+    #[serde(default)]
+    pub need_bind_totp: bool,
 }
 
 #[derive(Debug, Default, Clone, Deserialize, Serialize)]
@@ -104,4 +112,8 @@ pub struct TokenSession {
     pub username: Arc<String>,
     pub roles: Vec<Arc<String>>,
     pub extend_infos: HashMap<String, String>,
+    #[serde(default)]
+    pub namespace_privilege: Option<PrivilegeGroup<Arc<String>>>,
+    #[serde(default)]
+    pub resource_privilege: ResourcePrivilege,
 }
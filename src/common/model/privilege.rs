@@ -1,4 +1,5 @@
 use crate::common::constant::DEFAULT_NAMESPACE_ARC_STRING;
+use crate::common::string_utils::StringUtils;
 use crate::namespace::is_default_namespace;
 use bitflags::bitflags;
 use serde::{Deserialize, Serialize};
@@ -210,3 +211,164 @@ impl NamespacePrivilegeGroup {
         self.0.is_all()
     }
 }
+
+///
+/// 细粒度数据权限动作,写权限包含读权限
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ResourceAction {
+    Read,
+    Write,
+}
+
+impl ResourceAction {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "r" | "read" => Some(Self::Read),
+            "w" | "rw" | "write" => Some(Self::Write),
+            _ => None,
+        }
+    }
+
+    pub fn to_str(&self) -> &'static str {
+        match self {
+            Self::Read => "read",
+            Self::Write => "write",
+        }
+    }
+
+    pub fn contains(&self, action: ResourceAction) -> bool {
+        *self == Self::Write || action == Self::Read
+    }
+}
+
+///
+/// 细粒度数据权限项;group与resource(配置dataId或服务名)支持*与?通配符,namespace为*时匹配全部命名空间
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourcePermission {
+    pub namespace: Arc<String>,
+    pub group: Arc<String>,
+    pub resource: Arc<String>,
+    pub action: ResourceAction,
+}
+
+impl ResourcePermission {
+    pub fn is_match(&self, namespace: &str, group: &str, resource: &str) -> bool {
+        let namespace_matched = self.namespace.as_str() == "*"
+            || self.namespace.as_str() == namespace
+            || (is_default_namespace(&self.namespace) && is_default_namespace(namespace));
+        namespace_matched
+            && StringUtils::wildcard_match(&self.group, group)
+            && StringUtils::wildcard_match(&self.resource, resource)
+    }
+}
+
+///
+/// 用户细粒度数据权限,按命名空间、分组与资源名控制读写;
+/// 没有配置权限项时不做限制,只受命名空间权限与角色控制
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ResourcePrivilege(pub Option<Arc<Vec<ResourcePermission>>>);
+
+impl ResourcePrivilege {
+    pub fn new(items: Vec<ResourcePermission>) -> Self {
+        if items.is_empty() {
+            Self(None)
+        } else {
+            Self(Some(Arc::new(items)))
+        }
+    }
+
+    pub fn is_all(&self) -> bool {
+        self.0.is_none()
+    }
+
+    ///
+    /// 去掉写权限,只保留读权限
+    pub fn to_read_only(&self) -> Self {
+        let items = match &self.0 {
+            Some(items) => items
+                .iter()
+                .map(|e| ResourcePermission {
+                    action: ResourceAction::Read,
+                    ..e.clone()
+                })
+                .collect(),
+            None => {
+                let all = Arc::new("*".to_owned());
+                vec![ResourcePermission {
+                    namespace: all.clone(),
+                    group: all.clone(),
+                    resource: all,
+                    action: ResourceAction::Read,
+                }]
+            }
+        };
+        Self::new(items)
+    }
+
+    pub fn check_permission(
+        &self,
+        namespace: &str,
+        group: &str,
+        resource: &str,
+        action: ResourceAction,
+    ) -> bool {
+        if let Some(items) = &self.0 {
+            items
+                .iter()
+                .any(|e| e.action.contains(action) && e.is_match(namespace, group, resource))
+        } else {
+            true
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_permission(
+        namespace: &str,
+        group: &str,
+        resource: &str,
+        action: ResourceAction,
+    ) -> ResourcePermission {
+        ResourcePermission {
+            namespace: Arc::new(namespace.to_owned()),
+            group: Arc::new(group.to_owned()),
+            resource: Arc::new(resource.to_owned()),
+            action,
+        }
+    }
+
+    #[test]
+    fn resource_privilege() {
+        assert!(ResourcePrivilege::default().check_permission(
+            "a",
+            "b",
+            "c",
+            ResourceAction::Write
+        ));
+        let privilege = ResourcePrivilege::new(vec![
+            build_permission("prod", "payment", "*", ResourceAction::Write),
+            build_permission("*", "*", "*", ResourceAction::Read),
+        ]);
+        assert!(privilege.check_permission("prod", "payment", "app.yaml", ResourceAction::Write));
+        assert!(privilege.check_permission("prod", "order", "app.yaml", ResourceAction::Read));
+        assert!(!privilege.check_permission("prod", "order", "app.yaml", ResourceAction::Write));
+        assert!(!privilege.check_permission("dev", "payment", "app.yaml", ResourceAction::Write));
+        let privilege = ResourcePrivilege::new(vec![build_permission(
+            "public",
+            "DEFAULT_GROUP",
+            "order-*",
+            ResourceAction::Read,
+        )]);
+        assert!(privilege.check_permission("", "DEFAULT_GROUP", "order-api", ResourceAction::Read));
+        assert!(!privilege.check_permission("", "DEFAULT_GROUP", "user-api", ResourceAction::Read));
+        assert_eq!(ResourceAction::from_name("rw"), Some(ResourceAction::Write));
+        let read_only = ResourcePrivilege::default().to_read_only();
+        assert!(read_only.check_permission("dev", "g", "app.yaml", ResourceAction::Read));
+        assert!(!read_only.check_permission("dev", "g", "app.yaml", ResourceAction::Write));
+    }
+}
@@ -44,6 +44,30 @@ impl StringUtils {
         v
     }
 
+    ///
+    /// 通配符匹配,`*`匹配任意个字符,`?`匹配单个字符
+    pub fn wildcard_match(pattern: &str, text: &str) -> bool {
+        let (pattern, text) = (pattern.as_bytes(), text.as_bytes());
+        let (mut p, mut t) = (0, 0);
+        let mut star: Option<(usize, usize)> = None;
+        while t < text.len() {
+            if p < pattern.len() && (pattern[p] == b'?' || pattern[p] == text[t]) {
+                p += 1;
+                t += 1;
+            } else if p < pattern.len() && pattern[p] == b'*' {
+                star = Some((p, t));
+                p += 1;
+            } else if let Some((star_p, star_t)) = star {
+                p = star_p + 1;
+                t = star_t + 1;
+                star = Some((star_p, star_t + 1));
+            } else {
+                return false;
+            }
+        }
+        pattern[p..].iter().all(|c| *c == b'*')
+    }
+
     pub fn extract_ldap_value_cn(ldap_str: &str) -> Option<String> {
         let re = Regex::new(r"(?:^|,)cn=([^,]+)").unwrap();
         re.captures(ldap_str)
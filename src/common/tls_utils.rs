@@ -0,0 +1,380 @@
+use crate::common::AppSysConfig;
+use rustls::client::ResolvesClientCert;
+use rustls::crypto::CryptoProvider;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, UnixTime};
+use rustls::server::danger::{ClientCertVerified, ClientCertVerifier};
+use rustls::server::{ClientHello, ResolvesServerCert, WebPkiClientVerifier};
+use rustls::sign::CertifiedKey;
+use rustls::{
+    ClientConfig, DigitallySignedStruct, DistinguishedName, RootCertStore, ServerConfig,
+    SignatureScheme,
+};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant, SystemTime};
+
+type TlsLoader<T> = Box<dyn Fn(&[String]) -> anyhow::Result<Arc<T>> + Send + Sync>;
+
+///
+/// 从文件加载的tls对象,文件修改后在下次使用时重新加载;加载失败时继续使用旧值
+pub struct ReloadableTls<T: ?Sized> {
+    files: Vec<String>,
+    loader: TlsLoader<T>,
+    value: RwLock<Arc<T>>,
+    check_interval: Duration,
+    state: Mutex<(Instant, Vec<Option<SystemTime>>)>,
+}
+
+impl<T: ?Sized> ReloadableTls<T> {
+    pub fn new(
+        files: Vec<String>,
+        check_interval: Duration,
+        loader: TlsLoader<T>,
+    ) -> anyhow::Result<Self> {
+        let value = loader(&files)?;
+        let modified = Self::get_modified(&files);
+        Ok(Self {
+            files,
+            loader,
+            value: RwLock::new(value),
+            check_interval,
+            state: Mutex::new((Instant::now(), modified)),
+        })
+    }
+
+    fn get_modified(files: &[String]) -> Vec<Option<SystemTime>> {
+        files
+            .iter()
+            .map(|f| std::fs::metadata(f).and_then(|m| m.modified()).ok())
+            .collect()
+    }
+
+    pub fn get(&self) -> Arc<T> {
+        self.reload_if_changed();
+        self.value.read().unwrap().clone()
+    }
+
+    fn reload_if_changed(&self) {
+        let mut state = self.state.lock().unwrap();
+        if state.0.elapsed() < self.check_interval {
+            return;
+        }
+        state.0 = Instant::now();
+        let modified = Self::get_modified(&self.files);
+        if modified == state.1 {
+            return;
+        }
+        match (self.loader)(&self.files) {
+            Ok(value) => {
+                log::info!("tls files reloaded: {:?}", &self.files);
+                *self.value.write().unwrap() = value;
+                state.1 = modified;
+            }
+            Err(err) => {
+                log::error!("tls files reload error, {:?}: {}", &self.files, err);
+            }
+        }
+    }
+}
+
+impl<T: ?Sized> std::fmt::Debug for ReloadableTls<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReloadableTls")
+            .field("files", &self.files)
+            .finish()
+    }
+}
+
+///
+/// 证书变更后自动更新的服务端/客户端证书
+#[derive(Debug)]
+pub struct ReloadableCertResolver(ReloadableTls<CertifiedKey>);
+
+impl ReloadableCertResolver {
+    pub fn new(cert_file: &str, key_file: &str, check_interval: Duration) -> anyhow::Result<Self> {
+        let provider = TlsUtils::provider();
+        Ok(Self(ReloadableTls::new(
+            vec![cert_file.to_owned(), key_file.to_owned()],
+            check_interval,
+            Box::new(move |files| TlsUtils::load_certified_key(&provider, &files[0], &files[1])),
+        )?))
+    }
+}
+
+impl ResolvesServerCert for ReloadableCertResolver {
+    fn resolve(&self, _client_hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        Some(self.0.get())
+    }
+}
+
+impl ResolvesClientCert for ReloadableCertResolver {
+    fn resolve(
+        &self,
+        _root_hint_subjects: &[&[u8]],
+        _sigschemes: &[SignatureScheme],
+    ) -> Option<Arc<CertifiedKey>> {
+        Some(self.0.get())
+    }
+
+    fn has_certs(&self) -> bool {
+        true
+    }
+}
+
+///
+/// 集群节点客户端证书校验;客户端证书可选,sdk连接不需要提供证书,
+/// 由集群请求处理时判断是否提供了已校验的证书
+#[derive(Debug)]
+pub struct ReloadableClientVerifier(ReloadableTls<dyn ClientCertVerifier>);
+
+impl ReloadableClientVerifier {
+    pub fn new(ca_file: &str, check_interval: Duration) -> anyhow::Result<Self> {
+        let provider = TlsUtils::provider();
+        Ok(Self(ReloadableTls::new(
+            vec![ca_file.to_owned()],
+            check_interval,
+            Box::new(move |files| {
+                let roots = TlsUtils::load_root_store(&files[0])?;
+                let verifier: Arc<dyn ClientCertVerifier> =
+                    WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider.clone())
+                        .allow_unauthenticated()
+                        .build()?;
+                Ok(verifier)
+            }),
+        )?))
+    }
+}
+
+impl ClientCertVerifier for ReloadableClientVerifier {
+    fn client_auth_mandatory(&self) -> bool {
+        false
+    }
+
+    fn root_hint_subjects(&self) -> &[DistinguishedName] {
+        &[]
+    }
+
+    fn verify_client_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        now: UnixTime,
+    ) -> Result<ClientCertVerified, rustls::Error> {
+        self.0
+            .get()
+            .verify_client_cert(end_entity, intermediates, now)
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<rustls::client::danger::HandshakeSignatureValid, rustls::Error> {
+        self.0.get().verify_tls12_signature(message, cert, dss)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<rustls::client::danger::HandshakeSignatureValid, rustls::Error> {
+        self.0.get().verify_tls13_signature(message, cert, dss)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.get().supported_verify_schemes()
+    }
+}
+
+pub struct TlsUtils;
+
+impl TlsUtils {
+    pub fn provider() -> Arc<CryptoProvider> {
+        Arc::new(rustls::crypto::ring::default_provider())
+    }
+
+    pub fn load_certified_key(
+        provider: &CryptoProvider,
+        cert_file: &str,
+        key_file: &str,
+    ) -> anyhow::Result<Arc<CertifiedKey>> {
+        let certs = CertificateDer::pem_file_iter(cert_file)
+            .map_err(|e| anyhow::anyhow!("read cert file {} error: {}", cert_file, e))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| anyhow::anyhow!("parse cert file {} error: {}", cert_file, e))?;
+        if certs.is_empty() {
+            return Err(anyhow::anyhow!("no certificate in file: {}", cert_file));
+        }
+        let key = PrivateKeyDer::from_pem_file(key_file)
+            .map_err(|e| anyhow::anyhow!("read key file {} error: {}", key_file, e))?;
+        let signing_key = provider.key_provider.load_private_key(key)?;
+        Ok(Arc::new(CertifiedKey::new(certs, signing_key)))
+    }
+
+    pub fn load_root_store(ca_file: &str) -> anyhow::Result<RootCertStore> {
+        let mut roots = RootCertStore::empty();
+        for cert in CertificateDer::pem_file_iter(ca_file)
+            .map_err(|e| anyhow::anyhow!("read ca file {} error: {}", ca_file, e))?
+        {
+            roots.add(
+                cert.map_err(|e| anyhow::anyhow!("parse ca file {} error: {}", ca_file, e))?,
+            )?;
+        }
+        if roots.is_empty() {
+            return Err(anyhow::anyhow!("no certificate in ca file: {}", ca_file));
+        }
+        Ok(roots)
+    }
+
+    fn load_native_root_store() -> RootCertStore {
+        let mut roots = RootCertStore::empty();
+        let result = rustls_native_certs::load_native_certs();
+        for err in &result.errors {
+            log::warn!("load native root cert error: {}", err);
+        }
+        roots.add_parsable_certificates(result.certs);
+        roots
+    }
+
+    fn reload_interval(sys_config: &AppSysConfig) -> Duration {
+        Duration::from_secs(sys_config.tls_reload_interval_second)
+    }
+
+    ///
+    /// 构建http端口tls配置,证书为空时返回None
+    pub fn build_http_server_config(
+        sys_config: &AppSysConfig,
+        cert_file: &str,
+        key_file: &str,
+    ) -> anyhow::Result<Option<ServerConfig>> {
+        if cert_file.is_empty() {
+            return Ok(None);
+        }
+        let resolver =
+            ReloadableCertResolver::new(cert_file, key_file, Self::reload_interval(sys_config))?;
+        let config = ServerConfig::builder_with_provider(Self::provider())
+            .with_safe_default_protocol_versions()?
+            .with_no_client_auth()
+            .with_cert_resolver(Arc::new(resolver));
+        Ok(Some(config))
+    }
+
+    ///
+    /// 构建grpc端口tls配置,开启集群双向tls时校验节点客户端证书
+    pub fn build_grpc_server_config(
+        sys_config: &AppSysConfig,
+    ) -> anyhow::Result<Option<Arc<ServerConfig>>> {
+        if sys_config.grpc_tls_cert_file.is_empty() {
+            return Ok(None);
+        }
+        let interval = Self::reload_interval(sys_config);
+        let resolver = ReloadableCertResolver::new(
+            &sys_config.grpc_tls_cert_file,
+            &sys_config.grpc_tls_key_file,
+            interval,
+        )?;
+        let builder = ServerConfig::builder_with_provider(Self::provider())
+            .with_safe_default_protocol_versions()?;
+        let mut config = if sys_config.cluster_mutual_tls {
+            if sys_config.cluster_tls_ca_file.is_empty() {
+                return Err(anyhow::anyhow!(
+                    "RNACOS_CLUSTER_TLS_CA_FILE is required when cluster mutual tls is enabled"
+                ));
+            }
+            let verifier =
+                ReloadableClientVerifier::new(&sys_config.cluster_tls_ca_file, interval)?;
+            builder
+                .with_client_cert_verifier(Arc::new(verifier))
+                .with_cert_resolver(Arc::new(resolver))
+        } else {
+            builder
+                .with_no_client_auth()
+                .with_cert_resolver(Arc::new(resolver))
+        };
+        config.alpn_protocols = vec![b"h2".to_vec()];
+        Ok(Some(Arc::new(config)))
+    }
+
+    ///
+    /// 构建集群节点请求使用的tls客户端配置,未开启集群tls时返回None
+    pub fn build_cluster_client_config(
+        sys_config: &AppSysConfig,
+    ) -> anyhow::Result<Option<Arc<ReloadableTls<ClientConfig>>>> {
+        if !sys_config.cluster_tls_enable {
+            return Ok(None);
+        }
+        let interval = Self::reload_interval(sys_config);
+        let client_cert = if sys_config.cluster_mutual_tls {
+            Some(Arc::new(ReloadableCertResolver::new(
+                &sys_config.cluster_tls_client_cert_file,
+                &sys_config.cluster_tls_client_key_file,
+                interval,
+            )?))
+        } else {
+            None
+        };
+        let mut files = vec![];
+        if !sys_config.cluster_tls_ca_file.is_empty() {
+            files.push(sys_config.cluster_tls_ca_file.clone());
+        }
+        let config = ReloadableTls::new(
+            files,
+            interval,
+            Box::new(move |files| {
+                let roots = match files.first() {
+                    Some(ca_file) => Self::load_root_store(ca_file)?,
+                    None => Self::load_native_root_store(),
+                };
+                let builder = ClientConfig::builder_with_provider(Self::provider())
+                    .with_safe_default_protocol_versions()?
+                    .with_root_certificates(roots);
+                let mut config = match &client_cert {
+                    Some(resolver) => builder.with_client_cert_resolver(resolver.clone()),
+                    None => builder.with_no_client_auth(),
+                };
+                config.alpn_protocols = vec![b"h2".to_vec()];
+                Ok(Arc::new(config))
+            }),
+        )?;
+        Ok(Some(Arc::new(config)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn reload_when_file_changed() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(b"v1").unwrap();
+        let path = file.path().to_string_lossy().to_string();
+        let reloadable: ReloadableTls<String> = ReloadableTls::new(
+            vec![path.clone()],
+            Duration::ZERO,
+            Box::new(|files| {
+                let v = std::fs::read_to_string(&files[0])?;
+                if v.is_empty() {
+                    return Err(anyhow::anyhow!("empty"));
+                }
+                Ok(Arc::new(v))
+            }),
+        )
+        .unwrap();
+        assert_eq!(reloadable.get().as_str(), "v1");
+        std::fs::write(&path, "v2").unwrap();
+        file.as_file()
+            .set_modified(SystemTime::now() + Duration::from_secs(10))
+            .unwrap();
+        assert_eq!(reloadable.get().as_str(), "v2");
+        // 加载失败时保留旧值
+        std::fs::write(&path, "").unwrap();
+        file.as_file()
+            .set_modified(SystemTime::now() + Duration::from_secs(20))
+            .unwrap();
+        assert_eq!(reloadable.get().as_str(), "v2");
+    }
+}
@@ -1,4 +1,5 @@
-use actix_web::web;
+use actix_web::{web, HttpRequest};
+use std::net::SocketAddr;
 use tokio_stream::StreamExt;
 
 const MAX_SIZE: usize = 10485760;
@@ -15,3 +16,14 @@ pub async fn get_req_body(mut payload: web::Payload) -> anyhow::Result<Vec<u8>>
     }
     Ok(body.to_vec())
 }
+
+///
+/// 获取请求的客户端ip，优先使用代理头中的真实ip
+pub fn get_client_ip(req: &HttpRequest) -> String {
+    let conn_info = req.connection_info();
+    let addr = conn_info.realip_remote_addr().unwrap_or_default();
+    if let Ok(socket_addr) = addr.parse::<SocketAddr>() {
+        return socket_addr.ip().to_string();
+    }
+    addr.to_owned()
+}
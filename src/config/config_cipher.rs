@@ -0,0 +1,228 @@
+use crate::common::crypto_utils;
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_128_GCM, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+
+/// 加密配置的dataId前缀,与java nacos加密插件保持一致
+pub const CIPHER_PREFIX: &str = "cipher-";
+
+/// 未配置密钥文件时,从该环境变量读取主密钥
+pub const CONFIG_CIPHER_KEY_ENV: &str = "RNACOS_CONFIG_CIPHER_KEY";
+
+const KEY_LEN: usize = 16;
+
+///
+/// 配置加密主密钥提供者
+pub trait ConfigKeyProvider: Send + Sync {
+    fn get_key(&self) -> anyhow::Result<Arc<String>>;
+}
+
+///
+/// 从本地密钥文件读取主密钥
+pub struct FileKeyProvider {
+    path: String,
+}
+
+impl FileKeyProvider {
+    pub fn new(path: String) -> Self {
+        Self { path }
+    }
+}
+
+impl ConfigKeyProvider for FileKeyProvider {
+    fn get_key(&self) -> anyhow::Result<Arc<String>> {
+        let key = std::fs::read_to_string(&self.path)
+            .map_err(|e| anyhow::anyhow!("read cipher key file {} error,{}", &self.path, e))?;
+        Ok(Arc::new(key.trim().to_owned()))
+    }
+}
+
+///
+/// 从环境变量读取主密钥
+pub struct EnvKeyProvider {
+    name: String,
+}
+
+impl EnvKeyProvider {
+    pub fn new(name: String) -> Self {
+        Self { name }
+    }
+}
+
+impl ConfigKeyProvider for EnvKeyProvider {
+    fn get_key(&self) -> anyhow::Result<Arc<String>> {
+        let key = std::env::var(&self.name)
+            .map_err(|_| anyhow::anyhow!("the cipher key env {} is not set", &self.name))?;
+        Ok(Arc::new(key.trim().to_owned()))
+    }
+}
+
+///
+/// 加密配置处理器
+/// 配置内容使用随机数据密钥加密,数据密钥再使用主密钥加密后作为encrypted_data_key保存;
+/// 使用AES-128-GCM,解密时同时校验密文完整性
+pub struct ConfigCipher {
+    provider: Option<Arc<dyn ConfigKeyProvider>>,
+}
+
+impl Debug for ConfigCipher {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ConfigCipher")
+            .field("enable", &self.provider.is_some())
+            .finish()
+    }
+}
+
+impl ConfigCipher {
+    pub fn new(provider: Option<Arc<dyn ConfigKeyProvider>>) -> Self {
+        Self { provider }
+    }
+
+    ///
+    /// 配置了密钥文件时从文件读取,否则从环境变量读取
+    pub fn new_by_key_file(key_file: &str, key_env: &str) -> Self {
+        let provider: Arc<dyn ConfigKeyProvider> = if key_file.is_empty() {
+            Arc::new(EnvKeyProvider::new(key_env.to_owned()))
+        } else {
+            Arc::new(FileKeyProvider::new(key_file.to_owned()))
+        };
+        Self::new(Some(provider))
+    }
+
+    pub fn is_cipher_data_id(data_id: &str) -> bool {
+        data_id.starts_with(CIPHER_PREFIX)
+    }
+
+    fn get_master_key(&self) -> anyhow::Result<Arc<String>> {
+        let provider = self
+            .provider
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("the config cipher key provider is not set"))?;
+        let key = provider.get_key()?;
+        if key.len() != KEY_LEN {
+            return Err(anyhow::anyhow!(
+                "the config cipher key length must be {}",
+                KEY_LEN
+            ));
+        }
+        Ok(key)
+    }
+
+    ///
+    /// 加密配置内容,返回 (加密后内容, encrypted_data_key)
+    pub fn encrypt(&self, content: &str) -> anyhow::Result<(Arc<String>, Arc<String>)> {
+        let master_key = self.get_master_key()?;
+        let data_key = Self::random_str();
+        let value = Self::seal(&data_key, content.as_bytes())?;
+        let encrypted_data_key = Self::seal(&master_key, data_key.as_bytes())?;
+        Ok((Arc::new(value), Arc::new(encrypted_data_key)))
+    }
+
+    pub fn decrypt(&self, content: &str, encrypted_data_key: &str) -> anyhow::Result<String> {
+        let master_key = self.get_master_key()?;
+        let data_key = String::from_utf8(Self::open(&master_key, encrypted_data_key)?)?;
+        if data_key.len() != KEY_LEN {
+            return Err(anyhow::anyhow!("invalid encrypted data key"));
+        }
+        Ok(String::from_utf8(Self::open(&data_key, content)?)?)
+    }
+
+    fn random_str() -> String {
+        rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(KEY_LEN)
+            .map(char::from)
+            .collect()
+    }
+
+    fn build_key(key: &str) -> anyhow::Result<LessSafeKey> {
+        let key = UnboundKey::new(&AES_128_GCM, key.as_bytes())
+            .map_err(|_| anyhow::anyhow!("invalid cipher key"))?;
+        Ok(LessSafeKey::new(key))
+    }
+
+    /// 输出格式: base64(nonce + 密文 + tag)
+    fn seal(key: &str, plain: &[u8]) -> anyhow::Result<String> {
+        let key = Self::build_key(key)?;
+        let mut nonce = [0u8; NONCE_LEN];
+        SystemRandom::new()
+            .fill(&mut nonce)
+            .map_err(|_| anyhow::anyhow!("generate nonce failed"))?;
+        let mut in_out = plain.to_vec();
+        key.seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::empty(),
+            &mut in_out,
+        )
+        .map_err(|_| anyhow::anyhow!("encrypt config content failed"))?;
+        let mut sealed = nonce.to_vec();
+        sealed.extend(in_out);
+        Ok(crypto_utils::encode_base64(&sealed))
+    }
+
+    fn open(key: &str, sealed: &str) -> anyhow::Result<Vec<u8>> {
+        let key = Self::build_key(key)?;
+        let mut sealed = crypto_utils::decode_base64(sealed)?;
+        if sealed.len() <= NONCE_LEN {
+            return Err(anyhow::anyhow!("invalid cipher content"));
+        }
+        let mut in_out = sealed.split_off(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(&sealed)
+            .map_err(|_| anyhow::anyhow!("invalid cipher content"))?;
+        let plain = key
+            .open_in_place(nonce, Aad::empty(), &mut in_out)
+            .map_err(|_| anyhow::anyhow!("decrypt config content failed"))?;
+        Ok(plain.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct StaticKeyProvider(Arc<String>);
+
+    impl ConfigKeyProvider for StaticKeyProvider {
+        fn get_key(&self) -> anyhow::Result<Arc<String>> {
+            Ok(self.0.clone())
+        }
+    }
+
+    #[test]
+    fn encrypt_and_decrypt() {
+        let cipher = ConfigCipher::new(Some(Arc::new(StaticKeyProvider(Arc::new(
+            "0123456789abcdef".to_owned(),
+        )))));
+        let content = "db.password=中文密码123";
+        let (value, data_key) = cipher.encrypt(content).unwrap();
+        assert!(value.as_str() != content);
+        assert_eq!(cipher.decrypt(&value, &data_key).unwrap(), content);
+
+        let other = ConfigCipher::new(Some(Arc::new(StaticKeyProvider(Arc::new(
+            "fedcba9876543210".to_owned(),
+        )))));
+        assert!(other.decrypt(&value, &data_key).is_err());
+
+        // 密文被篡改时校验失败
+        let mut tampered = crypto_utils::decode_base64(&value).unwrap();
+        let last = tampered.len() - 1;
+        tampered[last] ^= 1;
+        assert!(cipher
+            .decrypt(&crypto_utils::encode_base64(&tampered), &data_key)
+            .is_err());
+    }
+
+    #[test]
+    fn invalid_key() {
+        assert!(ConfigCipher::new(None).encrypt("a").is_err());
+        let cipher = ConfigCipher::new(Some(Arc::new(StaticKeyProvider(Arc::new(
+            "short".to_owned(),
+        )))));
+        assert!(cipher.encrypt("a").is_err());
+        assert!(ConfigCipher::is_cipher_data_id("cipher-aes-db"));
+        assert!(!ConfigCipher::is_cipher_data_id("db"));
+    }
+}
@@ -0,0 +1,254 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use similar::{Algorithm, TextDiff};
+
+use crate::config::config_cipher::ConfigCipher;
+
+///
+/// 配置历史版本差异
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigHistoryDiffDto {
+    pub from_id: u64,
+    /// 为空时表示与当前配置比较
+    pub to_id: Option<u64>,
+    pub from_content: String,
+    pub to_content: String,
+    pub diff: String,
+    /// 加密配置没有解密权限时不返回内容,只标记内容是否变更
+    #[serde(default)]
+    pub encrypted: bool,
+    /// 加密配置两端内容对应的数据密钥,仅用于服务端解密后比较
+    #[serde(skip)]
+    pub from_encrypted_data_key: Option<Arc<String>>,
+    #[serde(skip)]
+    pub to_encrypted_data_key: Option<Arc<String>>,
+}
+
+impl ConfigHistoryDiffDto {
+    pub fn from_name(&self) -> String {
+        format!("history/{}", self.from_id)
+    }
+
+    pub fn to_name(&self) -> String {
+        if let Some(to_id) = self.to_id {
+            format!("history/{to_id}")
+        } else {
+            "current".to_owned()
+        }
+    }
+
+    ///
+    /// 解密加密配置两端的内容
+    pub fn decrypt(mut self, cipher: &ConfigCipher) -> anyhow::Result<Self> {
+        if let Some(key) = self.from_encrypted_data_key.as_ref() {
+            self.from_content = cipher.decrypt(&self.from_content, key)?;
+        }
+        if let Some(key) = self.to_encrypted_data_key.as_ref() {
+            self.to_content = cipher.decrypt(&self.to_content, key)?;
+        }
+        Ok(self)
+    }
+
+    ///
+    /// 不解密加密配置,只按密文比较内容是否变更;未变更的明文会复用原密文
+    pub fn mask_cipher(mut self) -> Self {
+        self.diff = if self.from_content == self.to_content {
+            String::new()
+        } else {
+            format!(
+                "--- {}\n+++ {}\nencrypted content changed\n",
+                self.from_name(),
+                self.to_name()
+            )
+        };
+        self.from_content = String::new();
+        self.to_content = String::new();
+        self.encrypted = true;
+        self
+    }
+
+    ///
+    /// 生成差异;加密配置有解密权限时解密后比较,否则只标记内容是否变更。
+    /// 差异计算不在ConfigActor中执行,避免大配置阻塞配置读写
+    pub async fn build_diff(
+        self,
+        cipher: &ConfigCipher,
+        is_cipher: bool,
+        can_decrypt: bool,
+    ) -> anyhow::Result<Self> {
+        let mut dto = if is_cipher {
+            if !can_decrypt {
+                return Ok(self.mask_cipher());
+            }
+            self.decrypt(cipher)?
+        } else {
+            self
+        };
+        ConfigDiffUtils::check_size(&dto.from_content)?;
+        ConfigDiffUtils::check_size(&dto.to_content)?;
+        Ok(tokio::task::spawn_blocking(move || {
+            dto.diff = ConfigDiffUtils::unified_diff(
+                &dto.from_content,
+                &dto.to_content,
+                &dto.from_name(),
+                &dto.to_name(),
+                ConfigDiffUtils::DEFAULT_CONTEXT,
+            );
+            dto
+        })
+        .await?)
+    }
+}
+
+pub struct ConfigDiffUtils;
+
+impl ConfigDiffUtils {
+    pub const DEFAULT_CONTEXT: usize = 3;
+    /// 参与比较的单个版本内容最大长度
+    pub const MAX_CONTENT_SIZE: usize = 1024 * 1024;
+    /// 差异计算超时后退化为较粗粒度的差异
+    const DIFF_TIMEOUT: Duration = Duration::from_secs(1);
+
+    pub fn check_size(content: &str) -> anyhow::Result<()> {
+        if content.len() > Self::MAX_CONTENT_SIZE {
+            return Err(anyhow::anyhow!(
+                "config content is too large to diff, max size: {}",
+                Self::MAX_CONTENT_SIZE
+            ));
+        }
+        Ok(())
+    }
+
+    ///
+    /// 按行生成unified diff格式的差异,内容相同时返回空字符串;
+    /// 使用线性空间的Myers算法,并限制计算时间
+    pub fn unified_diff(
+        old: &str,
+        new: &str,
+        old_name: &str,
+        new_name: &str,
+        context: usize,
+    ) -> String {
+        let diff = TextDiff::configure()
+            .algorithm(Algorithm::Myers)
+            .timeout(Self::DIFF_TIMEOUT)
+            .diff_lines(old, new);
+        diff.unified_diff()
+            .context_radius(context)
+            .header(old_name, new_name)
+            .to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::config_cipher::ConfigKeyProvider;
+
+    struct StaticKeyProvider(Arc<String>);
+
+    impl ConfigKeyProvider for StaticKeyProvider {
+        fn get_key(&self) -> anyhow::Result<Arc<String>> {
+            Ok(self.0.clone())
+        }
+    }
+
+    #[test]
+    fn unified_diff_same() {
+        let s = "a=1\nb=2\n";
+        assert!(ConfigDiffUtils::unified_diff(s, s, "a", "b", 3).is_empty());
+    }
+
+    #[test]
+    fn unified_diff_change() {
+        let old = "a=1\nb=2\nc=3\n";
+        let new = "a=1\nb=20\nc=3\nd=4\n";
+        let diff = ConfigDiffUtils::unified_diff(old, new, "history/1", "current", 3);
+        assert_eq!(
+            diff,
+            "--- history/1\n+++ current\n@@ -1,3 +1,4 @@\n a=1\n-b=2\n+b=20\n c=3\n+d=4\n"
+        );
+    }
+
+    #[test]
+    fn unified_diff_hunks() {
+        let old: Vec<String> = (1..=20).map(|i| format!("k{i}=v")).collect();
+        let mut new = old.clone();
+        new[1] = "k2=x".to_owned();
+        new[17] = "k18=x".to_owned();
+        let diff = ConfigDiffUtils::unified_diff(&old.join("\n"), &new.join("\n"), "a", "b", 1);
+        assert!(diff.contains("@@ -1,3 +1,3 @@\n k1=v\n-k2=v\n+k2=x\n k3=v\n"));
+        assert!(diff.contains("@@ -17,3 +17,3 @@\n k17=v\n-k18=v\n+k18=x\n k19=v\n"));
+    }
+
+    #[test]
+    fn unified_diff_empty_side() {
+        let diff = ConfigDiffUtils::unified_diff("", "a=1\n", "a", "b", 3);
+        //与GNU diff一致,单行范围省略行数
+        assert_eq!(diff, "--- a\n+++ b\n@@ -0,0 +1 @@\n+a=1\n");
+    }
+
+    #[tokio::test]
+    async fn decrypt_history_diff() {
+        let cipher = ConfigCipher::new(Some(Arc::new(StaticKeyProvider(Arc::new(
+            "0123456789abcdef".to_owned(),
+        )))));
+        let (from_content, from_key) = cipher.encrypt("a=1\nb=2\n").unwrap();
+        let (to_content, to_key) = cipher.encrypt("a=1\nb=3\n").unwrap();
+        let dto = ConfigHistoryDiffDto {
+            from_id: 1,
+            to_id: None,
+            from_content: from_content.as_ref().to_owned(),
+            to_content: to_content.as_ref().to_owned(),
+            from_encrypted_data_key: Some(from_key),
+            to_encrypted_data_key: Some(to_key),
+            ..Default::default()
+        };
+        let masked = dto.clone().build_diff(&cipher, true, false).await.unwrap();
+        assert!(masked.encrypted && masked.to_content.is_empty());
+        let dto = dto.build_diff(&cipher, true, true).await.unwrap();
+        assert_eq!(dto.from_content, "a=1\nb=2\n");
+        assert_eq!(dto.to_content, "a=1\nb=3\n");
+        assert_eq!(
+            dto.diff,
+            "--- history/1\n+++ current\n@@ -1,2 +1,2 @@\n a=1\n-b=2\n+b=3\n"
+        );
+        assert!(!serde_json::to_string(&dto)
+            .unwrap()
+            .contains("encryptedDataKey"));
+    }
+
+    #[test]
+    fn mask_cipher_history_diff() {
+        let build = |from: &str, to: &str| ConfigHistoryDiffDto {
+            from_id: 1,
+            to_id: Some(2),
+            from_content: from.to_owned(),
+            to_content: to.to_owned(),
+            ..Default::default()
+        };
+        let dto = build("c1", "c2").mask_cipher();
+        assert!(dto.encrypted);
+        assert!(dto.from_content.is_empty() && dto.to_content.is_empty());
+        assert_eq!(
+            dto.diff,
+            "--- history/1\n+++ history/2\nencrypted content changed\n"
+        );
+        assert!(build("c1", "c1").mask_cipher().diff.is_empty());
+    }
+
+    #[tokio::test]
+    async fn build_diff_reject_large_content() {
+        let cipher = ConfigCipher::new(None);
+        let dto = ConfigHistoryDiffDto {
+            from_id: 1,
+            from_content: "a\n".repeat(ConfigDiffUtils::MAX_CONTENT_SIZE),
+            to_content: "b\n".to_owned(),
+            ..Default::default()
+        };
+        assert!(dto.build_diff(&cipher, false, false).await.is_err());
+    }
+}
@@ -0,0 +1,104 @@
+use crate::common::fuzzy_watch_utils::FuzzyWatchUtils;
+use crate::config::core::ConfigKey;
+use crate::config::ConfigUtils;
+use std::sync::Arc;
+
+pub const ADD_CONFIG: &str = "ADD_CONFIG";
+pub const DELETE_CONFIG: &str = "DELETE_CONFIG";
+
+///
+/// 配置模糊监听表达式
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FuzzyGroupKeyPattern {
+    pub pattern: Arc<String>,
+    /// 客户端原始命名空间,推送groupKey时保持与客户端一致
+    pub namespace: Arc<String>,
+    pub tenant: Arc<String>,
+    pub group: Arc<String>,
+    pub data_id: Arc<String>,
+}
+
+impl FuzzyGroupKeyPattern {
+    pub fn parse(pattern: &str) -> anyhow::Result<Self> {
+        let (namespace, group, data_id) = FuzzyWatchUtils::split_pattern(pattern)?;
+        Ok(Self {
+            pattern: Arc::new(pattern.to_owned()),
+            namespace: Arc::new(namespace.to_owned()),
+            tenant: Arc::new(ConfigUtils::default_tenant(namespace.to_owned())),
+            group: Arc::new(group.to_owned()),
+            data_id: Arc::new(data_id.to_owned()),
+        })
+    }
+
+    pub fn matches(&self, key: &ConfigKey) -> bool {
+        self.tenant.as_str() == key.tenant.as_str()
+            && FuzzyWatchUtils::match_item(&self.group, &self.data_id, &key.group, &key.data_id)
+    }
+
+    ///
+    /// 与nacos GroupKey.getKeyTenant格式一致: dataId+group+namespace
+    pub fn build_group_key(&self, key: &ConfigKey) -> String {
+        let mut group_key = String::new();
+        Self::encode(&key.data_id, &mut group_key);
+        group_key.push('+');
+        Self::encode(&key.group, &mut group_key);
+        if !self.namespace.is_empty() {
+            group_key.push('+');
+            Self::encode(&self.namespace, &mut group_key);
+        }
+        group_key
+    }
+
+    fn encode(value: &str, out: &mut String) {
+        for c in value.chars() {
+            match c {
+                '+' => out.push_str("%2B"),
+                '%' => out.push_str("%25"),
+                _ => out.push(c),
+            }
+        }
+    }
+}
+
+///
+/// 模糊监听推送内容
+#[derive(Debug, Clone)]
+pub enum ConfigFuzzyWatchNotify {
+    /// 监听建立时的全量/差异同步,contexts为(groupKey,changedType)
+    Sync {
+        pattern: Arc<String>,
+        sync_type: &'static str,
+        contexts: Vec<(String, &'static str)>,
+    },
+    /// 匹配表达式的配置新增或删除
+    Change {
+        group_key: String,
+        change_type: &'static str,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_match() {
+        let pattern = FuzzyGroupKeyPattern::parse("public>>DEFAULT_GROUP>>app-*").unwrap();
+        assert!(pattern.tenant.is_empty());
+        assert!(pattern.matches(&ConfigKey::new("app-db", "DEFAULT_GROUP", "")));
+        assert!(!pattern.matches(&ConfigKey::new("app-db", "DEFAULT_GROUP", "dev")));
+        assert!(!pattern.matches(&ConfigKey::new("web-db", "DEFAULT_GROUP", "")));
+        let pattern = FuzzyGroupKeyPattern::parse("dev>>*>>*").unwrap();
+        assert!(pattern.matches(&ConfigKey::new("a", "G1", "dev")));
+        assert!(FuzzyGroupKeyPattern::parse("dev>>*").is_err());
+    }
+
+    #[test]
+    fn build_group_key() {
+        let pattern = FuzzyGroupKeyPattern::parse("public>>*>>*").unwrap();
+        let key = ConfigKey::new("a+b%", "G", "");
+        assert_eq!(pattern.build_group_key(&key), "a%2Bb%25+G+public");
+        let pattern = FuzzyGroupKeyPattern::parse(">>*>>*").unwrap();
+        assert_eq!(pattern.build_group_key(&key), "a%2Bb%25+G");
+    }
+}
@@ -1,11 +1,14 @@
-use crate::common::model::privilege::NamespacePrivilegeGroup;
+use crate::common::model::privilege::{NamespacePrivilegeGroup, ResourceAction, ResourcePrivilege};
 use crate::common::string_utils::StringUtils;
+use crate::config::config_cipher::ConfigCipher;
+use crate::config::config_fuzzy_watch::FuzzyGroupKeyPattern;
 use crate::config::core::ConfigKey;
 use crate::namespace::model::{NamespaceActorReq, WeakNamespaceFromType, WeakNamespaceParam};
 use crate::namespace::NamespaceActor;
 use actix::Addr;
 use std::{
-    collections::{BTreeMap, BTreeSet},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    ops::Bound,
     sync::Arc,
 };
 
@@ -18,7 +21,13 @@ pub struct ConfigQueryParam {
     pub data_id: Option<Arc<String>>,
     pub like_group: Option<String>,
     pub like_data_id: Option<String>,
+    /// 按配置内容全文检索
+    pub content_search: Option<String>,
+    /// 按配置标签过滤,需同时包含全部标签
+    pub tags: Vec<Arc<String>>,
     pub namespace_privilege: NamespacePrivilegeGroup,
+    /// 用户细粒度数据权限,只返回有读权限的配置
+    pub resource_privilege: ResourcePrivilege,
     pub query_context: bool,
     pub offset: usize,
     pub limit: usize,
@@ -43,6 +52,22 @@ impl ConfigQueryParam {
             true
         }
     }
+
+    pub fn match_resource(&self, tenant: &str, group: &str, data_id: &str) -> bool {
+        self.resource_privilege
+            .check_permission(tenant, group, data_id, ResourceAction::Read)
+    }
+
+    pub fn match_tags(&self, tags: &[Arc<String>]) -> bool {
+        self.tags.iter().all(|t| tags.contains(t))
+    }
+
+    pub fn get_content_search(&self) -> Option<&str> {
+        match &self.content_search {
+            Some(v) if !v.is_empty() => Some(v.as_str()),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Default)]
@@ -98,7 +123,7 @@ impl ConfigIndex {
         for (g, set) in &self.group_data {
             if param.match_group(g) {
                 for s in set {
-                    if param.match_data_id(s) {
+                    if param.match_data_id(s) && param.match_resource(tenant, g, s) {
                         if index >= param.offset && index < end_index {
                             let key = ConfigKey::new_by_arc(s.clone(), g.clone(), tenant.clone());
                             rlist.push(key);
@@ -235,6 +260,27 @@ impl TenantIndex {
         (size, rlist)
     }
 
+    pub fn query_fuzzy_watch_keys(&self, pattern: &FuzzyGroupKeyPattern) -> Vec<ConfigKey> {
+        let mut rlist = vec![];
+        if let Some(index) = self.tenant_group.get(&pattern.tenant) {
+            for (g, set) in &index.group_data {
+                if !StringUtils::wildcard_match(&pattern.group, g) {
+                    continue;
+                }
+                for s in set {
+                    if StringUtils::wildcard_match(&pattern.data_id, s) {
+                        rlist.push(ConfigKey::new_by_arc(
+                            s.clone(),
+                            g.clone(),
+                            pattern.tenant.clone(),
+                        ));
+                    }
+                }
+            }
+        }
+        rlist
+    }
+
     pub fn get_tenant_count(&self) -> usize {
         self.tenant_group.len()
     }
@@ -250,6 +296,136 @@ impl TenantIndex {
     }
 }
 
+/// 将内容按非字母数字字符切分为小写词,返回词及其在文本中的起止位置
+fn split_tokens(text: &str) -> Vec<(usize, usize)> {
+    let mut rlist = vec![];
+    let mut start = None;
+    for (i, c) in text.char_indices() {
+        if c.is_alphanumeric() {
+            if start.is_none() {
+                start = Some(i);
+            }
+        } else if let Some(s) = start.take() {
+            rlist.push((s, i));
+        }
+    }
+    if let Some(s) = start {
+        rlist.push((s, text.len()));
+    }
+    rlist
+}
+
+///
+/// 配置内容倒排索引,随配置变更增量维护
+#[derive(Debug, Clone, Default)]
+pub struct ConfigContentIndex {
+    token_map: BTreeMap<Arc<String>, HashSet<ConfigKey>>,
+    key_tokens: HashMap<ConfigKey, Vec<Arc<String>>>,
+}
+
+impl ConfigContentIndex {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    ///
+    /// 加密配置的内容是密文,不建立索引
+    pub fn update(&mut self, key: ConfigKey, content: &str) {
+        self.remove(&key);
+        if ConfigCipher::is_cipher_data_id(&key.data_id) {
+            return;
+        }
+        let content = content.to_lowercase();
+        let mut token_set = HashSet::new();
+        for (start, end) in split_tokens(&content) {
+            token_set.insert(&content[start..end]);
+        }
+        let mut tokens = Vec::with_capacity(token_set.len());
+        for token in token_set {
+            let token = token.to_owned();
+            let token = match self.token_map.get_key_value(&token) {
+                Some((k, _)) => k.clone(),
+                None => Arc::new(token),
+            };
+            self.token_map
+                .entry(token.clone())
+                .or_default()
+                .insert(key.clone());
+            tokens.push(token);
+        }
+        self.key_tokens.insert(key, tokens);
+    }
+
+    pub fn remove(&mut self, key: &ConfigKey) {
+        if let Some(tokens) = self.key_tokens.remove(key) {
+            for token in tokens {
+                if let Some(set) = self.token_map.get_mut(&token) {
+                    set.remove(key);
+                    if set.is_empty() {
+                        self.token_map.remove(&token);
+                    }
+                }
+            }
+        }
+    }
+
+    ///
+    /// 查询可能包含检索文本的配置;
+    /// 返回None表示检索文本中没有可用的词,需要由调用方全量匹配
+    pub fn search(&self, text: &str) -> Option<HashSet<ConfigKey>> {
+        let text = text.to_lowercase();
+        let mut result: Option<HashSet<ConfigKey>> = None;
+        for (start, end) in split_tokens(&text) {
+            let token = &text[start..end];
+            let token_key = token.to_owned();
+            //词在检索文本中的边界是分隔符时,才能确定是内容词的边界
+            let left_bound = start > 0;
+            let right_bound = end < text.len();
+            let mut keys = HashSet::new();
+            if left_bound && right_bound {
+                if let Some(set) = self.token_map.get(&token_key) {
+                    keys.extend(set.iter().cloned());
+                }
+            } else if left_bound {
+                let range = self
+                    .token_map
+                    .range::<String, _>((Bound::Included(token_key), Bound::Unbounded));
+                for (k, set) in range {
+                    if !k.starts_with(token) {
+                        break;
+                    }
+                    keys.extend(set.iter().cloned());
+                }
+            } else {
+                for (k, set) in &self.token_map {
+                    let matched = if right_bound {
+                        k.ends_with(token)
+                    } else {
+                        k.contains(token)
+                    };
+                    if matched {
+                        keys.extend(set.iter().cloned());
+                    }
+                }
+            }
+            result = match result {
+                Some(v) => Some(v.intersection(&keys).cloned().collect()),
+                None => Some(keys),
+            };
+            if let Some(v) = &result {
+                if v.is_empty() {
+                    break;
+                }
+            }
+        }
+        result
+    }
+
+    pub fn get_token_count(&self) -> usize {
+        self.token_map.len()
+    }
+}
+
 #[test]
 fn add_service() {
     let mut index = TenantIndex::new();
@@ -315,3 +491,66 @@ fn query_service() {
     assert!(size == 0);
     assert!(list.is_empty());
 }
+
+#[test]
+fn content_search() {
+    let mut index = ConfigContentIndex::new();
+    let key1 = ConfigKey::new("1", "1", "1");
+    let key2 = ConfigKey::new("2", "1", "1");
+    let key3 = ConfigKey::new("3", "1", "1");
+    index.update(key1.clone(), "db.host=mysql-01.prod.example.com\nport=3306");
+    index.update(key2.clone(), "redis.host=redis-01.prod.example.com");
+    index.update(key3.clone(), "Name=中文配置");
+
+    let keys = index.search("prod.example").unwrap();
+    assert!(keys.len() == 2);
+    let keys = index.search("mysql-01.PROD").unwrap();
+    assert!(keys.len() == 1 && keys.contains(&key1));
+    let keys = index.search("edi").unwrap();
+    assert!(keys.len() == 1 && keys.contains(&key2));
+    let keys = index.search("中文").unwrap();
+    assert!(keys.len() == 1 && keys.contains(&key3));
+    assert!(index.search("=").is_none());
+
+    index.update(key1.clone(), "db.host=mysql-02.test");
+    assert!(index.search("prod.example").unwrap().len() == 1);
+    index.remove(&key2);
+    index.remove(&key3);
+    assert!(index.search("prod.example").unwrap().is_empty());
+    index.remove(&key1);
+    assert!(index.get_token_count() == 0);
+
+    // 加密配置不建立内容索引
+    index.update(ConfigKey::new("cipher-db", "1", "1"), "password=secret");
+    assert!(index.search("secret").unwrap().is_empty());
+    assert!(index.get_token_count() == 0);
+}
+
+#[test]
+fn match_tags() {
+    let tags = crate::config::ConfigUtils::parse_tags(" app:order, owner:team-a,,app:order");
+    assert!(tags.len() == 2);
+    let mut param = ConfigQueryParam::default();
+    assert!(param.match_tags(&tags));
+    assert!(param.match_tags(&[]));
+    param.tags = crate::config::ConfigUtils::parse_tags("app:order");
+    assert!(param.match_tags(&tags));
+    assert!(!param.match_tags(&[]));
+    param.tags = crate::config::ConfigUtils::parse_tags("app:order,env:prod");
+    assert!(!param.match_tags(&tags));
+}
+
+#[test]
+fn query_fuzzy_watch_keys() {
+    let mut index = TenantIndex::new();
+    index.insert_config(ConfigKey::new("app-db", "G1", ""));
+    index.insert_config(ConfigKey::new("app-web", "G2", ""));
+    index.insert_config(ConfigKey::new("other", "G1", ""));
+    index.insert_config(ConfigKey::new("app-db", "G1", "dev"));
+    let pattern = FuzzyGroupKeyPattern::parse("public>>G*>>app-*").unwrap();
+    let keys = index.query_fuzzy_watch_keys(&pattern);
+    assert_eq!(keys.len(), 2);
+    assert!(keys.iter().all(|k| k.tenant.is_empty()));
+    let pattern = FuzzyGroupKeyPattern::parse("dev>>*>>*").unwrap();
+    assert_eq!(index.query_fuzzy_watch_keys(&pattern).len(), 1);
+}
@@ -3,14 +3,25 @@ use std::{
     sync::Arc,
 };
 
+use super::config_fuzzy_watch::{ConfigFuzzyWatchNotify, FuzzyGroupKeyPattern};
 use super::core::{ConfigKey, ListenerItem};
+use crate::common::model::privilege::{ResourceAction, ResourcePrivilege};
 use crate::grpc::bistream_manage::{BiStreamManage, BiStreamManageCmd};
 use actix::prelude::*;
 
+/// (表达式, 监听的客户端 -> 客户端用户的细粒度数据权限)
+type FuzzyWatchClients = (
+    Arc<FuzzyGroupKeyPattern>,
+    HashMap<Arc<String>, ResourcePrivilege>,
+);
+
 #[derive(Default)]
 pub struct Subscriber {
     listener: HashMap<ConfigKey, HashSet<Arc<String>>>,
     client_keys: HashMap<Arc<String>, HashSet<ConfigKey>>,
+    /// 模糊监听: pattern -> (表达式, 监听的客户端)
+    fuzzy_listener: HashMap<Arc<String>, FuzzyWatchClients>,
+    client_fuzzy_patterns: HashMap<Arc<String>, HashSet<Arc<String>>>,
     conn_manage: Option<Addr<BiStreamManage>>,
 }
 
@@ -19,6 +30,8 @@ impl Subscriber {
         Self {
             listener: Default::default(),
             client_keys: Default::default(),
+            fuzzy_listener: Default::default(),
+            client_fuzzy_patterns: Default::default(),
             conn_manage: Default::default(),
         }
     }
@@ -85,6 +98,11 @@ impl Subscriber {
     }
 
     pub fn remove_client_subscribe(&mut self, client_id: Arc<String>) {
+        if let Some(patterns) = self.client_fuzzy_patterns.remove(&client_id) {
+            for pattern in patterns {
+                self.remove_fuzzy_listener(&pattern, &client_id);
+            }
+        }
         if let Some(set) = self.client_keys.remove(&client_id) {
             let mut remove_keys = vec![];
             for key in set {
@@ -126,6 +144,86 @@ impl Subscriber {
         }
     }
 
+    pub fn add_fuzzy_watch(
+        &mut self,
+        client_id: Arc<String>,
+        pattern: Arc<FuzzyGroupKeyPattern>,
+        resource_privilege: ResourcePrivilege,
+    ) {
+        self.fuzzy_listener
+            .entry(pattern.pattern.clone())
+            .or_insert_with(|| (pattern.clone(), HashMap::new()))
+            .1
+            .insert(client_id.clone(), resource_privilege);
+        self.client_fuzzy_patterns
+            .entry(client_id)
+            .or_default()
+            .insert(pattern.pattern.clone());
+    }
+
+    pub fn remove_fuzzy_watch(&mut self, client_id: Arc<String>, pattern: &Arc<String>) {
+        let mut remove_empty_client = false;
+        if let Some(set) = self.client_fuzzy_patterns.get_mut(&client_id) {
+            set.remove(pattern);
+            remove_empty_client = set.is_empty();
+        }
+        if remove_empty_client {
+            self.client_fuzzy_patterns.remove(&client_id);
+        }
+        self.remove_fuzzy_listener(pattern, &client_id);
+    }
+
+    fn remove_fuzzy_listener(&mut self, pattern: &Arc<String>, client_id: &Arc<String>) {
+        let mut remove_empty_pattern = false;
+        if let Some((_, set)) = self.fuzzy_listener.get_mut(pattern) {
+            set.remove(client_id);
+            remove_empty_pattern = set.is_empty();
+        }
+        if remove_empty_pattern {
+            self.fuzzy_listener.remove(pattern);
+        }
+    }
+
+    pub fn notify_fuzzy_watch_sync(&self, client_id: Arc<String>, notify: ConfigFuzzyWatchNotify) {
+        if let Some(conn_manage) = &self.conn_manage {
+            let mut set = HashSet::new();
+            set.insert(client_id);
+            conn_manage.do_send(BiStreamManageCmd::NotifyConfigFuzzyWatch(notify, set));
+        }
+    }
+
+    ///
+    /// 配置新增或删除时通知匹配且有读权限的模糊监听客户端
+    pub fn notify_fuzzy_watch(&self, key: &ConfigKey, change_type: &'static str) {
+        if let Some(conn_manage) = &self.conn_manage {
+            for (pattern, clients) in self.fuzzy_listener.values() {
+                if !pattern.matches(key) {
+                    continue;
+                }
+                let set: HashSet<Arc<String>> = clients
+                    .iter()
+                    .filter(|(_, privilege)| {
+                        privilege.check_permission(
+                            &key.tenant,
+                            &key.group,
+                            &key.data_id,
+                            ResourceAction::Read,
+                        )
+                    })
+                    .map(|(client_id, _)| client_id.clone())
+                    .collect();
+                if set.is_empty() {
+                    continue;
+                }
+                let notify = ConfigFuzzyWatchNotify::Change {
+                    group_key: pattern.build_group_key(key),
+                    change_type,
+                };
+                conn_manage.do_send(BiStreamManageCmd::NotifyConfigFuzzyWatch(notify, set));
+            }
+        }
+    }
+
     pub fn get_listener_key_size(&self) -> usize {
         self.listener.len()
     }
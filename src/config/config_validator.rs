@@ -0,0 +1,284 @@
+use crate::config::config_type::ConfigType;
+use crate::namespace::model::{NamespaceQueryReq, NamespaceQueryResult};
+use crate::namespace::NamespaceActor;
+use actix::Addr;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+
+///
+/// 配置内容校验错误,行号与列号从1开始
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigValidateError {
+    pub config_type: Arc<String>,
+    pub message: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl ConfigValidateError {
+    pub fn new(config_type: &ConfigType, message: String, line: usize, column: usize) -> Self {
+        Self {
+            config_type: config_type.get_value(),
+            message,
+            line,
+            column,
+        }
+    }
+
+    /// 根据字节位置计算行号与列号
+    pub fn new_by_index(
+        config_type: &ConfigType,
+        message: String,
+        content: &str,
+        index: usize,
+    ) -> Self {
+        let mut line = 1;
+        let mut column = 1;
+        for (i, c) in content.char_indices() {
+            if i >= index {
+                break;
+            }
+            if c == '\n' {
+                line += 1;
+                column = 1;
+            } else {
+                column += 1;
+            }
+        }
+        Self::new(config_type, message, line, column)
+    }
+}
+
+impl Display for ConfigValidateError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "invalid {} content at line {} column {}: {}",
+            &self.config_type, self.line, self.column, &self.message
+        )
+    }
+}
+
+impl std::error::Error for ConfigValidateError {}
+
+///
+/// 配置内容校验器,按配置类型解析内容
+pub trait ConfigContentValidator: Send + Sync {
+    fn validate(&self, content: &str) -> Result<(), ConfigValidateError>;
+}
+
+pub struct JsonValidator;
+
+impl ConfigContentValidator for JsonValidator {
+    fn validate(&self, content: &str) -> Result<(), ConfigValidateError> {
+        match serde_json::from_str::<serde_json::Value>(content) {
+            Ok(_) => Ok(()),
+            Err(err) => Err(ConfigValidateError::new(
+                &ConfigType::Json,
+                err.to_string(),
+                err.line(),
+                err.column(),
+            )),
+        }
+    }
+}
+
+pub struct YamlValidator;
+
+impl ConfigContentValidator for YamlValidator {
+    fn validate(&self, content: &str) -> Result<(), ConfigValidateError> {
+        match serde_yml::from_str::<serde_yml::Value>(content) {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                let (line, column) = err
+                    .location()
+                    .map(|v| (v.line(), v.column()))
+                    .unwrap_or((1, 1));
+                Err(ConfigValidateError::new(
+                    &ConfigType::Yaml,
+                    err.to_string(),
+                    line,
+                    column,
+                ))
+            }
+        }
+    }
+}
+
+pub struct TomlValidator;
+
+impl ConfigContentValidator for TomlValidator {
+    fn validate(&self, content: &str) -> Result<(), ConfigValidateError> {
+        match toml::from_str::<toml::Table>(content) {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                let index = err.span().map(|v| v.start).unwrap_or_default();
+                Err(ConfigValidateError::new_by_index(
+                    &ConfigType::Toml,
+                    err.message().to_owned(),
+                    content,
+                    index,
+                ))
+            }
+        }
+    }
+}
+
+pub struct XmlValidator;
+
+impl ConfigContentValidator for XmlValidator {
+    fn validate(&self, content: &str) -> Result<(), ConfigValidateError> {
+        match roxmltree::Document::parse(content) {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                let pos = err.pos();
+                Err(ConfigValidateError::new(
+                    &ConfigType::Xml,
+                    err.to_string(),
+                    pos.row as usize,
+                    pos.col as usize,
+                ))
+            }
+        }
+    }
+}
+
+///
+/// properties格式较宽松,只校验\uXXXX转义是否合法
+pub struct PropertiesValidator;
+
+impl ConfigContentValidator for PropertiesValidator {
+    fn validate(&self, content: &str) -> Result<(), ConfigValidateError> {
+        for (i, line) in content.lines().enumerate() {
+            let chars: Vec<char> = line.chars().collect();
+            let mut j = 0;
+            while j < chars.len() {
+                if chars[j] == '\\' {
+                    if chars.get(j + 1) == Some(&'u') {
+                        let valid = chars.len() >= j + 6
+                            && chars[j + 2..j + 6].iter().all(|c| c.is_ascii_hexdigit());
+                        if !valid {
+                            return Err(ConfigValidateError::new(
+                                &ConfigType::Properties,
+                                "malformed \\uxxxx encoding".to_owned(),
+                                i + 1,
+                                j + 1,
+                            ));
+                        }
+                    }
+                    j += 2;
+                } else {
+                    j += 1;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+///
+/// 配置内容校验管理器,可按配置类型注册自定义校验器
+pub struct ConfigValidatorManager {
+    validators: HashMap<ConfigType, Arc<dyn ConfigContentValidator>>,
+}
+
+impl Default for ConfigValidatorManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ConfigValidatorManager {
+    pub fn new() -> Self {
+        let mut manager = Self {
+            validators: HashMap::new(),
+        };
+        manager.register(ConfigType::Json, Arc::new(JsonValidator));
+        manager.register(ConfigType::Yaml, Arc::new(YamlValidator));
+        manager.register(ConfigType::Toml, Arc::new(TomlValidator));
+        manager.register(ConfigType::Xml, Arc::new(XmlValidator));
+        manager.register(ConfigType::Properties, Arc::new(PropertiesValidator));
+        manager
+    }
+
+    pub fn register(
+        &mut self,
+        config_type: ConfigType,
+        validator: Arc<dyn ConfigContentValidator>,
+    ) {
+        self.validators.insert(config_type, validator);
+    }
+
+    pub fn validate(&self, config_type: &str, content: &str) -> Result<(), ConfigValidateError> {
+        if let Some(validator) = self.validators.get(&ConfigType::new_by_value(config_type)) {
+            validator.validate(content)
+        } else {
+            Ok(())
+        }
+    }
+
+    ///
+    /// 按命名空间的校验开关处理校验结果;
+    /// 未开启强制校验时只打印告警,不拒绝发布
+    pub async fn validate_by_namespace(
+        &self,
+        namespace_addr: &Addr<NamespaceActor>,
+        tenant: &Arc<String>,
+        config_type: Option<&str>,
+        content: &str,
+    ) -> Result<(), ConfigValidateError> {
+        let config_type = match config_type {
+            Some(v) if !v.is_empty() => v,
+            _ => return Ok(()),
+        };
+        if let Err(err) = self.validate(config_type, content) {
+            let enforce = matches!(
+                namespace_addr
+                    .send(NamespaceQueryReq::Info(tenant.clone()))
+                    .await,
+                Ok(Ok(NamespaceQueryResult::Info(v))) if v.config_validate_enforce
+            );
+            if enforce {
+                return Err(err);
+            }
+            log::warn!("config content validate fail,tenant:{},{}", tenant, &err);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_content() {
+        let manager = ConfigValidatorManager::new();
+        assert!(manager.validate("json", r#"{"a":1}"#).is_ok());
+        let err = manager.validate("json", "{\n\"a\":}").unwrap_err();
+        assert_eq!(err.line, 2);
+
+        assert!(manager.validate("yaml", "a:\n  b: 1\n").is_ok());
+        let err = manager.validate("yaml", "a: 1\n b: 2\n").unwrap_err();
+        assert_eq!(err.line, 2);
+
+        assert!(manager.validate("toml", "[a]\nb = 1\n").is_ok());
+        let err = manager.validate("toml", "[a]\nb = \n").unwrap_err();
+        assert_eq!(err.line, 2);
+
+        assert!(manager.validate("xml", "<a><b>1</b></a>").is_ok());
+        let err = manager.validate("xml", "<a>\n<b>1</a>").unwrap_err();
+        assert_eq!(err.line, 2);
+
+        assert!(manager.validate("properties", "a=\\u0041\nb=2").is_ok());
+        let err = manager
+            .validate("properties", "a=1\nb=\\u00G1")
+            .unwrap_err();
+        assert_eq!((err.line, err.column), (2, 3));
+
+        assert!(manager.validate("text", "{").is_ok());
+    }
+}
@@ -0,0 +1,613 @@
+use crate::common::constant::CONFIG_WEBHOOK_LOG_TREE_NAME;
+use crate::common::string_utils::StringUtils;
+use crate::common::AppSysConfig;
+use crate::config::core::ConfigKey;
+use crate::now_millis_i64;
+use crate::raft::db::route::TableRoute;
+use crate::raft::db::table::{
+    TableManager, TableManagerQueryReq, TableManagerReq, TableManagerResult,
+};
+use actix::prelude::*;
+use bean_factory::{bean, BeanFactory, FactoryData, Inject};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+
+pub const EVENT_TYPE_UPDATE: &str = "UPDATE";
+pub const EVENT_TYPE_DELETE: &str = "DELETE";
+
+const MAX_DELIVERY_LOG_SIZE: usize = 1000;
+
+///
+/// 配置变更webhook;namespace、group为空时匹配全部,data_id_pattern支持`*`与`?`通配符
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigWebhook {
+    pub id: u64,
+    pub name: Arc<String>,
+    pub url: Arc<String>,
+    pub tenant: Option<Arc<String>>,
+    pub group: Option<Arc<String>>,
+    pub data_id_pattern: Option<Arc<String>>,
+    pub enable: bool,
+    pub update_time: i64,
+}
+
+impl ConfigWebhook {
+    pub fn matches(&self, key: &ConfigKey) -> bool {
+        if !self.enable {
+            return false;
+        }
+        if let Some(tenant) = &self.tenant {
+            if tenant.as_str() != key.tenant.as_str() {
+                return false;
+            }
+        }
+        if let Some(group) = &self.group {
+            if group.as_str() != key.group.as_str() {
+                return false;
+            }
+        }
+        if let Some(pattern) = &self.data_id_pattern {
+            return StringUtils::wildcard_match(pattern, &key.data_id);
+        }
+        true
+    }
+}
+
+#[derive(Clone, PartialEq, prost::Message, Deserialize, Serialize)]
+pub struct ConfigWebhookDO {
+    #[prost(uint64, tag = "1")]
+    pub id: u64,
+    #[prost(string, tag = "2")]
+    pub name: String,
+    #[prost(string, tag = "3")]
+    pub url: String,
+    #[prost(string, optional, tag = "4")]
+    pub tenant: Option<String>,
+    #[prost(string, optional, tag = "5")]
+    pub group: Option<String>,
+    #[prost(string, optional, tag = "6")]
+    pub data_id_pattern: Option<String>,
+    #[prost(bool, tag = "7")]
+    pub enable: bool,
+    #[prost(int64, tag = "8")]
+    pub update_time: i64,
+}
+
+impl ConfigWebhookDO {
+    pub fn to_bytes(&self) -> anyhow::Result<Vec<u8>> {
+        use prost::Message;
+        let mut v = Vec::new();
+        self.encode(&mut v)?;
+        Ok(v)
+    }
+
+    pub fn from_bytes(data: &[u8]) -> anyhow::Result<Self> {
+        use prost::Message;
+        let s = Self::decode(data)?;
+        Ok(s)
+    }
+}
+
+impl From<&ConfigWebhook> for ConfigWebhookDO {
+    fn from(value: &ConfigWebhook) -> Self {
+        Self {
+            id: value.id,
+            name: value.name.as_ref().to_owned(),
+            url: value.url.as_ref().to_owned(),
+            tenant: value.tenant.as_ref().map(|v| v.as_ref().to_owned()),
+            group: value.group.as_ref().map(|v| v.as_ref().to_owned()),
+            data_id_pattern: value
+                .data_id_pattern
+                .as_ref()
+                .map(|v| v.as_ref().to_owned()),
+            enable: value.enable,
+            update_time: value.update_time,
+        }
+    }
+}
+
+impl From<ConfigWebhookDO> for ConfigWebhook {
+    fn from(value: ConfigWebhookDO) -> Self {
+        Self {
+            id: value.id,
+            name: Arc::new(value.name),
+            url: Arc::new(value.url),
+            tenant: value.tenant.map(Arc::new),
+            group: value.group.map(Arc::new),
+            data_id_pattern: value.data_id_pattern.map(Arc::new),
+            enable: value.enable,
+            update_time: value.update_time,
+        }
+    }
+}
+
+///
+/// webhook raft请求;新增时的id由接口从序列中预先分配,删除后的id不会被复用
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum ConfigWebhookRaftReq {
+    Update { webhook: ConfigWebhook },
+    Remove { id: u64 },
+}
+
+///
+/// 推送给webhook的配置变更事件
+#[derive(Clone, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigChangeEvent {
+    pub event_type: &'static str,
+    pub tenant: Arc<String>,
+    pub group: Arc<String>,
+    pub data_id: Arc<String>,
+    pub old_md5: Option<Arc<String>>,
+    pub new_md5: Option<Arc<String>>,
+    pub op_user: Option<Arc<String>>,
+    pub op_time: i64,
+}
+
+impl ConfigChangeEvent {
+    pub fn new(
+        event_type: &'static str,
+        key: &ConfigKey,
+        old_md5: Option<Arc<String>>,
+        new_md5: Option<Arc<String>>,
+        op_user: Option<Arc<String>>,
+        op_time: i64,
+    ) -> Self {
+        Self {
+            event_type,
+            tenant: key.tenant.clone(),
+            group: key.group.clone(),
+            data_id: key.data_id.clone(),
+            old_md5,
+            new_md5,
+            op_user,
+            op_time,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigWebhookDeliveryLog {
+    pub webhook_id: u64,
+    pub url: Arc<String>,
+    pub event_type: Arc<String>,
+    pub tenant: Arc<String>,
+    pub group: Arc<String>,
+    pub data_id: Arc<String>,
+    pub success: bool,
+    /// 总尝试次数,包含首次推送
+    pub attempts: u32,
+    pub status_code: Option<u16>,
+    pub error: Option<String>,
+    pub create_time: i64,
+}
+
+impl ConfigWebhookDeliveryLog {
+    ///
+    /// 日志表key按时间有序,同一毫秒内按节点与序号区分
+    pub fn build_key(&self, node_id: u64, seq: u32) -> String {
+        format!("{:016}-{:04}-{:06}", self.create_time, node_id, seq)
+    }
+}
+
+///
+/// 配置变更webhook推送actor,失败时按指数退避重试;
+/// 推送日志写入raft表,各节点都可查询,主节点切换或重启后仍保留
+#[bean(inject)]
+pub struct ConfigWebhookNotifyActor {
+    client: reqwest::Client,
+    max_retry: u32,
+    retry_interval: Duration,
+    node_id: u64,
+    seq: u32,
+    raft_table_route: Option<Arc<TableRoute>>,
+    table_manager: Option<Addr<TableManager>>,
+}
+
+impl Default for ConfigWebhookNotifyActor {
+    fn default() -> Self {
+        Self::new(3, Duration::from_secs(1))
+    }
+}
+
+impl ConfigWebhookNotifyActor {
+    pub fn new(max_retry: u32, retry_interval: Duration) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(5))
+            .build()
+            .unwrap_or_default();
+        Self {
+            client,
+            max_retry,
+            retry_interval,
+            node_id: 0,
+            seq: 0,
+            raft_table_route: None,
+            table_manager: None,
+        }
+    }
+
+    fn add_log(&mut self, log: ConfigWebhookDeliveryLog) {
+        let (raft_table_route, table_manager) =
+            match (self.raft_table_route.clone(), self.table_manager.clone()) {
+                (Some(a), Some(b)) => (a, b),
+                _ => return,
+            };
+        self.seq = (self.seq + 1) % 1_000_000;
+        let req = TableManagerReq::Set {
+            table_name: CONFIG_WEBHOOK_LOG_TREE_NAME.clone(),
+            key: log.build_key(self.node_id, self.seq).into_bytes(),
+            value: serde_json::to_vec(&log).unwrap_or_default(),
+            last_seq_id: None,
+        };
+        tokio::spawn(async move {
+            if let Err(err) = raft_table_route.request(req).await {
+                log::warn!("write config webhook log error: {}", err);
+                return;
+            }
+            if let Err(err) = Self::clear_overflow(&raft_table_route, &table_manager).await {
+                log::warn!("clear config webhook log error: {}", err);
+            }
+        });
+    }
+
+    ///
+    /// 只保留最近MAX_DELIVERY_LOG_SIZE条推送日志
+    async fn clear_overflow(
+        raft_table_route: &TableRoute,
+        table_manager: &Addr<TableManager>,
+    ) -> anyhow::Result<()> {
+        let query_req = TableManagerQueryReq::QueryRangeList {
+            table_name: CONFIG_WEBHOOK_LOG_TREE_NAME.clone(),
+            start_key: None,
+            end_key: None,
+            limit: Some(1),
+            is_rev: false,
+        };
+        let size = match table_manager.send(query_req).await?? {
+            TableManagerResult::PageListResult(size, _) => size,
+            _ => return Ok(()),
+        };
+        if size <= MAX_DELIVERY_LOG_SIZE {
+            return Ok(());
+        }
+        let query_req = TableManagerQueryReq::QueryRangeList {
+            table_name: CONFIG_WEBHOOK_LOG_TREE_NAME.clone(),
+            start_key: None,
+            end_key: None,
+            limit: Some((size - MAX_DELIVERY_LOG_SIZE) as i64),
+            is_rev: false,
+        };
+        if let TableManagerResult::PageListResult(_, items) =
+            table_manager.send(query_req).await??
+        {
+            for (key, _) in items {
+                let req = TableManagerReq::Remove {
+                    table_name: CONFIG_WEBHOOK_LOG_TREE_NAME.clone(),
+                    key,
+                };
+                raft_table_route.request(req).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn query_logs(
+        table_manager: Option<Addr<TableManager>>,
+        webhook_id: Option<u64>,
+        limit: usize,
+    ) -> anyhow::Result<Vec<Arc<ConfigWebhookDeliveryLog>>> {
+        let table_manager = if let Some(table_manager) = table_manager {
+            table_manager
+        } else {
+            return Ok(vec![]);
+        };
+        let query_req = TableManagerQueryReq::QueryRangeList {
+            table_name: CONFIG_WEBHOOK_LOG_TREE_NAME.clone(),
+            start_key: None,
+            end_key: None,
+            limit: Some(MAX_DELIVERY_LOG_SIZE as i64),
+            is_rev: true,
+        };
+        let items = match table_manager.send(query_req).await?? {
+            TableManagerResult::PageListResult(_, items) => items,
+            _ => vec![],
+        };
+        let mut list = vec![];
+        for (_, v) in items {
+            let log: ConfigWebhookDeliveryLog = serde_json::from_slice(&v)?;
+            if webhook_id.is_none_or(|id| log.webhook_id == id) {
+                list.push(Arc::new(log));
+                if list.len() >= limit {
+                    break;
+                }
+            }
+        }
+        Ok(list)
+    }
+
+    async fn deliver(
+        client: reqwest::Client,
+        webhook: Arc<ConfigWebhook>,
+        event: Arc<ConfigChangeEvent>,
+        max_retry: u32,
+        retry_interval: Duration,
+    ) -> ConfigWebhookDeliveryLog {
+        let mut log = ConfigWebhookDeliveryLog {
+            webhook_id: webhook.id,
+            url: webhook.url.clone(),
+            event_type: Arc::new(event.event_type.to_owned()),
+            tenant: event.tenant.clone(),
+            group: event.group.clone(),
+            data_id: event.data_id.clone(),
+            ..Default::default()
+        };
+        let mut interval = retry_interval;
+        loop {
+            log.attempts += 1;
+            match client
+                .post(webhook.url.as_str())
+                .json(event.as_ref())
+                .send()
+                .await
+            {
+                Ok(resp) => {
+                    let status = resp.status();
+                    log.status_code = Some(status.as_u16());
+                    if status.is_success() {
+                        log.success = true;
+                        log.error = None;
+                        break;
+                    }
+                    log.error = Some(format!("response status {}", status));
+                }
+                Err(err) => {
+                    log.status_code = None;
+                    log.error = Some(err.to_string());
+                }
+            }
+            if log.attempts > max_retry {
+                break;
+            }
+            tokio::time::sleep(interval).await;
+            interval *= 2;
+        }
+        if !log.success {
+            log::warn!(
+                "config webhook delivery failed,id:{},url:{},error:{}",
+                webhook.id,
+                webhook.url,
+                log.error.as_deref().unwrap_or_default()
+            );
+        }
+        log.create_time = now_millis_i64();
+        log
+    }
+}
+
+impl Actor for ConfigWebhookNotifyActor {
+    type Context = Context<Self>;
+
+    fn started(&mut self, _ctx: &mut Self::Context) {
+        log::info!("ConfigWebhookNotifyActor started");
+    }
+}
+
+impl Inject for ConfigWebhookNotifyActor {
+    type Context = Context<Self>;
+
+    fn inject(
+        &mut self,
+        factory_data: FactoryData,
+        _factory: BeanFactory,
+        _ctx: &mut Self::Context,
+    ) {
+        let sys_config: Option<Arc<AppSysConfig>> = factory_data.get_bean();
+        self.node_id = sys_config.map(|e| e.raft_node_id).unwrap_or_default();
+        self.raft_table_route = factory_data.get_bean();
+        self.table_manager = factory_data.get_actor();
+    }
+}
+
+#[derive(Message)]
+#[rtype(result = "anyhow::Result<ConfigWebhookNotifyResult>")]
+pub enum ConfigWebhookNotifyCmd {
+    Notify {
+        webhooks: Vec<Arc<ConfigWebhook>>,
+        event: Arc<ConfigChangeEvent>,
+    },
+    QueryLogs {
+        webhook_id: Option<u64>,
+        limit: usize,
+    },
+}
+
+pub enum ConfigWebhookNotifyResult {
+    None,
+    Logs(Vec<Arc<ConfigWebhookDeliveryLog>>),
+}
+
+impl Handler<ConfigWebhookNotifyCmd> for ConfigWebhookNotifyActor {
+    type Result = ResponseActFuture<Self, anyhow::Result<ConfigWebhookNotifyResult>>;
+
+    fn handle(&mut self, msg: ConfigWebhookNotifyCmd, ctx: &mut Self::Context) -> Self::Result {
+        match msg {
+            ConfigWebhookNotifyCmd::Notify { webhooks, event } => {
+                for webhook in webhooks {
+                    Self::deliver(
+                        self.client.clone(),
+                        webhook,
+                        event.clone(),
+                        self.max_retry,
+                        self.retry_interval,
+                    )
+                    .into_actor(self)
+                    .map(|log, act, _ctx| act.add_log(log))
+                    .spawn(ctx);
+                }
+                Box::pin(async { Ok(ConfigWebhookNotifyResult::None) }.into_actor(self))
+            }
+            ConfigWebhookNotifyCmd::QueryLogs { webhook_id, limit } => {
+                let table_manager = self.table_manager.clone();
+                let fut = async move {
+                    let list = Self::query_logs(table_manager, webhook_id, limit).await?;
+                    Ok(ConfigWebhookNotifyResult::Logs(list))
+                }
+                .into_actor(self)
+                .map(|r, _act, _ctx| r);
+                Box::pin(fut)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn webhook_matches() {
+        let key = ConfigKey::new("app-db.yaml", "DEFAULT_GROUP", "dev");
+        let mut webhook = ConfigWebhook {
+            enable: true,
+            ..Default::default()
+        };
+        assert!(webhook.matches(&key));
+        webhook.tenant = Some(Arc::new("dev".to_owned()));
+        webhook.data_id_pattern = Some(Arc::new("app-*.yaml".to_owned()));
+        assert!(webhook.matches(&key));
+        webhook.group = Some(Arc::new("OTHER".to_owned()));
+        assert!(!webhook.matches(&key));
+        webhook.group = None;
+        webhook.data_id_pattern = Some(Arc::new("app-??.yaml".to_owned()));
+        assert!(webhook.matches(&key));
+        webhook.data_id_pattern = Some(Arc::new("app-*.json".to_owned()));
+        assert!(!webhook.matches(&key));
+        webhook.data_id_pattern = None;
+        webhook.enable = false;
+        assert!(!webhook.matches(&key));
+    }
+
+    #[test]
+    fn delivery_log_key_order() {
+        let mut log = ConfigWebhookDeliveryLog {
+            create_time: 999,
+            ..Default::default()
+        };
+        let k1 = log.build_key(2, 999_999);
+        log.create_time = 1000;
+        let k2 = log.build_key(1, 1);
+        assert!(k1 < k2);
+        assert_eq!(k2, "0000000000001000-0001-000001");
+    }
+
+    /// 本地http桩:依次返回给定状态码,并把收到的请求体发回
+    async fn start_stub(
+        status_list: Vec<u16>,
+    ) -> (String, tokio::sync::mpsc::UnboundedReceiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            for status in status_list {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut buf = vec![];
+                let mut chunk = [0u8; 1024];
+                let body = loop {
+                    let n = stream.read(&mut chunk).await.unwrap();
+                    buf.extend_from_slice(&chunk[..n]);
+                    let text = String::from_utf8_lossy(&buf).to_string();
+                    if let Some(pos) = text.find("\r\n\r\n") {
+                        let content_length = text[..pos]
+                            .lines()
+                            .filter_map(|line| line.split_once(':'))
+                            .find(|(k, _)| k.eq_ignore_ascii_case("content-length"))
+                            .map(|(_, v)| v.trim().parse::<usize>().unwrap())
+                            .unwrap_or_default();
+                        if buf.len() >= pos + 4 + content_length || n == 0 {
+                            break text[pos + 4..].to_owned();
+                        }
+                    }
+                };
+                let resp = format!("HTTP/1.1 {} OK\r\nContent-Length: 0\r\n\r\n", status);
+                stream.write_all(resp.as_bytes()).await.unwrap();
+                tx.send(body).ok();
+            }
+        });
+        (format!("http://{}/hook", addr), rx)
+    }
+
+    #[tokio::test]
+    async fn deliver_with_retry() {
+        let (url, mut rx) = start_stub(vec![500, 200]).await;
+        let webhook = Arc::new(ConfigWebhook {
+            id: 1,
+            url: Arc::new(url),
+            enable: true,
+            ..Default::default()
+        });
+        let key = ConfigKey::new("app.yaml", "DEFAULT_GROUP", "");
+        let event = Arc::new(ConfigChangeEvent::new(
+            EVENT_TYPE_UPDATE,
+            &key,
+            Some(Arc::new("old".to_owned())),
+            Some(Arc::new("new".to_owned())),
+            Some(Arc::new("admin".to_owned())),
+            1,
+        ));
+        let log = ConfigWebhookNotifyActor::deliver(
+            reqwest::Client::new(),
+            webhook,
+            event,
+            3,
+            Duration::from_millis(10),
+        )
+        .await;
+        assert!(log.success);
+        assert_eq!(log.attempts, 2);
+        assert_eq!(log.status_code, Some(200));
+        rx.recv().await.unwrap();
+        let body: serde_json::Value = serde_json::from_str(&rx.recv().await.unwrap()).unwrap();
+        assert_eq!(body["eventType"], "UPDATE");
+        assert_eq!(body["dataId"], "app.yaml");
+        assert_eq!(body["oldMd5"], "old");
+        assert_eq!(body["newMd5"], "new");
+        assert_eq!(body["opUser"], "admin");
+    }
+
+    #[tokio::test]
+    async fn deliver_failed() {
+        let (url, _rx) = start_stub(vec![500, 502]).await;
+        let webhook = Arc::new(ConfigWebhook {
+            id: 2,
+            url: Arc::new(url),
+            enable: true,
+            ..Default::default()
+        });
+        let key = ConfigKey::new("app.yaml", "DEFAULT_GROUP", "");
+        let event = Arc::new(ConfigChangeEvent::new(
+            EVENT_TYPE_DELETE,
+            &key,
+            Some(Arc::new("old".to_owned())),
+            None,
+            None,
+            1,
+        ));
+        let log = ConfigWebhookNotifyActor::deliver(
+            reqwest::Client::new(),
+            webhook,
+            event,
+            1,
+            Duration::from_millis(10),
+        )
+        .await;
+        assert!(!log.success);
+        assert_eq!(log.attempts, 2);
+        assert_eq!(log.status_code, Some(502));
+    }
+}
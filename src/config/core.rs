@@ -4,6 +4,7 @@ use bean_factory::Inject;
 use chrono::Local;
 use std::collections::BTreeMap;
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::hash::Hash;
 use std::sync::Arc;
 use std::sync::Weak;
@@ -15,15 +16,31 @@ use crate::utils::get_md5;
 use serde::{Deserialize, Serialize};
 
 use crate::common::byte_utils::id_to_bin;
-use crate::common::constant::{CONFIG_TREE_NAME, SEQUENCE_TREE_NAME, SEQ_KEY_CONFIG};
+use crate::common::constant::{
+    CONFIG_TREE_NAME, CONFIG_WEBHOOK_TREE_NAME, SEQUENCE_TREE_NAME, SEQ_KEY_CONFIG,
+};
+use crate::common::model::privilege::{ResourceAction, ResourcePrivilege};
 use crate::common::sequence_utils::SimpleSequence;
 use actix::prelude::*;
 
 use super::config_subscribe::Subscriber;
 use super::dal::ConfigHistoryParam;
-use crate::config::config_index::{ConfigQueryParam, TenantIndex};
+use crate::common::fuzzy_watch_utils::{
+    FINISH_FUZZY_WATCH_INIT_NOTIFY, FUZZY_WATCH_DIFF_SYNC_NOTIFY, FUZZY_WATCH_INIT_NOTIFY,
+};
+use crate::config::config_cipher::ConfigCipher;
+use crate::config::config_diff::ConfigHistoryDiffDto;
+use crate::config::config_fuzzy_watch::{
+    ConfigFuzzyWatchNotify, FuzzyGroupKeyPattern, ADD_CONFIG, DELETE_CONFIG,
+};
+use crate::config::config_index::{ConfigContentIndex, ConfigQueryParam, TenantIndex};
 use crate::config::config_type::ConfigType;
+use crate::config::config_webhook::{
+    ConfigChangeEvent, ConfigWebhook, ConfigWebhookDO, ConfigWebhookNotifyActor,
+    ConfigWebhookNotifyCmd, ConfigWebhookRaftReq, EVENT_TYPE_DELETE, EVENT_TYPE_UPDATE,
+};
 use crate::config::model::{
+    ConfigClientInfo, ConfigGrayInfoDto, ConfigGrayRaftReq, ConfigGrayRule, ConfigGrayValue,
     ConfigRaftCmd, ConfigRaftResult, ConfigValueDO, HistoryItem, SetConfigParam,
 };
 use crate::config::utils::param_utils;
@@ -116,7 +133,11 @@ pub struct ConfigValue {
     pub(crate) histories: Vec<HistoryItem>,
     pub(crate) config_type: Option<Arc<String>>,
     pub(crate) desc: Option<Arc<String>>,
+    pub(crate) tags: Vec<Arc<String>>,
+    /// 加密配置的数据密钥,为空表示未加密
+    pub(crate) encrypted_data_key: Option<Arc<String>>,
     pub(crate) last_modified: i64,
+    pub(crate) gray: Option<ConfigGrayValue>,
 }
 
 impl ConfigValue {
@@ -129,7 +150,10 @@ impl ConfigValue {
             histories: vec![],
             config_type: None,
             desc: None,
+            tags: vec![],
+            encrypted_data_key: None,
             last_modified: now_millis_i64(),
+            gray: None,
         }
     }
 
@@ -154,10 +178,14 @@ impl ConfigValue {
                 content,
                 modified_time: op_time,
                 op_user,
+                encrypted_data_key: None,
             }],
             config_type: None,
             desc: None,
+            tags: vec![],
+            encrypted_data_key: None,
             last_modified: op_time,
+            gray: None,
         }
     }
 
@@ -182,6 +210,7 @@ impl ConfigValue {
             content,
             modified_time: op_time,
             op_user,
+            encrypted_data_key: None,
         };
         if self.histories.len() >= 100 {
             self.histories.remove(0);
@@ -189,6 +218,36 @@ impl ConfigValue {
         self.last_modified = op_time;
         self.histories.push(item);
     }
+
+    ///
+    /// 设置当前内容的数据密钥,同时记录到最新的历史记录中
+    pub fn set_encrypted_data_key(&mut self, encrypted_data_key: Option<Arc<String>>) {
+        if let Some(item) = self.histories.last_mut() {
+            item.encrypted_data_key = encrypted_data_key.clone();
+        }
+        self.encrypted_data_key = encrypted_data_key;
+    }
+
+    ///
+    /// 获取客户端命中的灰度配置，未命中则返回None
+    pub fn get_gray_by_client(&self, client: &ConfigClientInfo) -> Option<&ConfigGrayValue> {
+        if let Some(gray) = &self.gray {
+            if gray.rule.match_client(client) {
+                return Some(gray);
+            }
+        }
+        None
+    }
+
+    ///
+    /// 获取客户端对应的md5，灰度客户端使用灰度配置的md5
+    pub fn get_md5_by_client(&self, client: &ConfigClientInfo) -> &Arc<String> {
+        if let Some(gray) = self.get_gray_by_client(client) {
+            &gray.md5
+        } else {
+            &self.md5
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Default)]
@@ -200,6 +259,8 @@ pub struct ConfigInfoDto {
     pub content: Option<Arc<String>>,
     pub md5: Option<Arc<String>>,
     pub desc: Option<Arc<String>>,
+    pub tags: Vec<Arc<String>>,
+    pub encrypted_data_key: Option<Arc<String>>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Default, Clone)]
@@ -212,6 +273,7 @@ pub struct ConfigHistoryInfoDto {
     pub content: Option<String>,
     pub modified_time: Option<i64>, //给历史记录使用
     pub op_user: Option<String>,
+    pub encrypted_data_key: Option<String>,
 }
 
 #[derive(Debug)]
@@ -399,9 +461,12 @@ pub struct ConfigActor {
     pub(crate) listener: ConfigListener,
     pub(crate) subscriber: Subscriber,
     pub(crate) tenant_index: TenantIndex,
+    pub(crate) content_index: ConfigContentIndex,
     raft: Option<Weak<NacosRaft>>,
     namespace_actor: Option<Addr<NamespaceActor>>,
     sequence: SimpleSequence,
+    pub(crate) webhooks: BTreeMap<u64, Arc<ConfigWebhook>>,
+    webhook_notify: Option<Addr<ConfigWebhookNotifyActor>>,
 }
 
 impl Inject for ConfigActor {
@@ -417,6 +482,7 @@ impl Inject for ConfigActor {
         self.raft = raft.map(|e| Arc::downgrade(&e));
         self.namespace_actor = factory_data.get_actor();
         self.tenant_index.namespace_actor = self.namespace_actor.clone();
+        self.webhook_notify = factory_data.get_actor();
         if let Some(conn_manage) = factory_data.get_actor() {
             self.subscriber.set_conn_manage(conn_manage);
         }
@@ -437,9 +503,12 @@ impl ConfigActor {
             subscriber: Subscriber::new(),
             listener: ConfigListener::new(),
             tenant_index: TenantIndex::new(),
+            content_index: ConfigContentIndex::new(),
             raft: None,
             namespace_actor: None,
             sequence: SimpleSequence::new(0, 100),
+            webhooks: BTreeMap::new(),
+            webhook_notify: None,
         }
     }
 
@@ -457,6 +526,7 @@ impl ConfigActor {
 
     fn inner_set_config(&mut self, key: ConfigKey, value: ConfigValue) {
         self.tenant_index.insert_config(key.clone());
+        self.content_index.update(key.clone(), &value.content);
         self.cache.insert(key, value);
     }
 
@@ -464,6 +534,8 @@ impl ConfigActor {
         if let Some(history_table_id) = param.history_table_id {
             self.sequence.set_valid_last_id(history_table_id);
         }
+        let old_md5;
+        let mut added = false;
         if let Some(v) = self.cache.get_mut(&param.key) {
             let md5 = get_md5(param.value.as_str());
             if let Some(s) = param.config_type {
@@ -472,47 +544,295 @@ impl ConfigActor {
             if let Some(s) = param.desc {
                 v.desc = Some(s);
             }
+            if let Some(s) = param.tags {
+                v.tags = s;
+            }
             if !v.tmp && v.md5.as_str() == md5 {
                 return Ok(ConfigResult::NULL);
             }
+            old_md5 = if v.tmp { None } else { Some(v.md5.clone()) };
             if v.histories.is_empty() {
-                self.tenant_index.insert_config(param.key.clone());
+                added = self.tenant_index.insert_config(param.key.clone());
             }
             v.update_value(
                 param.value,
                 param.history_id,
                 param.op_time,
                 Some(Arc::new(md5)),
-                param.op_user,
+                param.op_user.clone(),
             );
+            v.set_encrypted_data_key(param.encrypted_data_key);
+            self.content_index.update(param.key.clone(), &v.content);
         } else {
             let mut v = ConfigValue::init(
                 param.value,
                 param.history_id,
                 param.op_time,
                 None,
-                param.op_user,
+                param.op_user.clone(),
             );
             v.config_type = param.config_type;
             v.desc = param.desc;
+            v.tags = param.tags.unwrap_or_default();
+            v.set_encrypted_data_key(param.encrypted_data_key);
+            self.content_index.update(param.key.clone(), &v.content);
             self.cache.insert(param.key.clone(), v);
-            self.tenant_index.insert_config(param.key.clone());
+            added = self.tenant_index.insert_config(param.key.clone());
+            old_md5 = None;
+        }
+        if added {
+            self.subscriber.notify_fuzzy_watch(&param.key, ADD_CONFIG);
+        }
+        if let Some(v) = self.cache.get(&param.key) {
+            let event = ConfigChangeEvent::new(
+                EVENT_TYPE_UPDATE,
+                &param.key,
+                old_md5,
+                Some(v.md5.clone()),
+                param.op_user,
+                param.op_time,
+            );
+            self.notify_webhook(event);
         }
         self.listener.notify(param.key.clone());
         self.subscriber.notify(param.key);
         Ok(ConfigResult::NULL)
     }
 
+    fn set_gray_config(
+        &mut self,
+        key: ConfigKey,
+        value: Arc<String>,
+        rule: ConfigGrayRule,
+        op_time: i64,
+        op_user: Option<Arc<String>>,
+    ) -> anyhow::Result<()> {
+        if let Some(v) = self.cache.get_mut(&key) {
+            v.gray = Some(ConfigGrayValue::new(value, rule, op_time, op_user));
+        } else {
+            log::warn!(
+                "set_gray_config ignore,the config is not exist,key:{}",
+                key.build_key()
+            );
+            return Ok(());
+        }
+        self.listener.notify(key.clone());
+        self.subscriber.notify(key);
+        Ok(())
+    }
+
+    fn remove_gray_config(&mut self, key: ConfigKey) -> anyhow::Result<()> {
+        let removed = if let Some(v) = self.cache.get_mut(&key) {
+            v.gray.take().is_some()
+        } else {
+            false
+        };
+        if removed {
+            self.listener.notify(key.clone());
+            self.subscriber.notify(key);
+        }
+        Ok(())
+    }
+
+    fn promote_gray_config(
+        &mut self,
+        key: ConfigKey,
+        history_id: u64,
+        history_table_id: Option<u64>,
+        op_time: i64,
+        op_user: Option<Arc<String>>,
+    ) -> anyhow::Result<()> {
+        let gray = match self.cache.get_mut(&key).and_then(|v| v.gray.take()) {
+            Some(v) => v,
+            None => {
+                if let Some(history_table_id) = history_table_id {
+                    self.sequence.set_valid_last_id(history_table_id);
+                }
+                log::warn!(
+                    "promote_gray_config ignore,the gray config is not exist,key:{}",
+                    key.build_key()
+                );
+                return Ok(());
+            }
+        };
+        let param = SetConfigParam {
+            key: key.clone(),
+            value: gray.content,
+            config_type: None,
+            desc: None,
+            tags: None,
+            encrypted_data_key: None,
+            history_id,
+            history_table_id,
+            op_time,
+            op_user,
+        };
+        self.set_config(param)?;
+        //灰度内容与正式内容一致时set_config不会通知,移除灰度后仍需通知命中灰度的客户端
+        self.listener.notify(key.clone());
+        self.subscriber.notify(key);
+        Ok(())
+    }
+
+    fn apply_gray_req(&mut self, req: ConfigGrayRaftReq) -> anyhow::Result<()> {
+        match req {
+            ConfigGrayRaftReq::Set {
+                key,
+                value,
+                rule,
+                op_time,
+                op_user,
+            } => {
+                let key: ConfigKey = (&key as &str).into();
+                self.set_gray_config(key, value, rule, op_time, op_user)
+            }
+            ConfigGrayRaftReq::Remove { key } => {
+                let key: ConfigKey = (&key as &str).into();
+                self.remove_gray_config(key)
+            }
+            ConfigGrayRaftReq::Promote {
+                key,
+                history_id,
+                history_table_id,
+                op_time,
+                op_user,
+            } => {
+                let key: ConfigKey = (&key as &str).into();
+                self.promote_gray_config(key, history_id, history_table_id, op_time, op_user)
+            }
+        }
+    }
+
     fn del_config(&mut self, key: ConfigKey) -> anyhow::Result<()> {
-        self.cache.remove(&key);
+        if let Some(v) = self.cache.remove(&key) {
+            let event = ConfigChangeEvent::new(
+                EVENT_TYPE_DELETE,
+                &key,
+                Some(v.md5),
+                None,
+                None,
+                now_millis_i64(),
+            );
+            self.notify_webhook(event);
+        }
         //self.config_db.del_config(&key).ok();
-        self.tenant_index.remove_config(&key);
+        if self.tenant_index.remove_config(&key) {
+            self.subscriber.notify_fuzzy_watch(&key, DELETE_CONFIG);
+        }
+        self.content_index.remove(&key);
         self.listener.notify(key.clone());
         self.subscriber.notify(key.clone());
         self.subscriber.remove_config_key(key);
         Ok(())
     }
 
+    ///
+    /// 注册模糊监听,并把匹配的配置与客户端已有的groupKey做差异同步
+    fn fuzzy_watch(
+        &mut self,
+        client_id: Arc<String>,
+        pattern: FuzzyGroupKeyPattern,
+        mut received_group_keys: HashSet<String>,
+        initializing: bool,
+        resource_privilege: ResourcePrivilege,
+    ) {
+        let mut contexts = vec![];
+        for key in self.tenant_index.query_fuzzy_watch_keys(&pattern) {
+            if !resource_privilege.check_permission(
+                &key.tenant,
+                &key.group,
+                &key.data_id,
+                ResourceAction::Read,
+            ) {
+                continue;
+            }
+            let group_key = pattern.build_group_key(&key);
+            if !received_group_keys.remove(&group_key) {
+                contexts.push((group_key, ADD_CONFIG));
+            }
+        }
+        for group_key in received_group_keys {
+            contexts.push((group_key, DELETE_CONFIG));
+        }
+        let pattern = Arc::new(pattern);
+        self.subscriber
+            .add_fuzzy_watch(client_id.clone(), pattern.clone(), resource_privilege);
+        if !contexts.is_empty() {
+            let sync_type = if initializing {
+                FUZZY_WATCH_INIT_NOTIFY
+            } else {
+                FUZZY_WATCH_DIFF_SYNC_NOTIFY
+            };
+            let notify = ConfigFuzzyWatchNotify::Sync {
+                pattern: pattern.pattern.clone(),
+                sync_type,
+                contexts,
+            };
+            self.subscriber
+                .notify_fuzzy_watch_sync(client_id.clone(), notify);
+        }
+        if initializing {
+            let notify = ConfigFuzzyWatchNotify::Sync {
+                pattern: pattern.pattern.clone(),
+                sync_type: FINISH_FUZZY_WATCH_INIT_NOTIFY,
+                contexts: vec![],
+            };
+            self.subscriber.notify_fuzzy_watch_sync(client_id, notify);
+        }
+    }
+
+    fn apply_webhook_req(&mut self, req: ConfigWebhookRaftReq) -> anyhow::Result<()> {
+        match req {
+            ConfigWebhookRaftReq::Update { webhook } => {
+                if webhook.id == 0 {
+                    log::warn!("apply config webhook ignore,the webhook id is empty");
+                    return Ok(());
+                }
+                self.webhooks.insert(webhook.id, Arc::new(webhook));
+            }
+            ConfigWebhookRaftReq::Remove { id } => {
+                self.webhooks.remove(&id);
+            }
+        }
+        Ok(())
+    }
+
+    ///
+    /// 只由主节点推送webhook,避免集群重复推送及启动加载日志时推送
+    fn notify_webhook(&self, event: ConfigChangeEvent) {
+        let notify = if let Some(notify) = &self.webhook_notify {
+            notify
+        } else {
+            return;
+        };
+        let key = ConfigKey::new_by_arc(
+            event.data_id.clone(),
+            event.group.clone(),
+            event.tenant.clone(),
+        );
+        let webhooks: Vec<Arc<ConfigWebhook>> = self
+            .webhooks
+            .values()
+            .filter(|v| v.matches(&key))
+            .cloned()
+            .collect();
+        if webhooks.is_empty() || !self.is_leader() {
+            return;
+        }
+        notify.do_send(ConfigWebhookNotifyCmd::Notify {
+            webhooks,
+            event: Arc::new(event),
+        });
+    }
+
+    fn is_leader(&self) -> bool {
+        self.raft
+            .as_ref()
+            .and_then(|v| v.upgrade())
+            .map(|raft| raft.metrics().borrow().state.is_leader())
+            .unwrap_or_default()
+    }
+
     /*
     fn load_config(&mut self) {
         for item in self.config_db.query_config_list().unwrap() {
@@ -542,8 +862,71 @@ impl ConfigActor {
         Ok(())
     }
 
+    ///
+    /// 按配置内容或标签检索;有内容检索时先通过倒排索引缩小范围,再按内容校验
+    fn query_config_page_by_filter(
+        &self,
+        param: &ConfigQueryParam,
+        search: Option<&str>,
+    ) -> (usize, Vec<ConfigKey>) {
+        let search_lower = search.map(|v| v.to_lowercase());
+        let match_key = |key: &ConfigKey| -> bool {
+            if let Some(tenant) = &param.tenant {
+                if tenant != &key.tenant {
+                    return false;
+                }
+            }
+            if !param.namespace_privilege.check_permission(&key.tenant)
+                || !param.match_group(&key.group)
+                || !param.match_data_id(&key.data_id)
+                || !param.match_resource(&key.tenant, &key.group, &key.data_id)
+            {
+                return false;
+            }
+            //加密配置的内容是密文,不参与内容检索
+            if search_lower.is_some() && ConfigCipher::is_cipher_data_id(&key.data_id) {
+                return false;
+            }
+            if let Some(v) = self.cache.get(key) {
+                !v.tmp
+                    && param.match_tags(&v.tags)
+                    && search_lower
+                        .as_ref()
+                        .map(|s| v.content.to_lowercase().contains(s))
+                        .unwrap_or(true)
+            } else {
+                false
+            }
+        };
+        let candidates = search.and_then(|s| self.content_index.search(s));
+        let mut keys: Vec<ConfigKey> = match candidates {
+            Some(set) => set.into_iter().filter(|k| match_key(k)).collect(),
+            None => self
+                .cache
+                .keys()
+                .filter(|k| match_key(k))
+                .cloned()
+                .collect(),
+        };
+        keys.sort_by(|a, b| {
+            (&a.tenant, &a.group, &a.data_id).cmp(&(&b.tenant, &b.group, &b.data_id))
+        });
+        let size = keys.len();
+        let list = keys
+            .into_iter()
+            .skip(param.offset)
+            .take(param.limit)
+            .collect();
+        (size, list)
+    }
+
     pub fn get_config_info_page(&self, param: &ConfigQueryParam) -> (usize, Vec<ConfigInfoDto>) {
-        let (size, list) = self.tenant_index.query_config_page(param);
+        let search = param.get_content_search();
+        let (size, list) = if search.is_some() || !param.tags.is_empty() {
+            self.query_config_page_by_filter(param, search)
+        } else {
+            self.tenant_index.query_config_page(param)
+        };
 
         if size == 0 {
             return (size, Vec::new());
@@ -557,6 +940,8 @@ impl ConfigActor {
                     group: item.group.clone(),
                     data_id: item.data_id.clone(),
                     desc: value.desc.clone(),
+                    tags: value.tags.clone(),
+                    encrypted_data_key: value.encrypted_data_key.clone(),
                     //md5:Some(value.md5.clone()),
                     //content:Some(value.content.clone()),
                     ..Default::default()
@@ -587,6 +972,8 @@ impl ConfigActor {
                     group: key.group.clone(),
                     data_id: key.data_id.clone(),
                     desc: value.desc.clone(),
+                    tags: value.tags.clone(),
+                    encrypted_data_key: value.encrypted_data_key.clone(),
                     content: Some(value.content.clone()),
                     md5: Some(value.md5.clone()),
                 };
@@ -648,6 +1035,34 @@ impl ConfigActor {
         (0, vec![])
     }
 
+    ///
+    /// 比较两个历史版本的差异,to_id为空时与当前配置比较
+    fn diff_history(
+        &self,
+        key: &ConfigKey,
+        from_id: u64,
+        to_id: Option<u64>,
+    ) -> Option<ConfigHistoryDiffDto> {
+        let v = self.cache.get(key)?;
+        let from = v.histories.iter().find(|e| e.id == from_id)?;
+        let (to_content, to_encrypted_data_key) = if let Some(to_id) = to_id {
+            let to = v.histories.iter().find(|e| e.id == to_id)?;
+            (to.content.clone(), to.encrypted_data_key.clone())
+        } else {
+            (v.content.clone(), v.encrypted_data_key.clone())
+        };
+        //只加载两端内容,差异在actor外计算
+        Some(ConfigHistoryDiffDto {
+            from_id,
+            to_id,
+            from_content: from.content.as_ref().to_owned(),
+            to_content: to_content.as_ref().to_owned(),
+            from_encrypted_data_key: from.encrypted_data_key.clone(),
+            to_encrypted_data_key,
+            ..Default::default()
+        })
+    }
+
     ///
     /// 将配置中心数据写入 raft snapshot文件中
     ///
@@ -669,6 +1084,16 @@ impl ConfigActor {
             op_type: 0,
         };
         writer.do_send(SnapshotWriterRequest::Record(seq_record));
+        for (id, webhook) in &self.webhooks {
+            let value_do: ConfigWebhookDO = webhook.as_ref().into();
+            let record = SnapshotRecordDto {
+                tree: CONFIG_WEBHOOK_TREE_NAME.clone(),
+                key: id.to_string().as_bytes().to_vec(),
+                value: value_do.to_bytes()?,
+                op_type: 0,
+            };
+            writer.do_send(SnapshotWriterRequest::Record(record));
+        }
         Ok(())
     }
 
@@ -714,15 +1139,35 @@ pub enum ConfigCmd {
     SetFullValue(ConfigKey, ConfigValue),
     InnerSetLastId(u64),
     GET(ConfigKey),
+    GetByClient(ConfigKey, ConfigClientInfo),
+    GetGray(ConfigKey),
     QueryPageInfo(Box<ConfigQueryParam>),
     QueryInfoByKeys(Box<Vec<ConfigKey>>),
     QueryHistoryPageInfo(Box<ConfigHistoryParam>),
-    LISTENER(Vec<ListenerItem>, ListenerSenderType, i64),
-    Subscribe(Vec<ListenerItem>, Arc<String>),
+    GetHistory(ConfigKey, u64),
+    DiffHistory {
+        key: ConfigKey,
+        from_id: u64,
+        to_id: Option<u64>,
+    },
+    LISTENER(Vec<ListenerItem>, ListenerSenderType, i64, ConfigClientInfo),
+    Subscribe(Vec<ListenerItem>, Arc<String>, ConfigClientInfo),
     RemoveSubscribe(Vec<ListenerItem>, Arc<String>),
     RemoveSubscribeClient(Arc<String>),
+    FuzzyWatch {
+        client_id: Arc<String>,
+        pattern: FuzzyGroupKeyPattern,
+        received_group_keys: HashSet<String>,
+        initializing: bool,
+        resource_privilege: ResourcePrivilege,
+    },
+    CancelFuzzyWatch {
+        client_id: Arc<String>,
+        pattern: Arc<String>,
+    },
     BuildSnapshot(Addr<SnapshotWriterActor>),
     GetSequenceSection(u64),
+    QueryWebhooks,
 }
 
 #[derive(Message)]
@@ -734,8 +1179,14 @@ pub enum ConfigAsyncCmd {
         op_user: Option<Arc<String>>,
         config_type: Option<Arc<String>>,
         desc: Option<Arc<String>>,
+        tags: Option<Vec<Arc<String>>>,
+        encrypted_data_key: Option<Arc<String>>,
     },
     Delete(ConfigKey),
+    PromoteGray {
+        key: ConfigKey,
+        op_user: Option<Arc<String>>,
+    },
 }
 
 pub enum ConfigResult {
@@ -744,12 +1195,19 @@ pub enum ConfigResult {
         md5: Arc<String>,
         config_type: Option<Arc<String>>,
         desc: Option<Arc<String>>,
+        tags: Vec<Arc<String>>,
+        encrypted_data_key: Option<Arc<String>>,
         last_modified: i64,
+        gray: bool,
     },
+    GrayData(Box<ConfigGrayInfoDto>),
     NULL,
     ChangeKey(Vec<ConfigKey>),
     ConfigInfoPage(usize, Vec<ConfigInfoDto>),
     ConfigHistoryInfoPage(usize, Vec<ConfigHistoryInfoDto>),
+    HistoryData(HistoryItem),
+    ConfigHistoryDiff(Box<ConfigHistoryDiffDto>),
+    Webhooks(Vec<Arc<ConfigWebhook>>),
     SequenceSection {
         //id包含start值
         start: u64,
@@ -794,15 +1252,49 @@ impl Handler<ConfigCmd> for ConfigActor {
                         md5: v.md5.clone(),
                         config_type: v.config_type.clone(),
                         desc: v.desc.clone(),
+                        tags: v.tags.clone(),
+                        encrypted_data_key: v.encrypted_data_key.clone(),
                         last_modified: v.last_modified,
+                        gray: false,
                     });
                 }
             }
-            ConfigCmd::LISTENER(items, sender, time) => {
+            ConfigCmd::GetByClient(key, client) => {
+                if let Some(v) = self.cache.get(&key) {
+                    if let Some(gray) = v.get_gray_by_client(&client) {
+                        return Ok(ConfigResult::Data {
+                            value: gray.content.clone(),
+                            md5: gray.md5.clone(),
+                            config_type: v.config_type.clone(),
+                            desc: v.desc.clone(),
+                            tags: v.tags.clone(),
+                            encrypted_data_key: v.encrypted_data_key.clone(),
+                            last_modified: gray.last_modified,
+                            gray: true,
+                        });
+                    }
+                    return Ok(ConfigResult::Data {
+                        value: v.content.clone(),
+                        md5: v.md5.clone(),
+                        config_type: v.config_type.clone(),
+                        desc: v.desc.clone(),
+                        tags: v.tags.clone(),
+                        encrypted_data_key: v.encrypted_data_key.clone(),
+                        last_modified: v.last_modified,
+                        gray: false,
+                    });
+                }
+            }
+            ConfigCmd::GetGray(key) => {
+                if let Some(gray) = self.cache.get(&key).and_then(|v| v.gray.as_ref()) {
+                    return Ok(ConfigResult::GrayData(Box::new(gray.into())));
+                }
+            }
+            ConfigCmd::LISTENER(items, sender, time, client) => {
                 let mut changes = vec![];
                 for item in &items {
                     if let Some(v) = self.cache.get(&item.key) {
-                        if v.md5 != item.md5 {
+                        if *v.get_md5_by_client(&client) != item.md5 {
                             changes.push(item.key.clone());
                         }
                     } else if !item.md5.is_empty() {
@@ -817,11 +1309,11 @@ impl Handler<ConfigCmd> for ConfigActor {
                     return Ok(ConfigResult::NULL);
                 }
             }
-            ConfigCmd::Subscribe(items, client_id) => {
+            ConfigCmd::Subscribe(items, client_id, client) => {
                 let mut changes = vec![];
                 for item in &items {
                     if let Some(v) = self.cache.get(&item.key) {
-                        if v.md5 != item.md5 {
+                        if *v.get_md5_by_client(&client) != item.md5 {
                             changes.push(item.key.clone());
                         }
                     } else if !item.md5.is_empty() {
@@ -839,6 +1331,24 @@ impl Handler<ConfigCmd> for ConfigActor {
             ConfigCmd::RemoveSubscribeClient(client_id) => {
                 self.subscriber.remove_client_subscribe(client_id);
             }
+            ConfigCmd::FuzzyWatch {
+                client_id,
+                pattern,
+                received_group_keys,
+                initializing,
+                resource_privilege,
+            } => {
+                self.fuzzy_watch(
+                    client_id,
+                    pattern,
+                    received_group_keys,
+                    initializing,
+                    resource_privilege,
+                );
+            }
+            ConfigCmd::CancelFuzzyWatch { client_id, pattern } => {
+                self.subscriber.remove_fuzzy_watch(client_id, &pattern);
+            }
             ConfigCmd::QueryPageInfo(config_query_param) => {
                 let (size, list) = self.get_config_info_page(config_query_param.as_ref());
                 return Ok(ConfigResult::ConfigInfoPage(size, list));
@@ -851,6 +1361,24 @@ impl Handler<ConfigCmd> for ConfigActor {
                 let (size, list) = self.get_history_info_page(query_param.as_ref());
                 return Ok(ConfigResult::ConfigHistoryInfoPage(size, list));
             }
+            ConfigCmd::GetHistory(key, history_id) => {
+                if let Some(item) = self
+                    .cache
+                    .get(&key)
+                    .and_then(|v| v.histories.iter().find(|e| e.id == history_id))
+                {
+                    return Ok(ConfigResult::HistoryData(item.clone()));
+                }
+            }
+            ConfigCmd::DiffHistory {
+                key,
+                from_id,
+                to_id,
+            } => {
+                if let Some(dto) = self.diff_history(&key, from_id, to_id) {
+                    return Ok(ConfigResult::ConfigHistoryDiff(Box::new(dto)));
+                }
+            }
             ConfigCmd::BuildSnapshot(writer) => {
                 self.build_snapshot(writer).ok();
             }
@@ -858,6 +1386,11 @@ impl Handler<ConfigCmd> for ConfigActor {
                 let (start, end) = self.sequence.next_section(size)?;
                 return Ok(ConfigResult::SequenceSection { start, end });
             }
+            ConfigCmd::QueryWebhooks => {
+                return Ok(ConfigResult::Webhooks(
+                    self.webhooks.values().cloned().collect(),
+                ));
+            }
         }
         Ok(ConfigResult::NULL)
     }
@@ -868,10 +1401,11 @@ impl Handler<ConfigAsyncCmd> for ConfigActor {
 
     fn handle(&mut self, msg: ConfigAsyncCmd, _ctx: &mut Context<Self>) -> Self::Result {
         let raft = self.raft.clone();
-        let history_info = if let ConfigAsyncCmd::Add { .. } = &msg {
-            self.sequence.next_state().ok()
-        } else {
-            None
+        let history_info = match &msg {
+            ConfigAsyncCmd::Add { .. } | ConfigAsyncCmd::PromoteGray { .. } => {
+                self.sequence.next_state().ok()
+            }
+            ConfigAsyncCmd::Delete(_) => None,
         };
         let fut = async move {
             match msg {
@@ -881,6 +1415,8 @@ impl Handler<ConfigAsyncCmd> for ConfigActor {
                     op_user,
                     config_type,
                     desc,
+                    tags,
+                    encrypted_data_key,
                 } => {
                     if let Some((history_id, history_table_id)) = history_info {
                         let req = ClientRequest::ConfigSet {
@@ -888,6 +1424,8 @@ impl Handler<ConfigAsyncCmd> for ConfigActor {
                             value,
                             config_type,
                             desc,
+                            tags,
+                            encrypted_data_key,
                             history_id,
                             history_table_id,
                             op_time: now_millis_i64(),
@@ -902,6 +1440,20 @@ impl Handler<ConfigAsyncCmd> for ConfigActor {
                     };
                     Self::send_raft_request(&raft, req).await.ok();
                 }
+                ConfigAsyncCmd::PromoteGray { key, op_user } => {
+                    let (history_id, history_table_id) = history_info
+                        .ok_or_else(|| anyhow::anyhow!("config history sequence is not ready"))?;
+                    let req = ClientRequest::ConfigGrayReq {
+                        req: ConfigGrayRaftReq::Promote {
+                            key: key.build_key(),
+                            history_id,
+                            history_table_id,
+                            op_time: now_millis_i64(),
+                            op_user,
+                        },
+                    };
+                    Self::send_raft_request(&raft, req).await?;
+                }
             }
             Ok(ConfigResult::NULL)
         }
@@ -921,6 +1473,8 @@ impl Handler<ConfigRaftCmd> for ConfigActor {
                 value,
                 config_type,
                 desc,
+                tags,
+                encrypted_data_key,
                 history_id,
                 history_table_id,
                 op_time,
@@ -933,6 +1487,8 @@ impl Handler<ConfigRaftCmd> for ConfigActor {
                     config_type: config_type
                         .map(|v| ConfigType::new_by_value(v.as_ref()).get_value()),
                     desc,
+                    tags,
+                    encrypted_data_key,
                     history_id,
                     history_table_id,
                     op_time,
@@ -954,6 +1510,12 @@ impl Handler<ConfigRaftCmd> for ConfigActor {
                 let config_key: ConfigKey = (&key as &str).into();
                 self.del_config(config_key).ok();
             }
+            ConfigRaftCmd::ConfigGray { req } => {
+                self.apply_gray_req(req).ok();
+            }
+            ConfigRaftCmd::ConfigWebhook { req } => {
+                self.apply_webhook_req(req).ok();
+            }
         }
         Ok(ConfigRaftResult::None)
     }
@@ -973,3 +1535,68 @@ impl Handler<TransferDataRequest> for ConfigActor {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_set_param(key: &ConfigKey, value: &str, history_id: u64) -> SetConfigParam {
+        SetConfigParam {
+            key: key.clone(),
+            value: Arc::new(value.to_owned()),
+            config_type: None,
+            desc: None,
+            tags: None,
+            encrypted_data_key: None,
+            history_id,
+            history_table_id: None,
+            op_time: 1,
+            op_user: None,
+        }
+    }
+
+    #[test]
+    fn promote_gray_config_in_one_apply() {
+        let mut actor = ConfigActor::new();
+        let key = ConfigKey::new("app.yaml", "DEFAULT_GROUP", "");
+        actor.set_config(build_set_param(&key, "v1", 1)).unwrap();
+        let mut rule = ConfigGrayRule::default();
+        rule.client_ips.push(Arc::new("127.0.0.1".to_owned()));
+        actor
+            .apply_gray_req(ConfigGrayRaftReq::Set {
+                key: key.build_key(),
+                value: Arc::new("v2".to_owned()),
+                rule,
+                op_time: 2,
+                op_user: None,
+            })
+            .unwrap();
+        assert!(actor.cache.get(&key).unwrap().gray.is_some());
+
+        actor
+            .apply_gray_req(ConfigGrayRaftReq::Promote {
+                key: key.build_key(),
+                history_id: 2,
+                history_table_id: None,
+                op_time: 3,
+                op_user: Some(Arc::new("admin".to_owned())),
+            })
+            .unwrap();
+        let value = actor.cache.get(&key).unwrap();
+        assert_eq!(value.content.as_str(), "v2");
+        assert!(value.gray.is_none());
+        assert_eq!(value.histories.len(), 2);
+
+        //灰度已移除时重复全量发布不改变正式配置
+        actor
+            .apply_gray_req(ConfigGrayRaftReq::Promote {
+                key: key.build_key(),
+                history_id: 3,
+                history_table_id: None,
+                op_time: 4,
+                op_user: None,
+            })
+            .unwrap();
+        assert_eq!(actor.cache.get(&key).unwrap().histories.len(), 2);
+    }
+}
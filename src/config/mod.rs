@@ -1,11 +1,16 @@
 use crate::common::constant::EMPTY_ARC_STRING;
 use std::sync::Arc;
 
+pub mod config_cipher;
 pub mod config_db;
+pub mod config_diff;
+pub mod config_fuzzy_watch;
 pub mod config_index;
 pub mod config_sled;
 pub mod config_subscribe;
 pub mod config_type;
+pub mod config_validator;
+pub mod config_webhook;
 pub mod core;
 pub mod dal;
 pub mod metrics;
@@ -36,4 +41,25 @@ impl ConfigUtils {
     pub fn is_default_tenant(val: &str) -> bool {
         val == DEFAULT_TENANT
     }
+
+    ///
+    /// 解析逗号分隔的配置标签,去空、去重并排序
+    pub fn parse_tags(val: &str) -> Vec<Arc<String>> {
+        let mut tags: Vec<Arc<String>> = val
+            .split(',')
+            .map(|e| e.trim())
+            .filter(|e| !e.is_empty())
+            .map(|e| Arc::new(e.to_owned()))
+            .collect();
+        tags.sort();
+        tags.dedup();
+        tags
+    }
+
+    pub fn join_tags(tags: &[Arc<String>]) -> String {
+        tags.iter()
+            .map(|e| e.as_str())
+            .collect::<Vec<&str>>()
+            .join(",")
+    }
 }
@@ -1,8 +1,10 @@
 use crate::config::config_type::ConfigType;
+use crate::config::config_webhook::ConfigWebhookRaftReq;
 use crate::config::core::{ConfigHistoryInfoDto, ConfigKey, ConfigValue};
 use crate::utils::get_md5;
 use actix::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::Arc;
 
 #[derive(Message)]
@@ -13,6 +15,8 @@ pub enum ConfigRaftCmd {
         value: Arc<String>,
         config_type: Option<Arc<String>>,
         desc: Option<Arc<String>>,
+        tags: Option<Vec<Arc<String>>>,
+        encrypted_data_key: Option<Arc<String>>,
         history_id: u64,
         history_table_id: Option<u64>,
         op_time: i64,
@@ -26,6 +30,140 @@ pub enum ConfigRaftCmd {
         value: ConfigValue,
         last_id: Option<u64>,
     },
+    ConfigGray {
+        req: ConfigGrayRaftReq,
+    },
+    ConfigWebhook {
+        req: ConfigWebhookRaftReq,
+    },
+}
+
+///
+/// 灰度配置raft请求
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum ConfigGrayRaftReq {
+    Set {
+        key: String,
+        value: Arc<String>,
+        rule: ConfigGrayRule,
+        op_time: i64,
+        op_user: Option<Arc<String>>,
+    },
+    Remove {
+        key: String,
+    },
+    /// 全量发布：在同一次apply中将灰度内容写为正式配置并移除灰度
+    Promote {
+        key: String,
+        history_id: u64,
+        history_table_id: Option<u64>,
+        op_time: i64,
+        op_user: Option<Arc<String>>,
+    },
+}
+
+///
+/// 灰度规则，客户端ip或标签任一匹配即命中灰度
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigGrayRule {
+    pub client_ips: Vec<Arc<String>>,
+    pub labels: HashMap<String, String>,
+}
+
+impl ConfigGrayRule {
+    pub fn is_empty(&self) -> bool {
+        self.client_ips.is_empty() && self.labels.is_empty()
+    }
+
+    pub fn match_client(&self, client: &ConfigClientInfo) -> bool {
+        if !client.ip.is_empty()
+            && self
+                .client_ips
+                .iter()
+                .any(|e| e.as_str() == client.ip.as_str())
+        {
+            return true;
+        }
+        for (k, v) in &self.labels {
+            if let Some(client_v) = client.labels.get(k) {
+                if client_v == v {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+}
+
+///
+/// 查询、监听配置的客户端信息，用于匹配灰度规则
+#[derive(Clone, Debug, Default)]
+pub struct ConfigClientInfo {
+    pub ip: Arc<String>,
+    pub labels: Arc<HashMap<String, String>>,
+}
+
+impl ConfigClientInfo {
+    pub fn new(ip: Arc<String>, labels: Arc<HashMap<String, String>>) -> Self {
+        Self { ip, labels }
+    }
+
+    pub fn new_by_ip(ip: Arc<String>) -> Self {
+        Self {
+            ip,
+            labels: Default::default(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ConfigGrayValue {
+    pub content: Arc<String>,
+    pub md5: Arc<String>,
+    pub rule: ConfigGrayRule,
+    pub op_user: Option<Arc<String>>,
+    pub last_modified: i64,
+}
+
+impl ConfigGrayValue {
+    pub fn new(
+        content: Arc<String>,
+        rule: ConfigGrayRule,
+        op_time: i64,
+        op_user: Option<Arc<String>>,
+    ) -> Self {
+        let md5 = Arc::new(get_md5(&content));
+        Self {
+            content,
+            md5,
+            rule,
+            op_user,
+            last_modified: op_time,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigGrayInfoDto {
+    pub content: Arc<String>,
+    pub md5: Arc<String>,
+    pub rule: ConfigGrayRule,
+    pub op_user: Option<Arc<String>>,
+    pub last_modified: i64,
+}
+
+impl From<&ConfigGrayValue> for ConfigGrayInfoDto {
+    fn from(value: &ConfigGrayValue) -> Self {
+        Self {
+            content: value.content.clone(),
+            md5: value.md5.clone(),
+            rule: value.rule.clone(),
+            op_user: value.op_user.clone(),
+            last_modified: value.last_modified,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -34,6 +172,8 @@ pub struct SetConfigParam {
     pub value: Arc<String>,
     pub config_type: Option<Arc<String>>,
     pub desc: Option<Arc<String>>,
+    pub tags: Option<Vec<Arc<String>>>,
+    pub encrypted_data_key: Option<Arc<String>>,
     pub history_id: u64,
     pub history_table_id: Option<u64>,
     pub op_time: i64,
@@ -54,6 +194,7 @@ pub struct HistoryItem {
     pub content: Arc<String>,
     pub modified_time: i64,
     pub op_user: Option<Arc<String>>,
+    pub encrypted_data_key: Option<Arc<String>>,
 }
 
 impl HistoryItem {
@@ -66,6 +207,7 @@ impl HistoryItem {
             content: Some(self.content.to_string()),
             modified_time: Some(self.modified_time),
             op_user: self.op_user.as_ref().map(|e| e.to_string()),
+            encrypted_data_key: self.encrypted_data_key.as_ref().map(|e| e.to_string()),
         }
     }
 }
@@ -80,6 +222,8 @@ pub struct ConfigHistoryItemDO {
     pub last_time: Option<i64>,
     #[prost(string, optional, tag = "4")]
     pub op_user: Option<String>,
+    #[prost(string, optional, tag = "5")]
+    pub encrypted_data_key: Option<String>,
 }
 
 impl From<HistoryItem> for ConfigHistoryItemDO {
@@ -89,6 +233,7 @@ impl From<HistoryItem> for ConfigHistoryItemDO {
             content: Some(value.content.as_ref().to_string()),
             last_time: Some(value.modified_time),
             op_user: value.op_user.map(|e| e.as_ref().to_string()),
+            encrypted_data_key: value.encrypted_data_key.map(|e| e.as_ref().to_string()),
         }
     }
 }
@@ -100,6 +245,7 @@ impl From<ConfigHistoryItemDO> for HistoryItem {
             content: Arc::new(value.content.unwrap_or_default()),
             modified_time: value.last_time.unwrap_or_default(),
             op_user: value.op_user.map(Arc::new),
+            encrypted_data_key: value.encrypted_data_key.map(Arc::new),
         }
     }
 }
@@ -113,6 +259,58 @@ pub struct ConfigValueDO {
     pub config_type: Option<String>,
     #[prost(string, optional, tag = "4")]
     pub desc: Option<String>,
+    #[prost(message, optional, tag = "5")]
+    pub gray: Option<ConfigGrayDO>,
+    #[prost(string, repeated, tag = "6")]
+    pub tags: Vec<String>,
+    #[prost(string, optional, tag = "7")]
+    pub encrypted_data_key: Option<String>,
+}
+
+#[derive(Clone, PartialEq, prost_derive::Message, Deserialize, Serialize)]
+pub struct ConfigGrayDO {
+    #[prost(string, optional, tag = "1")]
+    pub content: Option<String>,
+    #[prost(string, repeated, tag = "2")]
+    pub client_ips: Vec<String>,
+    #[prost(map = "string, string", tag = "3")]
+    pub labels: HashMap<String, String>,
+    #[prost(string, optional, tag = "4")]
+    pub op_user: Option<String>,
+    #[prost(int64, optional, tag = "5")]
+    pub last_time: Option<i64>,
+}
+
+impl From<ConfigGrayValue> for ConfigGrayDO {
+    fn from(value: ConfigGrayValue) -> Self {
+        Self {
+            content: Some(value.content.as_ref().to_owned()),
+            client_ips: value
+                .rule
+                .client_ips
+                .into_iter()
+                .map(|e| e.as_ref().to_owned())
+                .collect(),
+            labels: value.rule.labels,
+            op_user: value.op_user.map(|e| e.as_ref().to_owned()),
+            last_time: Some(value.last_modified),
+        }
+    }
+}
+
+impl From<ConfigGrayDO> for ConfigGrayValue {
+    fn from(value: ConfigGrayDO) -> Self {
+        let rule = ConfigGrayRule {
+            client_ips: value.client_ips.into_iter().map(Arc::new).collect(),
+            labels: value.labels,
+        };
+        ConfigGrayValue::new(
+            Arc::new(value.content.unwrap_or_default()),
+            rule,
+            value.last_time.unwrap_or_default(),
+            value.op_user.map(Arc::new),
+        )
+    }
 }
 
 impl ConfigValueDO {
@@ -137,6 +335,13 @@ impl From<ConfigValue> for ConfigValueDO {
             histories: value.histories.into_iter().map(|e| e.into()).collect(),
             config_type: value.config_type.map(|e| e.as_ref().to_owned()),
             desc: value.desc.map(|e| e.as_ref().to_owned()),
+            gray: value.gray.map(|e| e.into()),
+            tags: value
+                .tags
+                .into_iter()
+                .map(|e| e.as_ref().to_owned())
+                .collect(),
+            encrypted_data_key: value.encrypted_data_key.map(|e| e.as_ref().to_owned()),
         }
     }
 }
@@ -160,7 +365,83 @@ impl From<ConfigValueDO> for ConfigValue {
                 .config_type
                 .map(|v| ConfigType::new_by_value(&v).get_value()),
             desc: value.desc.map(Arc::new),
+            tags: value.tags.into_iter().map(Arc::new).collect(),
+            encrypted_data_key: value.encrypted_data_key.map(Arc::new),
             last_modified,
+            gray: value.gray.map(|e| e.into()),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_rule() -> ConfigGrayRule {
+        let mut labels = HashMap::new();
+        labels.insert("env".to_owned(), "gray".to_owned());
+        ConfigGrayRule {
+            client_ips: vec![Arc::new("10.0.0.1".to_owned())],
+            labels,
+        }
+    }
+
+    fn build_client(ip: &str, labels: &[(&str, &str)]) -> ConfigClientInfo {
+        let labels = labels
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        ConfigClientInfo::new(Arc::new(ip.to_owned()), Arc::new(labels))
+    }
+
+    #[test]
+    fn gray_rule_match_client() {
+        let rule = build_rule();
+        assert!(rule.match_client(&build_client("10.0.0.1", &[])));
+        assert!(rule.match_client(&build_client("10.0.0.2", &[("env", "gray")])));
+        assert!(!rule.match_client(&build_client("10.0.0.2", &[("env", "prod")])));
+        assert!(!rule.match_client(&build_client("", &[("app", "gray")])));
+        assert!(!ConfigGrayRule::default().match_client(&build_client("10.0.0.1", &[])));
+    }
+
+    #[test]
+    fn get_md5_by_client() {
+        let mut value = ConfigValue::new(Arc::new("a=1".to_owned()));
+        let gray_client = build_client("10.0.0.1", &[]);
+        let normal_client = build_client("10.0.0.2", &[]);
+        assert_eq!(value.get_md5_by_client(&gray_client), &value.md5);
+        value.gray = Some(ConfigGrayValue::new(
+            Arc::new("a=2".to_owned()),
+            build_rule(),
+            1,
+            None,
+        ));
+        assert_eq!(
+            value.get_md5_by_client(&gray_client).as_str(),
+            get_md5("a=2")
+        );
+        assert_eq!(
+            value.get_md5_by_client(&normal_client).as_str(),
+            get_md5("a=1")
+        );
+    }
+
+    #[test]
+    fn gray_do_round_trip() {
+        let mut value = ConfigValue::new(Arc::new("a=1".to_owned()));
+        value.gray = Some(ConfigGrayValue::new(
+            Arc::new("a=2".to_owned()),
+            build_rule(),
+            100,
+            Some(Arc::new("admin".to_owned())),
+        ));
+        let bytes = ConfigValueDO::from(value).to_bytes().unwrap();
+        let value: ConfigValue = ConfigValueDO::from_bytes(&bytes).unwrap().into();
+        let gray = value.gray.unwrap();
+        assert_eq!(gray.content.as_str(), "a=2");
+        assert_eq!(gray.md5.as_str(), get_md5("a=2"));
+        assert_eq!(gray.rule, build_rule());
+        assert_eq!(gray.op_user.unwrap().as_str(), "admin");
+        assert_eq!(gray.last_modified, 100);
+    }
+}
@@ -192,6 +192,7 @@ pub fn console_api_config_v1(config: &mut web::ServiceConfig) {
             .service(web::resource("/login/login").route(web::post().to(login_api::login)))
             .service(web::resource("/login/captcha").route(web::get().to(login_api::gen_captcha)))
             .service(web::resource("/login/logout").route(web::post().to(login_api::logout)))
+            .service(web::resource("/login/totp").route(web::post().to(login_api::login_totp)))
             .service(web::resource("/user/info").route(web::get().to(user_api::get_user_info)))
             .service(
                 web::resource("/user/web_resources")
@@ -224,6 +225,7 @@ pub fn console_api_config_v2(config: &mut web::ServiceConfig) {
                 web::resource("/login/captcha").route(web::get().to(v2::login_api::gen_captcha)),
             )
             .service(web::resource("/login/logout").route(web::post().to(v2::login_api::logout)))
+            .service(web::resource("/login/totp").route(web::post().to(v2::login_api::login_totp)))
             .service(
                 web::resource("/login/config").route(web::get().to(login_api::get_login_config)),
             )
@@ -238,6 +240,20 @@ pub fn console_api_config_v2(config: &mut web::ServiceConfig) {
             .service(web::resource("/user/add").route(web::post().to(v2::user_api::add_user)))
             .service(web::resource("/user/update").route(web::post().to(v2::user_api::update_user)))
             .service(web::resource("/user/remove").route(web::post().to(v2::user_api::remove_user)))
+            .service(web::resource("/user/unlock").route(web::post().to(v2::user_api::unlock_user)))
+            .service(
+                web::resource("/user/totp/init").route(web::post().to(v2::user_api::init_totp)),
+            )
+            .service(
+                web::resource("/user/totp/enable").route(web::post().to(v2::user_api::enable_totp)),
+            )
+            .service(
+                web::resource("/user/totp/disable")
+                    .route(web::post().to(v2::user_api::disable_totp)),
+            )
+            .service(
+                web::resource("/user/totp/reset").route(web::post().to(v2::user_api::reset_totp)),
+            )
             .service(
                 web::resource("/user/web_resources")
                     .route(web::get().to(v2::user_api::get_user_web_resources)),
@@ -246,6 +262,27 @@ pub fn console_api_config_v2(config: &mut web::ServiceConfig) {
                 web::resource("/user/reset_password")
                     .route(web::post().to(v2::user_api::reset_password)),
             )
+            .service(
+                web::resource("/user/apikey/list")
+                    .route(web::get().to(v2::user_api::get_api_key_list)),
+            )
+            .service(
+                web::resource("/user/apikey/add").route(web::post().to(v2::user_api::add_api_key)),
+            )
+            .service(
+                web::resource("/user/apikey/revoke")
+                    .route(web::post().to(v2::user_api::revoke_api_key)),
+            )
+            .service(
+                web::resource("/audit/list").route(web::get().to(v2::audit_api::query_audit_page)),
+            )
+            .service(web::resource("/role/list").route(web::get().to(v2::role_api::get_role_list)))
+            .service(
+                web::resource("/role/modules").route(web::get().to(v2::role_api::get_role_modules)),
+            )
+            .service(web::resource("/role/add").route(web::post().to(v2::role_api::add_role)))
+            .service(web::resource("/role/update").route(web::post().to(v2::role_api::update_role)))
+            .service(web::resource("/role/remove").route(web::post().to(v2::role_api::remove_role)))
             .service(
                 web::resource("/namespaces/list")
                     .route(web::get().to(v2::namespace_api::query_namespace_list)),
@@ -279,6 +316,10 @@ pub fn console_api_config_v2(config: &mut web::ServiceConfig) {
                     .route(web::get().to(v2::config_api::query_config_list)),
             )
             .service(web::resource("/config/info").route(web::get().to(v2::config_api::get_config)))
+            .service(
+                web::resource("/config/decrypt")
+                    .route(web::get().to(v2::config_api::decrypt_config)),
+            )
             .service(web::resource("/config/add").route(web::post().to(v2::config_api::add_config)))
             .service(
                 web::resource("/config/update").route(web::post().to(v2::config_api::add_config)),
@@ -291,6 +332,50 @@ pub fn console_api_config_v2(config: &mut web::ServiceConfig) {
                 web::resource("/config/history")
                     .route(web::get().to(v2::config_api::query_history_config_page)),
             )
+            .service(
+                web::resource("/config/history/diff")
+                    .route(web::get().to(v2::config_api::diff_history_config)),
+            )
+            .service(
+                web::resource("/config/history/rollback")
+                    .route(web::post().to(v2::config_api::rollback_history_config)),
+            )
+            .service(
+                web::resource("/config/gray/info")
+                    .route(web::get().to(v2::config_api::get_gray_config)),
+            )
+            .service(
+                web::resource("/config/gray/publish")
+                    .route(web::post().to(v2::config_api::publish_gray_config)),
+            )
+            .service(
+                web::resource("/config/gray/promote")
+                    .route(web::post().to(v2::config_api::promote_gray_config)),
+            )
+            .service(
+                web::resource("/config/gray/abort")
+                    .route(web::post().to(v2::config_api::abort_gray_config)),
+            )
+            .service(
+                web::resource("/config/webhook/list")
+                    .route(web::get().to(v2::config_api::query_config_webhook_list)),
+            )
+            .service(
+                web::resource("/config/webhook/add")
+                    .route(web::post().to(v2::config_api::add_config_webhook)),
+            )
+            .service(
+                web::resource("/config/webhook/update")
+                    .route(web::post().to(v2::config_api::update_config_webhook)),
+            )
+            .service(
+                web::resource("/config/webhook/remove")
+                    .route(web::post().to(v2::config_api::remove_config_webhook)),
+            )
+            .service(
+                web::resource("/config/webhook/logs")
+                    .route(web::get().to(v2::config_api::query_config_webhook_logs)),
+            )
             .service(
                 web::resource("/service/list")
                     .route(web::get().to(v2::naming_api::query_service_list)),
@@ -12,13 +12,14 @@ use zip::write::FileOptions;
 
 use super::model::PageResult;
 use crate::common::appdata::AppShareData;
+use crate::common::model::privilege::ResourceAction;
 use crate::config::core::{ConfigActor, ConfigCmd, ConfigInfoDto, ConfigKey, ConfigResult};
 use crate::config::ConfigUtils;
 use crate::console::model::config_model::{
-    ConfigParams, OpsConfigOptQueryListResponse, OpsConfigQueryListRequest,
+    ConfigImportFailItem, ConfigParams, OpsConfigOptQueryListResponse, OpsConfigQueryListRequest,
 };
 use crate::raft::cluster::model::SetConfigReq;
-use crate::{now_millis, user_namespace_privilege};
+use crate::{now_millis, user_namespace_privilege, user_resource_privilege};
 use actix::prelude::Addr;
 use tokio_stream::StreamExt;
 use zip::ZipWriter;
@@ -118,6 +119,8 @@ pub async fn import_config(
             tenant.as_str()
         )));
     }
+    let resource_privilege = user_resource_privilege!(req);
+    let mut config_list = vec![];
     //let tenant = Arc::new(ConfigUtils::default_tenant(config_info.0.tenant.unwrap_or_default()));
     for f in form.files {
         match zip::ZipArchive::new(f.file) {
@@ -142,6 +145,14 @@ pub async fn import_config(
                             Arc::new(parts[0].to_owned()),
                             tenant.clone(),
                         );
+                        if !resource_privilege.check_permission(
+                            &config_key.tenant,
+                            &config_key.group,
+                            &config_key.data_id,
+                            ResourceAction::Write,
+                        ) {
+                            continue;
+                        }
                         let value = match io::read_to_string(&mut file) {
                             Ok(v) => v,
                             Err(_) => continue,
@@ -151,14 +162,39 @@ pub async fn import_config(
                         let mut req = SetConfigReq::new(config_key.clone(), Arc::new(value));
                         let data_id_clone = config_key.data_id.clone();
                         req.config_type = SetConfigReq::detect_config_type(data_id_clone);
-
-                        app.config_route.set_config(req).await.ok();
+                        config_list.push(req);
                     }
                 }
             }
             Err(_) => todo!(),
         }
     }
+    //任一配置内容校验不通过时整体拒绝导入,并返回不通过的条目
+    let mut fail_list = vec![];
+    for req in &config_list {
+        if let Err(error) = app
+            .config_validator
+            .validate_by_namespace(
+                &app.namespace_addr,
+                &req.config_key.tenant,
+                req.config_type.as_ref().map(|v| v.as_str()),
+                &req.value,
+            )
+            .await
+        {
+            fail_list.push(ConfigImportFailItem {
+                group: req.config_key.group.clone(),
+                data_id: req.config_key.data_id.clone(),
+                error,
+            });
+        }
+    }
+    if !fail_list.is_empty() {
+        return Ok(HttpResponse::BadRequest().json(fail_list));
+    }
+    for req in config_list {
+        app.config_route.set_config(req).await.ok();
+    }
     Ok(HttpResponse::Ok().finish())
 }
 
@@ -228,6 +264,7 @@ pub async fn download_config(
 
 /// 按 key 导出配置
 pub async fn download_config_by_keys(
+    req: HttpRequest,
     request: web::Json<Vec<ConfigParams>>,
     config_addr: web::Data<Addr<ConfigActor>>,
 ) -> impl Responder {
@@ -236,6 +273,8 @@ pub async fn download_config_by_keys(
         return HttpResponse::BadRequest().body("keys cannot be empty");
     }
 
+    let namespace_privilege = user_namespace_privilege!(req);
+    let resource_privilege = user_resource_privilege!(req);
     let keys = params
         .into_iter()
         .map(|k| {
@@ -245,6 +284,15 @@ pub async fn download_config_by_keys(
                 ..k
             }
         })
+        .filter(|k| {
+            namespace_privilege.check_permission(&k.tenant)
+                && resource_privilege.check_permission(
+                    &k.tenant,
+                    &k.group,
+                    &k.data_id,
+                    ResourceAction::Read,
+                )
+        })
         .collect();
 
     let cmd = ConfigCmd::QueryInfoByKeys(Box::new(keys));
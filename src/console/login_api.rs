@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 
-use super::model::login_model::{LoginConfig, LoginParam, LoginToken};
+use super::model::login_model::{LoginConfig, LoginParam, LoginToken, TotpLoginParam};
 use crate::cache::actor_model::{CacheManagerRaftResult, CacheSetParam};
 use crate::ldap::model::actor_model::{LdapMsgReq, LdapMsgResult};
 use crate::ldap::model::LdapUserParam;
@@ -14,12 +14,12 @@ use crate::{
         crypto_utils,
         model::{ApiResult, UserSession},
     },
-    now_second_i32,
+    now_millis_i64, now_second_i32,
     raft::cache::{
         model::{CacheKey, CacheType},
         CacheLimiterReq,
     },
-    user::{UserManagerReq, UserManagerResult},
+    user::{model::UserDto, permission::USER_ROLE_MANAGER, UserManagerReq, UserManagerResult},
 };
 use actix_web::http::{header, StatusCode};
 use actix_web::{
@@ -31,6 +31,9 @@ use captcha::filters::{Grid, Noise};
 use captcha::Captcha;
 use serde::Deserialize;
 
+const TOTP_TOKEN_PREFIX: &str = "TOTP_L#";
+const TOTP_TOKEN_TTL: i32 = 300;
+
 pub async fn login(
     request: HttpRequest,
     app: Data<Arc<AppShareData>>,
@@ -77,14 +80,13 @@ pub async fn login(
     if let Ok(Ok(res)) = app.user_manager.send(msg).await {
         if let UserManagerResult::CheckUserResult(valid, user) = res {
             if valid {
-                session = Some(Arc::new(UserSession {
-                    username: user.username,
-                    nickname: user.nickname,
-                    roles: user.roles.unwrap_or_default(),
-                    extend_infos: user.extend_info.unwrap_or_default(),
-                    namespace_privilege: user.namespace_privilege,
-                    refresh_time: now_second_i32() as u32,
-                }));
+                if user.totp_enable.unwrap_or_default() {
+                    //开启两步验证的用户需要再校验动态码
+                    return totp_challenge(&app, user.username).await;
+                }
+                session = Some(build_inner_user_session(&app, user));
+            } else if let Some(value) = check_user_locked(&user) {
+                return value;
             }
         }
         if !app.sys_config.ldap_enable && !app.sys_config.oauth2_enable && session.is_none() {
@@ -120,6 +122,122 @@ pub async fn login(
     HttpResponse::Ok().json(ApiResult::<()>::error(error_code, None))
 }
 
+fn build_inner_user_session(app: &Data<Arc<AppShareData>>, user: UserDto) -> Arc<UserSession> {
+    let roles = user.roles.clone().unwrap_or_default();
+    let need_bind_totp = app.sys_config.console_totp_require_manager
+        && !user.totp_enable.unwrap_or_default()
+        && roles
+            .iter()
+            .any(|e| e.as_str() == USER_ROLE_MANAGER.as_str());
+    Arc::new(UserSession {
+        resource_privilege: user.build_resource_privilege(),
+        username: user.username,
+        nickname: user.nickname,
+        roles,
+        extend_infos: user.extend_info.unwrap_or_default(),
+        namespace_privilege: user.namespace_privilege,
+        refresh_time: now_second_i32() as u32,
+        need_change_password: user.need_change_password.unwrap_or_default(),
+        need_bind_totp,
+    })
+}
+
+fn check_user_locked(user: &UserDto) -> Option<HttpResponse> {
+    let lock_until_time = user.lock_until_time.filter(|e| *e > now_millis_i64())?;
+    Some(HttpResponse::Ok().json(ApiResult::<()>::error(
+        "USER_LOCKED_ERROR".to_owned(),
+        Some(format!(
+            "user is locked, please try again after {} seconds",
+            (lock_until_time - now_millis_i64()) / 1000 + 1
+        )),
+    )))
+}
+
+///
+/// 密码校验通过后生成两步验证临时token,5分钟内有效
+async fn totp_challenge(app: &Data<Arc<AppShareData>>, username: Arc<String>) -> HttpResponse {
+    let totp_token = uuid::Uuid::new_v4().to_string().replace('-', "");
+    let cache_req =
+        crate::cache::actor_model::CacheManagerRaftReq::Set(CacheSetParam::new_with_ttl(
+            CacheKey::new(
+                CacheType::String,
+                Arc::new(format!("{}{}", TOTP_TOKEN_PREFIX, &totp_token)),
+            ),
+            crate::cache::model::CacheValue::String(username),
+            TOTP_TOKEN_TTL,
+        ));
+    if let Err(e) = app
+        .raft_request_route
+        .request(ClientRequest::CacheReq { req: cache_req })
+        .await
+    {
+        return HttpResponse::Ok().json(ApiResult::<()>::error(
+            "SAVE_SESSION_ERROR".to_string(),
+            Some(e.to_string()),
+        ));
+    }
+    let login_token = LoginToken {
+        need_totp: true,
+        totp_token: Some(totp_token),
+        ..Default::default()
+    };
+    HttpResponse::Ok().json(ApiResult::success(Some(login_token)))
+}
+
+///
+/// 两步验证登录,code可以是动态码或恢复码
+pub async fn login_totp(
+    app: Data<Arc<AppShareData>>,
+    web::Form(param): web::Form<TotpLoginParam>,
+) -> HttpResponse {
+    let totp_key = CacheKey::new(
+        CacheType::String,
+        Arc::new(format!("{}{}", TOTP_TOKEN_PREFIX, &param.totp_token)),
+    );
+    let req = crate::cache::actor_model::CacheManagerLocalReq::Get(totp_key.clone());
+    let username =
+        if let Ok(Ok(CacheManagerRaftResult::Value(crate::cache::model::CacheValue::String(v)))) =
+            app.direct_cache_manager.send(req).await
+        {
+            v
+        } else {
+            return HttpResponse::Ok().json(ApiResult::<()>::error(
+                "TOTP_TOKEN_INVALID".to_owned(),
+                Some("totp token is invalid or expired".to_owned()),
+            ));
+        };
+    let limit_key = Arc::new(format!("USER_L#{}", &username));
+    if let Some(value) = login_limit(&app, &limit_key).await {
+        return value;
+    }
+    let msg = UserManagerReq::CheckTotp {
+        username,
+        code: param.code,
+    };
+    match app.user_manager.send(msg).await {
+        Ok(Ok(UserManagerResult::CheckUserResult(true, user))) => {
+            let remove_req = crate::cache::actor_model::CacheManagerRaftReq::Remove(totp_key);
+            app.raft_request_route
+                .request(ClientRequest::CacheReq { req: remove_req })
+                .await
+                .ok();
+            let session = build_inner_user_session(&app, user);
+            match apply_session(app, limit_key, session).await {
+                Ok(value) => value,
+                Err(e) => HttpResponse::Ok().json(ApiResult::<()>::error(
+                    "SAVE_SESSION_ERROR".to_string(),
+                    Some(e.to_string()),
+                )),
+            }
+        }
+        Ok(Ok(UserManagerResult::CheckUserResult(false, user))) => check_user_locked(&user)
+            .unwrap_or_else(|| {
+                HttpResponse::Ok().json(ApiResult::<()>::error("TOTP_CHECK_ERROR".to_owned(), None))
+            }),
+        _ => HttpResponse::Ok().json(ApiResult::<()>::error("SYSTEM_ERROR".to_owned(), None)),
+    }
+}
+
 #[derive(Deserialize)]
 pub struct OAuth2CallbackQuery {
     code: String,
@@ -159,8 +277,11 @@ pub async fn oauth2_callback(
             nickname: Some(meta.user_name),
             roles: vec![meta.role],
             namespace_privilege: meta.namespace_privilege,
+            resource_privilege: Default::default(),
             extend_infos: HashMap::default(),
             refresh_time: now_second_i32() as u32,
+            need_change_password: false,
+            need_bind_totp: false,
         })),
         Ok(Ok(OAuth2MsgResult::None)) => None,
         Ok(Ok(OAuth2MsgResult::AuthorizeUrl(_))) => {
@@ -244,6 +365,8 @@ async fn apply_session(
     };
     app.cache_manager.do_send(cache_req);
     */
+    let need_change_password = session.need_change_password;
+    let need_bind_totp = session.need_bind_totp;
     let cache_req =
         crate::cache::actor_model::CacheManagerRaftReq::Set(CacheSetParam::new_with_ttl(
             CacheKey::new(CacheType::UserSession, token.clone()),
@@ -266,6 +389,9 @@ async fn apply_session(
         .ok();
     let login_token = LoginToken {
         token: token.to_string(),
+        need_change_password,
+        need_bind_totp,
+        ..Default::default()
     };
     Ok(HttpResponse::Ok()
         .cookie(
@@ -305,8 +431,11 @@ async fn ldap_login(
             nickname: Some(meta.user_name),
             roles: vec![meta.role],
             namespace_privilege: meta.namespace_privilege,
+            resource_privilege: Default::default(),
             extend_infos: HashMap::default(),
             refresh_time: now_second_i32() as u32,
+            need_change_password: false,
+            need_bind_totp: false,
         }))
     } else {
         None
@@ -23,6 +23,18 @@ lazy_static::lazy_static! {
         "/rnacos/api/console/login/login", "/rnacos/api/console/login/captcha",
         "/rnacos/api/console/v2/login/login", "/rnacos/api/console/v2/login/captcha",
         "/rnacos/api/console/v2/login/config", "/rnacos/api/console/v2/login/oauth2/login",
+        "/rnacos/api/console/login/totp", "/rnacos/api/console/v2/login/totp",
+    ];
+    /// 必须修改密码的会话只允许访问的接口
+    pub static ref CHANGE_PASSWORD_ALLOW_PATHS: Vec<&'static str> = vec![
+        "/rnacos/api/console/user/info", "/rnacos/api/console/user/web_resources",
+        "/rnacos/api/console/user/reset_password", "/rnacos/api/console/login/logout",
+        "/rnacos/api/console/v2/user/info", "/rnacos/api/console/v2/user/web_resources",
+        "/rnacos/api/console/v2/user/reset_password", "/rnacos/api/console/v2/login/logout",
+    ];
+    /// 必须绑定两步验证的会话额外允许访问的接口
+    pub static ref BIND_TOTP_ALLOW_PATHS: Vec<&'static str> = vec![
+        "/rnacos/api/console/v2/user/totp/init", "/rnacos/api/console/v2/user/totp/enable",
     ];
     pub static ref STATIC_FILE_PATH: Regex= Regex::new(r"(?i).*\.(js|css|png|jpg|jpeg|bmp|svg)").unwrap();
     pub static ref API_PATH: Regex = Regex::new(r"(?i)/(api|nacos)/.*").unwrap();
@@ -99,6 +111,7 @@ where
         Box::pin(async move {
             let mut is_login = true;
             let mut user_has_permission = true;
+            let mut restrict_code = None;
             let path = request.path();
             let method = request.method().as_str();
             if is_check_path {
@@ -109,6 +122,13 @@ where
                 {
                     user_has_permission =
                         UserRole::match_url_by_roles(&session.roles, path, method);
+                    if !is_page && !CHANGE_PASSWORD_ALLOW_PATHS.contains(&path) {
+                        if session.need_change_password {
+                            restrict_code = Some("NEED_CHANGE_PASSWORD");
+                        } else if session.need_bind_totp && !BIND_TOTP_ALLOW_PATHS.contains(&path) {
+                            restrict_code = Some("NEED_BIND_TOTP");
+                        }
+                    }
                     request.extensions_mut().insert(session);
                     true
                 } else {
@@ -117,7 +137,20 @@ where
             }
             //log::info!("token: {}|{}|{}|{}|{}|{}",&token,is_page,is_check_path,is_login,request.path(),request.query_string());
             if is_login {
-                if user_has_permission {
+                if let Some(code) = restrict_code {
+                    let response = HttpResponse::Ok()
+                        .insert_header(("Need-Security-Action", code))
+                        .json(ApiResultOld::<()>::error(
+                            code.to_owned(),
+                            Some(
+                                "please change your password or bind two-factor authentication first"
+                                    .to_owned(),
+                            ),
+                        ))
+                        .map_into_right_body();
+                    let (http_request, _pl) = request.into_parts();
+                    Ok(ServiceResponse::new(http_request, response))
+                } else if user_has_permission {
                     let res = service.call(request);
                     // forwarded responses map to "left" body
                     res.await.map(ServiceResponse::map_into_left_body)
@@ -36,6 +36,7 @@ lazy_static::lazy_static! {
             namespace_id: Some(Arc::new("".to_owned())),
             namespace_name: Some(DEFAULT_NAMESPACE.to_owned()),
             r#type: Some("0".to_owned()),
+            config_validate_enforce: None,
     });
 }
 
@@ -123,6 +124,7 @@ impl NamespaceUtilsOld {
                 namespace_id: Some(namespace_id),
                 namespace_name: Some(namespace_name),
                 r#type: Some("2".to_owned()),
+                config_validate_enforce: None,
             };
             infos.push(new_info);
             Self::save_namespace(app_data, &infos).await
@@ -1,11 +1,17 @@
+use crate::common::string_utils::StringUtils;
 use crate::config::config_index::ConfigQueryParam;
+use crate::config::config_validator::ConfigValidateError;
+use crate::config::config_webhook::ConfigWebhook;
 use crate::config::core::{ConfigInfoDto, ConfigKey};
 use crate::config::dal::ConfigHistoryParam;
+use crate::config::model::ConfigGrayRule;
 use crate::config::ConfigUtils;
-use crate::user_namespace_privilege;
+use crate::now_millis_i64;
+use crate::{user_namespace_privilege, user_resource_privilege};
 use actix_http::HttpMessage;
 use actix_web::HttpRequest;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::Arc;
 
 #[derive(Debug, Serialize, Deserialize, Default)]
@@ -14,6 +20,16 @@ pub struct OpsConfigImportInfo {
     pub tenant: Option<String>,
 }
 
+///
+/// 导入配置时内容校验不通过的条目
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigImportFailItem {
+    pub group: Arc<String>,
+    pub data_id: Arc<String>,
+    pub error: ConfigValidateError,
+}
+
 #[derive(Debug, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct OpsConfigQueryListRequest {
@@ -22,6 +38,9 @@ pub struct OpsConfigQueryListRequest {
     pub tenant: Option<String>,
     pub group_param: Option<String>,
     pub data_param: Option<String>,
+    pub content_param: Option<String>,
+    /// 按配置标签过滤,多个标签用逗号分隔
+    pub tags_param: Option<String>,
     pub group: Option<String>,
     pub data_id: Option<String>,
 }
@@ -36,7 +55,13 @@ impl OpsConfigQueryListRequest {
             offset,
             like_group: self.group_param,
             like_data_id: self.data_param,
+            content_search: self.content_param,
+            tags: self
+                .tags_param
+                .map(|v| ConfigUtils::parse_tags(&v))
+                .unwrap_or_default(),
             namespace_privilege,
+            resource_privilege: user_resource_privilege!(req),
             ..Default::default()
         };
         if let Some(tenant) = self.tenant {
@@ -91,6 +116,7 @@ pub struct ConfigParams {
     pub content: Option<Arc<String>>,
     pub config_type: Option<Arc<String>>,
     pub desc: Option<Arc<String>>,
+    pub tags: Option<Vec<String>>,
 }
 
 impl ConfigParams {
@@ -102,6 +128,92 @@ impl ConfigParams {
         let tenant = ConfigUtils::default_tenant(self.tenant.clone().unwrap_or_default());
         ConfigKey::new_by_arc(self.data_id.clone(), group, Arc::new(tenant))
     }
+
+    pub fn to_tags(&self) -> Option<Vec<Arc<String>>> {
+        self.tags
+            .as_ref()
+            .map(|tags| ConfigUtils::parse_tags(&tags.join(",")))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigHistoryDiffParams {
+    pub data_id: Arc<String>,
+    pub group: Option<Arc<String>>,
+    pub tenant: Option<String>,
+    pub from_id: u64,
+    /// 为空时与当前配置比较
+    pub to_id: Option<u64>,
+}
+
+impl ConfigHistoryDiffParams {
+    pub fn to_key(&self) -> ConfigKey {
+        let group = self
+            .group
+            .clone()
+            .unwrap_or(Arc::new("DEFAULT_GROUP".to_owned()));
+        let tenant = ConfigUtils::default_tenant(self.tenant.clone().unwrap_or_default());
+        ConfigKey::new_by_arc(self.data_id.clone(), group, Arc::new(tenant))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigHistoryRollbackParams {
+    pub data_id: Arc<String>,
+    pub group: Option<Arc<String>>,
+    pub tenant: Option<String>,
+    pub history_id: u64,
+}
+
+impl ConfigHistoryRollbackParams {
+    pub fn to_key(&self) -> ConfigKey {
+        let group = self
+            .group
+            .clone()
+            .unwrap_or(Arc::new("DEFAULT_GROUP".to_owned()));
+        let tenant = ConfigUtils::default_tenant(self.tenant.clone().unwrap_or_default());
+        ConfigKey::new_by_arc(self.data_id.clone(), group, Arc::new(tenant))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigGrayParams {
+    pub data_id: Arc<String>,
+    pub group: Option<Arc<String>>,
+    pub tenant: Option<String>,
+    pub content: Option<Arc<String>>,
+    pub client_ips: Option<Vec<String>>,
+    pub labels: Option<HashMap<String, String>>,
+}
+
+impl ConfigGrayParams {
+    pub fn to_key(&self) -> ConfigKey {
+        let group = self
+            .group
+            .clone()
+            .unwrap_or(Arc::new("DEFAULT_GROUP".to_owned()));
+        let tenant = ConfigUtils::default_tenant(self.tenant.clone().unwrap_or_default());
+        ConfigKey::new_by_arc(self.data_id.clone(), group, Arc::new(tenant))
+    }
+
+    pub fn to_rule(&self) -> ConfigGrayRule {
+        let client_ips = self
+            .client_ips
+            .clone()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|e| e.trim().to_owned())
+            .filter(|e| !e.is_empty())
+            .map(Arc::new)
+            .collect();
+        ConfigGrayRule {
+            client_ips,
+            labels: self.labels.clone().unwrap_or_default(),
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Default)]
@@ -111,4 +223,43 @@ pub struct ConfigInfo {
     pub md5: Option<Arc<String>>,
     pub config_type: Option<Arc<String>>,
     pub desc: Option<Arc<String>>,
+    pub tags: Vec<Arc<String>>,
+    pub encrypted_data_key: Option<Arc<String>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigWebhookParams {
+    pub id: Option<u64>,
+    pub name: Option<String>,
+    pub url: Option<String>,
+    /// 为空时匹配全部命名空间
+    pub tenant: Option<String>,
+    pub group: Option<String>,
+    pub data_id_pattern: Option<String>,
+    pub enable: Option<bool>,
+}
+
+impl ConfigWebhookParams {
+    pub fn to_webhook(self) -> ConfigWebhook {
+        ConfigWebhook {
+            id: self.id.unwrap_or_default(),
+            name: Arc::new(self.name.unwrap_or_default().trim().to_owned()),
+            url: Arc::new(self.url.unwrap_or_default().trim().to_owned()),
+            tenant: self
+                .tenant
+                .map(|v| Arc::new(ConfigUtils::default_tenant(v))),
+            group: StringUtils::map_not_empty(self.group).map(Arc::new),
+            data_id_pattern: StringUtils::map_not_empty(self.data_id_pattern).map(Arc::new),
+            enable: self.enable.unwrap_or(true),
+            update_time: now_millis_i64(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigWebhookLogParams {
+    pub webhook_id: Option<u64>,
+    pub limit: Option<usize>,
 }
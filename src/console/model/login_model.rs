@@ -13,6 +13,18 @@ pub struct LoginParam {
 #[serde(rename_all = "camelCase")]
 pub struct LoginToken {
     pub token: String,
+    pub need_change_password: bool,
+    pub need_bind_totp: bool,
+    /// 需要进行两步验证,使用totp_token与动态码完成登录
+    pub need_totp: bool,
+    pub totp_token: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TotpLoginParam {
+    pub totp_token: String,
+    pub code: String,
 }
 
 #[derive(Debug, Default, Serialize, Deserialize)]
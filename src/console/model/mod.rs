@@ -18,6 +18,8 @@ pub struct NamespaceInfo {
     pub namespace_id: Option<Arc<String>>,
     pub namespace_name: Option<String>,
     pub r#type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub config_validate_enforce: Option<bool>,
 }
 
 impl From<Namespace> for NamespaceInfo {
@@ -26,6 +28,7 @@ impl From<Namespace> for NamespaceInfo {
             namespace_id: Some(value.namespace_id),
             namespace_name: Some(value.namespace_name),
             r#type: Some(NamespaceFromFlags::get_api_type(value.flag)),
+            config_validate_enforce: Some(value.config_validate_enforce),
         }
     }
 }
@@ -36,6 +39,7 @@ impl From<NamespaceInfo> for NamespaceParam {
             namespace_id: value.namespace_id.unwrap_or_default(),
             namespace_name: value.namespace_name,
             r#type: value.r#type,
+            config_validate_enforce: value.config_validate_enforce,
         }
     }
 }
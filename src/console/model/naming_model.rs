@@ -4,14 +4,17 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
 
+use crate::naming::health_check::ServiceHealthCheck;
+use crate::naming::route_rule::ServiceRouteRule;
+use crate::naming::selector::ServiceSelector;
 use crate::naming::service::ServiceInfoDto;
 use crate::naming::service_index::ServiceQueryParam;
 use crate::naming::{
     model::{Instance, ServiceKey},
     NamingUtils,
 };
-use crate::user_namespace_privilege;
 use crate::utils::get_bool_from_string;
+use crate::{user_namespace_privilege, user_resource_privilege};
 
 /*
 #[derive(Debug,Serialize,Deserialize,Default)]
@@ -44,6 +47,7 @@ impl ServiceQueryListRequest {
             limit,
             offset,
             namespace_privilege,
+            resource_privilege: user_resource_privilege!(req),
             ..Default::default()
         };
         if let Some(namespace_id) = self.namespace_id {
@@ -74,6 +78,9 @@ pub struct ServiceDto {
     pub trigger_flag: Option<bool>,
     pub metadata: Option<String>,
     pub protect_threshold: Option<f32>,
+    pub selector: Option<ServiceSelector>,
+    pub health_check: Option<ServiceHealthCheck>,
+    pub route_rule: Option<Arc<ServiceRouteRule>>,
 }
 
 impl From<ServiceInfoDto> for ServiceDto {
@@ -91,6 +98,9 @@ impl From<ServiceInfoDto> for ServiceDto {
             trigger_flag: Some(value.trigger_flag),
             metadata,
             protect_threshold: value.protect_threshold,
+            selector: value.selector,
+            health_check: value.health_check.map(|e| e.without_secret()),
+            route_rule: value.route_rule,
         }
     }
 }
@@ -141,6 +151,10 @@ pub struct ServiceParam {
     pub group_name: Option<String>,
     pub metadata: Option<String>,
     pub protect_threshold: Option<f32>,
+    pub selector: Option<ServiceSelector>,
+    pub health_check: Option<ServiceHealthCheck>,
+    /// 流量路由规则,rules为空时删除规则
+    pub route_rule: Option<ServiceRouteRule>,
 }
 
 impl ServiceParam {
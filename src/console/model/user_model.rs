@@ -1,7 +1,10 @@
 use std::sync::Arc;
 
-use crate::common::model::privilege::PrivilegeGroupOptionParam;
-use crate::user::{model::UserDto, permission::UserRoleHelper};
+use crate::common::model::privilege::{PrivilegeGroupOptionParam, ResourcePermission};
+use crate::user::{
+    model::{UserDto, UserSourceType},
+    permission::UserRoleHelper,
+};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -29,6 +32,11 @@ pub struct UpdateUserInfoParam {
     pub enable: Option<bool>,
     pub roles: Option<String>,
     pub namespace_privilege_param: Option<PrivilegeGroupOptionParam<Arc<String>>>,
+    pub resource_permissions: Option<Vec<ResourcePermission>>,
+    /// 创建服务账号,服务账号不支持密码登录,只能通过API key访问
+    pub service_account: Option<bool>,
+    /// 要求用户下次登录必须修改密码
+    pub need_change_password: Option<bool>,
 }
 
 impl UpdateUserInfoParam {
@@ -53,11 +61,32 @@ impl From<UpdateUserInfoParam> for UserDto {
             password: value.password,
             enable: value.enable,
             roles,
+            resource_permissions: value.resource_permissions,
+            source: if value.service_account.unwrap_or_default() {
+                Some(UserSourceType::ServiceAccount.to_str().to_owned())
+            } else {
+                None
+            },
+            need_change_password: value.need_change_password,
             ..Default::default()
         }
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiKeyParams {
+    pub username: Option<Arc<String>>,
+    pub key_id: Option<Arc<String>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TotpParams {
+    pub username: Option<Arc<String>>,
+    pub code: Option<String>,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct UserPageParams {
@@ -8,8 +8,9 @@ use super::model::{
     naming_model::{OpsNamingQueryListResponse, QueryAllInstanceListParam},
     PageResult,
 };
+use crate::common::model::privilege::ResourceAction;
 use crate::naming::core::{NamingActor, NamingCmd, NamingResult};
-use crate::user_namespace_privilege;
+use crate::{user_namespace_privilege, user_resource_privilege};
 
 pub async fn query_ops_instances_list(
     req: HttpRequest,
@@ -25,6 +26,17 @@ pub async fn query_ops_instances_list(
                     &key.namespace_id
                 ));
             }
+            if !user_resource_privilege!(req).check_permission(
+                &key.namespace_id,
+                &key.group_name,
+                &key.service_name,
+                ResourceAction::Read,
+            ) {
+                return HttpResponse::Unauthorized().body(format!(
+                    "user no such resource permission: {}/{}/{}",
+                    &key.namespace_id, &key.group_name, &key.service_name
+                ));
+            }
             match naming_addr.send(NamingCmd::QueryAllInstanceList(key)).await {
                 Ok(res) => match res as anyhow::Result<NamingResult> {
                     Ok(result) => match result {
@@ -8,7 +8,10 @@ use actix_web::{
 use serde::{Deserialize, Serialize};
 
 use super::model::user_model::{UpdateUserInfoParam, UserInfo, UserPageParams, UserPermissions};
+use crate::cache::actor_model::{CacheManagerRaftReq, CacheSetParam};
+use crate::cache::model::{CacheKey, CacheType, CacheValue};
 use crate::common::get_app_version;
+use crate::raft::store::ClientRequest;
 use crate::{
     common::{
         appdata::AppShareData,
@@ -25,6 +28,46 @@ pub struct ResetPasswordParam {
     pub new_password: String,
 }
 
+///
+/// 修改密码后清除当前会话的必须修改密码标记
+pub(crate) async fn clear_session_change_password_flag(
+    req: &HttpRequest,
+    app: &Data<Arc<AppShareData>>,
+    session: &Arc<UserSession>,
+) {
+    if session.need_change_password {
+        update_current_session(req, app, session, |s| s.need_change_password = false).await;
+    }
+}
+
+///
+/// 更新当前登录会话,用于解除会话的访问限制
+pub(crate) async fn update_current_session<F: FnOnce(&mut UserSession)>(
+    req: &HttpRequest,
+    app: &Data<Arc<AppShareData>>,
+    session: &Arc<UserSession>,
+    f: F,
+) {
+    let token = if let Some(ck) = req.cookie("token") {
+        ck.value().to_owned()
+    } else if let Some(v) = req.headers().get("Token") {
+        v.to_str().unwrap_or_default().to_owned()
+    } else {
+        return;
+    };
+    let mut new_session = session.as_ref().to_owned();
+    f(&mut new_session);
+    let cache_req = CacheManagerRaftReq::Set(CacheSetParam::new_with_ttl(
+        CacheKey::new(CacheType::UserSession, Arc::new(token)),
+        CacheValue::UserSession(Arc::new(new_session)),
+        app.sys_config.console_login_timeout,
+    ));
+    app.raft_request_route
+        .request(ClientRequest::CacheReq { req: cache_req })
+        .await
+        .ok();
+}
+
 pub async fn get_user_info(req: HttpRequest) -> actix_web::Result<impl Responder> {
     if let Some(session) = req.extensions().get::<Arc<UserSession>>() {
         let userinfo = UserInfo {
@@ -72,21 +115,19 @@ pub async fn reset_password(
     app: Data<Arc<AppShareData>>,
     web::Form(param): web::Form<ResetPasswordParam>,
 ) -> actix_web::Result<impl Responder> {
-    let (msg, username) = if let Some(session) = req.extensions().get::<Arc<UserSession>>() {
-        let username = Arc::new(session.username.to_string());
-        (
-            UserManagerReq::CheckUser {
-                name: username.clone(),
-                password: param.old_password,
-            },
-            username,
-        )
+    let session = if let Some(session) = req.extensions().get::<Arc<UserSession>>() {
+        session.clone()
     } else {
         return Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
             "NOT_FOUND_USER_SESSION".to_owned(),
             None,
         )));
     };
+    let username = Arc::new(session.username.to_string());
+    let msg = UserManagerReq::CheckUser {
+        name: username.clone(),
+        password: param.old_password,
+    };
     if let Ok(Ok(v)) = app.user_manager.send(msg).await {
         match v {
             UserManagerResult::CheckUserResult(valid, _user) => {
@@ -99,8 +140,18 @@ pub async fn reset_password(
                         },
                         namespace_privilege_param: None,
                     };
-                    if let Ok(Ok(_r)) = app.user_manager.send(msg).await {
-                        return Ok(HttpResponse::Ok().json(ApiResult::success(Some(true))));
+                    match app.user_manager.send(msg).await {
+                        Ok(Ok(_r)) => {
+                            clear_session_change_password_flag(&req, &app, &session).await;
+                            return Ok(HttpResponse::Ok().json(ApiResult::success(Some(true))));
+                        }
+                        Ok(Err(e)) => {
+                            return Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+                                "UPDATE_PASSWORD_ERROR".to_owned(),
+                                Some(e.to_string()),
+                            )));
+                        }
+                        Err(_) => {}
                     }
                 }
             }
@@ -159,8 +210,16 @@ pub async fn add_user(
         },
         namespace_privilege_param,
     };
-    app.user_manager.send(msg).await.ok();
-    Ok(HttpResponse::Ok().json(ApiResult::success(Some(true))))
+    match app.user_manager.send(msg).await {
+        Ok(Ok(_)) => Ok(HttpResponse::Ok().json(ApiResult::success(Some(true)))),
+        Ok(Err(e)) => Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+            "UPDATE_USER_ERROR".to_owned(),
+            Some(e.to_string()),
+        ))),
+        Err(_) => {
+            Ok(HttpResponse::Ok().json(ApiResult::<()>::error("SYSTEM_ERROR".to_owned(), None)))
+        }
+    }
 }
 
 pub async fn update_user(
@@ -177,8 +236,16 @@ pub async fn update_user(
         },
         namespace_privilege_param,
     };
-    app.user_manager.send(msg).await.ok();
-    Ok(HttpResponse::Ok().json(ApiResult::success(Some(true))))
+    match app.user_manager.send(msg).await {
+        Ok(Ok(_)) => Ok(HttpResponse::Ok().json(ApiResult::success(Some(true)))),
+        Ok(Err(e)) => Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+            "UPDATE_USER_ERROR".to_owned(),
+            Some(e.to_string()),
+        ))),
+        Err(_) => {
+            Ok(HttpResponse::Ok().json(ApiResult::<()>::error("SYSTEM_ERROR".to_owned(), None)))
+        }
+    }
 }
 
 pub async fn remove_user(
@@ -0,0 +1,26 @@
+use crate::audit::model::AuditQueryParam;
+use crate::audit::{AuditManagerReq, AuditManagerResult};
+use crate::common::appdata::AppShareData;
+use crate::common::model::{ApiResult, PageResult};
+use crate::console::v2::{handle_error, handle_unexpected_response_error};
+use actix_web::web::Data;
+use actix_web::{web, HttpResponse, Responder};
+use std::sync::Arc;
+
+pub async fn query_audit_page(
+    app: Data<Arc<AppShareData>>,
+    web::Query(param): web::Query<AuditQueryParam>,
+) -> actix_web::Result<impl Responder> {
+    match app
+        .audit_manager
+        .send(AuditManagerReq::QueryPage(param))
+        .await
+    {
+        Ok(Ok(AuditManagerResult::Page(total_count, list))) => {
+            Ok(HttpResponse::Ok().json(ApiResult::success(Some(PageResult { total_count, list }))))
+        }
+        Ok(Ok(_)) => Ok(handle_unexpected_response_error("query_audit_page")),
+        Ok(Err(err)) => Ok(handle_error(err)),
+        Err(err) => Ok(handle_error(err.into())),
+    }
+}
@@ -1,15 +1,37 @@
+use crate::audit::model::AuditSummary;
 use crate::common::appdata::AppShareData;
+use crate::common::constant::SEQ_CONFIG_WEBHOOK_ID;
+use crate::common::model::privilege::ResourceAction;
 use crate::common::model::{ApiResult, PageResult, UserSession};
-use crate::config::core::{ConfigActor, ConfigCmd, ConfigResult};
+use crate::config::config_cipher::ConfigCipher;
+use crate::config::config_webhook::{
+    ConfigWebhook, ConfigWebhookNotifyCmd, ConfigWebhookNotifyResult, ConfigWebhookRaftReq,
+};
+use crate::config::core::{ConfigActor, ConfigCmd, ConfigKey, ConfigResult};
+use crate::config::model::ConfigGrayRaftReq;
 pub use crate::console::config_api::{download_config, import_config};
-use crate::console::model::config_model::{ConfigInfo, ConfigParams, OpsConfigQueryListRequest};
-use crate::console::v2::ERROR_CODE_SYSTEM_ERROR;
+use crate::console::model::config_model::{
+    ConfigGrayParams, ConfigHistoryDiffParams, ConfigHistoryRollbackParams, ConfigInfo,
+    ConfigParams, ConfigWebhookLogParams, ConfigWebhookParams, OpsConfigQueryListRequest,
+};
+use crate::console::v2::{
+    handle_config_validate_error, handle_error, handle_not_found_error, handle_param_error,
+    ERROR_CODE_SYSTEM_ERROR,
+};
+use crate::now_millis_i64;
 use crate::raft::cluster::model::{DelConfigReq, SetConfigReq};
-use crate::{user_namespace_privilege, user_no_namespace_permission};
+use crate::raft::store::ClientRequest;
+use crate::sequence::{SequenceRequest, SequenceResult};
+use crate::user::permission::UserRole;
+use crate::{
+    user_namespace_privilege, user_no_namespace_permission, user_no_resource_permission,
+    user_resource_privilege,
+};
 use actix::Addr;
 use actix_web::web::Data;
 use actix_web::HttpMessage;
 use actix_web::{web, HttpRequest, HttpResponse, Responder};
+use std::collections::BTreeMap;
 use std::sync::Arc;
 
 pub async fn query_config_list(
@@ -64,6 +86,13 @@ pub async fn query_history_config_page(
     {
         user_no_namespace_permission!(&param.tenant);
     }
+    let tenant = param.tenant.as_deref().unwrap_or_default();
+    let group = param.group.as_deref().unwrap_or_default();
+    let data_id = param.data_id.as_deref().unwrap_or_default();
+    if !user_resource_privilege!(req).check_permission(tenant, group, data_id, ResourceAction::Read)
+    {
+        user_no_resource_permission!(tenant, group, data_id);
+    }
     let cmd = ConfigCmd::QueryHistoryPageInfo(Box::new(param));
     match config_addr.send(cmd).await {
         Ok(res) => {
@@ -94,12 +123,22 @@ pub(crate) async fn get_config(
     if !namespace_privilege.check_permission(&config_key.tenant) {
         user_no_namespace_permission!(&config_key.tenant);
     }
+    if !user_resource_privilege!(req).check_permission(
+        &config_key.tenant,
+        &config_key.group,
+        &config_key.data_id,
+        ResourceAction::Read,
+    ) {
+        user_no_resource_permission!(&config_key.tenant, &config_key.group, &config_key.data_id);
+    }
     let cmd = ConfigCmd::GET(config_key);
     if let Ok(Ok(ConfigResult::Data {
         value: v,
         md5,
         config_type,
         desc,
+        tags,
+        encrypted_data_key,
         ..
     })) = appdata.config_addr.send(cmd).await
     {
@@ -108,6 +147,8 @@ pub(crate) async fn get_config(
             md5: Some(md5),
             config_type,
             desc,
+            tags,
+            encrypted_data_key,
         })))
     } else {
         HttpResponse::Ok().json(ApiResult::<()>::error(
@@ -117,6 +158,58 @@ pub(crate) async fn get_config(
     }
 }
 
+///
+/// 解密加密配置,仅对有配置管理权限的用户开放
+pub async fn decrypt_config(
+    req: HttpRequest,
+    web::Query(param): web::Query<ConfigParams>,
+    appdata: Data<Arc<AppShareData>>,
+) -> impl Responder {
+    let config_key = param.to_key();
+    let namespace_privilege = user_namespace_privilege!(req);
+    if !namespace_privilege.check_permission(&config_key.tenant) {
+        user_no_namespace_permission!(&config_key.tenant);
+    }
+    if !user_resource_privilege!(req).check_permission(
+        &config_key.tenant,
+        &config_key.group,
+        &config_key.data_id,
+        ResourceAction::Read,
+    ) {
+        user_no_resource_permission!(&config_key.tenant, &config_key.group, &config_key.data_id);
+    }
+    let cmd = ConfigCmd::GET(config_key.clone());
+    match appdata.config_addr.send(cmd).await {
+        Ok(Ok(ConfigResult::Data {
+            value,
+            md5,
+            config_type,
+            desc,
+            tags,
+            encrypted_data_key,
+            ..
+        })) => {
+            let value = if let Some(encrypted_data_key) = &encrypted_data_key {
+                match appdata.config_cipher.decrypt(&value, encrypted_data_key) {
+                    Ok(v) => Arc::new(v),
+                    Err(err) => return handle_error(err),
+                }
+            } else {
+                value
+            };
+            HttpResponse::Ok().json(ApiResult::success(Some(ConfigInfo {
+                value: Some(value),
+                md5: Some(md5),
+                config_type,
+                desc,
+                tags,
+                encrypted_data_key: None,
+            })))
+        }
+        _ => handle_not_found_error("config", &config_key.build_key()),
+    }
+}
+
 pub async fn add_config(
     req: HttpRequest,
     appdata: Data<Arc<AppShareData>>,
@@ -133,13 +226,35 @@ pub async fn add_config(
     if !namespace_privilege.check_permission(&config_key.tenant) {
         user_no_namespace_permission!(&config_key.tenant);
     }
+    if !user_resource_privilege!(req).check_permission(
+        &config_key.tenant,
+        &config_key.group,
+        &config_key.data_id,
+        ResourceAction::Write,
+    ) {
+        user_no_resource_permission!(&config_key.tenant, &config_key.group, &config_key.data_id);
+    }
     if let Err(e) = config_key.is_valid() {
         return HttpResponse::Ok().json(ApiResult::<()>::error(
             ERROR_CODE_SYSTEM_ERROR.to_string(),
             Some(e.to_string()),
         ));
     }
+    if let Err(err) = appdata
+        .config_validator
+        .validate_by_namespace(
+            &appdata.namespace_addr,
+            &config_key.tenant,
+            param.config_type.as_ref().map(|v| v.as_str()),
+            &content,
+        )
+        .await
+    {
+        return handle_config_validate_error(err);
+    }
+    set_config_audit_before(&req, &appdata, &config_key).await;
     let mut req = SetConfigReq::new(config_key, content);
+    req.tags = param.to_tags();
     req.config_type = param.config_type;
     req.desc = param.desc;
     req.op_user = op_user;
@@ -163,6 +278,15 @@ pub async fn remove_config(
     if !namespace_privilege.check_permission(&config_key.tenant) {
         user_no_namespace_permission!(&config_key.tenant);
     }
+    if !user_resource_privilege!(req).check_permission(
+        &config_key.tenant,
+        &config_key.group,
+        &config_key.data_id,
+        ResourceAction::Write,
+    ) {
+        user_no_resource_permission!(&config_key.tenant, &config_key.group, &config_key.data_id);
+    }
+    set_config_audit_before(&req, &appdata, &config_key).await;
     let req = DelConfigReq::new(config_key);
     if appdata.config_route.del_config(req).await.is_ok() {
         HttpResponse::Ok().json(ApiResult::success(Some(true)))
@@ -173,3 +297,471 @@ pub async fn remove_config(
         ))
     }
 }
+
+///
+/// 记录配置变更前的内容摘要
+async fn set_config_audit_before(
+    req: &HttpRequest,
+    appdata: &Arc<AppShareData>,
+    config_key: &ConfigKey,
+) {
+    if let Ok(Ok(ConfigResult::Data {
+        value,
+        config_type,
+        desc,
+        tags,
+        ..
+    })) = appdata
+        .config_addr
+        .send(ConfigCmd::GET(config_key.clone()))
+        .await
+    {
+        let mut params = BTreeMap::new();
+        params.insert("content".to_owned(), value.as_ref().to_owned());
+        if let Some(v) = config_type {
+            params.insert("configType".to_owned(), v.as_ref().to_owned());
+        }
+        if let Some(v) = desc {
+            params.insert("desc".to_owned(), v.as_ref().to_owned());
+        }
+        if !tags.is_empty() {
+            let tags: Vec<&str> = tags.iter().map(|e| e.as_str()).collect();
+            params.insert("tags".to_owned(), tags.join(","));
+        }
+        req.extensions_mut()
+            .insert(AuditSummary::new_before(&params));
+    }
+}
+
+pub async fn get_gray_config(
+    req: HttpRequest,
+    web::Query(param): web::Query<ConfigParams>,
+    appdata: Data<Arc<AppShareData>>,
+) -> impl Responder {
+    let config_key = param.to_key();
+    let namespace_privilege = user_namespace_privilege!(req);
+    if !namespace_privilege.check_permission(&config_key.tenant) {
+        user_no_namespace_permission!(&config_key.tenant);
+    }
+    if !user_resource_privilege!(req).check_permission(
+        &config_key.tenant,
+        &config_key.group,
+        &config_key.data_id,
+        ResourceAction::Read,
+    ) {
+        user_no_resource_permission!(&config_key.tenant, &config_key.group, &config_key.data_id);
+    }
+    match appdata
+        .config_addr
+        .send(ConfigCmd::GetGray(config_key))
+        .await
+    {
+        Ok(Ok(ConfigResult::GrayData(gray))) => {
+            HttpResponse::Ok().json(ApiResult::success(Some(gray)))
+        }
+        Ok(Ok(_)) => HttpResponse::Ok().json(ApiResult::<()>::success(None)),
+        Ok(Err(err)) => handle_error(err),
+        Err(err) => handle_error(err.into()),
+    }
+}
+
+pub async fn publish_gray_config(
+    req: HttpRequest,
+    appdata: Data<Arc<AppShareData>>,
+    web::Json(param): web::Json<ConfigGrayParams>,
+) -> impl Responder {
+    let op_user = req
+        .extensions()
+        .get::<Arc<UserSession>>()
+        .map(|session| session.username.clone());
+    let config_key = param.to_key();
+    let namespace_privilege = user_namespace_privilege!(req);
+    if !namespace_privilege.check_permission(&config_key.tenant) {
+        user_no_namespace_permission!(&config_key.tenant);
+    }
+    if !user_resource_privilege!(req).check_permission(
+        &config_key.tenant,
+        &config_key.group,
+        &config_key.data_id,
+        ResourceAction::Write,
+    ) {
+        user_no_resource_permission!(&config_key.tenant, &config_key.group, &config_key.data_id);
+    }
+    let content = match &param.content {
+        Some(v) if !v.is_empty() => v.clone(),
+        _ => return handle_param_error("content is empty", "publish_gray_config"),
+    };
+    let rule = param.to_rule();
+    if rule.is_empty() {
+        return handle_param_error("gray rule is empty", "publish_gray_config");
+    }
+    if ConfigCipher::is_cipher_data_id(&config_key.data_id) {
+        return handle_param_error(
+            "gray release is not supported for cipher config",
+            "publish_gray_config",
+        );
+    }
+    let config_type = match appdata
+        .config_addr
+        .send(ConfigCmd::GET(config_key.clone()))
+        .await
+    {
+        Ok(Ok(ConfigResult::Data { config_type, .. })) => config_type,
+        _ => return handle_not_found_error("config", &config_key.build_key()),
+    };
+    if let Err(err) = appdata
+        .config_validator
+        .validate_by_namespace(
+            &appdata.namespace_addr,
+            &config_key.tenant,
+            config_type.as_ref().map(|v| v.as_str()),
+            &content,
+        )
+        .await
+    {
+        return handle_config_validate_error(err);
+    }
+    let raft_req = ConfigGrayRaftReq::Set {
+        key: config_key.build_key(),
+        value: content,
+        rule,
+        op_time: now_millis_i64(),
+        op_user,
+    };
+    match appdata
+        .raft_request_route
+        .request(ClientRequest::ConfigGrayReq { req: raft_req })
+        .await
+    {
+        Ok(_) => HttpResponse::Ok().json(ApiResult::success(Some(true))),
+        Err(err) => handle_error(err),
+    }
+}
+
+///
+/// 灰度配置全量发布：将灰度内容发布为正式配置并移除灰度
+pub async fn promote_gray_config(
+    req: HttpRequest,
+    appdata: Data<Arc<AppShareData>>,
+    web::Json(param): web::Json<ConfigParams>,
+) -> impl Responder {
+    let op_user = req
+        .extensions()
+        .get::<Arc<UserSession>>()
+        .map(|session| session.username.clone());
+    let config_key = param.to_key();
+    let namespace_privilege = user_namespace_privilege!(req);
+    if !namespace_privilege.check_permission(&config_key.tenant) {
+        user_no_namespace_permission!(&config_key.tenant);
+    }
+    if !user_resource_privilege!(req).check_permission(
+        &config_key.tenant,
+        &config_key.group,
+        &config_key.data_id,
+        ResourceAction::Write,
+    ) {
+        user_no_resource_permission!(&config_key.tenant, &config_key.group, &config_key.data_id);
+    }
+    if !matches!(
+        appdata
+            .config_addr
+            .send(ConfigCmd::GetGray(config_key.clone()))
+            .await,
+        Ok(Ok(ConfigResult::GrayData(_)))
+    ) {
+        return handle_not_found_error("gray config", &config_key.build_key());
+    }
+    set_config_audit_before(&req, &appdata, &config_key).await;
+    match appdata
+        .config_route
+        .promote_gray_config(config_key, op_user)
+        .await
+    {
+        Ok(_) => HttpResponse::Ok().json(ApiResult::success(Some(true))),
+        Err(err) => handle_error(err),
+    }
+}
+
+///
+/// 终止灰度：直接移除灰度配置
+pub async fn abort_gray_config(
+    req: HttpRequest,
+    appdata: Data<Arc<AppShareData>>,
+    web::Json(param): web::Json<ConfigParams>,
+) -> impl Responder {
+    let config_key = param.to_key();
+    let namespace_privilege = user_namespace_privilege!(req);
+    if !namespace_privilege.check_permission(&config_key.tenant) {
+        user_no_namespace_permission!(&config_key.tenant);
+    }
+    if !user_resource_privilege!(req).check_permission(
+        &config_key.tenant,
+        &config_key.group,
+        &config_key.data_id,
+        ResourceAction::Write,
+    ) {
+        user_no_resource_permission!(&config_key.tenant, &config_key.group, &config_key.data_id);
+    }
+    let raft_req = ConfigGrayRaftReq::Remove {
+        key: config_key.build_key(),
+    };
+    match appdata
+        .raft_request_route
+        .request(ClientRequest::ConfigGrayReq { req: raft_req })
+        .await
+    {
+        Ok(_) => HttpResponse::Ok().json(ApiResult::success(Some(true))),
+        Err(err) => handle_error(err),
+    }
+}
+
+///
+/// 比较配置历史版本差异,未指定toId时与当前配置比较
+pub async fn diff_history_config(
+    req: HttpRequest,
+    web::Query(param): web::Query<ConfigHistoryDiffParams>,
+    appdata: Data<Arc<AppShareData>>,
+) -> impl Responder {
+    let config_key = param.to_key();
+    let namespace_privilege = user_namespace_privilege!(req);
+    if !namespace_privilege.check_permission(&config_key.tenant) {
+        user_no_namespace_permission!(&config_key.tenant);
+    }
+    let resource_privilege = user_resource_privilege!(req);
+    if !resource_privilege.check_permission(
+        &config_key.tenant,
+        &config_key.group,
+        &config_key.data_id,
+        ResourceAction::Read,
+    ) {
+        user_no_resource_permission!(&config_key.tenant, &config_key.group, &config_key.data_id);
+    }
+    //加密配置需要与解密接口相同的权限及写权限才返回明文差异
+    let can_decrypt = req
+        .extensions()
+        .get::<Arc<UserSession>>()
+        .map(|session| UserRole::can_decrypt_config_by_roles(&session.roles))
+        .unwrap_or(false)
+        && resource_privilege.check_permission(
+            &config_key.tenant,
+            &config_key.group,
+            &config_key.data_id,
+            ResourceAction::Write,
+        );
+    let cmd = ConfigCmd::DiffHistory {
+        key: config_key.clone(),
+        from_id: param.from_id,
+        to_id: param.to_id,
+    };
+    match appdata.config_addr.send(cmd).await {
+        Ok(Ok(ConfigResult::ConfigHistoryDiff(dto))) => {
+            let is_cipher = ConfigCipher::is_cipher_data_id(&config_key.data_id);
+            match (*dto)
+                .build_diff(&appdata.config_cipher, is_cipher, can_decrypt)
+                .await
+            {
+                Ok(dto) => HttpResponse::Ok().json(ApiResult::success(Some(dto))),
+                Err(err) => handle_error(err),
+            }
+        }
+        Ok(Ok(_)) => handle_not_found_error("config history", &config_key.build_key()),
+        Ok(Err(err)) => handle_error(err),
+        Err(err) => handle_error(err.into()),
+    }
+}
+
+///
+/// 回滚到指定历史版本：通过raft重新发布历史内容,并以当前操作人记录新的历史
+pub async fn rollback_history_config(
+    req: HttpRequest,
+    appdata: Data<Arc<AppShareData>>,
+    web::Json(param): web::Json<ConfigHistoryRollbackParams>,
+) -> impl Responder {
+    let op_user = req
+        .extensions()
+        .get::<Arc<UserSession>>()
+        .map(|session| session.username.clone());
+    let config_key = param.to_key();
+    let namespace_privilege = user_namespace_privilege!(req);
+    if !namespace_privilege.check_permission(&config_key.tenant) {
+        user_no_namespace_permission!(&config_key.tenant);
+    }
+    if !user_resource_privilege!(req).check_permission(
+        &config_key.tenant,
+        &config_key.group,
+        &config_key.data_id,
+        ResourceAction::Write,
+    ) {
+        user_no_resource_permission!(&config_key.tenant, &config_key.group, &config_key.data_id);
+    }
+    let history = match appdata
+        .config_addr
+        .send(ConfigCmd::GetHistory(config_key.clone(), param.history_id))
+        .await
+    {
+        Ok(Ok(ConfigResult::HistoryData(history))) => history,
+        _ => return handle_not_found_error("config history", &config_key.build_key()),
+    };
+    set_config_audit_before(&req, &appdata, &config_key).await;
+    let mut set_req = SetConfigReq::new(config_key, history.content);
+    set_req.encrypted_data_key = history.encrypted_data_key;
+    set_req.op_user = op_user;
+    match appdata.config_route.set_config(set_req).await {
+        Ok(_) => HttpResponse::Ok().json(ApiResult::success(Some(true))),
+        Err(err) => handle_error(err),
+    }
+}
+
+pub async fn query_config_webhook_list(
+    req: HttpRequest,
+    appdata: Data<Arc<AppShareData>>,
+) -> impl Responder {
+    let namespace_privilege = user_namespace_privilege!(req);
+    match appdata.config_addr.send(ConfigCmd::QueryWebhooks).await {
+        Ok(Ok(ConfigResult::Webhooks(list))) => {
+            let list: Vec<Arc<ConfigWebhook>> = list
+                .into_iter()
+                .filter(|v| {
+                    namespace_privilege
+                        .check_option_value_permission(&v.tenant, namespace_privilege.is_all())
+                })
+                .collect();
+            HttpResponse::Ok().json(ApiResult::success(Some(list)))
+        }
+        Ok(Ok(_)) => HttpResponse::Ok().json(ApiResult::<()>::error(
+            ERROR_CODE_SYSTEM_ERROR.to_string(),
+            Some("config result type is error".to_owned()),
+        )),
+        Ok(Err(err)) => handle_error(err),
+        Err(err) => handle_error(err.into()),
+    }
+}
+
+pub async fn add_config_webhook(
+    req: HttpRequest,
+    appdata: Data<Arc<AppShareData>>,
+    web::Json(mut param): web::Json<ConfigWebhookParams>,
+) -> impl Responder {
+    param.id = None;
+    apply_config_webhook(req, appdata, param.to_webhook()).await
+}
+
+pub async fn update_config_webhook(
+    req: HttpRequest,
+    appdata: Data<Arc<AppShareData>>,
+    web::Json(param): web::Json<ConfigWebhookParams>,
+) -> impl Responder {
+    let id = param.id.unwrap_or_default();
+    let namespace_privilege = user_namespace_privilege!(req);
+    match get_config_webhook(&appdata, id).await {
+        Some(old) => {
+            if !namespace_privilege
+                .check_option_value_permission(&old.tenant, namespace_privilege.is_all())
+            {
+                user_no_namespace_permission!(&old.tenant);
+            }
+        }
+        None => return handle_not_found_error("config webhook", &id.to_string()),
+    }
+    apply_config_webhook(req, appdata, param.to_webhook()).await
+}
+
+pub async fn remove_config_webhook(
+    req: HttpRequest,
+    appdata: Data<Arc<AppShareData>>,
+    web::Json(param): web::Json<ConfigWebhookParams>,
+) -> impl Responder {
+    let id = param.id.unwrap_or_default();
+    let namespace_privilege = user_namespace_privilege!(req);
+    match get_config_webhook(&appdata, id).await {
+        Some(old) => {
+            if !namespace_privilege
+                .check_option_value_permission(&old.tenant, namespace_privilege.is_all())
+            {
+                user_no_namespace_permission!(&old.tenant);
+            }
+        }
+        None => return handle_not_found_error("config webhook", &id.to_string()),
+    }
+    let req = ConfigWebhookRaftReq::Remove { id };
+    match appdata
+        .raft_request_route
+        .request(ClientRequest::ConfigWebhookReq { req })
+        .await
+    {
+        Ok(_) => HttpResponse::Ok().json(ApiResult::success(Some(true))),
+        Err(err) => handle_error(err),
+    }
+}
+
+///
+/// 查询webhook推送日志,按时间倒序
+pub async fn query_config_webhook_logs(
+    web::Query(param): web::Query<ConfigWebhookLogParams>,
+    appdata: Data<Arc<AppShareData>>,
+) -> impl Responder {
+    let cmd = ConfigWebhookNotifyCmd::QueryLogs {
+        webhook_id: param.webhook_id,
+        limit: param.limit.unwrap_or(100),
+    };
+    match appdata.config_webhook_notify.send(cmd).await {
+        Ok(Ok(ConfigWebhookNotifyResult::Logs(list))) => {
+            HttpResponse::Ok().json(ApiResult::success(Some(list)))
+        }
+        Ok(Ok(_)) => HttpResponse::Ok().json(ApiResult::<()>::error(
+            ERROR_CODE_SYSTEM_ERROR.to_string(),
+            Some("config webhook result type is error".to_owned()),
+        )),
+        Ok(Err(err)) => handle_error(err),
+        Err(err) => handle_error(err.into()),
+    }
+}
+
+async fn get_config_webhook(appdata: &Arc<AppShareData>, id: u64) -> Option<Arc<ConfigWebhook>> {
+    match appdata.config_addr.send(ConfigCmd::QueryWebhooks).await {
+        Ok(Ok(ConfigResult::Webhooks(list))) => list.into_iter().find(|v| v.id == id),
+        _ => None,
+    }
+}
+
+async fn apply_config_webhook(
+    req: HttpRequest,
+    appdata: Data<Arc<AppShareData>>,
+    mut webhook: ConfigWebhook,
+) -> HttpResponse {
+    if webhook.name.is_empty() {
+        return handle_param_error("webhook name is empty", "apply_config_webhook");
+    }
+    if !webhook.url.starts_with("http://") && !webhook.url.starts_with("https://") {
+        return handle_param_error("webhook url is invalid", "apply_config_webhook");
+    }
+    let namespace_privilege = user_namespace_privilege!(req);
+    if !namespace_privilege
+        .check_option_value_permission(&webhook.tenant, namespace_privilege.is_all())
+    {
+        user_no_namespace_permission!(&webhook.tenant);
+    }
+    if webhook.id == 0 {
+        webhook.id = match appdata
+            .sequence_manager
+            .send(SequenceRequest::GetNextId(SEQ_CONFIG_WEBHOOK_ID.clone()))
+            .await
+        {
+            Ok(Ok(SequenceResult::NextId(id))) => id,
+            Ok(Err(err)) => return handle_error(err),
+            Err(err) => return handle_error(err.into()),
+            Ok(Ok(_)) => {
+                return handle_error(anyhow::anyhow!("failed to get config webhook id"));
+            }
+        };
+    }
+    let req = ConfigWebhookRaftReq::Update { webhook };
+    match appdata
+        .raft_request_route
+        .request(ClientRequest::ConfigWebhookReq { req })
+        .await
+    {
+        Ok(_) => HttpResponse::Ok().json(ApiResult::success(Some(true))),
+        Err(err) => handle_error(err),
+    }
+}
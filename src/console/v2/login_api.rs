@@ -1,3 +1,3 @@
 pub use crate::console::login_api::{
-    gen_captcha, get_login_config, login, logout, oauth2_callback,
+    gen_captcha, get_login_config, login, login_totp, logout, oauth2_callback,
 };
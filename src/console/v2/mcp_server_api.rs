@@ -1,3 +1,4 @@
+use crate::audit::model::AuditSummary;
 use crate::common::appdata::AppShareData;
 use crate::common::constant::{EMPTY_ARC_STRING, SEQ_MCP_SERVER_ID, SEQ_MCP_SERVER_VALUE_ID};
 use crate::common::model::{ApiResult, PageResult, UserSession};
@@ -11,13 +12,14 @@ use crate::console::v2::{
     handle_system_error, handle_unexpected_response_error,
 };
 use crate::mcp::model::actor_model::{McpManagerRaftReq, McpManagerReq, McpManagerResult};
-use crate::mcp::model::mcp::McpServerDto;
+use crate::mcp::model::mcp::{McpServer, McpServerDto};
 use crate::raft::store::{ClientRequest, ClientResponse};
 use crate::sequence::{SequenceRequest, SequenceResult};
 use actix_multipart::form::tempfile::TempFile;
 use actix_multipart::form::text::Text;
 use actix_multipart::form::MultipartForm;
 use actix_web::{web, Error, HttpMessage, HttpRequest, HttpResponse, Responder};
+use std::collections::BTreeMap;
 use std::fs::File;
 use std::io::{self, Read, Seek, SeekFrom, Write};
 use std::sync::Arc;
@@ -372,7 +374,7 @@ pub async fn publish_current_mcp_server(
 }
 
 async fn do_publish_current_mcp_server(
-    _req: HttpRequest,
+    req: HttpRequest,
     appdata: web::Data<Arc<AppShareData>>,
     param: McpServerParams,
 ) -> anyhow::Result<HttpResponse> {
@@ -386,7 +388,7 @@ async fn do_publish_current_mcp_server(
     let check_result = appdata.mcp_manager.send(check_cmd).await??;
 
     match check_result {
-        McpManagerResult::ServerInfo(Some(_)) => {}
+        McpManagerResult::ServerInfo(Some(server)) => set_publish_audit_before(&req, &server),
         _ => {
             return Err(anyhow::anyhow!("McpServer not found: {}", server_id));
         }
@@ -419,6 +421,25 @@ async fn do_publish_current_mcp_server(
     }
 }
 
+/// 记录发布前的线上版本摘要
+fn set_publish_audit_before(req: &HttpRequest, server: &McpServer) {
+    let release_value = &server.release_value;
+    let mut params = BTreeMap::new();
+    params.insert("releaseValueId".to_owned(), release_value.id.to_string());
+    params.insert(
+        "description".to_owned(),
+        release_value.description.as_ref().to_owned(),
+    );
+    let tools: Vec<&str> = release_value
+        .tools
+        .iter()
+        .map(|e| e.tool_name.as_str())
+        .collect();
+    params.insert("tools".to_owned(), tools.join(","));
+    req.extensions_mut()
+        .insert(AuditSummary::new_before(&params));
+}
+
 /// 发布历史版本
 pub async fn publish_history_mcp_server(
     req: HttpRequest,
@@ -432,7 +453,7 @@ pub async fn publish_history_mcp_server(
 }
 
 async fn do_publish_history_mcp_server(
-    _req: HttpRequest,
+    req: HttpRequest,
     appdata: web::Data<Arc<AppShareData>>,
     param: McpServerHistoryPublishParams,
 ) -> anyhow::Result<HttpResponse> {
@@ -445,7 +466,7 @@ async fn do_publish_history_mcp_server(
     // 首先检查McpServer是否存在
     let check_cmd = McpManagerReq::GetServer(server_id);
     match appdata.mcp_manager.send(check_cmd).await?? {
-        McpManagerResult::ServerInfo(Some(_)) => {}
+        McpManagerResult::ServerInfo(Some(server)) => set_publish_audit_before(&req, &server),
         _ => {
             return Err(anyhow::anyhow!("McpServer not found: {}", server_id));
         }
@@ -1,5 +1,6 @@
 use crate::common::appdata::AppShareData;
 use crate::common::constant::{EMPTY_ARC_STRING, SEQ_TOOL_SPEC_VERSION};
+use crate::common::model::privilege::ResourceAction;
 use crate::common::model::{ApiResult, PageResult, UserSession};
 use crate::console::model::mcp_tool_spec_model::{
     ToolSpecImportDto, ToolSpecParams, ToolSpecQueryRequest,
@@ -55,7 +56,7 @@ pub async fn query_tool_spec_list(
 
 /// 获取单个ToolSpec
 pub async fn get_tool_spec(
-    _req: HttpRequest,
+    req: HttpRequest,
     web::Query(param): web::Query<ToolSpecParams>,
     appdata: web::Data<Arc<AppShareData>>,
 ) -> impl Responder {
@@ -66,6 +67,18 @@ pub async fn get_tool_spec(
 
     // 构建ToolKey
     let tool_key = param.to_tool_key();
+    if !crate::user_resource_privilege!(req).check_permission(
+        &tool_key.namespace,
+        &tool_key.group,
+        &tool_key.tool_name,
+        ResourceAction::Read,
+    ) {
+        crate::user_no_resource_permission!(
+            &tool_key.namespace,
+            &tool_key.group,
+            &tool_key.tool_name
+        );
+    }
 
     // 发送获取请求到MCP Manager
     let cmd = McpManagerReq::GetToolSpec(tool_key);
@@ -98,6 +111,19 @@ pub async fn add_or_update_tool_spec(
     if let Err(err) = param.validate() {
         return handle_param_error(err, "ToolSpec create/update parameter validation failed");
     }
+    let tool_key = param.to_tool_key();
+    if !crate::user_resource_privilege!(req).check_permission(
+        &tool_key.namespace,
+        &tool_key.group,
+        &tool_key.tool_name,
+        ResourceAction::Write,
+    ) {
+        crate::user_no_resource_permission!(
+            &tool_key.namespace,
+            &tool_key.group,
+            &tool_key.tool_name
+        );
+    }
 
     // 从HttpRequest中提取用户会话信息作为op_user
     let op_user = req
@@ -160,6 +186,19 @@ pub async fn update_tool_specs(
                 "ToolSpec batch update parameter validation failed",
             );
         }
+        let tool_key = param.to_tool_key();
+        if !crate::user_resource_privilege!(req).check_permission(
+            &tool_key.namespace,
+            &tool_key.group,
+            &tool_key.tool_name,
+            ResourceAction::Write,
+        ) {
+            crate::user_no_resource_permission!(
+                &tool_key.namespace,
+                &tool_key.group,
+                &tool_key.tool_name
+            );
+        }
     }
 
     // 从HttpRequest中提取用户会话信息作为op_user
@@ -221,7 +260,7 @@ pub async fn update_tool_specs(
 
 /// 删除ToolSpec
 pub async fn remove_tool_spec(
-    _req: HttpRequest,
+    req: HttpRequest,
     appdata: web::Data<Arc<AppShareData>>,
     web::Json(param): web::Json<ToolSpecParams>,
 ) -> impl Responder {
@@ -232,6 +271,18 @@ pub async fn remove_tool_spec(
 
     // 构建ToolKey
     let tool_key = param.to_tool_key();
+    if !crate::user_resource_privilege!(req).check_permission(
+        &tool_key.namespace,
+        &tool_key.group,
+        &tool_key.tool_name,
+        ResourceAction::Write,
+    ) {
+        crate::user_no_resource_permission!(
+            &tool_key.namespace,
+            &tool_key.group,
+            &tool_key.tool_name
+        );
+    }
 
     // 构建McpManagerRaftReq::RemoveToolSpec请求
     let raft_req = McpManagerRaftReq::RemoveToolSpec(tool_key.clone());
@@ -393,6 +444,7 @@ pub async fn import_tool_specs(
             namespace.as_str()
         )));
     }
+    let resource_privilege = crate::user_resource_privilege!(req);
 
     // 从HttpRequest中提取用户会话信息作为op_user
     let op_user = req
@@ -443,6 +495,19 @@ pub async fn import_tool_specs(
                             }),
                             op_user: op_user.clone(),
                         };
+                        if !resource_privilege.check_permission(
+                            &tool_spec_param.namespace,
+                            &tool_spec_param.group,
+                            &tool_spec_param.tool_name,
+                            ResourceAction::Write,
+                        ) {
+                            return Ok(HttpResponse::Unauthorized().body(format!(
+                                "user no such resource permission: {}/{}/{}",
+                                &tool_spec_param.namespace,
+                                &tool_spec_param.group,
+                                &tool_spec_param.tool_name
+                            )));
+                        }
 
                         tool_spec_params.push(tool_spec_param);
                     }
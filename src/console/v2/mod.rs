@@ -1,6 +1,8 @@
 use crate::common::model::ApiResult;
+use crate::config::config_validator::ConfigValidateError;
 use actix_web::HttpResponse;
 
+pub mod audit_api;
 pub mod cluster_api;
 pub mod config_api;
 pub mod login_api;
@@ -9,6 +11,7 @@ pub mod mcp_tool_spec_api;
 pub mod metrics_api;
 pub mod namespace_api;
 pub mod naming_api;
+pub mod role_api;
 pub mod user_api;
 
 pub const ERROR_CODE_SYSTEM_ERROR: &str = "SYSTEM_ERROR";
@@ -16,6 +19,7 @@ pub const ERROR_CODE_PARAM_ERROR: &str = "PARAM_ERROR";
 pub const ERROR_CODE_NOT_FOUND: &str = "NOT_FOUND";
 pub const ERROR_CODE_MCP_MANAGER_ERROR: &str = "MCP_MANAGER_ERROR";
 pub const ERROR_CODE_RAFT_ERROR: &str = "RAFT_ERROR";
+pub const ERROR_CODE_CONFIG_CONTENT_INVALID: &str = "CONFIG_CONTENT_INVALID";
 
 pub enum ApiResponse<T>
 where
@@ -55,6 +59,17 @@ pub fn handle_param_error(error: impl std::fmt::Display, context: &str) -> HttpR
     ))
 }
 
+/// 处理配置内容校验错误,data中返回错误所在的行列
+pub fn handle_config_validate_error(error: ConfigValidateError) -> HttpResponse {
+    log::warn!("config content validate fail: {}", error);
+    HttpResponse::Ok().json(ApiResult {
+        message: Some(error.to_string()),
+        data: Some(error),
+        success: false,
+        code: Some(ERROR_CODE_CONFIG_CONTENT_INVALID.to_string()),
+    })
+}
+
 /// 处理资源不存在错误
 pub fn handle_not_found_error(resource: &str, identifier: &str) -> HttpResponse {
     log::debug!("{} not found: {}", resource, identifier);
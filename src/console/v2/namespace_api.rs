@@ -1,3 +1,4 @@
+use crate::audit::model::AuditSummary;
 use crate::common::appdata::AppShareData;
 use crate::common::model::ApiResult;
 use crate::common::string_utils::StringUtils;
@@ -6,6 +7,7 @@ use crate::console::NamespaceUtils;
 use crate::{user_namespace_privilege, user_no_namespace_permission};
 use actix_http::HttpMessage;
 use actix_web::{web, HttpRequest, HttpResponse, Responder};
+use std::collections::BTreeMap;
 use std::sync::Arc;
 use uuid::Uuid;
 
@@ -59,6 +61,7 @@ pub async fn update_namespace(
     if !namespace_privilege.check_option_value_permission(&param.namespace_id, false) {
         user_no_namespace_permission!(&param.namespace_id);
     }
+    set_namespace_audit_before(&req, &app_data, &param.namespace_id).await;
     match NamespaceUtils::update_namespace(&app_data, param.0).await {
         Ok(_) => HttpResponse::Ok().json(ApiResult::success(Some(true))),
         Err(e) => HttpResponse::Ok().json(ApiResult::<()>::error(
@@ -77,6 +80,7 @@ pub async fn remove_namespace(
     if !namespace_privilege.check_option_value_permission(&param.namespace_id, false) {
         user_no_namespace_permission!(&param.namespace_id);
     }
+    set_namespace_audit_before(&req, &app_data, &param.namespace_id).await;
     match NamespaceUtils::remove_namespace(&app_data, param.0.namespace_id).await {
         Ok(_) => HttpResponse::Ok().json(ApiResult::success(Some(true))),
         Err(e) => HttpResponse::Ok().json(ApiResult::<()>::error(
@@ -85,3 +89,35 @@ pub async fn remove_namespace(
         )),
     }
 }
+
+///
+/// 记录命名空间变更前的内容摘要
+async fn set_namespace_audit_before(
+    req: &HttpRequest,
+    app_data: &Arc<AppShareData>,
+    namespace_id: &Option<Arc<String>>,
+) {
+    let namespace_id = match namespace_id {
+        Some(v) => v,
+        None => return,
+    };
+    let namespace = NamespaceUtils::get_namespaces(app_data)
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .find(|e| e.namespace_id.as_ref() == Some(namespace_id));
+    if let Some(namespace) = namespace {
+        let mut params = BTreeMap::new();
+        if let Some(v) = namespace.namespace_name {
+            params.insert("namespaceName".to_owned(), v);
+        }
+        if let Some(v) = namespace.r#type {
+            params.insert("type".to_owned(), v);
+        }
+        if let Some(v) = namespace.config_validate_enforce {
+            params.insert("configValidateEnforce".to_owned(), v.to_string());
+        }
+        req.extensions_mut()
+            .insert(AuditSummary::new_before(&params));
+    }
+}
@@ -1,23 +1,31 @@
+use crate::audit::model::AuditSummary;
 use crate::common::appdata::AppShareData;
+use crate::common::model::privilege::ResourceAction;
 use crate::common::model::{ApiResult, PageResult};
 use crate::console::model::naming_model::{
     InstanceParams, ServiceDto, ServiceParam, ServiceQueryListRequest,
 };
-use crate::console::v2::ERROR_CODE_SYSTEM_ERROR;
+use crate::console::v2::{handle_param_error, ERROR_CODE_SYSTEM_ERROR};
 use crate::grpc::handler::NAMING_ROUTE_REQUEST;
 use crate::grpc::PayloadUtils;
 use crate::naming::api_model::InstanceVO;
 use crate::naming::cluster::model::{NamingRouteRequest, NamingRouterResponse};
 use crate::naming::core::{NamingActor, NamingCmd, NamingResult};
+use crate::naming::model::actor_model::NamingRaftReq;
 use crate::naming::model::{InstanceUpdateTag, ServiceDetailDto};
+use crate::naming::route_rule::ServiceRouteRuleParam;
 use crate::naming::service::SubscriberInfoDto;
 use crate::naming::service_index::ServiceQueryParam;
 use crate::naming::NamingUtils;
-use crate::{user_namespace_privilege, user_no_namespace_permission};
+use crate::{
+    user_namespace_privilege, user_no_namespace_permission, user_no_resource_permission,
+    user_resource_privilege,
+};
 use actix::Addr;
 use actix_web::web::Data;
 use actix_web::HttpMessage;
 use actix_web::{web, HttpRequest, HttpResponse, Responder};
+use std::collections::BTreeMap;
 use std::sync::Arc;
 
 pub async fn query_service_list(
@@ -69,40 +77,86 @@ pub async fn add_service(
     if !namespace_privilege.check_permission(&service_key.namespace_id) {
         user_no_namespace_permission!(&service_key.namespace_id);
     }
-    let metadata = if let Some(metadata_str) = param.metadata {
-        match NamingUtils::parse_metadata(&metadata_str) {
+    if !user_resource_privilege!(req).check_permission(
+        &service_key.namespace_id,
+        &service_key.group_name,
+        &service_key.service_name,
+        ResourceAction::Write,
+    ) {
+        user_no_resource_permission!(
+            &service_key.namespace_id,
+            &service_key.group_name,
+            &service_key.service_name
+        );
+    }
+    if let Some(Err(err)) = param.health_check.as_ref().map(|e| e.validate()) {
+        return HttpResponse::Ok().json(ApiResult::<()>::error(
+            ERROR_CODE_SYSTEM_ERROR.to_string(),
+            Some(err.to_string()),
+        ));
+    }
+    if let Some(Err(err)) = param.selector.as_ref().map(|e| e.validate()) {
+        return handle_param_error(err, "service selector validation failed");
+    }
+    if let Some(Err(err)) = param.route_rule.as_ref().map(|e| e.validate()) {
+        return HttpResponse::Ok().json(ApiResult::<()>::error(
+            ERROR_CODE_SYSTEM_ERROR.to_string(),
+            Some(err.to_string()),
+        ));
+    }
+    let metadata = match param.metadata.as_ref().filter(|e| !e.trim().is_empty()) {
+        Some(metadata_str) => match NamingUtils::parse_metadata(metadata_str) {
             Ok(metadata) => Some(Arc::new(metadata)),
-            Err(_) => None,
-        }
-    } else {
-        None
+            Err(err) => return handle_param_error(err, "service metadata parse failed"),
+        },
+        None => None,
     };
+    //参数全部校验通过后再写入,服务信息更新成功后最后写入路由规则
     let service_info = ServiceDetailDto {
-        namespace_id: service_key.namespace_id,
-        service_name: service_key.service_name,
-        group_name: service_key.group_name,
+        namespace_id: service_key.namespace_id.clone(),
+        service_name: service_key.service_name.clone(),
+        group_name: service_key.group_name.clone(),
         metadata,
         protect_threshold: param.protect_threshold,
+        selector: param.selector,
+        health_check: param.health_check.map(Box::new),
         ..Default::default()
     };
-    if let Ok(res) = appdata
+    match appdata
         .naming_addr
         .send(NamingCmd::UpdateService(service_info))
         .await
     {
-        match res {
-            Ok(_) => HttpResponse::Ok().json(ApiResult::success(Some(true))),
-            Err(err) => HttpResponse::Ok().json(ApiResult::<()>::error(
+        Ok(Ok(_)) => {}
+        Ok(Err(err)) => {
+            return HttpResponse::Ok().json(ApiResult::<()>::error(
                 ERROR_CODE_SYSTEM_ERROR.to_string(),
                 Some(err.to_string()),
-            )),
+            ))
+        }
+        Err(_) => {
+            return HttpResponse::Ok().json(ApiResult::<()>::error(
+                ERROR_CODE_SYSTEM_ERROR.to_string(),
+                None,
+            ))
+        }
+    }
+    if let Some(route_rule) = param.route_rule {
+        //路由规则需要集群一致,通过raft写入
+        let req = NamingRaftReq::UpdateRouteRule(ServiceRouteRuleParam::new(
+            &service_key,
+            Some(route_rule),
+        ));
+        if let Err(err) =
+            NamingActor::raft_request(req, Some(appdata.raft_request_route.clone())).await
+        {
+            return HttpResponse::Ok().json(ApiResult::<()>::error(
+                ERROR_CODE_SYSTEM_ERROR.to_string(),
+                Some(err.to_string()),
+            ));
         }
-    } else {
-        HttpResponse::Ok().json(ApiResult::<()>::error(
-            ERROR_CODE_SYSTEM_ERROR.to_string(),
-            None,
-        ))
     }
+    HttpResponse::Ok().json(ApiResult::success(Some(true)))
 }
 
 pub async fn remove_service(
@@ -115,6 +169,38 @@ pub async fn remove_service(
     if !namespace_privilege.check_permission(&service_key.namespace_id) {
         user_no_namespace_permission!(&service_key.namespace_id);
     }
+    if !user_resource_privilege!(req).check_permission(
+        &service_key.namespace_id,
+        &service_key.group_name,
+        &service_key.service_name,
+        ResourceAction::Write,
+    ) {
+        user_no_resource_permission!(
+            &service_key.namespace_id,
+            &service_key.group_name,
+            &service_key.service_name
+        );
+    }
+    if let Ok(Ok(NamingResult::ServiceDto(Some(service)))) = appdata
+        .naming_addr
+        .send(NamingCmd::QueryServiceOnly(service_key.clone()))
+        .await
+    {
+        let mut params = BTreeMap::new();
+        if let Some(metadata) = &service.metadata {
+            let metadata: BTreeMap<_, _> = metadata.iter().collect();
+            params.insert(
+                "metadata".to_owned(),
+                serde_json::to_string(&metadata).unwrap_or_default(),
+            );
+        }
+        if let Some(v) = service.protect_threshold {
+            params.insert("protectThreshold".to_owned(), v.to_string());
+        }
+        params.insert("instanceSize".to_owned(), service.instance_size.to_string());
+        req.extensions_mut()
+            .insert(AuditSummary::new_before(&params));
+    }
     if let Ok(res) = appdata
         .naming_addr
         .send(NamingCmd::RemoveService(service_key))
@@ -145,6 +231,18 @@ pub async fn query_instances_list(
     if !namespace_privilege.check_permission(&service_key.namespace_id) {
         user_no_namespace_permission!(&service_key.namespace_id);
     }
+    if !user_resource_privilege!(req).check_permission(
+        &service_key.namespace_id,
+        &service_key.group_name,
+        &service_key.service_name,
+        ResourceAction::Read,
+    ) {
+        user_no_resource_permission!(
+            &service_key.namespace_id,
+            &service_key.group_name,
+            &service_key.service_name
+        );
+    }
     match appdata
         .naming_addr
         .send(NamingCmd::QueryAllInstanceList(service_key))
@@ -186,6 +284,18 @@ pub async fn get_instance(
             if !namespace_privilege.check_permission(&instance.namespace_id) {
                 user_no_namespace_permission!(&instance.namespace_id);
             }
+            if !user_resource_privilege!(req).check_permission(
+                &instance.namespace_id,
+                &instance.group_name,
+                &instance.service_name,
+                ResourceAction::Read,
+            ) {
+                user_no_resource_permission!(
+                    &instance.namespace_id,
+                    &instance.group_name,
+                    &instance.service_name
+                );
+            }
             match appdata.naming_addr.send(NamingCmd::Query(instance)).await {
                 Ok(res) => {
                     let result: NamingResult = res.unwrap();
@@ -242,6 +352,18 @@ pub async fn add_instance(
             if !namespace_privilege.check_permission(&instance.namespace_id) {
                 user_no_namespace_permission!(&instance.namespace_id);
             }
+            if !user_resource_privilege!(req).check_permission(
+                &instance.namespace_id,
+                &instance.group_name,
+                &instance.service_name,
+                ResourceAction::Write,
+            ) {
+                user_no_resource_permission!(
+                    &instance.namespace_id,
+                    &instance.group_name,
+                    &instance.service_name
+                );
+            }
             if !instance.check_valid() {
                 HttpResponse::Ok().json(ApiResult::<()>::error(
                     ERROR_CODE_SYSTEM_ERROR.to_string(),
@@ -279,6 +401,18 @@ pub async fn remove_instance(
             if !namespace_privilege.check_permission(&instance.namespace_id) {
                 user_no_namespace_permission!(&instance.namespace_id);
             }
+            if !user_resource_privilege!(req).check_permission(
+                &instance.namespace_id,
+                &instance.group_name,
+                &instance.service_name,
+                ResourceAction::Write,
+            ) {
+                user_no_resource_permission!(
+                    &instance.namespace_id,
+                    &instance.group_name,
+                    &instance.service_name
+                );
+            }
             if !instance.check_valid() {
                 HttpResponse::Ok().json(ApiResult::<()>::error(
                     ERROR_CODE_SYSTEM_ERROR.to_string(),
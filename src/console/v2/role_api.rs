@@ -0,0 +1,63 @@
+use crate::common::appdata::AppShareData;
+use crate::common::model::ApiResult;
+use crate::console::v2::{handle_error, handle_param_error, handle_unexpected_response_error};
+use crate::user::model::RoleDto;
+use crate::user::permission::UserRoleHelper;
+use crate::user::{UserManagerReq, UserManagerResult};
+use actix_web::web::Data;
+use actix_web::{web, HttpResponse, Responder};
+use std::sync::Arc;
+
+pub async fn get_role_list(app: Data<Arc<AppShareData>>) -> actix_web::Result<impl Responder> {
+    match app.user_manager.send(UserManagerReq::QueryRoleList).await {
+        Ok(Ok(UserManagerResult::RoleList(list))) => {
+            Ok(HttpResponse::Ok().json(ApiResult::success(Some(list))))
+        }
+        Ok(Ok(_)) => Ok(handle_unexpected_response_error("get_role_list")),
+        Ok(Err(err)) => Ok(handle_error(err)),
+        Err(err) => Ok(handle_error(err.into())),
+    }
+}
+
+///
+/// 可用于组合自定义角色的功能模块名称
+pub async fn get_role_modules() -> actix_web::Result<impl Responder> {
+    Ok(HttpResponse::Ok().json(ApiResult::success(Some(UserRoleHelper::get_module_names()))))
+}
+
+pub async fn add_role(
+    app: Data<Arc<AppShareData>>,
+    web::Json(role): web::Json<RoleDto>,
+) -> actix_web::Result<impl Responder> {
+    if let Err(err) =
+        UserRoleHelper::check_custom_role(&role.name, role.modules.as_deref().unwrap_or_default())
+    {
+        return Ok(handle_param_error(err, "add_role"));
+    }
+    do_role_request(app, UserManagerReq::AddRole { role }).await
+}
+
+pub async fn update_role(
+    app: Data<Arc<AppShareData>>,
+    web::Json(role): web::Json<RoleDto>,
+) -> actix_web::Result<impl Responder> {
+    do_role_request(app, UserManagerReq::UpdateRole { role }).await
+}
+
+pub async fn remove_role(
+    app: Data<Arc<AppShareData>>,
+    web::Json(role): web::Json<RoleDto>,
+) -> actix_web::Result<impl Responder> {
+    do_role_request(app, UserManagerReq::RemoveRole { name: role.name }).await
+}
+
+async fn do_role_request(
+    app: Data<Arc<AppShareData>>,
+    req: UserManagerReq,
+) -> actix_web::Result<HttpResponse> {
+    match app.user_manager.send(req).await {
+        Ok(Ok(_)) => Ok(HttpResponse::Ok().json(ApiResult::success(Some(true)))),
+        Ok(Err(err)) => Ok(handle_error(err)),
+        Err(err) => Ok(handle_error(err.into())),
+    }
+}
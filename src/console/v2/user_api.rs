@@ -1,36 +1,41 @@
+use crate::audit::model::AuditSummary;
 use crate::common::appdata::AppShareData;
 use crate::common::model::{ApiResult, PageResult, UserSession};
-use crate::console::model::user_model::{UpdateUserInfoParam, UserPageParams};
+use crate::console::model::user_model::{
+    ApiKeyParams, TotpParams, UpdateUserInfoParam, UserPageParams,
+};
+use crate::console::v2::{handle_error, handle_param_error, handle_unexpected_response_error};
 use crate::user::{UserManagerReq, UserManagerResult};
 use actix_http::HttpMessage;
 use actix_web::web::Data;
 use actix_web::{web, HttpRequest, HttpResponse, Responder};
 use std::sync::Arc;
 
-use crate::console::user_api::ResetPasswordParam;
+use crate::console::user_api::{
+    clear_session_change_password_flag, update_current_session, ResetPasswordParam,
+};
 pub use crate::console::user_api::{get_user_info, get_user_web_resources};
-use crate::user::model::UserDto;
+use crate::user::model::{ApiKeyDto, UserDto};
+use crate::user::permission::USER_ROLE_MANAGER;
 
 pub async fn reset_password(
     req: HttpRequest,
     app: Data<Arc<AppShareData>>,
     web::Json(param): web::Json<ResetPasswordParam>,
 ) -> actix_web::Result<impl Responder> {
-    let (msg, username) = if let Some(session) = req.extensions().get::<Arc<UserSession>>() {
-        let username = Arc::new(session.username.to_string());
-        (
-            UserManagerReq::CheckUser {
-                name: username.clone(),
-                password: param.old_password,
-            },
-            username,
-        )
+    let session = if let Some(session) = req.extensions().get::<Arc<UserSession>>() {
+        session.clone()
     } else {
         return Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
             "NOT_FOUND_USER_SESSION".to_owned(),
             None,
         )));
     };
+    let username = Arc::new(session.username.to_string());
+    let msg = UserManagerReq::CheckUser {
+        name: username.clone(),
+        password: param.old_password,
+    };
     if let Ok(Ok(v)) = app.user_manager.send(msg).await {
         match v {
             UserManagerResult::CheckUserResult(valid, _user) => {
@@ -43,8 +48,18 @@ pub async fn reset_password(
                         },
                         namespace_privilege_param: None,
                     };
-                    if let Ok(Ok(_r)) = app.user_manager.send(msg).await {
-                        return Ok(HttpResponse::Ok().json(ApiResult::success(Some(true))));
+                    match app.user_manager.send(msg).await {
+                        Ok(Ok(_r)) => {
+                            clear_session_change_password_flag(&req, &app, &session).await;
+                            return Ok(HttpResponse::Ok().json(ApiResult::success(Some(true))));
+                        }
+                        Ok(Err(e)) => {
+                            return Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+                                "UPDATE_PASSWORD_ERROR".to_owned(),
+                                Some(e.to_string()),
+                            )));
+                        }
+                        Err(_) => {}
                     }
                 }
             }
@@ -103,14 +118,39 @@ pub async fn add_user(
         },
         namespace_privilege_param,
     };
-    app.user_manager.send(msg).await.ok();
-    Ok(HttpResponse::Ok().json(ApiResult::success(Some(true))))
+    match app.user_manager.send(msg).await {
+        Ok(Ok(_)) => Ok(HttpResponse::Ok().json(ApiResult::success(Some(true)))),
+        Ok(Err(err)) => Ok(handle_error(err)),
+        Err(err) => Ok(handle_error(err.into())),
+    }
 }
 
 pub async fn update_user(
+    req: HttpRequest,
     app: Data<Arc<AppShareData>>,
     web::Json(user_param): web::Json<UpdateUserInfoParam>,
 ) -> actix_web::Result<impl Responder> {
+    let query_msg = UserManagerReq::Query {
+        name: user_param.username.clone(),
+    };
+    if let Ok(Ok(UserManagerResult::QueryUser(Some(last_user)))) =
+        app.user_manager.send(query_msg).await
+    {
+        req.extensions_mut().insert(AuditSummary {
+            before: Some(format!(
+                "roles={}&enable={}",
+                last_user
+                    .roles
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|e| e.as_str())
+                    .collect::<Vec<_>>()
+                    .join(","),
+                last_user.enable.unwrap_or_default()
+            )),
+            ..Default::default()
+        });
+    }
     let namespace_privilege_param = user_param.namespace_privilege_param.clone();
     let user: UserDto = user_param.into();
     let msg = UserManagerReq::UpdateUser {
@@ -121,8 +161,11 @@ pub async fn update_user(
         },
         namespace_privilege_param,
     };
-    app.user_manager.send(msg).await.ok();
-    Ok(HttpResponse::Ok().json(ApiResult::success(Some(true))))
+    match app.user_manager.send(msg).await {
+        Ok(Ok(_)) => Ok(HttpResponse::Ok().json(ApiResult::success(Some(true)))),
+        Ok(Err(err)) => Ok(handle_error(err)),
+        Err(err) => Ok(handle_error(err.into())),
+    }
 }
 
 pub async fn remove_user(
@@ -135,3 +178,185 @@ pub async fn remove_user(
     app.user_manager.send(msg).await.ok();
     Ok(HttpResponse::Ok().json(ApiResult::success(Some(true))))
 }
+
+///
+/// 解锁因连续登录失败被锁定的用户
+pub async fn unlock_user(
+    app: Data<Arc<AppShareData>>,
+    web::Json(user): web::Json<UpdateUserInfoParam>,
+) -> actix_web::Result<impl Responder> {
+    let msg = UserManagerReq::UnlockUser {
+        username: user.username,
+    };
+    match app.user_manager.send(msg).await {
+        Ok(Ok(_)) => Ok(HttpResponse::Ok().json(ApiResult::success(Some(true)))),
+        Ok(Err(err)) => Ok(handle_error(err)),
+        Err(err) => Ok(handle_error(err.into())),
+    }
+}
+
+fn get_session(req: &HttpRequest) -> Option<Arc<UserSession>> {
+    req.extensions().get::<Arc<UserSession>>().cloned()
+}
+
+///
+/// 为当前用户生成两步验证密钥,返回密钥与otpauth链接
+pub async fn init_totp(
+    req: HttpRequest,
+    app: Data<Arc<AppShareData>>,
+) -> actix_web::Result<impl Responder> {
+    let session = match get_session(&req) {
+        Some(v) => v,
+        None => return Ok(handle_param_error("user session is empty", "init_totp")),
+    };
+    let msg = UserManagerReq::InitTotp {
+        username: session.username.clone(),
+    };
+    match app.user_manager.send(msg).await {
+        Ok(Ok(UserManagerResult::Totp(v))) => {
+            Ok(HttpResponse::Ok().json(ApiResult::success(Some(v))))
+        }
+        Ok(Ok(_)) => Ok(handle_unexpected_response_error("init_totp")),
+        Ok(Err(err)) => Ok(handle_error(err)),
+        Err(err) => Ok(handle_error(err.into())),
+    }
+}
+
+///
+/// 校验动态码后开启两步验证,返回只展示一次的恢复码
+pub async fn enable_totp(
+    req: HttpRequest,
+    app: Data<Arc<AppShareData>>,
+    web::Json(param): web::Json<TotpParams>,
+) -> actix_web::Result<impl Responder> {
+    let session = match get_session(&req) {
+        Some(v) => v,
+        None => return Ok(handle_param_error("user session is empty", "enable_totp")),
+    };
+    let msg = UserManagerReq::EnableTotp {
+        username: session.username.clone(),
+        code: param.code.unwrap_or_default(),
+    };
+    match app.user_manager.send(msg).await {
+        Ok(Ok(UserManagerResult::Totp(v))) => {
+            if session.need_bind_totp {
+                update_current_session(&req, &app, &session, |s| s.need_bind_totp = false).await;
+            }
+            Ok(HttpResponse::Ok().json(ApiResult::success(Some(v))))
+        }
+        Ok(Ok(_)) => Ok(handle_unexpected_response_error("enable_totp")),
+        Ok(Err(err)) => Ok(handle_error(err)),
+        Err(err) => Ok(handle_error(err.into())),
+    }
+}
+
+pub async fn disable_totp(
+    req: HttpRequest,
+    app: Data<Arc<AppShareData>>,
+    web::Json(param): web::Json<TotpParams>,
+) -> actix_web::Result<impl Responder> {
+    let session = match get_session(&req) {
+        Some(v) => v,
+        None => return Ok(handle_param_error("user session is empty", "disable_totp")),
+    };
+    if app.sys_config.console_totp_require_manager
+        && session
+            .roles
+            .iter()
+            .any(|e| e.as_str() == USER_ROLE_MANAGER.as_str())
+    {
+        return Ok(handle_param_error(
+            "two-factor authentication is required for manager",
+            "disable_totp",
+        ));
+    }
+    let msg = UserManagerReq::DisableTotp {
+        username: session.username.clone(),
+        code: Some(param.code.unwrap_or_default()),
+    };
+    match app.user_manager.send(msg).await {
+        Ok(Ok(_)) => Ok(HttpResponse::Ok().json(ApiResult::success(Some(true)))),
+        Ok(Err(err)) => Ok(handle_error(err)),
+        Err(err) => Ok(handle_error(err.into())),
+    }
+}
+
+///
+/// 管理员重置用户的两步验证,用于用户丢失认证设备与恢复码的情况
+pub async fn reset_totp(
+    app: Data<Arc<AppShareData>>,
+    web::Json(param): web::Json<TotpParams>,
+) -> actix_web::Result<impl Responder> {
+    let username = match param.username {
+        Some(v) if !v.is_empty() => v,
+        _ => return Ok(handle_param_error("username is empty", "reset_totp")),
+    };
+    let msg = UserManagerReq::DisableTotp {
+        username,
+        code: None,
+    };
+    match app.user_manager.send(msg).await {
+        Ok(Ok(_)) => Ok(HttpResponse::Ok().json(ApiResult::success(Some(true)))),
+        Ok(Err(err)) => Ok(handle_error(err)),
+        Err(err) => Ok(handle_error(err.into())),
+    }
+}
+
+pub async fn get_api_key_list(
+    app: Data<Arc<AppShareData>>,
+    web::Query(param): web::Query<ApiKeyParams>,
+) -> actix_web::Result<impl Responder> {
+    let msg = UserManagerReq::QueryApiKeyList {
+        username: param.username.filter(|e| !e.is_empty()),
+    };
+    match app.user_manager.send(msg).await {
+        Ok(Ok(UserManagerResult::ApiKeyList(list))) => {
+            Ok(HttpResponse::Ok().json(ApiResult::success(Some(list))))
+        }
+        Ok(Ok(_)) => Ok(handle_unexpected_response_error("get_api_key_list")),
+        Ok(Err(err)) => Ok(handle_error(err)),
+        Err(err) => Ok(handle_error(err.into())),
+    }
+}
+
+///
+/// 创建API key,完整的key只在创建结果中返回一次
+pub async fn add_api_key(
+    app: Data<Arc<AppShareData>>,
+    web::Json(param): web::Json<ApiKeyDto>,
+) -> actix_web::Result<impl Responder> {
+    if param.username.is_empty() {
+        return Ok(handle_param_error("username is empty", "add_api_key"));
+    }
+    match app
+        .user_manager
+        .send(UserManagerReq::CreateApiKey { param })
+        .await
+    {
+        Ok(Ok(UserManagerResult::ApiKey(v))) => {
+            Ok(HttpResponse::Ok().json(ApiResult::success(Some(v))))
+        }
+        Ok(Ok(_)) => Ok(handle_unexpected_response_error("add_api_key")),
+        Ok(Err(err)) => Ok(handle_error(err)),
+        Err(err) => Ok(handle_error(err.into())),
+    }
+}
+
+pub async fn revoke_api_key(
+    app: Data<Arc<AppShareData>>,
+    web::Json(param): web::Json<ApiKeyParams>,
+) -> actix_web::Result<impl Responder> {
+    let key_id = match param.key_id {
+        Some(key_id) if !key_id.is_empty() => key_id,
+        _ => return Ok(handle_param_error("keyId is empty", "revoke_api_key")),
+    };
+    match app
+        .user_manager
+        .send(UserManagerReq::RevokeApiKey { key_id })
+        .await
+    {
+        Ok(Ok(_)) => Ok(HttpResponse::Ok().json(ApiResult::success(Some(true)))),
+        Ok(Err(err)) => Ok(handle_error(err)),
+        Err(err) => Ok(handle_error(err.into())),
+    }
+}
@@ -1,4 +1,7 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
 
 use serde::{Deserialize, Serialize};
 
@@ -210,8 +213,104 @@ pub struct ConfigChangeNotifyRequest {
     pub tenant: Arc<String>,
 }
 
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigFuzzyWatchRequest {
+    pub module: Option<String>,
+    pub request_id: Option<String>,
+    pub headers: Option<HashMap<String, String>>,
+
+    pub group_key_pattern: String,
+    #[serde(default)]
+    pub received_group_keys: HashSet<String>,
+    pub watch_type: Option<String>,
+    #[serde(default, alias = "isInitializing")]
+    pub initializing: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigFuzzyWatchContext {
+    pub group_key: String,
+    pub changed_type: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigFuzzyWatchSyncRequest {
+    pub module: Option<String>,
+    pub request_id: Option<String>,
+    pub headers: HashMap<String, String>,
+
+    pub group_key_pattern: Arc<String>,
+    pub contexts: Vec<ConfigFuzzyWatchContext>,
+    pub sync_type: String,
+    pub total_batch: usize,
+    pub current_batch: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigFuzzyWatchChangeNotifyRequest {
+    pub module: Option<String>,
+    pub request_id: Option<String>,
+    pub headers: HashMap<String, String>,
+
+    pub group_key: String,
+    pub change_type: String,
+}
+
 // ----- naming model -----
 
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct NamingFuzzyWatchRequest {
+    pub module: Option<String>,
+    pub request_id: Option<String>,
+    pub headers: Option<HashMap<String, String>>,
+
+    pub namespace: Option<String>,
+    pub group_key_pattern: String,
+    #[serde(default)]
+    pub received_group_keys: HashSet<String>,
+    pub watch_type: Option<String>,
+    #[serde(default, alias = "isInitializing")]
+    pub initializing: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct NamingFuzzyWatchContext {
+    pub service_key: String,
+    pub changed_type: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct NamingFuzzyWatchSyncRequest {
+    pub module: Option<String>,
+    pub request_id: Option<String>,
+    pub headers: HashMap<String, String>,
+
+    pub group_key_pattern: Arc<String>,
+    pub contexts: Vec<NamingFuzzyWatchContext>,
+    pub sync_type: String,
+    pub total_batch: usize,
+    pub current_batch: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct NamingFuzzyWatchChangeNotifyRequest {
+    pub module: Option<String>,
+    pub request_id: Option<String>,
+    pub headers: HashMap<String, String>,
+
+    pub service_key: String,
+    pub changed_type: String,
+    pub sync_type: String,
+}
+
 #[derive(Debug, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct Instance {
@@ -0,0 +1,269 @@
+use crate::common::appdata::AppShareData;
+use crate::common::model::privilege::{NamespacePrivilegeGroup, ResourceAction, ResourcePrivilege};
+use crate::common::model::TokenSession;
+use crate::grpc::{HandlerResult, RequestMeta};
+use crate::metrics::metrics_key::MetricsKey;
+use crate::metrics::model::{MetricsItem, MetricsRecord, MetricsRequest};
+use crate::user::permission::UserRole;
+use actix::Recipient;
+use std::sync::Arc;
+
+/// 与nacos NacosException.NO_RIGHT一致
+pub const NO_RIGHT_CODE: u16 = 403;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GrpcPermission {
+    Read,
+    Write,
+}
+
+impl GrpcPermission {
+    pub fn to_resource_action(self) -> ResourceAction {
+        match self {
+            GrpcPermission::Read => ResourceAction::Read,
+            GrpcPermission::Write => ResourceAction::Write,
+        }
+    }
+}
+
+pub struct GrpcAuthUtils;
+
+impl GrpcAuthUtils {
+    ///
+    /// 校验sdk请求的命名空间与读写权限,没有权限时返回403错误结果;
+    /// 未开启鉴权时没有会话信息,直接放行
+    pub fn check(
+        app: &AppShareData,
+        request_meta: &RequestMeta,
+        namespace: &Arc<String>,
+        permission: GrpcPermission,
+    ) -> Option<HandlerResult> {
+        Self::do_check(app, request_meta, namespace, None, permission)
+    }
+
+    ///
+    /// 在命名空间与角色校验基础上,再按分组与资源名(dataId或服务名)校验用户细粒度数据权限
+    pub fn check_resource(
+        app: &AppShareData,
+        request_meta: &RequestMeta,
+        namespace: &Arc<String>,
+        group: &str,
+        resource: &str,
+        permission: GrpcPermission,
+    ) -> Option<HandlerResult> {
+        Self::do_check(
+            app,
+            request_meta,
+            namespace,
+            Some((group, resource)),
+            permission,
+        )
+    }
+
+    ///
+    /// sdk请求用户的细粒度数据权限,用于过滤批量返回或推送的资源;
+    /// 未开启鉴权时不做限制
+    pub fn resource_privilege(app: &AppShareData, request_meta: &RequestMeta) -> ResourcePrivilege {
+        if !app.sys_config.openapi_enable_auth {
+            return ResourcePrivilege::default();
+        }
+        request_meta
+            .token_session
+            .as_ref()
+            .map(|session| session.resource_privilege.clone())
+            .unwrap_or_default()
+    }
+
+    fn do_check(
+        app: &AppShareData,
+        request_meta: &RequestMeta,
+        namespace: &Arc<String>,
+        resource: Option<(&str, &str)>,
+        permission: GrpcPermission,
+    ) -> Option<HandlerResult> {
+        if !app.sys_config.openapi_enable_auth {
+            return None;
+        }
+        let session = request_meta.token_session.as_ref()?;
+        let message = Self::check_session(session, namespace, resource, permission)?;
+        Some(Self::denied_result(
+            &app.metrics_manager.clone().recipient(),
+            message,
+        ))
+    }
+
+    ///
+    /// 按会话校验权限,没有权限时返回拒绝原因
+    fn check_session(
+        session: &TokenSession,
+        namespace: &Arc<String>,
+        resource: Option<(&str, &str)>,
+        permission: GrpcPermission,
+    ) -> Option<String> {
+        let namespace_privilege = session
+            .namespace_privilege
+            .clone()
+            .map(NamespacePrivilegeGroup::new)
+            .unwrap_or_default();
+        if !namespace_privilege.check_permission(namespace) {
+            Some(format!(
+                "user {} no such namespace permission: {}",
+                &session.username, namespace
+            ))
+        } else if permission == GrpcPermission::Write
+            && !UserRole::can_write_by_roles(&session.roles)
+        {
+            Some(format!(
+                "user {} no write permission, namespace: {}",
+                &session.username, namespace
+            ))
+        } else if let Some((group, resource)) = resource.filter(|(group, resource)| {
+            !session.resource_privilege.check_permission(
+                namespace,
+                group,
+                resource,
+                permission.to_resource_action(),
+            )
+        }) {
+            Some(format!(
+                "user {} no such resource permission: {}/{}/{}",
+                &session.username, namespace, group, resource
+            ))
+        } else {
+            None
+        }
+    }
+
+    ///
+    /// 记录鉴权拒绝次数,返回403错误结果
+    fn denied_result(
+        metrics_manager: &Recipient<MetricsRequest>,
+        message: String,
+    ) -> HandlerResult {
+        metrics_manager.do_send(MetricsRequest::BatchRecord(vec![MetricsItem::new(
+            MetricsKey::GrpcRequestAuthDeniedCount,
+            MetricsRecord::CounterInc(1),
+        )]));
+        HandlerResult::error(NO_RIGHT_CODE, message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::model::privilege::{PrivilegeGroup, ResourcePermission};
+    use crate::grpc::api_model::BaseResponse;
+    use crate::metrics::model::MetricsResponse;
+    use crate::user::permission::{USER_ROLE_DEVELOPER, USER_ROLE_VISITOR};
+    use actix::{Actor, Context, Handler};
+    use std::collections::HashSet;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    struct MockMetricsManager(Arc<AtomicU64>);
+
+    impl Actor for MockMetricsManager {
+        type Context = Context<Self>;
+    }
+
+    impl Handler<MetricsRequest> for MockMetricsManager {
+        type Result = anyhow::Result<MetricsResponse>;
+
+        fn handle(&mut self, msg: MetricsRequest, _ctx: &mut Self::Context) -> Self::Result {
+            if let MetricsRequest::BatchRecord(items) = msg {
+                for item in items {
+                    if let (MetricsKey::GrpcRequestAuthDeniedCount, MetricsRecord::CounterInc(v)) =
+                        (&item.metrics_type, &item.record)
+                    {
+                        self.0.fetch_add(*v, Ordering::SeqCst);
+                    }
+                }
+            }
+            Ok(MetricsResponse::None)
+        }
+    }
+
+    fn build_session(role: &Arc<String>, namespaces: &[&str]) -> TokenSession {
+        let whitelist: HashSet<Arc<String>> =
+            namespaces.iter().map(|v| Arc::new(v.to_string())).collect();
+        let mut namespace_privilege = PrivilegeGroup::empty();
+        namespace_privilege.whitelist = Some(Arc::new(whitelist));
+        TokenSession {
+            username: Arc::new("test".to_owned()),
+            roles: vec![role.clone()],
+            namespace_privilege: Some(namespace_privilege),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn check_session_allow() {
+        let namespace = Arc::new("dev".to_owned());
+        let session = build_session(&USER_ROLE_DEVELOPER, &["dev"]);
+        assert!(
+            GrpcAuthUtils::check_session(&session, &namespace, None, GrpcPermission::Write)
+                .is_none()
+        );
+        let session = build_session(&USER_ROLE_VISITOR, &["dev"]);
+        assert!(
+            GrpcAuthUtils::check_session(&session, &namespace, None, GrpcPermission::Read)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn check_session_deny() {
+        let namespace = Arc::new("prod".to_owned());
+        let session = build_session(&USER_ROLE_DEVELOPER, &["dev"]);
+        let message =
+            GrpcAuthUtils::check_session(&session, &namespace, None, GrpcPermission::Read).unwrap();
+        assert!(message.contains("no such namespace permission"));
+
+        let session = build_session(&USER_ROLE_VISITOR, &["prod"]);
+        let message =
+            GrpcAuthUtils::check_session(&session, &namespace, None, GrpcPermission::Write)
+                .unwrap();
+        assert!(message.contains("no write permission"));
+
+        let mut session = build_session(&USER_ROLE_DEVELOPER, &["prod"]);
+        session.resource_privilege = ResourcePrivilege::new(vec![ResourcePermission {
+            namespace: Arc::new("*".to_owned()),
+            group: Arc::new("payment".to_owned()),
+            resource: Arc::new("*".to_owned()),
+            action: ResourceAction::Write,
+        }]);
+        assert!(GrpcAuthUtils::check_session(
+            &session,
+            &namespace,
+            Some(("payment", "app.yaml")),
+            GrpcPermission::Write
+        )
+        .is_none());
+        let message = GrpcAuthUtils::check_session(
+            &session,
+            &namespace,
+            Some(("order", "app.yaml")),
+            GrpcPermission::Read,
+        )
+        .unwrap();
+        assert!(message.contains("no such resource permission"));
+    }
+
+    #[actix_rt::test]
+    async fn denied_result_code_and_metrics() {
+        let count = Arc::new(AtomicU64::new(0));
+        let metrics_manager = MockMetricsManager(count.clone()).start();
+        let result =
+            GrpcAuthUtils::denied_result(&metrics_manager.clone().recipient(), "deny".to_owned());
+        assert!(!result.success);
+        let body = result.payload.body.unwrap().value;
+        let response: BaseResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(response.error_code, NO_RIGHT_CODE);
+        assert_eq!(response.message.as_deref(), Some("deny"));
+        metrics_manager
+            .send(MetricsRequest::Export)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+    }
+}
@@ -5,22 +5,30 @@ use std::{
 };
 
 use crate::{
+    config::config_fuzzy_watch::ConfigFuzzyWatchNotify,
     config::core::{ConfigActor, ConfigCmd, ConfigKey},
     naming::{
         core::{NamingActor, NamingCmd},
+        filter::InstanceFilterUtils,
         model::{ServiceInfo, ServiceKey},
+        naming_fuzzy_watch::NamingFuzzyWatchNotify,
     },
     now_millis,
 };
 
 use super::{
-    api_model::{ConfigChangeNotifyRequest, NotifySubscriberRequest, CONFIG_MODEL, NAMING_MODEL},
+    api_model::{
+        ConfigChangeNotifyRequest, ConfigFuzzyWatchChangeNotifyRequest, ConfigFuzzyWatchContext,
+        ConfigFuzzyWatchSyncRequest, NamingFuzzyWatchChangeNotifyRequest, NamingFuzzyWatchContext,
+        NamingFuzzyWatchSyncRequest, NotifySubscriberRequest, CONFIG_MODEL, NAMING_MODEL,
+    },
     bistream_conn::{BiStreamConn, BiStreamSenderCmd},
     handler::converter::ModelConverter,
     nacos_proto::Payload,
     PayloadUtils,
 };
 use crate::common::constant::EMPTY_CLIENT_VERSION;
+use crate::common::fuzzy_watch_utils::{FUZZY_WATCH_RESOURCE_CHANGED, FUZZY_WATCH_SYNC_BATCH_SIZE};
 use crate::common::model::ClientVersion;
 use crate::grpc::api_model::ConnectionSetupRequest;
 use crate::grpc::bistream_conn::NamespaceType;
@@ -28,11 +36,14 @@ use actix::prelude::*;
 use bean_factory::{bean, Inject};
 use inner_mem_cache::TimeoutSet;
 
+pub(crate) type ConnLabels = Arc<HashMap<String, String>>;
+
 pub(crate) struct ConnCacheItem {
     last_active_time: u64,
     conn: Addr<BiStreamConn>,
     pub(crate) client_version: Arc<ClientVersion>,
     pub(crate) namespace: NamespaceType,
+    pub(crate) labels: ConnLabels,
 }
 
 impl ConnCacheItem {
@@ -42,6 +53,7 @@ impl ConnCacheItem {
             conn,
             client_version: EMPTY_CLIENT_VERSION.clone(),
             namespace: NamespaceType::Unknown,
+            labels: Default::default(),
         }
     }
 }
@@ -80,12 +92,15 @@ impl BiStreamManage {
             .add(now + self.detection_time_out, client_id);
     }
 
-    fn active_client(&mut self, client_id: Arc<String>) -> anyhow::Result<Arc<ClientVersion>> {
+    fn active_client(
+        &mut self,
+        client_id: Arc<String>,
+    ) -> anyhow::Result<(Arc<ClientVersion>, ConnLabels)> {
         let now = now_millis();
         if let Some(item) = self.conn_cache.get_mut(&client_id) {
             //log::info!("active_client success client_id:{}",&client_id);
             item.last_active_time = now;
-            Ok(item.client_version.clone())
+            Ok((item.client_version.clone(), item.labels.clone()))
         } else {
             //log::info!("active_client empty client_id:{}",&client_id);
             Err(anyhow::anyhow!("Connection is unregistered."))
@@ -101,6 +116,166 @@ impl BiStreamManage {
         self.request_id.to_string()
     }
 
+    fn build_notify_naming_payload(
+        &mut self,
+        service_key: &ServiceKey,
+        service_info: ServiceInfo,
+    ) -> Payload {
+        let service_info = ModelConverter::to_api_service_info(service_info);
+        let request = NotifySubscriberRequest {
+            namespace: Some(service_key.namespace_id.clone()),
+            group_name: Some(service_key.group_name.clone()),
+            service_name: Some(service_key.service_name.clone()),
+            service_info: Some(service_info),
+            request_id: Some(self.next_request_id()),
+            module: Some(NAMING_MODEL.to_string()),
+            ..Default::default()
+        };
+        PayloadUtils::build_payload(
+            "NotifySubscriberRequest",
+            serde_json::to_string(&request).unwrap(),
+        )
+    }
+
+    fn build_config_fuzzy_watch_payloads(
+        &mut self,
+        notify: ConfigFuzzyWatchNotify,
+    ) -> anyhow::Result<Vec<Payload>> {
+        let mut payloads = vec![];
+        match notify {
+            ConfigFuzzyWatchNotify::Sync {
+                pattern,
+                sync_type,
+                contexts,
+            } => {
+                if contexts.is_empty() {
+                    let request = ConfigFuzzyWatchSyncRequest {
+                        group_key_pattern: pattern,
+                        sync_type: sync_type.to_owned(),
+                        request_id: Some(self.next_request_id()),
+                        module: Some(CONFIG_MODEL.to_string()),
+                        ..Default::default()
+                    };
+                    payloads.push(PayloadUtils::build_payload(
+                        "ConfigFuzzyWatchSyncRequest",
+                        serde_json::to_string(&request)?,
+                    ));
+                    return Ok(payloads);
+                }
+                let total_batch = contexts.len().div_ceil(FUZZY_WATCH_SYNC_BATCH_SIZE);
+                for (i, batch) in contexts.chunks(FUZZY_WATCH_SYNC_BATCH_SIZE).enumerate() {
+                    let request = ConfigFuzzyWatchSyncRequest {
+                        group_key_pattern: pattern.clone(),
+                        contexts: batch
+                            .iter()
+                            .map(|(group_key, changed_type)| ConfigFuzzyWatchContext {
+                                group_key: group_key.to_owned(),
+                                changed_type: changed_type.to_string(),
+                            })
+                            .collect(),
+                        sync_type: sync_type.to_owned(),
+                        total_batch,
+                        current_batch: i + 1,
+                        request_id: Some(self.next_request_id()),
+                        module: Some(CONFIG_MODEL.to_string()),
+                        ..Default::default()
+                    };
+                    payloads.push(PayloadUtils::build_payload(
+                        "ConfigFuzzyWatchSyncRequest",
+                        serde_json::to_string(&request)?,
+                    ));
+                }
+            }
+            ConfigFuzzyWatchNotify::Change {
+                group_key,
+                change_type,
+            } => {
+                let request = ConfigFuzzyWatchChangeNotifyRequest {
+                    group_key,
+                    change_type: change_type.to_owned(),
+                    request_id: Some(self.next_request_id()),
+                    module: Some(CONFIG_MODEL.to_string()),
+                    ..Default::default()
+                };
+                payloads.push(PayloadUtils::build_payload(
+                    "ConfigFuzzyWatchChangeNotifyRequest",
+                    serde_json::to_string(&request)?,
+                ));
+            }
+        }
+        Ok(payloads)
+    }
+
+    fn build_naming_fuzzy_watch_payloads(
+        &mut self,
+        notify: NamingFuzzyWatchNotify,
+    ) -> anyhow::Result<Vec<Payload>> {
+        let mut payloads = vec![];
+        match notify {
+            NamingFuzzyWatchNotify::Sync {
+                pattern,
+                sync_type,
+                contexts,
+            } => {
+                if contexts.is_empty() {
+                    let request = NamingFuzzyWatchSyncRequest {
+                        group_key_pattern: pattern,
+                        sync_type: sync_type.to_owned(),
+                        request_id: Some(self.next_request_id()),
+                        module: Some(NAMING_MODEL.to_string()),
+                        ..Default::default()
+                    };
+                    payloads.push(PayloadUtils::build_payload(
+                        "NamingFuzzyWatchSyncRequest",
+                        serde_json::to_string(&request)?,
+                    ));
+                    return Ok(payloads);
+                }
+                let total_batch = contexts.len().div_ceil(FUZZY_WATCH_SYNC_BATCH_SIZE);
+                for (i, batch) in contexts.chunks(FUZZY_WATCH_SYNC_BATCH_SIZE).enumerate() {
+                    let request = NamingFuzzyWatchSyncRequest {
+                        group_key_pattern: pattern.clone(),
+                        contexts: batch
+                            .iter()
+                            .map(|(service_key, changed_type)| NamingFuzzyWatchContext {
+                                service_key: service_key.to_owned(),
+                                changed_type: changed_type.to_string(),
+                            })
+                            .collect(),
+                        sync_type: sync_type.to_owned(),
+                        total_batch,
+                        current_batch: i + 1,
+                        request_id: Some(self.next_request_id()),
+                        module: Some(NAMING_MODEL.to_string()),
+                        ..Default::default()
+                    };
+                    payloads.push(PayloadUtils::build_payload(
+                        "NamingFuzzyWatchSyncRequest",
+                        serde_json::to_string(&request)?,
+                    ));
+                }
+            }
+            NamingFuzzyWatchNotify::Change {
+                service_key,
+                changed_type,
+            } => {
+                let request = NamingFuzzyWatchChangeNotifyRequest {
+                    service_key,
+                    changed_type: changed_type.to_owned(),
+                    sync_type: FUZZY_WATCH_RESOURCE_CHANGED.to_owned(),
+                    request_id: Some(self.next_request_id()),
+                    module: Some(NAMING_MODEL.to_string()),
+                    ..Default::default()
+                };
+                payloads.push(PayloadUtils::build_payload(
+                    "NamingFuzzyWatchChangeNotifyRequest",
+                    serde_json::to_string(&request)?,
+                ));
+            }
+        }
+        Ok(payloads)
+    }
+
     fn check_active_time_set(&mut self, now: u64) {
         let keys = self.active_time_set.timeout(now);
         let mut check_keys = vec![];
@@ -216,13 +391,15 @@ pub enum BiStreamManageCmd {
     AddConn(Arc<String>, BiStreamConn),
     ActiveClinet(Arc<String>),
     NotifyConfig(ConfigKey, HashSet<Arc<String>>),
+    NotifyConfigFuzzyWatch(ConfigFuzzyWatchNotify, HashSet<Arc<String>>),
+    NotifyNamingFuzzyWatch(NamingFuzzyWatchNotify, HashSet<Arc<String>>),
     NotifyNaming(ServiceKey, HashSet<Arc<String>>, ServiceInfo),
     QueryConnList,
 }
 
 pub enum BiStreamManageResult {
     ConnList(Vec<Arc<String>>),
-    ClientInfo(Arc<ClientVersion>),
+    ClientInfo(Arc<ClientVersion>, ConnLabels),
     None,
 }
 
@@ -248,6 +425,9 @@ impl Handler<BiStreamManageCmd> for BiStreamManage {
                                     Arc::new(ClientVersion::from_string(&client_version));
                             }
                             item.namespace = NamespaceType::from_option(request.tenant);
+                            if let Some(labels) = request.labels {
+                                item.labels = Arc::new(labels);
+                            }
                         }
                     }
                     self.active_client(client_id).ok();
@@ -268,8 +448,8 @@ impl Handler<BiStreamManageCmd> for BiStreamManage {
                 //println!("|AddConn|conn size: {}",self.conn_cache.len());
             }
             BiStreamManageCmd::ActiveClinet(client_id) => {
-                let client_version = self.active_client(client_id)?;
-                return Ok(BiStreamManageResult::ClientInfo(client_version));
+                let (client_version, labels) = self.active_client(client_id)?;
+                return Ok(BiStreamManageResult::ClientInfo(client_version, labels));
             }
             BiStreamManageCmd::NotifyConfig(config_key, client_id_set) => {
                 let tenant = config_key.tenant.clone();
@@ -308,21 +488,49 @@ impl Handler<BiStreamManageCmd> for BiStreamManage {
                     }
                 }
             }
+            BiStreamManageCmd::NotifyConfigFuzzyWatch(notify, client_id_set) => {
+                for payload in self.build_config_fuzzy_watch_payloads(notify)? {
+                    let payload = Arc::new(payload);
+                    for item in &client_id_set {
+                        if let Some(item) = self.conn_cache.get(item) {
+                            item.conn.do_send(BiStreamSenderCmd::Send(payload.clone()));
+                        }
+                    }
+                }
+            }
+            BiStreamManageCmd::NotifyNamingFuzzyWatch(notify, client_id_set) => {
+                for payload in self.build_naming_fuzzy_watch_payloads(notify)? {
+                    let payload = Arc::new(payload);
+                    for item in &client_id_set {
+                        if let Some(item) = self.conn_cache.get(item) {
+                            item.conn.do_send(BiStreamSenderCmd::Send(payload.clone()));
+                        }
+                    }
+                }
+            }
             BiStreamManageCmd::NotifyNaming(service_key, client_id_set, service_info) => {
-                let service_info = ModelConverter::to_api_service_info(service_info);
-                let request = NotifySubscriberRequest {
-                    namespace: Some(service_key.namespace_id),
-                    group_name: Some(service_key.group_name),
-                    service_name: Some(service_key.service_name),
-                    service_info: Some(service_info),
-                    request_id: Some(self.next_request_id()),
-                    module: Some(NAMING_MODEL.to_string()),
-                    ..Default::default()
-                };
-                let payload = Arc::new(PayloadUtils::build_payload(
-                    "NotifySubscriberRequest",
-                    serde_json::to_string(&request).unwrap(),
-                ));
+                if service_info.selector.is_some() {
+                    //选择器依赖消费者标签,按连接分别过滤后推送
+                    for client_id in &client_id_set {
+                        let labels = if let Some(item) = self.conn_cache.get(client_id) {
+                            item.labels.clone()
+                        } else {
+                            continue;
+                        };
+                        let service_info = InstanceFilterUtils::consumer_service_filter(
+                            service_info.clone(),
+                            &labels,
+                        );
+                        let payload =
+                            Arc::new(self.build_notify_naming_payload(&service_key, service_info));
+                        if let Some(item) = self.conn_cache.get(client_id) {
+                            item.conn.do_send(BiStreamSenderCmd::Send(payload));
+                        }
+                    }
+                    return Ok(BiStreamManageResult::None);
+                }
+                let payload =
+                    Arc::new(self.build_notify_naming_payload(&service_key, service_info));
                 for item in &client_id_set {
                     if let Some(item) = self.conn_cache.get(item) {
                         item.conn.do_send(BiStreamSenderCmd::Send(payload.clone()));
@@ -2,7 +2,9 @@
 
 use std::sync::Arc;
 
+use crate::config::model::ConfigClientInfo;
 use crate::config::{ConfigUtils, DEFAULT_TENANT};
+use crate::grpc::auth::{GrpcAuthUtils, GrpcPermission};
 use crate::grpc::HandlerResult;
 use crate::{
     common::appdata::AppShareData,
@@ -51,10 +53,26 @@ impl PayloadHandler for ConfigChangeBatchListenRequestHandler {
                 &item.group,
                 &ConfigUtils::default_tenant(tenant),
             );
+            if request.listen {
+                if let Some(result) = GrpcAuthUtils::check_resource(
+                    &self.app_data,
+                    &request_meta,
+                    &key.tenant,
+                    &key.group,
+                    &key.data_id,
+                    GrpcPermission::Read,
+                ) {
+                    return Ok(result);
+                }
+            }
             listener_items.push(ListenerItem::new(key, item.md5.unwrap_or_default()));
         }
         let cmd = if request.listen {
-            ConfigCmd::Subscribe(listener_items, request_meta.connection_id)
+            let client = ConfigClientInfo::new(
+                Arc::new(request_meta.client_ip.clone()),
+                request_meta.labels.clone(),
+            );
+            ConfigCmd::Subscribe(listener_items, request_meta.connection_id, client)
         } else {
             ConfigCmd::RemoveSubscribe(listener_items, request_meta.connection_id)
         };
@@ -5,6 +5,7 @@ use std::sync::Arc;
 use crate::common::string_utils::StringUtils;
 use crate::config::config_type::ConfigType;
 use crate::config::ConfigUtils;
+use crate::grpc::auth::{GrpcAuthUtils, GrpcPermission};
 use crate::grpc::HandlerResult;
 use crate::{
     common::appdata::AppShareData,
@@ -34,7 +35,7 @@ impl PayloadHandler for ConfigPublishRequestHandler {
     async fn handle(
         &self,
         request_payload: crate::grpc::nacos_proto::Payload,
-        _request_meta: crate::grpc::RequestMeta,
+        request_meta: crate::grpc::RequestMeta,
     ) -> anyhow::Result<HandlerResult> {
         let body_vec = request_payload.body.unwrap_or_default().value;
         let request: ConfigPublishRequest = serde_json::from_slice(&body_vec)?;
@@ -42,6 +43,12 @@ impl PayloadHandler for ConfigPublishRequestHandler {
             .map(|v| ConfigType::new_by_value(v.as_ref()).get_value());
         let desc =
             StringUtils::map_not_empty(request.get_addition_param("desc").cloned()).map(Arc::new);
+        let tags = request
+            .get_addition_param("config_tags")
+            .map(|v| ConfigUtils::parse_tags(v));
+        let encrypted_data_key =
+            StringUtils::map_not_empty(request.get_addition_param("encryptedDataKey").cloned())
+                .map(Arc::new);
         let mut req = SetConfigReq::new(
             ConfigKey::new(
                 &request.data_id,
@@ -50,8 +57,42 @@ impl PayloadHandler for ConfigPublishRequestHandler {
             ),
             request.content,
         );
+        if let Some(result) = GrpcAuthUtils::check_resource(
+            &self.app_data,
+            &request_meta,
+            &req.config_key.tenant,
+            &req.config_key.group,
+            &req.config_key.data_id,
+            GrpcPermission::Write,
+        ) {
+            return Ok(result);
+        }
         req.config_type = config_type;
         req.desc = desc;
+        req.tags = tags;
+        req.encrypted_data_key = encrypted_data_key;
+        //客户端已加密的内容无法按类型校验
+        let validate_result = if req.encrypted_data_key.is_none() {
+            self.app_data
+                .config_validator
+                .validate_by_namespace(
+                    &self.app_data.namespace_addr,
+                    &req.config_key.tenant,
+                    req.config_type.as_ref().map(|v| v.as_str()),
+                    &req.value,
+                )
+                .await
+        } else {
+            Ok(())
+        };
+        if let Err(err) = validate_result {
+            let mut response = BaseResponse::build_error_response(400u16, err.to_string());
+            response.request_id = request.request_id;
+            return Ok(HandlerResult::success(PayloadUtils::build_payload(
+                "ErrorResponse",
+                serde_json::to_string(&response)?,
+            )));
+        }
         match self.app_data.config_route.set_config(req).await {
             Ok(_res) => {
                 //let res:ConfigResult = res.unwrap();
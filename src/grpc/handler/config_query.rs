@@ -4,8 +4,10 @@ use std::sync::Arc;
 
 use crate::common::model::client_version::ClientNameType;
 use crate::config::config_type::ConfigType;
+use crate::config::model::ConfigClientInfo;
 use crate::config::ConfigUtils;
 use crate::grpc::api_model::NOT_FOUND;
+use crate::grpc::auth::{GrpcAuthUtils, GrpcPermission};
 use crate::grpc::HandlerResult;
 use crate::{
     common::appdata::AppShareData,
@@ -41,11 +43,26 @@ impl PayloadHandler for ConfigQueryRequestHandler {
     ) -> anyhow::Result<HandlerResult> {
         let body_vec = request_payload.body.unwrap_or_default().value;
         let request: ConfigQueryRequest = serde_json::from_slice(&body_vec)?;
-        let cmd = ConfigCmd::GET(ConfigKey::new(
+        let client = ConfigClientInfo::new(
+            Arc::new(request_meta.client_ip.clone()),
+            request_meta.labels.clone(),
+        );
+        let config_key = ConfigKey::new(
             &request.data_id,
             &request.group,
             &ConfigUtils::default_tenant(request.tenant),
-        ));
+        );
+        if let Some(result) = GrpcAuthUtils::check_resource(
+            &self.app_data,
+            &request_meta,
+            &config_key.tenant,
+            &config_key.group,
+            &config_key.data_id,
+            GrpcPermission::Read,
+        ) {
+            return Ok(result);
+        }
+        let cmd = ConfigCmd::GetByClient(config_key, client);
         let mut response = ConfigQueryResponse {
             request_id: request.request_id,
             ..Default::default()
@@ -65,7 +82,9 @@ impl PayloadHandler for ConfigQueryRequestHandler {
                         value: content,
                         md5,
                         config_type,
+                        encrypted_data_key,
                         last_modified,
+                        gray,
                         ..
                     } => {
                         //v.to_owned()
@@ -73,7 +92,10 @@ impl PayloadHandler for ConfigQueryRequestHandler {
                         response.content = content;
                         response.content_type =
                             Some(config_type.unwrap_or(ConfigType::Text.get_value()));
-                        //response.encrypted_data_key = Some("".to_owned());
+                        if let Some(encrypted_data_key) = encrypted_data_key {
+                            response.encrypted_data_key =
+                                Some(encrypted_data_key.as_ref().to_owned());
+                        }
                         //java nacos中定义tag类型是String;
                         //nacos-sdk-go中定义tag类型为bool, nacos-sdk-go中直接设置 response.tag = request.tag会报错
                         if let Some(tag) = request.tag {
@@ -86,6 +108,7 @@ impl PayloadHandler for ConfigQueryRequestHandler {
                         }
                         response.last_modified = last_modified;
                         response.md5 = Some(md5);
+                        response.beta = gray;
                     }
                     _ => {
                         response.result_code = ERROR_CODE;
@@ -3,6 +3,7 @@
 use std::sync::Arc;
 
 use crate::config::ConfigUtils;
+use crate::grpc::auth::{GrpcAuthUtils, GrpcPermission};
 use crate::grpc::HandlerResult;
 use crate::{
     common::appdata::AppShareData,
@@ -32,7 +33,7 @@ impl PayloadHandler for ConfigRemoveRequestHandler {
     async fn handle(
         &self,
         request_payload: crate::grpc::nacos_proto::Payload,
-        _request_meta: crate::grpc::RequestMeta,
+        request_meta: crate::grpc::RequestMeta,
     ) -> anyhow::Result<HandlerResult> {
         let body_vec = request_payload.body.unwrap_or_default().value;
         let request: ConfigRemoveRequest = serde_json::from_slice(&body_vec)?;
@@ -41,6 +42,16 @@ impl PayloadHandler for ConfigRemoveRequestHandler {
             &request.group,
             &ConfigUtils::default_tenant(request.tenant),
         ));
+        if let Some(result) = GrpcAuthUtils::check_resource(
+            &self.app_data,
+            &request_meta,
+            &req.config_key.tenant,
+            &req.config_key.group,
+            &req.config_key.data_id,
+            GrpcPermission::Write,
+        ) {
+            return Ok(result);
+        }
         match self.app_data.config_route.del_config(req).await {
             Ok(_res) => {
                 let mut response = BaseResponse::build_success_response();
@@ -4,10 +4,11 @@ use crate::common::appdata::AppShareData;
 
 use self::{
     config_change_batch_listen::ConfigChangeBatchListenRequestHandler,
+    config_fuzzy_watch::ConfigFuzzyWatchRequestHandler,
     config_publish::ConfigPublishRequestHandler, config_query::ConfigQueryRequestHandler,
     config_remove::ConfigRemoveRequestHandler, naming_batch_instance::BatchInstanceRequestHandler,
-    naming_instance::InstanceRequestHandler, naming_route::NamingRouteRequestHandler,
-    naming_service_list::ServiceListRequestHandler,
+    naming_fuzzy_watch::NamingFuzzyWatchRequestHandler, naming_instance::InstanceRequestHandler,
+    naming_route::NamingRouteRequestHandler, naming_service_list::ServiceListRequestHandler,
     naming_service_query::ServiceQueryRequestHandler,
     naming_subscribe_service::SubscribeServiceRequestHandler, raft_route::RaftRouteRequestHandler,
 };
@@ -23,12 +24,14 @@ use crate::grpc::handler::raft_vote::RaftVoteRequestHandler;
 use async_trait::async_trait;
 
 pub mod config_change_batch_listen;
+pub mod config_fuzzy_watch;
 pub mod config_publish;
 pub mod config_query;
 pub mod config_remove;
 
 pub mod converter;
 pub mod naming_batch_instance;
+pub mod naming_fuzzy_watch;
 pub mod naming_instance;
 pub mod naming_route;
 pub mod naming_service_list;
@@ -53,12 +56,14 @@ pub(crate) const CONFIG_QUERY_REQUEST: &str = "ConfigQueryRequest";
 pub(crate) const CONFIG_PUBLISH_REQUEST: &str = "ConfigPublishRequest";
 pub(crate) const CONFIG_REMOVE_REQUEST: &str = "ConfigRemoveRequest";
 pub(crate) const CONFIG_BATCH_LISTEN_REQUEST: &str = "ConfigBatchListenRequest";
+pub(crate) const CONFIG_FUZZY_WATCH_REQUEST: &str = "ConfigFuzzyWatchRequest";
 
 pub(crate) const INSTANCE_REQUEST: &str = "InstanceRequest";
 pub(crate) const BATCH_INSTANCE_REQUEST: &str = "BatchInstanceRequest";
 pub(crate) const SUBSCRIBE_SERVICE_REQUEST: &str = "SubscribeServiceRequest";
 pub(crate) const SERVICE_QUERY_REQUEST: &str = "ServiceQueryRequest";
 pub(crate) const SERVICE_LIST_REQUEST: &str = "ServiceListRequest";
+pub(crate) const NAMING_FUZZY_WATCH_REQUEST: &str = "NamingFuzzyWatchRequest";
 
 pub struct InvokerHandler {
     app: Arc<AppShareData>,
@@ -163,6 +168,10 @@ impl InvokerHandler {
             CONFIG_BATCH_LISTEN_REQUEST,
             Box::new(ConfigChangeBatchListenRequestHandler::new(app_data.clone())),
         );
+        self.add_handler(
+            CONFIG_FUZZY_WATCH_REQUEST,
+            Box::new(ConfigFuzzyWatchRequestHandler::new(app_data.clone())),
+        );
     }
 
     pub fn add_naming_handler(&mut self, app_data: &Arc<AppShareData>) {
@@ -186,6 +195,10 @@ impl InvokerHandler {
             SERVICE_LIST_REQUEST,
             Box::new(ServiceListRequestHandler::new(app_data.clone())),
         );
+        self.add_handler(
+            NAMING_FUZZY_WATCH_REQUEST,
+            Box::new(NamingFuzzyWatchRequestHandler::new(app_data.clone())),
+        );
     }
 }
 
@@ -238,6 +251,15 @@ impl PayloadHandler for InvokerHandler {
                     500u16,
                     "request cluster token is invalid".to_string(),
                 ));
+            } else if self.app.sys_config.cluster_mutual_tls
+                && self.is_cluster_request(url)
+                && !request_meta.cluster_cert_is_valid
+            {
+                //开启集群双向tls，但请求连接没有提供节点证书
+                return Ok(HandlerResult::error(
+                    403u16,
+                    "request cluster client certificate is required".to_string(),
+                ));
             }
             //println!("InvokerHandler type:{}",url);
             if let Some(handler) = self.match_handler(url) {
@@ -2,6 +2,7 @@
 
 use std::sync::Arc;
 
+use crate::grpc::auth::{GrpcAuthUtils, GrpcPermission};
 use crate::grpc::HandlerResult;
 use crate::{
     common::appdata::AppShareData,
@@ -118,7 +119,30 @@ impl PayloadHandler for BatchInstanceRequestHandler {
                 is_de_register = true;
             }
         }
-        let instances = Self::convert_to_instances(request, request_meta.connection_id)?;
+        let namespace = Arc::new(NamingUtils::default_namespace(
+            request.namespace.clone().unwrap_or_default(),
+        ));
+        if let Some(result) = GrpcAuthUtils::check(
+            &self.app_data,
+            &request_meta,
+            &namespace,
+            GrpcPermission::Write,
+        ) {
+            return Ok(result);
+        }
+        let instances = Self::convert_to_instances(request, request_meta.connection_id.clone())?;
+        for instance in &instances {
+            if let Some(result) = GrpcAuthUtils::check_resource(
+                &self.app_data,
+                &request_meta,
+                &instance.namespace_id,
+                &instance.group_name,
+                &instance.service_name,
+                GrpcPermission::Write,
+            ) {
+                return Ok(result);
+            }
+        }
         let mut response = InstanceResponse {
             request_id,
             message: Some("".to_string()),
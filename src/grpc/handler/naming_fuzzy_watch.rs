@@ -0,0 +1,96 @@
+use std::sync::Arc;
+
+use crate::common::fuzzy_watch_utils::WATCH_TYPE_CANCEL_WATCH;
+use crate::grpc::auth::{GrpcAuthUtils, GrpcPermission};
+use crate::grpc::HandlerResult;
+use crate::naming::naming_fuzzy_watch::FuzzyServicePattern;
+use crate::{
+    common::appdata::AppShareData,
+    grpc::{
+        api_model::{BaseResponse, NamingFuzzyWatchRequest},
+        PayloadHandler, PayloadUtils,
+    },
+    naming::core::NamingCmd,
+};
+use async_trait::async_trait;
+
+pub struct NamingFuzzyWatchRequestHandler {
+    app_data: Arc<AppShareData>,
+}
+
+impl NamingFuzzyWatchRequestHandler {
+    pub fn new(app_data: Arc<AppShareData>) -> Self {
+        Self { app_data }
+    }
+}
+
+#[async_trait]
+impl PayloadHandler for NamingFuzzyWatchRequestHandler {
+    async fn handle(
+        &self,
+        request_payload: crate::grpc::nacos_proto::Payload,
+        request_meta: crate::grpc::RequestMeta,
+    ) -> anyhow::Result<HandlerResult> {
+        let body_vec = request_payload.body.unwrap_or_default().value;
+        let request: NamingFuzzyWatchRequest = serde_json::from_slice(&body_vec)?;
+        let request_id = request.request_id;
+        let cmd = if request.watch_type.as_deref() == Some(WATCH_TYPE_CANCEL_WATCH) {
+            Ok(NamingCmd::CancelFuzzyWatch {
+                client_id: request_meta.connection_id,
+                pattern: Arc::new(request.group_key_pattern),
+            })
+        } else {
+            let received_service_keys = request.received_group_keys;
+            let initializing = request.initializing;
+            let pattern = FuzzyServicePattern::parse(&request.group_key_pattern);
+            if let Ok(pattern) = &pattern {
+                if let Some(result) = GrpcAuthUtils::check(
+                    &self.app_data,
+                    &request_meta,
+                    &pattern.namespace_id,
+                    GrpcPermission::Read,
+                ) {
+                    return Ok(result);
+                }
+            }
+            let resource_privilege =
+                GrpcAuthUtils::resource_privilege(&self.app_data, &request_meta);
+            let client_id = request_meta.connection_id;
+            pattern.map(|pattern| NamingCmd::FuzzyWatch {
+                client_id,
+                pattern,
+                received_service_keys,
+                initializing,
+                resource_privilege,
+            })
+        };
+        let result = match cmd {
+            Ok(cmd) => self
+                .app_data
+                .naming_addr
+                .send(cmd)
+                .await
+                .map_err(anyhow::Error::from)
+                .and_then(|r| r),
+            Err(err) => Err(err),
+        };
+        match result {
+            Ok(_) => {
+                let mut response = BaseResponse::build_success_response();
+                response.request_id = request_id;
+                Ok(HandlerResult::success(PayloadUtils::build_payload(
+                    "NamingFuzzyWatchResponse",
+                    serde_json::to_string(&response)?,
+                )))
+            }
+            Err(err) => {
+                let mut response = BaseResponse::build_error_response(500u16, err.to_string());
+                response.request_id = request_id;
+                Ok(HandlerResult::success(PayloadUtils::build_payload(
+                    "ErrorResponse",
+                    serde_json::to_string(&response)?,
+                )))
+            }
+        }
+    }
+}
@@ -5,6 +5,7 @@ use std::sync::{
     Arc,
 };
 
+use crate::grpc::auth::{GrpcAuthUtils, GrpcPermission};
 use crate::grpc::HandlerResult;
 use crate::{
     common::appdata::AppShareData,
@@ -110,7 +111,17 @@ impl PayloadHandler for InstanceRequestHandler {
                 is_de_register = true;
             }
         }
-        let instance = Self::convert_to_instance(request, request_meta.connection_id)?;
+        let instance = Self::convert_to_instance(request, request_meta.connection_id.clone())?;
+        if let Some(result) = GrpcAuthUtils::check_resource(
+            &self.app_data,
+            &request_meta,
+            &instance.namespace_id,
+            &instance.group_name,
+            &instance.service_name,
+            GrpcPermission::Write,
+        ) {
+            return Ok(result);
+        }
         let cmd = if is_de_register {
             NamingCmd::Delete(instance)
         } else {
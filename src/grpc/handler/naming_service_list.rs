@@ -5,6 +5,7 @@ use std::sync::Arc;
 use actix::prelude::Addr;
 use async_trait::async_trait;
 
+use crate::grpc::auth::{GrpcAuthUtils, GrpcPermission};
 use crate::grpc::HandlerResult;
 use crate::{
     common::appdata::AppShareData,
@@ -37,7 +38,7 @@ impl PayloadHandler for ServiceListRequestHandler {
     async fn handle(
         &self,
         request_payload: crate::grpc::nacos_proto::Payload,
-        _request_meta: crate::grpc::RequestMeta,
+        request_meta: crate::grpc::RequestMeta,
     ) -> anyhow::Result<HandlerResult> {
         let body_vec = request_payload.body.unwrap_or_default().value;
         let request: ServiceListRequest = serde_json::from_slice(&body_vec)?;
@@ -58,8 +59,20 @@ impl PayloadHandler for ServiceListRequestHandler {
             &request.group_name.unwrap_or_default(),
             &request.service_name.unwrap_or_default(),
         );
-        let cmd =
-            NamingCmd::QueryServicePage(key, request.page_size as usize, request.page_no as usize);
+        if let Some(result) = GrpcAuthUtils::check(
+            &self.app_data,
+            &request_meta,
+            &key.namespace_id,
+            GrpcPermission::Read,
+        ) {
+            return Ok(result);
+        }
+        let cmd = NamingCmd::QueryServicePage(
+            key,
+            request.page_size as usize,
+            request.page_no as usize,
+            GrpcAuthUtils::resource_privilege(&self.app_data, &request_meta),
+        );
         match self.app_data.naming_addr.send(cmd).await {
             Ok(res) => {
                 let result: NamingResult = res.unwrap();
@@ -2,6 +2,7 @@
 
 use std::sync::Arc;
 
+use crate::grpc::auth::{GrpcAuthUtils, GrpcPermission};
 use crate::grpc::HandlerResult;
 use crate::{
     common::appdata::AppShareData,
@@ -15,6 +16,7 @@ use crate::{
     },
     naming::{
         core::{NamingActor, NamingCmd, NamingResult},
+        filter::InstanceFilterUtils,
         model::{Instance, ServiceInfo, ServiceKey},
         NamingUtils,
     },
@@ -44,7 +46,7 @@ impl PayloadHandler for ServiceQueryRequestHandler {
     async fn handle(
         &self,
         request_payload: crate::grpc::nacos_proto::Payload,
-        _request_meta: crate::grpc::RequestMeta,
+        request_meta: crate::grpc::RequestMeta,
     ) -> anyhow::Result<HandlerResult> {
         let body_vec = request_payload.body.unwrap_or_default().value;
         let request: ServiceQueryRequest = serde_json::from_slice(&body_vec)?;
@@ -69,12 +71,26 @@ impl PayloadHandler for ServiceQueryRequestHandler {
             &NamingUtils::default_group(request.group_name.unwrap_or_default()),
             &request.service_name.unwrap_or_default(),
         );
+        if let Some(result) = GrpcAuthUtils::check_resource(
+            &self.app_data,
+            &request_meta,
+            &key.namespace_id,
+            &key.group_name,
+            &key.service_name,
+            GrpcPermission::Read,
+        ) {
+            return Ok(result);
+        }
         let cmd = NamingCmd::QueryServiceInfo(key, cluster, true);
         match self.app_data.naming_addr.send(cmd).await {
             Ok(res) => {
                 let result: NamingResult = res.unwrap();
                 match result {
                     NamingResult::ServiceInfo(service_info) => {
+                        let service_info = InstanceFilterUtils::consumer_service_filter(
+                            service_info,
+                            &request_meta.labels,
+                        );
                         let api_service_info = self.convert_to_service_info(service_info);
                         response.service_info = Some(api_service_info);
                         response.result_code = SUCCESS_CODE;
@@ -2,6 +2,7 @@
 
 use std::sync::Arc;
 
+use crate::grpc::auth::{GrpcAuthUtils, GrpcPermission};
 use crate::grpc::HandlerResult;
 use crate::{
     common::appdata::AppShareData,
@@ -15,6 +16,7 @@ use crate::{
     },
     naming::{
         core::{NamingActor, NamingCmd, NamingResult},
+        filter::InstanceFilterUtils,
         model::{Instance, ServiceInfo, ServiceKey},
         naming_subscriber::NamingListenerItem,
         NamingUtils,
@@ -88,6 +90,16 @@ impl PayloadHandler for SubscribeServiceRequestHandler {
             &NamingUtils::default_group(request.group_name.unwrap_or_default()),
             &request.service_name.unwrap_or_default(),
         );
+        if let Some(result) = GrpcAuthUtils::check_resource(
+            &self.app_data,
+            &request_meta,
+            &key.namespace_id,
+            &key.group_name,
+            &key.service_name,
+            GrpcPermission::Read,
+        ) {
+            return Ok(result);
+        }
         let subscribe_cmd = self.build_subscribe_cmd(
             request.subscribe,
             key.clone(),
@@ -100,6 +112,10 @@ impl PayloadHandler for SubscribeServiceRequestHandler {
                 let result: NamingResult = res.unwrap();
                 match result {
                     NamingResult::ServiceInfo(service_info) => {
+                        let service_info = InstanceFilterUtils::consumer_service_filter(
+                            service_info,
+                            &request_meta.labels,
+                        );
                         let api_service_info = self.convert_to_service_info(service_info);
                         response.service_info = Some(api_service_info);
                         response.result_code = SUCCESS_CODE;
@@ -5,20 +5,24 @@ use std::fmt::Display;
 use std::{collections::HashMap, sync::Arc};
 
 pub mod api_model;
+pub mod auth;
 pub mod bistream_conn;
 pub mod bistream_manage;
 pub mod handler;
 pub mod metrics;
 pub mod nacos_proto;
 pub mod server;
+pub mod tls;
 
 #[derive(Default)]
 pub struct RequestMeta {
     pub connection_id: Arc<String>,
     pub client_ip: String,
-    pub labels: HashMap<String, String>,
+    pub labels: Arc<HashMap<String, String>>,
     pub token_session: Option<Arc<TokenSession>>,
     pub cluster_token_is_valid: bool,
+    /// 连接提供了集群ca签发的客户端证书
+    pub cluster_cert_is_valid: bool,
     pub client_version: Arc<ClientVersion>,
 }
 
@@ -1,6 +1,9 @@
+use std::collections::BTreeMap;
 use std::sync::Arc;
 use std::time::SystemTime;
 
+use crate::audit::model::{AuditRecordDo, AuditUtils, AUDIT_SOURCE_GRPC};
+use crate::audit::AuditManagerReq;
 use crate::cache::actor_model::CacheManagerRaftResult;
 use crate::common::appdata::AppShareData;
 use crate::common::constant::{
@@ -10,7 +13,10 @@ use crate::common::model::TokenSession;
 
 use super::bistream_conn::BiStreamConn;
 use super::bistream_manage::BiStreamManageCmd;
-use super::handler::{InvokerHandler, CLUSTER_TOKEN};
+use super::handler::{
+    InvokerHandler, BATCH_INSTANCE_REQUEST, CLUSTER_TOKEN, CONFIG_PUBLISH_REQUEST,
+    CONFIG_REMOVE_REQUEST, INSTANCE_REQUEST,
+};
 use super::nacos_proto::bi_request_stream_server::BiRequestStream;
 use crate::grpc::bistream_manage::BiStreamManageResult;
 use crate::grpc::nacos_proto::{request_server, Payload};
@@ -19,6 +25,7 @@ use crate::metrics::metrics_key::MetricsKey;
 use crate::metrics::model::{MetricsItem, MetricsRecord, MetricsRequest};
 use crate::raft::cache::model::{CacheKey, CacheType};
 use crate::raft::cluster::model::{RouterRequest, RouterResponse};
+use crate::user::api_key::{get_api_key_session, ApiKeyUtils};
 
 pub struct RequestServerImpl {
     app: Arc<AppShareData>,
@@ -45,7 +52,11 @@ impl RequestServerImpl {
         } else {
             EMPTY_ARC_STRING.clone()
         };
-        if self.app.sys_config.openapi_enable_auth && !token.is_empty() {
+        if self.app.sys_config.openapi_enable_auth && ApiKeyUtils::is_api_key(&token) {
+            if let Ok(Some(session)) = get_api_key_session(&self.app.user_manager, &token).await {
+                request_meta.token_session = Some(session);
+            }
+        } else if self.app.sys_config.openapi_enable_auth && !token.is_empty() {
             if let Ok(Some(session)) = get_user_session(
                 &self.app,
                 CacheKey::new(CacheType::ApiTokenSession, token.clone()),
@@ -91,9 +102,11 @@ impl request_server::Request for RequestServerImpl {
     ) -> Result<tonic::Response<Payload>, tonic::Status> {
         let start = SystemTime::now();
         let remote_addr = request.remote_addr().unwrap();
+        let cluster_cert_is_valid = request.peer_certs().is_some_and(|c| !c.is_empty());
         let payload = request.into_inner();
         let mut request_meta = RequestMeta {
             client_ip: remote_addr.ip().to_string(),
+            cluster_cert_is_valid,
             client_version: EMPTY_CLIENT_VERSION.clone(),
             connection_id: Arc::new(format!(
                 "{}_{}",
@@ -120,8 +133,11 @@ impl request_server::Request for RequestServerImpl {
                 let result: anyhow::Result<BiStreamManageResult> = result;
                 match result {
                     Ok(conn_result) => {
-                        if let BiStreamManageResult::ClientInfo(client_version) = conn_result {
+                        if let BiStreamManageResult::ClientInfo(client_version, labels) =
+                            conn_result
+                        {
                             request_meta.client_version = client_version;
+                            request_meta.labels = labels;
                         }
                     }
                     Err(err) => {
@@ -170,11 +186,22 @@ impl request_server::Request for RequestServerImpl {
             .await
             .ok();
         let args = self.invoker.get_log_args(&payload, &request_meta);
+        let audit_record = if self.app.sys_config.audit_enable {
+            build_audit_record(&payload, &request_meta)
+        } else {
+            None
+        };
         let handle_result = self.invoker.handle(payload, request_meta).await;
         let duration = SystemTime::now()
             .duration_since(start)
             .unwrap_or_default()
             .as_secs_f64();
+        if let Some(mut record) = audit_record {
+            record.success = handle_result.as_ref().is_ok_and(|e| e.success);
+            self.app
+                .audit_manager
+                .do_send(AuditManagerReq::Record(record));
+        }
         match handle_result {
             Ok(res) => {
                 //log::info!("{}|ok|{}",PayloadUtils::get_payload_header(&res.payload));
@@ -285,3 +312,57 @@ async fn get_user_session(
         }
     }
 }
+
+///
+/// sdk的配置发布、删除与实例注册注销请求记录审计日志
+fn build_audit_record(payload: &Payload, request_meta: &RequestMeta) -> Option<AuditRecordDo> {
+    let t = PayloadUtils::get_payload_type(payload)?;
+    if !(CONFIG_PUBLISH_REQUEST.eq(t)
+        || CONFIG_REMOVE_REQUEST.eq(t)
+        || INSTANCE_REQUEST.eq(t)
+        || BATCH_INSTANCE_REQUEST.eq(t))
+    {
+        return None;
+    }
+    let mut params = BTreeMap::new();
+    AuditUtils::parse_body_params(&payload.body.as_ref()?.value, &mut params);
+    for key in ["module", "requestId", "headers"] {
+        params.remove(key);
+    }
+    for key in ["instance", "instances"] {
+        if let Some(v) = params.get(key) {
+            let addrs = build_instance_addrs(v);
+            params.insert(key.to_owned(), addrs);
+        }
+    }
+    let action = match params.get("type") {
+        Some(v) => format!("{} {}", t, v),
+        None => t.to_owned(),
+    };
+    Some(AuditRecordDo {
+        time: crate::now_millis_i64(),
+        actor: request_meta
+            .token_session
+            .as_ref()
+            .map(|e| e.username.as_ref().to_owned())
+            .unwrap_or_default(),
+        source_ip: request_meta.client_ip.clone(),
+        source: AUDIT_SOURCE_GRPC.to_owned(),
+        action,
+        resource: AuditUtils::build_resource(&params),
+        after: AuditUtils::build_params_summary(&params),
+        ..Default::default()
+    })
+}
+
+///
+/// 实例只记录ip:port
+fn build_instance_addrs(value: &str) -> String {
+    let addr =
+        |v: &serde_json::Value| format!("{}:{}", v["ip"].as_str().unwrap_or_default(), v["port"]);
+    match serde_json::from_str::<serde_json::Value>(value) {
+        Ok(serde_json::Value::Array(items)) => items.iter().map(addr).collect::<Vec<_>>().join(","),
+        Ok(v) if v.is_object() => addr(&v),
+        _ => String::new(),
+    }
+}
@@ -0,0 +1,224 @@
+use crate::common::tls_utils::ReloadableTls;
+use base64::{engine::general_purpose, Engine};
+use rustls::pki_types::ServerName;
+use rustls::{ClientConfig, ServerConfig};
+use std::convert::TryFrom;
+use std::future::Future;
+use std::io;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_rustls::{TlsAcceptor, TlsConnector};
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::transport::server::Connected;
+use tonic::transport::{Certificate, Channel, Endpoint, Uri};
+
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+///
+/// grpc服务端tls连接
+pub struct GrpcServerTlsStream(tokio_rustls::server::TlsStream<TcpStream>);
+
+impl Connected for GrpcServerTlsStream {
+    fn remote_addr(&self) -> Option<SocketAddr> {
+        self.0.get_ref().0.peer_addr().ok()
+    }
+
+    ///
+    /// 客户端证书链在握手时已由服务端的客户端证书校验器验证,这里只把已验证的证书传给请求;
+    /// rustls提供的是DER格式,转换为PEM后再构建tonic的Certificate
+    fn peer_certs(&self) -> Option<Vec<Certificate>> {
+        self.0.get_ref().1.peer_certificates().map(|certs| {
+            certs
+                .iter()
+                .map(|c| Certificate::from_pem(der_to_pem(c.as_ref())))
+                .collect()
+        })
+    }
+}
+
+fn der_to_pem(der: &[u8]) -> String {
+    let encoded = general_purpose::STANDARD.encode(der);
+    let mut pem = String::with_capacity(encoded.len() + encoded.len() / 64 + 64);
+    pem.push_str("-----BEGIN CERTIFICATE-----\n");
+    for line in encoded.as_bytes().chunks(64) {
+        pem.push_str(std::str::from_utf8(line).unwrap_or_default());
+        pem.push('\n');
+    }
+    pem.push_str("-----END CERTIFICATE-----\n");
+    pem
+}
+
+impl AsyncRead for GrpcServerTlsStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for GrpcServerTlsStream {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.0).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_shutdown(cx)
+    }
+}
+
+///
+/// 接收tcp连接并完成tls握手;握手在独立任务中进行,失败的连接直接丢弃,不影响grpc服务
+pub fn tls_incoming(
+    listener: TcpListener,
+    config: Arc<ServerConfig>,
+) -> ReceiverStream<Result<GrpcServerTlsStream, io::Error>> {
+    let acceptor = TlsAcceptor::from(config);
+    let (tx, rx) = tokio::sync::mpsc::channel(128);
+    tokio::spawn(async move {
+        while !tx.is_closed() {
+            let (stream, addr) = match listener.accept().await {
+                Ok(v) => v,
+                Err(err) => {
+                    log::error!("grpc tls accept error: {}", err);
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    continue;
+                }
+            };
+            stream.set_nodelay(true).ok();
+            let acceptor = acceptor.clone();
+            let tx = tx.clone();
+            tokio::spawn(async move {
+                match tokio::time::timeout(TLS_HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await {
+                    Ok(Ok(tls_stream)) => {
+                        tx.send(Ok(GrpcServerTlsStream(tls_stream))).await.ok();
+                    }
+                    Ok(Err(err)) => {
+                        log::warn!("grpc tls handshake error, {}: {}", addr, err);
+                    }
+                    Err(_) => {
+                        log::warn!("grpc tls handshake timeout, {}", addr);
+                    }
+                }
+            });
+        }
+    });
+    ReceiverStream::new(rx)
+}
+
+///
+/// grpc客户端tls连接
+pub struct GrpcClientTlsStream(tokio_rustls::client::TlsStream<TcpStream>);
+
+impl hyper::client::connect::Connection for GrpcClientTlsStream {
+    fn connected(&self) -> hyper::client::connect::Connected {
+        hyper::client::connect::Connected::new()
+    }
+}
+
+impl AsyncRead for GrpcClientTlsStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for GrpcClientTlsStream {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.0).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_shutdown(cx)
+    }
+}
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+///
+/// 集群节点间grpc请求的tls连接器
+#[derive(Clone, Debug)]
+pub struct GrpcTlsConnector {
+    config: Arc<ReloadableTls<ClientConfig>>,
+}
+
+impl GrpcTlsConnector {
+    pub fn new(config: Arc<ReloadableTls<ClientConfig>>) -> Self {
+        Self { config }
+    }
+
+    pub async fn connect_channel(&self, addr: &str) -> anyhow::Result<Channel> {
+        let endpoint = Endpoint::from_shared(format!("https://{}", addr))?;
+        Ok(endpoint.connect_with_connector(self.clone()).await?)
+    }
+}
+
+impl tower_service::Service<Uri> for GrpcTlsConnector {
+    type Response = GrpcClientTlsStream;
+    type Error = BoxError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, uri: Uri) -> Self::Future {
+        let connector = TlsConnector::from(self.config.get());
+        Box::pin(async move {
+            let host = uri
+                .host()
+                .ok_or_else(|| format!("grpc tls uri has no host: {}", uri))?
+                .trim_start_matches('[')
+                .trim_end_matches(']')
+                .to_owned();
+            let port = uri.port_u16().unwrap_or(443);
+            let stream = TcpStream::connect((host.as_str(), port)).await?;
+            stream.set_nodelay(true)?;
+            let server_name = ServerName::try_from(host)?;
+            let tls_stream = connector.connect(server_name, stream).await?;
+            Ok(GrpcClientTlsStream(tls_stream))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustls::pki_types::pem::PemObject;
+    use rustls::pki_types::CertificateDer;
+
+    #[test]
+    fn der_to_pem_round_trip() {
+        let der: Vec<u8> = (0..200u8).collect();
+        let pem = der_to_pem(&der);
+        assert!(pem.starts_with("-----BEGIN CERTIFICATE-----\n"));
+        assert!(pem.lines().all(|line| line.len() <= 64));
+        let cert = CertificateDer::from_pem_slice(pem.as_bytes()).unwrap();
+        assert_eq!(cert.as_ref(), der.as_slice());
+    }
+}
@@ -2,10 +2,11 @@ use crate::ldap::ldap_conn::LdapConnActorAddr;
 use crate::ldap::ldap_msg_actor::LdapMsgActor;
 use crate::ldap::model::actor_model::{LdapMsgReq, LdapMsgResult};
 use crate::ldap::model::LdapConfig;
-use crate::user::UserManager;
+use crate::user::{UserManager, UserManagerReq};
 use actix::prelude::*;
 use bean_factory::{bean, BeanFactory, FactoryData, Inject};
 use std::sync::Arc;
+use std::time::Duration;
 
 #[bean(inject)]
 pub struct LdapManager {
@@ -13,6 +14,7 @@ pub struct LdapManager {
     ldap_conn_addr: Option<LdapConnActorAddr>,
     enable_ldap: bool,
     user_manager_addr: Option<Addr<UserManager>>,
+    syncing: bool,
 }
 
 impl LdapManager {
@@ -22,6 +24,7 @@ impl LdapManager {
             ldap_conn_addr: None,
             enable_ldap,
             user_manager_addr: None,
+            syncing: false,
         }
     }
 
@@ -42,6 +45,63 @@ impl LdapManager {
                 }
             })
             .wait(ctx);
+        if self.ldap_config.ldap_sync_interval_second > 0 {
+            let interval = Duration::from_secs(self.ldap_config.ldap_sync_interval_second);
+            ctx.run_interval(interval, |act, ctx| {
+                act.sync_users(ctx);
+            });
+        }
+    }
+
+    fn sync_users(&mut self, ctx: &mut Context<Self>) {
+        if self.syncing {
+            return;
+        }
+        self.syncing = true;
+        let msg_addr = self
+            .ldap_conn_addr
+            .as_ref()
+            .map(|v| v.ldap_msg_actor.clone());
+        let user_manager_addr = self.user_manager_addr.clone();
+        Self::do_sync_users(msg_addr, user_manager_addr)
+            .into_actor(self)
+            .map(|res, act, _ctx| {
+                act.syncing = false;
+                if let Err(err) = res {
+                    log::error!("ldap user sync error:{}", err);
+                }
+            })
+            .spawn(ctx);
+    }
+
+    ///
+    /// 定时从LDAP拉取有效用户,禁用已离开的用户并按用户组刷新角色
+    async fn do_sync_users(
+        ldap_msg_addr: Option<Addr<LdapMsgActor>>,
+        user_manager_addr: Option<Addr<UserManager>>,
+    ) -> anyhow::Result<()> {
+        let user_manager_addr = if let Some(v) = user_manager_addr {
+            v
+        } else {
+            return Err(anyhow::anyhow!("user_manager_addr is None"));
+        };
+        if let LdapMsgResult::UserList(list) =
+            Self::handle_req(LdapMsgReq::QueryUserList, ldap_msg_addr).await?
+        {
+            if list.is_empty() {
+                // 防止base dn或过滤器配置错误时禁用全部LDAP用户
+                log::warn!("ldap user sync query empty user list, ignore");
+                return Ok(());
+            }
+            let users = list
+                .into_iter()
+                .map(|e| (Arc::new(e.user_name), e.role))
+                .collect();
+            user_manager_addr
+                .send(UserManagerReq::SyncLdapUsers { users })
+                .await??;
+        }
+        Ok(())
     }
 
     async fn handle_req(
@@ -2,11 +2,17 @@ use crate::common::string_utils::StringUtils;
 use crate::ldap::model::actor_model::{LdapMsgActorReq, LdapMsgReq, LdapMsgResult};
 use crate::ldap::model::{LdapConfig, LdapUserMeta};
 use crate::user::model::{UserDto, UserSourceType};
-use crate::user::{permission, UserManager, UserManagerReq, UserManagerResult};
+use crate::user::{UserManager, UserManagerReq, UserManagerResult};
 use actix::prelude::*;
+use ldap3::adapters::{Adapter, EntriesOnly, PagedResults};
 use ldap3::{Ldap, SearchEntry};
 use std::sync::Arc;
 
+const SYNC_PAGE_SIZE: i32 = 500;
+/// AD userAccountControl中的ACCOUNTDISABLE标记
+const AD_ACCOUNT_DISABLE: u32 = 0x2;
+const PWD_PERMANENT_LOCKED_TIME: &str = "000001010000Z";
+
 pub struct LdapMsgActor {
     ldap: Option<Ldap>,
     ldap_version: u64,
@@ -84,33 +90,15 @@ impl LdapMsgActor {
                 if !rs.is_empty() {
                     let entry = rs.remove(0);
                     let mut entry = SearchEntry::construct(entry);
-                    let mut role = ldap_config.ldap_user_default_role.clone();
                     let mut namespace_privilege = None;
-                    let groups = entry.attrs.remove("memberOf").unwrap_or_default();
-                    let groups = groups
-                        .iter()
-                        .map(|s| StringUtils::extract_ldap_value_cn(s))
-                        .filter(|s| s.is_some())
-                        .map(|s| s.unwrap())
-                        .collect::<Vec<_>>();
-                    for group in groups.iter() {
-                        if ldap_config.ldap_user_developer_groups.contains(group) {
-                            role = permission::USER_ROLE_DEVELOPER.clone();
-                            break;
-                        }
-                    }
-                    for group in groups.iter() {
-                        if ldap_config.ldap_user_admin_groups.contains(group) {
-                            role = permission::USER_ROLE_MANAGER.clone();
-                            break;
-                        }
-                    }
+                    let groups = Self::take_groups(&mut entry);
+                    let role = ldap_config.map_role(&groups);
                     if let Some(user_manager_addr) = user_manager_addr {
                         //init user
                         let user = UserDto {
                             username: Arc::new(bind_req.user_name.clone()),
                             nickname: Some(bind_req.user_name.clone()),
-                            source: Some(UserSourceType::Inner.to_str().to_owned()),
+                            source: Some(UserSourceType::Ldap.to_str().to_owned()),
                             roles: Some(vec![role.clone()]),
                             ..Default::default()
                         };
@@ -122,6 +110,9 @@ impl LdapMsgActor {
                                 })
                                 .await
                         {
+                            if !user_dto.enable.unwrap_or(true) {
+                                return Err(anyhow::anyhow!("user is disabled"));
+                            }
                             namespace_privilege = user_dto.namespace_privilege;
                         }
                     }
@@ -133,8 +124,84 @@ impl LdapMsgActor {
                     Ok(LdapMsgResult::UserMeta(meta))
                 }
             }
+            LdapMsgReq::QueryUserList => {
+                ldap.simple_bind(&ldap_config.ldap_bind_dn, &ldap_config.ldap_bind_password)
+                    .await?
+                    .success()?;
+                let user_name_attr = ldap_config.get_user_name_attribute();
+                let adapters: Vec<Box<dyn Adapter<_, _>>> = vec![
+                    Box::new(EntriesOnly::new()),
+                    Box::new(PagedResults::new(SYNC_PAGE_SIZE)),
+                ];
+                let mut stream = ldap
+                    .streaming_search_with(
+                        adapters,
+                        &ldap_config.ldap_user_base_dn,
+                        ldap3::Scope::Subtree,
+                        &ldap_config.build_sync_filter(),
+                        vec![
+                            user_name_attr.as_str(),
+                            "memberOf",
+                            "userAccountControl",
+                            "nsAccountLock",
+                            "pwdAccountLockedTime",
+                        ],
+                    )
+                    .await?;
+                let mut list = vec![];
+                while let Some(entry) = stream.next().await? {
+                    let mut entry = SearchEntry::construct(entry);
+                    if Self::is_entry_disabled(&entry) {
+                        continue;
+                    }
+                    let user_name = if let Some(v) = entry
+                        .attrs
+                        .get(&user_name_attr)
+                        .and_then(|v| v.first())
+                        .cloned()
+                    {
+                        v
+                    } else {
+                        continue;
+                    };
+                    let groups = Self::take_groups(&mut entry);
+                    let role = ldap_config.map_role(&groups);
+                    list.push(LdapUserMeta::new(user_name, groups, role, None));
+                }
+                stream.finish().await.success()?;
+                Ok(LdapMsgResult::UserList(list))
+            }
         }
     }
+
+    fn take_groups(entry: &mut SearchEntry) -> Vec<String> {
+        entry
+            .attrs
+            .remove("memberOf")
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|s| StringUtils::extract_ldap_value_cn(s))
+            .collect()
+    }
+
+    ///
+    /// 判断账号是否已在LDAP中停用,兼容AD、389ds与OpenLDAP ppolicy的永久锁定
+    fn is_entry_disabled(entry: &SearchEntry) -> bool {
+        let first = |name: &str| entry.attrs.get(name).and_then(|v| v.first());
+        if let Some(flags) = first("userAccountControl").and_then(|v| v.parse::<u32>().ok()) {
+            if flags & AD_ACCOUNT_DISABLE != 0 {
+                return true;
+            }
+        }
+        if let Some(v) = first("nsAccountLock") {
+            if v.eq_ignore_ascii_case("true") {
+                return true;
+            }
+        }
+        first("pwdAccountLockedTime")
+            .map(|v| v == PWD_PERMANENT_LOCKED_TIME)
+            .unwrap_or(false)
+    }
 }
 
 impl Actor for LdapMsgActor {
@@ -197,3 +264,48 @@ impl Handler<LdapMsgActorReq> for LdapMsgActor {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn build_entry(attrs: Vec<(&str, &str)>) -> SearchEntry {
+        SearchEntry {
+            dn: "uid=u1,ou=people,dc=example,dc=com".to_owned(),
+            attrs: attrs
+                .into_iter()
+                .map(|(k, v)| (k.to_owned(), vec![v.to_owned()]))
+                .collect::<HashMap<_, _>>(),
+            bin_attrs: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn entry_disabled() {
+        assert!(!LdapMsgActor::is_entry_disabled(&build_entry(vec![(
+            "uid", "u1"
+        )])));
+        assert!(LdapMsgActor::is_entry_disabled(&build_entry(vec![(
+            "userAccountControl",
+            "514"
+        )])));
+        assert!(!LdapMsgActor::is_entry_disabled(&build_entry(vec![(
+            "userAccountControl",
+            "512"
+        )])));
+        assert!(LdapMsgActor::is_entry_disabled(&build_entry(vec![(
+            "nsAccountLock",
+            "TRUE"
+        )])));
+        assert!(!LdapMsgActor::is_entry_disabled(&build_entry(vec![(
+            "pwdAccountLockedTime",
+            "20240101000000Z"
+        )])));
+        let mut entry = build_entry(vec![("memberOf", "cn=dev,ou=groups,dc=example,dc=com")]);
+        assert_eq!(
+            LdapMsgActor::take_groups(&mut entry),
+            vec!["dev".to_owned()]
+        );
+    }
+}
@@ -7,6 +7,8 @@ use ldap3::Ldap;
 #[rtype(result = "anyhow::Result<LdapMsgResult>")]
 pub enum LdapMsgReq {
     Bind(LdapUserParam),
+    /// 枚举LDAP中的有效用户
+    QueryUserList,
 }
 
 #[derive(Clone, Debug, Message)]
@@ -36,4 +38,5 @@ pub enum LdapManagerReq {
 pub enum LdapMsgResult {
     None,
     UserMeta(LdapUserMeta),
+    UserList(Vec<LdapUserMeta>),
 }
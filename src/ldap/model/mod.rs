@@ -1,9 +1,12 @@
 use crate::common::model::privilege::PrivilegeGroup;
+use crate::user::permission;
 use std::collections::HashSet;
 use std::sync::Arc;
 
 pub mod actor_model;
 
+const DEFAULT_USER_NAME_ATTRIBUTE: &str = "uid";
+
 #[derive(Clone, Debug, Default)]
 pub struct LdapConfig {
     pub ldap_url: Arc<String>,
@@ -12,6 +15,60 @@ pub struct LdapConfig {
     pub ldap_user_developer_groups: Arc<HashSet<String>>,
     pub ldap_user_admin_groups: Arc<HashSet<String>>,
     pub ldap_user_default_role: Arc<String>,
+    /// 同步用户时查询使用的账号,为空时匿名查询
+    pub ldap_bind_dn: Arc<String>,
+    pub ldap_bind_password: Arc<String>,
+    pub ldap_sync_interval_second: u64,
+    /// 同步时读取用户名的属性,为空时从登录过滤器推导
+    pub ldap_user_name_attribute: Arc<String>,
+}
+
+impl LdapConfig {
+    ///
+    /// 按用户组计算角色,管理员组优先于开发者组
+    pub fn map_role(&self, groups: &[String]) -> Arc<String> {
+        if groups
+            .iter()
+            .any(|g| self.ldap_user_admin_groups.contains(g))
+        {
+            permission::USER_ROLE_MANAGER.clone()
+        } else if groups
+            .iter()
+            .any(|g| self.ldap_user_developer_groups.contains(g))
+        {
+            permission::USER_ROLE_DEVELOPER.clone()
+        } else {
+            self.ldap_user_default_role.clone()
+        }
+    }
+
+    ///
+    /// 同步时枚举全部用户的过滤器,由登录过滤器中的用户名占位符替换为通配符得到
+    pub fn build_sync_filter(&self) -> String {
+        self.ldap_user_filter.replace("%s", "*")
+    }
+
+    ///
+    /// 用户名属性:优先使用配置,否则取登录过滤器中用户名占位符对应的属性(如AD的sAMAccountName),默认uid
+    pub fn get_user_name_attribute(&self) -> String {
+        if !self.ldap_user_name_attribute.is_empty() {
+            return self.ldap_user_name_attribute.as_ref().to_owned();
+        }
+        if let Some(index) = self.ldap_user_filter.find("=%s") {
+            let attr: String = self.ldap_user_filter[..index]
+                .chars()
+                .rev()
+                .take_while(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == ';')
+                .collect::<Vec<char>>()
+                .into_iter()
+                .rev()
+                .collect();
+            if !attr.is_empty() {
+                return attr;
+            }
+        }
+        DEFAULT_USER_NAME_ATTRIBUTE.to_owned()
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -50,3 +107,43 @@ impl LdapUserMeta {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn map_role() {
+        let config = LdapConfig {
+            ldap_user_filter: Arc::new("(&(objectClass=person)(uid=%s))".to_owned()),
+            ldap_user_developer_groups: Arc::new(HashSet::from(["dev".to_owned()])),
+            ldap_user_admin_groups: Arc::new(HashSet::from(["admin".to_owned()])),
+            ldap_user_default_role: permission::USER_ROLE_VISITOR.clone(),
+            ..Default::default()
+        };
+        assert_eq!(config.map_role(&[]), permission::USER_ROLE_VISITOR.clone());
+        assert_eq!(
+            config.map_role(&["dev".to_owned()]),
+            permission::USER_ROLE_DEVELOPER.clone()
+        );
+        assert_eq!(
+            config.map_role(&["dev".to_owned(), "admin".to_owned()]),
+            permission::USER_ROLE_MANAGER.clone()
+        );
+        assert_eq!(config.build_sync_filter(), "(&(objectClass=person)(uid=*))");
+        assert_eq!(config.get_user_name_attribute(), "uid");
+    }
+
+    #[test]
+    fn user_name_attribute() {
+        let mut config = LdapConfig {
+            ldap_user_filter: Arc::new("(&(objectCategory=person)(sAMAccountName=%s))".to_owned()),
+            ..Default::default()
+        };
+        assert_eq!(config.get_user_name_attribute(), "sAMAccountName");
+        config.ldap_user_filter = Arc::new("(objectClass=person)".to_owned());
+        assert_eq!(config.get_user_name_attribute(), "uid");
+        config.ldap_user_name_attribute = Arc::new("cn".to_owned());
+        assert_eq!(config.get_user_name_attribute(), "cn");
+    }
+}
@@ -1,3 +1,4 @@
+pub mod audit;
 pub mod common;
 pub mod config;
 pub mod console;
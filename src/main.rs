@@ -6,6 +6,8 @@ use actix::Actor;
 use actix_web::{web::Data, App};
 use async_raft_ext::raft::ClientWriteRequest;
 use async_raft_ext::{Config, Raft, RaftStorage};
+use rnacos::audit::middle::AuditLog;
+use rnacos::common::tls_utils::TlsUtils;
 use rnacos::common::{get_app_version, AppSysConfig};
 use rnacos::config::core::{ConfigActor, ConfigCmd};
 use rnacos::console::middle::login_middle::CheckLogin;
@@ -14,6 +16,7 @@ use rnacos::grpc::handler::InvokerHandler;
 use rnacos::grpc::nacos_proto::bi_request_stream_server::BiRequestStreamServer;
 use rnacos::grpc::nacos_proto::request_server::RequestServer;
 use rnacos::grpc::server::BiRequestStreamServerImpl;
+use rnacos::grpc::tls::tls_incoming;
 use rnacos::grpc::PayloadUtils;
 use rnacos::naming::core::{NamingCmd, NamingResult};
 use rnacos::raft::cluster::model::RouterRequest;
@@ -26,6 +29,7 @@ use rnacos::{grpc::server::RequestServerImpl, naming::core::NamingActor, openapi
 use sled::Db;
 use std::collections::{BTreeMap, HashSet};
 use std::error::Error;
+use std::net::SocketAddr;
 use std::ops::Deref;
 use std::sync::Arc;
 use std::time::Duration;
@@ -91,21 +95,47 @@ async fn main() -> Result<(), Box<dyn Error>> {
     invoker.add_raft_handler(&app_data);
 
     let grpc_app_data = app_data.clone();
+    let grpc_tls_config = TlsUtils::build_grpc_server_config(&sys_config)?;
+
+    let grpc_addr: SocketAddr = grpc_addr
+        .parse()
+        .map_err(|err| format!("grpc server addr {} is invalid: {}", &grpc_addr, err))?;
+    // tls监听在启动任务前绑定,端口占用等错误直接返回
+    let grpc_tls_listener = if let Some(tls_config) = grpc_tls_config {
+        let listener = tokio::net::TcpListener::bind(grpc_addr)
+            .await
+            .map_err(|err| format!("grpc server bind {} error: {}", grpc_addr, err))?;
+        Some((listener, tls_config))
+    } else {
+        None
+    };
 
     tokio::spawn(async move {
-        let addr = grpc_addr.parse().unwrap();
         let request_server = RequestServerImpl::new(grpc_app_data.clone(), invoker);
         let bi_request_stream_server = BiRequestStreamServerImpl::new(grpc_app_data.clone());
-        Server::builder()
+        let router = Server::builder()
             .add_service(RequestServer::new(request_server))
-            .add_service(BiRequestStreamServer::new(bi_request_stream_server))
-            .serve(addr)
-            .await
-            .unwrap();
+            .add_service(BiRequestStreamServer::new(bi_request_stream_server));
+        let res = if let Some((listener, tls_config)) = grpc_tls_listener {
+            log::info!("grpc server tls enabled");
+            router
+                .serve_with_incoming(tls_incoming(listener, tls_config))
+                .await
+        } else {
+            router.serve(grpc_addr).await
+        };
+        if let Err(err) = res {
+            exit_with_error(&format!("grpc server {} error: {}", grpc_addr, err));
+        }
     });
 
     if sys_config.http_console_port > 0 {
         let app_console_data = app_data.clone();
+        let console_tls_config = TlsUtils::build_http_server_config(
+            &sys_config,
+            &sys_config.console_tls_cert_file,
+            &sys_config.console_tls_key_file,
+        )?;
 
         std::thread::spawn(move || {
             actix_rt::System::with_tokio_rt(|| {
@@ -114,7 +144,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
                     .build()
                     .unwrap()
             })
-            .block_on(run_console_web(app_console_data));
+            .block_on(run_console_web(app_console_data, console_tls_config));
         });
     }
 
@@ -130,6 +160,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
             .app_data(Data::new(config_addr))
             .app_data(Data::new(naming_addr))
             .app_data(Data::new(bistream_manage_http_addr))
+            .wrap(AuditLog::new(source_app_data.clone()))
             .wrap(ApiCheckAuth::new(source_app_data))
             .wrap(middleware::Logger::default())
             .configure(app_config(app_config_shard))
@@ -137,9 +168,20 @@ async fn main() -> Result<(), Box<dyn Error>> {
     if let Some(num) = sys_config.http_workers {
         server = server.workers(num);
     }
+    let http_tls_config = TlsUtils::build_http_server_config(
+        &sys_config,
+        &sys_config.http_tls_cert_file,
+        &sys_config.http_tls_key_file,
+    )?;
+    server = if let Some(tls_config) = http_tls_config {
+        log::info!("http server tls enabled");
+        server.bind_rustls_0_23(http_addr, tls_config)?
+    } else {
+        server.bind(http_addr)?
+    };
     // 这里不使用log:info避免日志等级高于info时不打印
     println!("rnacos started");
-    server.bind(http_addr)?.run().await?;
+    server.run().await?;
     Ok(())
 }
 
@@ -179,11 +221,14 @@ async fn run_subcommand(commands: Commands) -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
-async fn run_console_web(source_app_data: Arc<AppShareData>) {
+async fn run_console_web(
+    source_app_data: Arc<AppShareData>,
+    tls_config: Option<rustls::ServerConfig>,
+) {
     let http_console_addr = source_app_data.sys_config.get_http_console_addr();
     log::info!("new console server http addr:{}", &http_console_addr);
     let app_data = Data::new(source_app_data.clone());
-    HttpServer::new(move || {
+    let server = HttpServer::new(move || {
         let source_app_data = source_app_data.clone();
         let config_addr = app_data.config_addr.clone();
         let naming_addr = app_data.naming_addr.clone();
@@ -194,15 +239,37 @@ async fn run_console_web(source_app_data: Arc<AppShareData>) {
             .app_data(Data::new(config_addr))
             .app_data(Data::new(naming_addr))
             .app_data(Data::new(bistream_manage_http_addr))
+            .wrap(AuditLog::new(source_app_data.clone()))
             .wrap(CheckLogin::new(source_app_data))
             .wrap(middleware::Logger::default())
             .wrap(middleware::Compress::default())
             .configure(console_config)
     })
-    .workers(2)
-    .bind(http_console_addr)
-    .unwrap()
-    .run()
-    .await
-    .ok();
+    .workers(2);
+    let server = if let Some(tls_config) = tls_config {
+        log::info!("console server tls enabled");
+        server.bind_rustls_0_23(&http_console_addr, tls_config)
+    } else {
+        server.bind(&http_console_addr)
+    };
+    let server = match server {
+        Ok(server) => server,
+        Err(err) => {
+            exit_with_error(&format!(
+                "console server bind {} error: {}",
+                &http_console_addr, err
+            ));
+        }
+    };
+    if let Err(err) = server.run().await {
+        exit_with_error(&format!("console server error: {}", err));
+    }
+}
+
+///
+/// 后台任务中的服务启动失败时无法向main返回错误,打印原因后退出进程
+fn exit_with_error(msg: &str) -> ! {
+    log::error!("{}", msg);
+    eprintln!("{}", msg);
+    std::process::exit(1);
 }
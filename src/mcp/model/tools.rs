@@ -1,5 +1,6 @@
 use crate::common::constant::EMPTY_ARC_STRING;
 use crate::common::pb::data_object::{McpToolDo, McpToolSpecDo, ToolSpecVersionDo};
+use crate::naming::balancer::InstanceSelectParam;
 use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
 use std::collections::BTreeMap;
@@ -332,6 +333,9 @@ pub struct ToolRouteRule {
     pub service_namespace: Option<Arc<String>>,
     pub service_group: Arc<String>,
     pub service_name: Arc<String>,
+    /// 服务实例选择策略与就近可用区
+    #[serde(default)]
+    pub select: InstanceSelectParam,
 }
 
 impl Default for ToolRouteRule {
@@ -345,6 +349,7 @@ impl Default for ToolRouteRule {
             service_namespace: None,
             service_group: EMPTY_ARC_STRING.clone(),
             service_name: EMPTY_ARC_STRING.clone(),
+            select: InstanceSelectParam::default(),
         }
     }
 }
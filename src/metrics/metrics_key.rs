@@ -53,6 +53,7 @@ pub enum MetricsKey {
     GrpcRequestHandleRtHistogram,
     GrpcRequestHandleRtSummary,
     GrpcRequestTotalCount,
+    GrpcRequestAuthDeniedCount,
     //http api request
     HttpRequestHandleRtHistogram,
     HttpRequestHandleRtSummary,
@@ -106,6 +107,7 @@ lazy_static! {
         MetricsKey::GrpcRequestHandleRtHistogram,
         MetricsKey::GrpcRequestHandleRtSummary,
         MetricsKey::GrpcRequestTotalCount,
+        MetricsKey::GrpcRequestAuthDeniedCount,
         //http request
         MetricsKey::HttpRequestHandleRtHistogram,
         MetricsKey::HttpRequestHandleRtSummary,
@@ -170,6 +172,7 @@ impl MetricsKey {
             MetricsKey::GrpcRequestHandleRtHistogram => "grpc_request_handle_rt_histogram",
             MetricsKey::GrpcRequestHandleRtSummary => "grpc_request_handle_rt_summary",
             MetricsKey::GrpcRequestTotalCount => "grpc_request_total_count",
+            MetricsKey::GrpcRequestAuthDeniedCount => "grpc_request_auth_denied_count",
             MetricsKey::HttpRequestHandleRtHistogram => "http_request_handle_rt_histogram",
             MetricsKey::HttpRequestHandleRtSummary => "http_request_handle_rt_summary",
             MetricsKey::HttpRequestTotalCount => "http_request_total_count",
@@ -246,6 +249,7 @@ impl MetricsKey {
             }
             MetricsKey::GrpcRequestHandleRtSummary => "Grpc request handle rt summary, unit is ms",
             MetricsKey::GrpcRequestTotalCount => "Grpc request total count",
+            MetricsKey::GrpcRequestAuthDeniedCount => "Grpc request auth denied count",
             MetricsKey::HttpRequestHandleRtHistogram => {
                 "Http request handle rt histogram,unit is ms"
             }
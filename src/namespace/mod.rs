@@ -34,6 +34,7 @@ pub fn build_already_mark_param() -> NamespaceParam {
         namespace_id: Arc::new(ALREADY_SYNC_FROM_CONFIG_KEY.to_string()),
         namespace_name: None,
         r#type: None,
+        config_validate_enforce: None,
     }
 }
 
@@ -104,6 +105,7 @@ impl NamespaceActor {
                 namespace_id: EMPTY_ARC_STRING.clone(),
                 namespace_name: Some(DEFAULT_NAMESPACE.to_owned()),
                 r#type: Some(FROM_SYSTEM_VALUE.to_owned()),
+                config_validate_enforce: None,
             },
             false,
             false,
@@ -143,6 +145,9 @@ impl NamespaceActor {
                 v.namespace_name.to_owned()
             };
             value.flag = v.flag | param_flag;
+            value.config_validate_enforce = param
+                .config_validate_enforce
+                .unwrap_or(v.config_validate_enforce);
             value
         } else {
             if only_update {
@@ -153,6 +158,7 @@ impl NamespaceActor {
                 namespace_id: param.namespace_id,
                 namespace_name: param.namespace_name.unwrap_or_default(),
                 flag: param_flag,
+                config_validate_enforce: param.config_validate_enforce.unwrap_or_default(),
             }
         };
         self.data
@@ -179,6 +185,7 @@ impl NamespaceActor {
                 namespace_id: namespace_id.clone(),
                 namespace_name: namespace_id.as_str().to_owned(),
                 flag,
+                config_validate_enforce: false,
             };
             self.data.insert(namespace_id.clone(), Arc::new(value));
         }
@@ -221,6 +228,16 @@ impl NamespaceActor {
         }
     }
 
+    ///
+    /// 只持久化用户数据;默认命名空间只在设置过校验开关时记录
+    fn need_persist(key: &Arc<String>, value: &Namespace) -> bool {
+        if key.is_empty() {
+            value.config_validate_enforce
+        } else {
+            value.flag & NamespaceFromFlags::USER.bits() != 0
+        }
+    }
+
     fn query_list(&mut self) -> Vec<Arc<Namespace>> {
         let mut list = Vec::with_capacity(self.id_order_list.len());
         for id in self.id_order_list.iter() {
@@ -233,7 +250,7 @@ impl NamespaceActor {
 
     fn build_snapshot(&self, writer: Addr<SnapshotWriterActor>) -> anyhow::Result<()> {
         for (key, value) in &self.data {
-            if key.is_empty() || value.flag & NamespaceFromFlags::USER.bits() == 0 {
+            if !Self::need_persist(key, value) {
                 //非用户数据不记录
                 continue;
             }
@@ -252,6 +269,7 @@ impl NamespaceActor {
                 namespace_id: param.namespace_id,
                 namespace_name: param.namespace_name.unwrap_or_default(),
                 flag: NamespaceFromFlags::USER.bits(),
+                config_validate_enforce: false,
             };
             let key = value.namespace_id.clone();
             let value_db: NamespaceDO = value.into();
@@ -270,7 +288,7 @@ impl NamespaceActor {
     /// 迁移数据备件
     fn transfer_backup(&self, writer: Addr<TransferWriterActor>) -> anyhow::Result<()> {
         for (key, value) in &self.data {
-            if key.is_empty() || value.flag & NamespaceFromFlags::USER.bits() == 0 {
+            if !Self::need_persist(key, value) {
                 //非用户数据不记录
                 continue;
             }
@@ -294,6 +312,7 @@ impl NamespaceActor {
                 namespace_id: value.namespace_id,
                 namespace_name: Some(value.namespace_name),
                 r#type: Some(NamespaceFromFlags::get_db_type(value.flag)),
+                config_validate_enforce: Some(value.config_validate_enforce),
             },
             false,
             false,
@@ -317,6 +336,7 @@ impl NamespaceActor {
                     namespace_id,
                     namespace_name: item.namespace_name,
                     r#type: item.r#type,
+                    config_validate_enforce: None,
                 },
                 true,
                 false,
@@ -19,6 +19,8 @@ pub struct Namespace {
     pub namespace_name: String,
     //pub r#type: String,
     pub flag: u32,
+    /// 是否强制校验配置内容,关闭时只告警
+    pub config_validate_enforce: bool,
 }
 
 #[derive(Default, Debug, Clone, Serialize, Deserialize)]
@@ -26,6 +28,7 @@ pub struct NamespaceParam {
     pub namespace_id: Arc<String>,
     pub namespace_name: Option<String>,
     pub r#type: Option<String>,
+    pub config_validate_enforce: Option<bool>,
 }
 
 #[derive(Clone, PartialEq, prost_derive::Message, Deserialize, Serialize)]
@@ -36,6 +39,8 @@ pub struct NamespaceDO {
     pub namespace_name: Option<String>,
     #[prost(string, optional, tag = "3")]
     pub r#type: Option<String>,
+    #[prost(bool, optional, tag = "4")]
+    pub config_validate_enforce: Option<bool>,
 }
 
 impl NamespaceDO {
@@ -64,6 +69,7 @@ impl From<NamespaceDO> for Namespace {
             namespace_id: Arc::new(value.namespace_id.unwrap_or_default()),
             namespace_name: value.namespace_name.unwrap_or_default(),
             flag,
+            config_validate_enforce: value.config_validate_enforce.unwrap_or_default(),
         }
     }
 }
@@ -75,6 +81,7 @@ impl From<Namespace> for NamespaceDO {
             namespace_id: Some(value.namespace_id.as_str().to_string()),
             namespace_name: Some(value.namespace_name),
             r#type: Some(t),
+            config_validate_enforce: Some(value.config_validate_enforce),
         }
     }
 }
@@ -1,6 +1,8 @@
 use crate::now_millis_i64;
 
+use super::health_check::ServiceHealthCheck;
 use super::model::{Instance, ServiceDetailDto, ServiceKey};
+use super::selector::ServiceSelector;
 use super::NamingUtils;
 use crate::common::option_utils::OptionUtils;
 use chrono::Local;
@@ -132,6 +134,8 @@ pub struct ServiceInfoParam {
     pub protect_threshold: Option<f32>,
     pub metadata: Option<String>,
     pub selector: Option<String>,
+    /// 健康检测配置json,如: {"checker":{"type":"HTTP","path":"/health"}}
+    pub health_check: Option<String>,
 }
 
 impl ServiceInfoParam {
@@ -143,6 +147,7 @@ impl ServiceInfoParam {
             protect_threshold: OptionUtils::select(self.protect_threshold, b.protect_threshold),
             metadata: OptionUtils::select(self.metadata, b.metadata),
             selector: OptionUtils::select(self.selector, b.selector),
+            health_check: OptionUtils::select(self.health_check, b.health_check),
         }
     }
 
@@ -159,6 +164,16 @@ impl ServiceInfoParam {
             } else {
                 None
             };
+            let selector = if let Some(selector_str) = self.selector {
+                Some(ServiceSelector::parse(&selector_str)?)
+            } else {
+                None
+            };
+            let health_check = if let Some(health_check_str) = self.health_check {
+                Some(Box::new(ServiceHealthCheck::parse(&health_check_str)?))
+            } else {
+                None
+            };
 
             Ok(ServiceDetailDto {
                 namespace_id: Arc::new(NamingUtils::default_namespace(
@@ -170,6 +185,8 @@ impl ServiceInfoParam {
                 )),
                 metadata,
                 protect_threshold: self.protect_threshold,
+                selector,
+                health_check,
                 ..Default::default()
             })
         } else {
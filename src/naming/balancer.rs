@@ -0,0 +1,245 @@
+use crate::naming::model::{Instance, InstanceShortKey};
+use rand::prelude::IteratorRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// 实例metadata中的可用区与地域标识
+pub const ZONE_METADATA_KEY: &str = "zone";
+pub const REGION_METADATA_KEY: &str = "region";
+
+///
+/// 单实例选择策略
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum SelectStrategy {
+    #[default]
+    Random,
+    WeightedRandom,
+    RoundRobin,
+    LeastRecentlySelected,
+}
+
+impl SelectStrategy {
+    pub fn from_str_or_default(v: &str) -> Self {
+        match v.to_uppercase().replace('-', "_").as_str() {
+            "WEIGHTED_RANDOM" | "WEIGHTED" => SelectStrategy::WeightedRandom,
+            "ROUND_ROBIN" => SelectStrategy::RoundRobin,
+            "LEAST_RECENTLY_SELECTED" | "LRS" => SelectStrategy::LeastRecentlySelected,
+            _ => SelectStrategy::Random,
+        }
+    }
+}
+
+///
+/// 单实例选择参数;
+/// 设置zone/region时优先选择同可用区实例,同可用区没有健康实例时依次回退到同地域、全部实例
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct InstanceSelectParam {
+    pub strategy: SelectStrategy,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub zone: Option<Arc<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub region: Option<Arc<String>>,
+}
+
+impl InstanceSelectParam {
+    pub fn new(strategy: SelectStrategy) -> Self {
+        Self {
+            strategy,
+            ..Default::default()
+        }
+    }
+
+    fn filter_locality(&self, candidates: Vec<Arc<Instance>>) -> Vec<Arc<Instance>> {
+        for (key, value) in [
+            (ZONE_METADATA_KEY, &self.zone),
+            (REGION_METADATA_KEY, &self.region),
+        ] {
+            let value = match value {
+                Some(v) if !v.is_empty() => v,
+                _ => continue,
+            };
+            let local: Vec<Arc<Instance>> = candidates
+                .iter()
+                .filter(|e| e.metadata.get(key) == Some(value.as_ref()))
+                .cloned()
+                .collect();
+            if !local.is_empty() {
+                return local;
+            }
+        }
+        candidates
+    }
+}
+
+///
+/// 服务级别的实例选择状态,用于轮询与最久未选中策略
+#[derive(Debug, Default)]
+pub struct InstanceSelectState {
+    round_robin_index: usize,
+    select_seq: u64,
+    last_selected: HashMap<InstanceShortKey, u64>,
+}
+
+impl InstanceSelectState {
+    ///
+    /// 从候选实例中按参数选择一个实例,候选实例应已过滤掉不健康与下线实例
+    pub fn select(
+        &mut self,
+        candidates: Vec<Arc<Instance>>,
+        param: &InstanceSelectParam,
+    ) -> Option<Arc<Instance>> {
+        let mut candidates = param.filter_locality(candidates);
+        if candidates.is_empty() {
+            return None;
+        }
+        let selected = match param.strategy {
+            SelectStrategy::Random => candidates.into_iter().choose(&mut rand::thread_rng()),
+            SelectStrategy::WeightedRandom => Self::select_by_weight(candidates),
+            SelectStrategy::RoundRobin => {
+                candidates.sort_by(|a, b| (&a.ip, a.port).cmp(&(&b.ip, b.port)));
+                let index = self.round_robin_index % candidates.len();
+                self.round_robin_index = self.round_robin_index.wrapping_add(1);
+                Some(candidates.swap_remove(index))
+            }
+            SelectStrategy::LeastRecentlySelected => candidates.into_iter().min_by(|a, b| {
+                let a_seq = self.get_last_selected(a);
+                let b_seq = self.get_last_selected(b);
+                a_seq
+                    .cmp(&b_seq)
+                    .then((&a.ip, a.port).cmp(&(&b.ip, b.port)))
+            }),
+        };
+        if let Some(instance) = &selected {
+            self.select_seq += 1;
+            self.last_selected.insert(
+                InstanceShortKey::new(instance.ip.clone(), instance.port),
+                self.select_seq,
+            );
+        }
+        selected
+    }
+
+    pub fn remove(&mut self, key: &InstanceShortKey) {
+        self.last_selected.remove(key);
+    }
+
+    fn get_last_selected(&self, instance: &Instance) -> u64 {
+        self.last_selected
+            .get(&InstanceShortKey::new(instance.ip.clone(), instance.port))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    ///
+    /// 按权重随机选择;权重小于等于0的实例不参与,全部权重无效时退化为均匀随机
+    fn select_by_weight(candidates: Vec<Arc<Instance>>) -> Option<Arc<Instance>> {
+        let total: f64 = candidates
+            .iter()
+            .filter(|e| e.weight > 0f32)
+            .map(|e| e.weight as f64)
+            .sum();
+        let mut rng = rand::thread_rng();
+        if total <= 0f64 {
+            return candidates.into_iter().choose(&mut rng);
+        }
+        let mut point = rng.gen_range(0f64..total);
+        let mut last = None;
+        for instance in candidates.into_iter().filter(|e| e.weight > 0f32) {
+            point -= instance.weight as f64;
+            if point < 0f64 {
+                return Some(instance);
+            }
+            last = Some(instance);
+        }
+        //浮点误差兜底
+        last
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_instance(ip: &str, weight: f32, zone: &str) -> Arc<Instance> {
+        let mut metadata = HashMap::new();
+        metadata.insert(ZONE_METADATA_KEY.to_owned(), zone.to_owned());
+        metadata.insert(REGION_METADATA_KEY.to_owned(), "r1".to_owned());
+        Arc::new(Instance {
+            ip: Arc::new(ip.to_owned()),
+            port: 80,
+            weight,
+            metadata: Arc::new(metadata),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn select_strategy() {
+        let candidates = vec![
+            build_instance("10.0.0.2", 1f32, "z1"),
+            build_instance("10.0.0.1", 0f32, "z1"),
+            build_instance("10.0.0.3", 2f32, "z2"),
+        ];
+        let mut state = InstanceSelectState::default();
+        let param = InstanceSelectParam::new(SelectStrategy::RoundRobin);
+        let ips: Vec<String> = (0..4)
+            .map(|_| {
+                state
+                    .select(candidates.clone(), &param)
+                    .unwrap()
+                    .ip
+                    .to_string()
+            })
+            .collect();
+        assert_eq!(ips, vec!["10.0.0.1", "10.0.0.2", "10.0.0.3", "10.0.0.1"]);
+
+        let mut state = InstanceSelectState::default();
+        let param = InstanceSelectParam::new(SelectStrategy::LeastRecentlySelected);
+        state.select(candidates.clone(), &param);
+        state.select(candidates.clone(), &param);
+        let v = state.select(candidates.clone(), &param).unwrap();
+        assert_eq!(v.ip.as_str(), "10.0.0.3");
+
+        let param = InstanceSelectParam::new(SelectStrategy::WeightedRandom);
+        for _ in 0..20 {
+            let v = state.select(candidates.clone(), &param).unwrap();
+            assert_ne!(v.ip.as_str(), "10.0.0.1");
+        }
+        let zero = vec![build_instance("10.0.0.1", 0f32, "z1")];
+        assert!(state.select(zero, &param).is_some());
+        assert!(state.select(vec![], &param).is_none());
+    }
+
+    #[test]
+    fn select_locality() {
+        let candidates = vec![
+            build_instance("10.0.0.1", 1f32, "z1"),
+            build_instance("10.0.0.2", 1f32, "z2"),
+        ];
+        let mut state = InstanceSelectState::default();
+        let mut param = InstanceSelectParam {
+            zone: Some(Arc::new("z2".to_owned())),
+            ..Default::default()
+        };
+        for _ in 0..10 {
+            let v = state.select(candidates.clone(), &param).unwrap();
+            assert_eq!(v.ip.as_str(), "10.0.0.2");
+        }
+        // 同可用区没有可用实例时回退
+        param.zone = Some(Arc::new("z3".to_owned()));
+        param.region = Some(Arc::new("r2".to_owned()));
+        assert!(state.select(candidates, &param).is_some());
+
+        let param: InstanceSelectParam =
+            serde_json::from_str(r#"{"strategy":"ROUND_ROBIN","zone":"z1"}"#).unwrap();
+        assert_eq!(param.strategy, SelectStrategy::RoundRobin);
+        assert_eq!(
+            SelectStrategy::from_str_or_default("weighted_random"),
+            SelectStrategy::WeightedRandom
+        );
+    }
+}
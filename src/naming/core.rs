@@ -26,14 +26,20 @@ use super::model::{DistroData, Instance};
 use super::model::{InstanceKey, UpdatePerpetualType};
 use super::naming_delay_nofity::DelayNotifyActor;
 use super::naming_delay_nofity::DelayNotifyCmd;
+use super::naming_fuzzy_watch::{
+    FuzzyServicePattern, NamingFuzzyWatchNotify, ADD_SERVICE, DELETE_SERVICE,
+};
 use super::naming_subscriber::NamingListenerItem;
 use super::naming_subscriber::Subscriber;
 use super::service::ServiceInfoDto;
 use super::service::ServiceMetadata;
-use super::service::{Service, SubscriberInfoDto};
+use super::service::{InstanceLeaseConfig, Service, SubscriberInfoDto};
 use super::service_index::NamespaceIndex;
 use super::service_index::ServiceQueryParam;
 use super::NamingUtils;
+use crate::common::fuzzy_watch_utils::{
+    FINISH_FUZZY_WATCH_INIT_NOTIFY, FUZZY_WATCH_DIFF_SYNC_NOTIFY, FUZZY_WATCH_INIT_NOTIFY,
+};
 use crate::common::hash_utils::get_hash_value;
 use crate::common::NamingSysConfig;
 use crate::common::{delay_notify, AppSysConfig};
@@ -56,18 +62,20 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use crate::common::constant::EMPTY_ARC_STRING;
-use crate::common::constant::NAMING_INSTANCE_TABLE;
-use crate::common::model::privilege::NamespacePrivilegeGroup;
+use crate::common::constant::{NAMING_INSTANCE_TABLE, NAMING_ROUTE_RULE_TABLE};
+use crate::common::model::privilege::{NamespacePrivilegeGroup, ResourceAction, ResourcePrivilege};
 use crate::common::pb::data_object::InstanceDo;
 use crate::metrics::metrics_key::MetricsKey;
 use crate::metrics::model::{MetricsItem, MetricsQuery, MetricsRecord};
 use crate::namespace::NamespaceActor;
+use crate::naming::balancer::InstanceSelectParam;
 use crate::naming::instance_meta_manager::{InstanceMetaManager, InstanceMetaManagerReq};
 use crate::naming::instance_meta_repository::InstanceMetaDto;
 use crate::naming::model::actor_model::{
     InstanceRegisterParam, LoadResult, NamingRaftReq, NamingRaftResult, SnapshotBuildRequest,
     SnapshotLoadRequest,
 };
+use crate::naming::route_rule::{ServiceRouteRuleDO, ServiceRouteRuleParam};
 use crate::naming::sniffing::{NetSniffing, NetSniffingCmd};
 use crate::raft::cluster::route::RaftRequestRoute;
 use crate::raft::filestore::model::SnapshotRecordDto;
@@ -129,6 +137,10 @@ impl Inject for NamingActor {
         if let Some(notify_addr) = self.delay_notify_addr.as_ref() {
             self.subscriber.set_notify_addr(notify_addr.clone());
         }
+        let conn_manage: Option<Addr<BiStreamManage>> = factory_data.get_actor();
+        if let Some(conn_manage) = conn_manage {
+            self.subscriber.set_conn_manage(conn_manage);
+        }
         self.cluster_node_manage = factory_data.get_actor();
         self.cluster_delay_notify = factory_data.get_actor();
         self.namespace_actor = factory_data.get_actor();
@@ -209,12 +221,20 @@ impl NamingActor {
         self.service_map.get_mut(key)
     }
 
+    fn get_instance_lease_config(&self) -> InstanceLeaseConfig {
+        InstanceLeaseConfig::new(
+            self.sys_config.instance_health_timeout_millis,
+            self.sys_config.instance_timeout_millis,
+        )
+    }
+
     pub(crate) fn create_empty_service(&mut self, key: &ServiceKey) {
         match self.get_service(key) {
             Some(_) => {}
             None => {
                 let mut service = Service::default();
                 let current_time = Local::now().timestamp_millis();
+                service.lease_config = self.get_instance_lease_config();
                 service.service_name = key.service_name.clone();
                 service.namespace_id = key.namespace_id.clone();
                 service.group_name = key.group_name.clone();
@@ -224,7 +244,9 @@ impl NamingActor {
                 ));
                 service.last_modified_millis = current_time;
                 service.recalculate_checksum();
-                self.namespace_index.insert_service(key.clone());
+                if self.namespace_index.insert_service(key.clone()) {
+                    self.subscriber.notify_fuzzy_watch(key, ADD_SERVICE);
+                }
                 //self.dal_addr.do_send(ServiceDalMsg::AddService(service.get_service_do()));
                 self.service_map.insert(key.clone(), service);
                 self.empty_service_set.add(
@@ -249,10 +271,19 @@ impl NamingActor {
                 if let Some(metadata) = service_info.metadata {
                     service.metadata = metadata;
                 }
+                if let Some(health_check) = service_info.health_check {
+                    service.update_health_check(*health_check);
+                }
+                if let Some(selector) = service_info.selector {
+                    service.update_selector(selector);
+                    //选择器变更后可见实例可能变化,通知订阅者
+                    self.subscriber.notify(key);
+                }
             }
             None => {
                 let mut service = Service::default();
                 let current_time = Local::now().timestamp_millis();
+                service.lease_config = self.get_instance_lease_config();
                 service.service_name = key.service_name.clone();
                 service.namespace_id = key.namespace_id.clone();
                 service.group_name = key.group_name.clone();
@@ -267,8 +298,16 @@ impl NamingActor {
                 if let Some(metadata) = service_info.metadata {
                     service.metadata = metadata;
                 }
+                if let Some(selector) = service_info.selector {
+                    service.update_selector(selector);
+                }
+                if let Some(health_check) = service_info.health_check {
+                    service.update_health_check(*health_check);
+                }
                 service.recalculate_checksum();
-                self.namespace_index.insert_service(key.clone());
+                if self.namespace_index.insert_service(key.clone()) {
+                    self.subscriber.notify_fuzzy_watch(&key, ADD_SERVICE);
+                }
                 //self.dal_addr.do_send(ServiceDalMsg::AddService(service.get_service_do()));
                 self.service_map.insert(key.clone(), service);
                 self.empty_service_set.add(
@@ -323,7 +362,11 @@ impl NamingActor {
 
     fn remove_empty_service(&mut self, service_map_key: ServiceKey) -> anyhow::Result<()> {
         if let Some(service) = self.service_map.get(&service_map_key) {
-            if service.instance_size <= 0 {
+            if service.route_rule.is_some() {
+                Err(anyhow::anyhow!(
+                    "The service has route rule,remove the route rule first!"
+                ))
+            } else if service.instance_size <= 0 {
                 //控制台发起的不校验过期时间标记
                 self.clear_one_empty_service(service_map_key.clone(), 0x7fff_ffff_ffff_ffff);
                 Ok(())
@@ -685,14 +728,12 @@ impl NamingActor {
 
     pub fn time_check(&mut self) {
         let current_time = Local::now().timestamp_millis();
-        let healthy_time = current_time - self.sys_config.instance_health_timeout_millis;
-        let offline_time = current_time - self.sys_config.instance_timeout_millis;
         let mut size = 0;
         let now = now_millis();
         let mut change_list = vec![];
         for item in self.service_map.values_mut() {
             let service_key = item.get_service_key();
-            let (rlist, ulist) = item.time_check(healthy_time, offline_time);
+            let (rlist, ulist) = item.time_check(current_time);
             size += rlist.len() + ulist.len();
             if !rlist.is_empty() {
                 for short_key in &rlist {
@@ -727,28 +768,41 @@ impl NamingActor {
 
     fn trigger_perpetual_health_check(&mut self) {
         //TODO 可考虑增加是否主动检测永久实例状态的配置
-        let now = now_second_i32();
-        if now - self.last_perpetual_instance_probe_time
-            < self.sys_config.perpetual_instance_probe_interval
-        {
-            return;
-        }
         let sniffing_addr = if let Some(v) = &self.net_sniffing_addr {
             v.clone()
         } else {
             return;
         };
-        self.last_perpetual_instance_probe_time = now;
+        let now = now_second_i32();
+        let default_interval = self.sys_config.perpetual_instance_probe_interval;
+        let default_due = now - self.last_perpetual_instance_probe_time >= default_interval;
+        if default_due {
+            self.last_perpetual_instance_probe_time = now;
+        }
         let mut host_servers: HashMap<InstanceShortKey, Vec<ServiceKey>> = HashMap::new();
-        for service in self.service_map.values() {
-            for key in &service.perpetual_host_set {
-                let service_key = service.get_service_key();
-                if let Some(list) = host_servers.get_mut(key) {
-                    list.push(service_key);
+        for service in self.service_map.values_mut() {
+            if service.perpetual_host_set.is_empty()
+                || (!default_due && service.health_check.is_none())
+            {
+                continue;
+            }
+            let service_key = service.get_service_key();
+            let (default_hosts, custom_hosts) =
+                service.collect_health_check_hosts(now, default_interval, default_due);
+            for key in default_hosts {
+                if let Some(list) = host_servers.get_mut(&key) {
+                    list.push(service_key.clone());
                 } else {
-                    host_servers.insert(key.clone(), vec![service_key]);
+                    host_servers.insert(key, vec![service_key.clone()]);
                 }
             }
+            for (key, checker) in custom_hosts {
+                sniffing_addr.do_send(NetSniffingCmd::CheckServiceHost(
+                    key,
+                    Arc::new(checker),
+                    service_key.clone(),
+                ));
+            }
         }
         for (key, value) in host_servers {
             sniffing_addr.do_send(NetSniffingCmd::ProbeServiceHost(key, value));
@@ -770,11 +824,7 @@ impl NamingActor {
         );
         for service_key in service_keys {
             if let Some(server) = self.service_map.get_mut(&service_key) {
-                if sniffing_result {
-                    server.update_perpetual_instance_healthy_valid(&host);
-                } else {
-                    server.update_instance_healthy_invalid(&host);
-                }
+                server.update_health_check_result(&host, sniffing_result);
             }
         }
     }
@@ -843,6 +893,7 @@ impl NamingActor {
         page_size: usize,
         page_index: usize,
         key: &ServiceKey,
+        resource_privilege: ResourcePrivilege,
     ) -> (usize, Vec<Arc<String>>) {
         let offset = if page_index == 0 {
             0
@@ -854,6 +905,7 @@ impl NamingActor {
             limit: page_size,
             namespace_id: Some(key.namespace_id.clone()),
             group: Some(key.group_name.clone()),
+            resource_privilege,
             ..Default::default()
         };
         let (size, list) = self.namespace_index.query_service_page(&param);
@@ -968,6 +1020,59 @@ impl NamingActor {
         }
     }
 
+    fn fuzzy_watch(
+        &mut self,
+        client_id: Arc<String>,
+        pattern: FuzzyServicePattern,
+        mut received_service_keys: HashSet<String>,
+        initializing: bool,
+        resource_privilege: ResourcePrivilege,
+    ) {
+        let mut contexts = vec![];
+        for key in self.namespace_index.query_fuzzy_watch_keys(&pattern) {
+            if !resource_privilege.check_permission(
+                &key.namespace_id,
+                &key.group_name,
+                &key.service_name,
+                ResourceAction::Read,
+            ) {
+                continue;
+            }
+            let service_key = FuzzyServicePattern::build_service_key(&key);
+            if !received_service_keys.remove(&service_key) {
+                contexts.push((service_key, ADD_SERVICE));
+            }
+        }
+        for service_key in received_service_keys {
+            contexts.push((service_key, DELETE_SERVICE));
+        }
+        let pattern = Arc::new(pattern);
+        self.subscriber
+            .add_fuzzy_watch(client_id.clone(), pattern.clone(), resource_privilege);
+        if !contexts.is_empty() {
+            let sync_type = if initializing {
+                FUZZY_WATCH_INIT_NOTIFY
+            } else {
+                FUZZY_WATCH_DIFF_SYNC_NOTIFY
+            };
+            let notify = NamingFuzzyWatchNotify::Sync {
+                pattern: pattern.pattern.clone(),
+                sync_type,
+                contexts,
+            };
+            self.subscriber
+                .notify_fuzzy_watch_sync(client_id.clone(), notify);
+        }
+        if initializing {
+            let notify = NamingFuzzyWatchNotify::Sync {
+                pattern: pattern.pattern.clone(),
+                sync_type: FINISH_FUZZY_WATCH_INIT_NOTIFY,
+                contexts: vec![],
+            };
+            self.subscriber.notify_fuzzy_watch_sync(client_id, notify);
+        }
+    }
+
     fn clear_empty_service(&mut self) {
         //println!("clear_empty_service");
         let now = now_millis();
@@ -978,12 +1083,17 @@ impl NamingActor {
 
     fn clear_one_empty_service(&mut self, service_map_key: ServiceKey, now: u64) {
         if let Some(service) = self.service_map.get(&service_map_key) {
+            //有路由规则的服务需要保留,删除规则后再清理
             if service.instance_size <= 0
+                && service.route_rule.is_none()
                 && now - self.sys_config.service_time_out_millis >= service.last_empty_times
             {
                 //self.dal_addr.do_send(ServiceDalMsg::DeleteService(service.get_service_do().get_key_param().unwrap()));
-                self.namespace_index
-                    .remove_service(&service.get_service_key());
+                let service_key = service.get_service_key();
+                if self.namespace_index.remove_service(&service_key) {
+                    self.subscriber
+                        .notify_fuzzy_watch(&service_key, DELETE_SERVICE);
+                }
                 self.service_map.remove(&service_map_key);
                 log::info!("clear_empty_service:{:?}", &service_map_key);
             }
@@ -1258,9 +1368,46 @@ impl NamingActor {
                 self.remove_instance(&service_key, &instance_short_key, None);
                 Ok(NamingRaftResult::None)
             }
+            NamingRaftReq::UpdateRouteRule(param) => {
+                self.update_route_rule(param);
+                Ok(NamingRaftResult::None)
+            }
+        }
+    }
+
+    fn update_route_rule(&mut self, param: ServiceRouteRuleParam) {
+        let key = param.to_service_key();
+        let rule = param.rule.filter(|e| !e.is_empty()).map(Arc::new);
+        if rule.is_some() && !self.service_map.contains_key(&key) {
+            self.update_service(ServiceDetailDto {
+                namespace_id: key.namespace_id.clone(),
+                service_name: key.service_name.clone(),
+                group_name: key.group_name.clone(),
+                ..Default::default()
+            });
+        }
+        if let Some(service) = self.service_map.get_mut(&key) {
+            service.route_rule = rule;
+            if service.route_rule.is_none() && service.instance_size <= 0 {
+                //删除路由规则后空服务按正常过期逻辑清理
+                self.empty_service_set
+                    .add(now_millis() + self.sys_config.service_time_out_millis, key);
+            }
         }
     }
 
+    fn get_route_rule_params(&self) -> Vec<ServiceRouteRuleParam> {
+        self.service_map
+            .iter()
+            .filter_map(|(key, service)| {
+                service
+                    .route_rule
+                    .as_ref()
+                    .map(|e| ServiceRouteRuleParam::new(key, Some(e.as_ref().clone())))
+            })
+            .collect()
+    }
+
     /// 构建快照 - 写入所有永久实例数据
     fn build_snapshot(
         &self,
@@ -1291,6 +1438,17 @@ impl NamingActor {
                 }
             }
         }
+        for param in self.get_route_rule_params() {
+            let record = SnapshotRecordDto {
+                tree: NAMING_ROUTE_RULE_TABLE.clone(),
+                key: Vec::new(),
+                value: ServiceRouteRuleDO::from(&param).to_bytes()?,
+                op_type: 0,
+            };
+            writer.do_send(
+                crate::raft::filestore::raftsnapshot::SnapshotWriterRequest::Record(record),
+            );
+        }
         Ok(())
     }
 
@@ -1302,6 +1460,10 @@ impl NamingActor {
             let instance = Instance::from_do(instance_do);
             let service_key = instance.get_service_key();
             self.update_instance(&service_key, instance, None, true, None);
+        } else if record.tree.as_str() == NAMING_ROUTE_RULE_TABLE.as_str() {
+            let param: ServiceRouteRuleParam =
+                ServiceRouteRuleDO::from_bytes(&record.value)?.into();
+            self.update_route_rule(param);
         }
         Ok(())
     }
@@ -1399,6 +1561,15 @@ impl NamingActor {
                 }
             }
         }
+        for param in self.get_route_rule_params() {
+            let record = TransferRecordDto {
+                table_name: Some(NAMING_ROUTE_RULE_TABLE.clone()),
+                key: Vec::new(),
+                value: ServiceRouteRuleDO::from(&param).to_bytes()?,
+                table_id: 0,
+            };
+            writer.do_send(TransferWriterRequest::AddRecord(record));
+        }
         Ok(())
     }
 }
@@ -1420,11 +1591,12 @@ pub enum NamingCmd {
         page_size: usize,
         page_index: usize,
     },
-    SelectOneInstance(ServiceKey),
+    /// 选择一个实例,第三个参数为用于路由规则匹配的请求header(key为小写)
+    SelectOneInstance(ServiceKey, InstanceSelectParam, HashMap<String, String>),
     QueryAllInstanceList(ServiceKey),
     QueryListString(ServiceKey, String, bool, Option<SocketAddr>),
     QueryServiceInfo(ServiceKey, String, bool),
-    QueryServicePage(ServiceKey, usize, usize),
+    QueryServicePage(ServiceKey, usize, usize, ResourcePrivilege),
     QueryServiceSubscribersPage(ServiceKey, usize, usize),
     QueryServiceSubscribersPageV2(ServiceQueryParam),
     //查询服务实际信息列表
@@ -1439,6 +1611,17 @@ pub enum NamingCmd {
     NotifyListener(ServiceKey, u64),
     Subscribe(Vec<NamingListenerItem>, Arc<String>),
     RemoveSubscribe(Vec<NamingListenerItem>, Arc<String>),
+    FuzzyWatch {
+        client_id: Arc<String>,
+        pattern: FuzzyServicePattern,
+        received_service_keys: HashSet<String>,
+        initializing: bool,
+        resource_privilege: ResourcePrivilege,
+    },
+    CancelFuzzyWatch {
+        client_id: Arc<String>,
+        pattern: Arc<String>,
+    },
     RemoveClient(Arc<String>),
     RemoveClientsFromCluster(Vec<Arc<String>>),
     RemoveClientFromCluster(Arc<String>),
@@ -1595,11 +1778,12 @@ impl Handler<NamingCmd> for NamingActor {
                 let service_info = self.get_service_info(&service_key, cluster_str, only_healthy);
                 Ok(NamingResult::ServiceInfo(service_info))
             }
-            NamingCmd::QueryServicePage(service_key, page_size, page_index) => {
+            NamingCmd::QueryServicePage(service_key, page_size, page_index, resource_privilege) => {
                 Ok(NamingResult::ServicePage(self.get_service_list(
                     page_size,
                     page_index,
                     &service_key,
+                    resource_privilege,
                 )))
             }
             NamingCmd::QueryServiceSubscribersPage(service_key, page_size, page_index) => {
@@ -1642,6 +1826,26 @@ impl Handler<NamingCmd> for NamingActor {
                 self.subscriber.remove_subscribe(client_id, items);
                 Ok(NamingResult::NULL)
             }
+            NamingCmd::FuzzyWatch {
+                client_id,
+                pattern,
+                received_service_keys,
+                initializing,
+                resource_privilege,
+            } => {
+                self.fuzzy_watch(
+                    client_id,
+                    pattern,
+                    received_service_keys,
+                    initializing,
+                    resource_privilege,
+                );
+                Ok(NamingResult::NULL)
+            }
+            NamingCmd::CancelFuzzyWatch { client_id, pattern } => {
+                self.subscriber.remove_fuzzy_watch(client_id, &pattern);
+                Ok(NamingResult::NULL)
+            }
             NamingCmd::RemoveClient(client_id) => {
                 self.subscriber.remove_client_subscribe(client_id.clone());
                 self.remove_client_instance(&client_id);
@@ -1696,9 +1900,9 @@ impl Handler<NamingCmd> for NamingActor {
                     Ok(NamingResult::InstanceList(vec![]))
                 }
             }
-            NamingCmd::SelectOneInstance(service_key) => {
-                let v = if let Some(service) = self.service_map.get(&service_key) {
-                    service.select_one_instance(true, true)
+            NamingCmd::SelectOneInstance(service_key, param, headers) => {
+                let v = if let Some(service) = self.service_map.get_mut(&service_key) {
+                    service.select_one_instance(true, true, &param, &headers)
                 } else {
                     None
                 };
@@ -1851,6 +2055,92 @@ async fn query_healthy_instances() {
     );
 }
 
+#[test]
+fn instance_lease_from_metadata() {
+    use super::*;
+    let mut naming = NamingActor::new();
+    let mut metadata = HashMap::new();
+    metadata.insert(PRESERVED_HEART_BEAT_TIMEOUT.to_owned(), "1000".to_owned());
+    metadata.insert(PRESERVED_IP_DELETE_TIMEOUT.to_owned(), "2000".to_owned());
+    let mut instance = Instance::new("127.0.0.1".to_owned(), 8080);
+    instance.namespace_id = Arc::new("public".to_owned());
+    instance.service_name = Arc::new("foo".to_owned());
+    instance.group_name = Arc::new("DEFUALT".to_owned());
+    instance.cluster_name = "DEFUALT".to_owned();
+    let mut default_instance = instance.clone();
+    default_instance.ip = Arc::new("127.0.0.2".to_owned());
+    instance.metadata = Arc::new(metadata);
+    let key = instance.get_service_key();
+    let short_key = instance.get_short_key();
+    naming.update_instance(&key, instance, None, false, None);
+    naming.update_instance(&key, default_instance, None, false, None);
+    let service = naming.service_map.get_mut(&key).unwrap();
+    let last_modified = service
+        .instances
+        .get(&short_key)
+        .unwrap()
+        .last_modified_millis;
+
+    let (rlist, ulist) = service.time_check(last_modified + 999);
+    assert!(rlist.is_empty() && ulist.is_empty());
+    let (rlist, ulist) = service.time_check(last_modified + 1000);
+    assert!(rlist.is_empty());
+    assert_eq!(ulist, vec![short_key.clone()]);
+    let (rlist, ulist) = service.time_check(last_modified + 2000);
+    assert_eq!(rlist, vec![short_key]);
+    assert!(ulist.is_empty());
+    // 未配置租约的实例使用默认超时
+    assert_eq!(service.instance_size, 1);
+    assert_eq!(service.healthy_instance_size, 1);
+}
+
+#[test]
+fn instance_lease_clamp_oversized_value() {
+    use super::*;
+    let mut naming = NamingActor::new();
+    let mut metadata = HashMap::new();
+    metadata.insert(
+        PRESERVED_HEART_BEAT_TIMEOUT.to_owned(),
+        i64::MAX.to_string(),
+    );
+    metadata.insert(PRESERVED_IP_DELETE_TIMEOUT.to_owned(), i64::MAX.to_string());
+    metadata.insert(PRESERVED_HEART_BEAT_INTERVAL.to_owned(), "1".to_owned());
+    let mut instance = Instance::new("127.0.0.1".to_owned(), 8080);
+    instance.namespace_id = Arc::new("public".to_owned());
+    instance.service_name = Arc::new("foo".to_owned());
+    instance.group_name = Arc::new("DEFUALT".to_owned());
+    instance.cluster_name = "DEFUALT".to_owned();
+    instance.metadata = Arc::new(metadata);
+    assert_eq!(
+        instance.get_heart_beat_timeout(0),
+        PRESERVED_LEASE_MAX_MILLIS
+    );
+    assert_eq!(
+        instance.get_ip_delete_timeout(0),
+        PRESERVED_LEASE_MAX_MILLIS
+    );
+    assert_eq!(
+        instance.get_heart_beat_interval(),
+        Some(PRESERVED_LEASE_MIN_MILLIS)
+    );
+    let key = instance.get_service_key();
+    let short_key = instance.get_short_key();
+    naming.update_instance(&key, instance, None, false, None);
+    let service = naming.service_map.get_mut(&key).unwrap();
+    let last_modified = service
+        .instances
+        .get(&short_key)
+        .unwrap()
+        .last_modified_millis;
+    let (rlist, ulist) = service.time_check(last_modified + 1000);
+    assert!(rlist.is_empty() && ulist.is_empty());
+    let (rlist, ulist) = service.time_check(last_modified + PRESERVED_LEASE_MAX_MILLIS);
+    assert!(rlist.is_empty());
+    assert_eq!(ulist, vec![short_key.clone()]);
+    let (rlist, _) = service.time_check(last_modified + PRESERVED_LEASE_MAX_MILLIS);
+    assert_eq!(rlist, vec![short_key]);
+}
+
 #[test]
 fn test_add_service() {
     let mut naming = NamingActor::new();
@@ -1870,6 +2160,32 @@ fn test_add_service() {
     assert!(naming.namespace_index.service_size == 0);
 }
 
+#[test]
+fn service_list_filter_by_resource_privilege() {
+    use crate::common::model::privilege::ResourcePermission;
+    let mut naming = NamingActor::new();
+    for service_name in ["order-api", "user-api"] {
+        naming.update_service(ServiceDetailDto {
+            namespace_id: Arc::new("public".to_owned()),
+            group_name: Arc::new("DEFAULT_GROUP".to_owned()),
+            service_name: Arc::new(service_name.to_owned()),
+            ..Default::default()
+        });
+    }
+    let key = ServiceKey::new("public", "DEFAULT_GROUP", "");
+    let (count, _) = naming.get_service_list(10, 1, &key, ResourcePrivilege::default());
+    assert_eq!(count, 2);
+    let privilege = ResourcePrivilege::new(vec![ResourcePermission {
+        namespace: Arc::new("public".to_owned()),
+        group: Arc::new("*".to_owned()),
+        resource: Arc::new("order-*".to_owned()),
+        action: ResourceAction::Read,
+    }]);
+    let (count, list) = naming.get_service_list(10, 1, &key, privilege);
+    assert_eq!(count, 1);
+    assert_eq!(list[0].as_str(), "order-api");
+}
+
 #[test]
 fn test_remove_has_instance_service() {
     let mut naming = NamingActor::new();
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use super::{
@@ -12,13 +13,15 @@ impl InstanceFilterUtils {
         instances.into_iter().filter(|i| i.healthy).collect()
     }
     pub fn default_instance_filter(
-        all_instances: Vec<Arc<Instance>>,
+        mut all_instances: Vec<Arc<Instance>>,
         metadata: Option<ServiceMetadata>,
         filter_headlthy: bool,
     ) -> Vec<Arc<Instance>> {
         if let Some(metadata) = metadata {
+            if let Some(selector) = &metadata.selector {
+                all_instances = selector.select(all_instances, None);
+            }
             let original_total = all_instances.len();
-            // all_instances = from metadata select
             let mut healthy_count = 0;
             for item in &all_instances {
                 if item.healthy {
@@ -58,9 +61,14 @@ impl InstanceFilterUtils {
         metadata: Option<ServiceMetadata>,
         filter_headlthy: bool,
     ) -> ServiceInfo {
+        if let Some(selector) = metadata.as_ref().and_then(|e| e.selector.as_ref()) {
+            service_info.hosts = service_info.hosts.map(|e| selector.select(e, None));
+            if selector.need_consumer_labels() {
+                service_info.selector = Some(selector.clone());
+            }
+        }
         if let (Some(all_instances), Some(metadata)) = (service_info.hosts.as_ref(), metadata) {
             let original_total = all_instances.len();
-            // all_instances = from metadata select
             let mut healthy_count = 0;
             for item in all_instances {
                 if item.healthy {
@@ -95,4 +103,18 @@ impl InstanceFilterUtils {
         }
         service_info
     }
+
+    ///
+    /// 按消费者标签过滤依赖消费者的选择器
+    pub fn consumer_service_filter(
+        mut service_info: ServiceInfo,
+        consumer_labels: &HashMap<String, String>,
+    ) -> ServiceInfo {
+        if let Some(selector) = service_info.selector.as_ref() {
+            service_info.hosts = service_info
+                .hosts
+                .map(|e| selector.select(e, Some(consumer_labels)));
+        }
+        service_info
+    }
 }
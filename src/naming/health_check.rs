@@ -0,0 +1,377 @@
+use crate::naming::model::InstanceShortKey;
+use crate::naming::sniffing::probe_tcp;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use sqlx::{ConnectOptions, Connection, Executor};
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::time::timeout;
+
+pub const DEFAULT_CLUSTER_NAME: &str = "DEFAULT";
+const DEFAULT_TIMEOUT_MILLIS: u64 = 2000;
+const DEFAULT_MYSQL_CMD: &str = "SELECT 1";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum HealthCheckType {
+    #[default]
+    Tcp,
+    Http,
+    Mysql,
+    /// 不主动检测,实例健康状态只由接口更新
+    None,
+}
+
+impl HealthCheckType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HealthCheckType::Tcp => "TCP",
+            HealthCheckType::Http => "HTTP",
+            HealthCheckType::Mysql => "MYSQL",
+            HealthCheckType::None => "NONE",
+        }
+    }
+}
+
+///
+/// 持久实例主动健康检测配置,字段与nacos healthChecker json兼容,如:
+/// {"type":"HTTP","path":"/health","expectedResponseCode":200}
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthChecker {
+    #[serde(rename = "type", default)]
+    pub check_type: HealthCheckType,
+    /// 检测端口,为空时使用实例端口
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub check_port: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interval_second: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_millis: Option<u64>,
+    /// 连续成功多少次后标记为健康
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub healthy_threshold: Option<u32>,
+    /// 连续失败多少次后标记为不健康
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unhealthy_threshold: Option<u32>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub path: String,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub headers: HashMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected_response_code: Option<u16>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub user: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub pwd: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub cmd: String,
+}
+
+impl HealthChecker {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.interval_second.unwrap_or(1) <= 0 {
+            return Err(anyhow::anyhow!("health check interval must be positive"));
+        }
+        if self.healthy_threshold == Some(0) || self.unhealthy_threshold == Some(0) {
+            return Err(anyhow::anyhow!("health check threshold must be positive"));
+        }
+        if self.check_type == HealthCheckType::Http
+            && !self.path.is_empty()
+            && !self.path.starts_with('/')
+        {
+            return Err(anyhow::anyhow!("http health check path must start with /"));
+        }
+        if self.check_type == HealthCheckType::Mysql && self.user.is_empty() {
+            return Err(anyhow::anyhow!("mysql health check user is empty"));
+        }
+        Ok(())
+    }
+
+    pub fn get_interval_second(&self, default_interval: i32) -> i32 {
+        self.interval_second.unwrap_or(default_interval)
+    }
+
+    pub fn get_timeout(&self) -> Duration {
+        Duration::from_millis(self.timeout_millis.unwrap_or(DEFAULT_TIMEOUT_MILLIS))
+    }
+
+    pub fn get_thresholds(&self) -> (u32, u32) {
+        (
+            self.healthy_threshold.unwrap_or(1),
+            self.unhealthy_threshold.unwrap_or(1),
+        )
+    }
+
+    pub fn to_map(&self) -> HashMap<String, String> {
+        let mut map = HashMap::new();
+        map.insert("type".to_owned(), self.check_type.as_str().to_owned());
+        match self.check_type {
+            HealthCheckType::Http => {
+                map.insert("path".to_owned(), self.path.clone());
+                map.insert(
+                    "expectedResponseCode".to_owned(),
+                    self.expected_response_code.unwrap_or(200).to_string(),
+                );
+            }
+            HealthCheckType::Mysql => {
+                map.insert("user".to_owned(), self.user.clone());
+                map.insert("cmd".to_owned(), self.get_mysql_cmd().to_owned());
+            }
+            _ => {}
+        }
+        map
+    }
+
+    fn get_mysql_cmd(&self) -> &str {
+        if self.cmd.is_empty() {
+            DEFAULT_MYSQL_CMD
+        } else {
+            &self.cmd
+        }
+    }
+
+    ///
+    /// 按配置检测实例,检测失败时返回具体原因
+    pub async fn check(&self, client: &Client, host: &InstanceShortKey) -> anyhow::Result<()> {
+        let port = self.check_port.unwrap_or(host.port as u16);
+        let timeout_duration = self.get_timeout();
+        match self.check_type {
+            HealthCheckType::Tcp => probe_tcp(host.ip.as_ref(), port, timeout_duration).await,
+            HealthCheckType::Http => self.check_http(client, host, port, timeout_duration).await,
+            HealthCheckType::Mysql => {
+                timeout(timeout_duration, self.check_mysql(host, port)).await?
+            }
+            HealthCheckType::None => Ok(()),
+        }
+    }
+
+    async fn check_http(
+        &self,
+        client: &Client,
+        host: &InstanceShortKey,
+        port: u16,
+        timeout_duration: Duration,
+    ) -> anyhow::Result<()> {
+        let path = if self.path.is_empty() {
+            "/"
+        } else {
+            &self.path
+        };
+        let url = format!("http://{}:{}{}", host.ip, port, path);
+        let mut request = client.get(&url).timeout(timeout_duration);
+        for (key, value) in &self.headers {
+            request = request.header(key, value);
+        }
+        let status = request.send().await?.status().as_u16();
+        let expected = self.expected_response_code.unwrap_or(200);
+        if status != expected {
+            return Err(anyhow::anyhow!(
+                "http health check status {}, expected {}",
+                status,
+                expected
+            ));
+        }
+        Ok(())
+    }
+
+    async fn check_mysql(&self, host: &InstanceShortKey, port: u16) -> anyhow::Result<()> {
+        let mut conn = sqlx::mysql::MySqlConnectOptions::new()
+            .host(host.ip.as_ref())
+            .port(port)
+            .username(&self.user)
+            .password(&self.pwd)
+            .connect()
+            .await?;
+        conn.execute(self.get_mysql_cmd()).await?;
+        conn.close().await?;
+        Ok(())
+    }
+}
+
+///
+/// 服务的主动健康检测配置,可按集群覆盖;都没有配置时使用全局tcp检测
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServiceHealthCheck {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checker: Option<HealthChecker>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub clusters: HashMap<String, HealthChecker>,
+}
+
+impl ServiceHealthCheck {
+    ///
+    /// 解析接口中的健康检测配置json字符串,空字符串表示恢复默认检测
+    pub fn parse(value: &str) -> anyhow::Result<Self> {
+        if value.trim().is_empty() {
+            return Ok(Self::default());
+        }
+        let health_check: Self = serde_json::from_str(value)?;
+        health_check.validate()?;
+        Ok(health_check)
+    }
+
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if let Some(checker) = &self.checker {
+            checker.validate()?;
+        }
+        for checker in self.clusters.values() {
+            checker.validate()?;
+        }
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.checker.is_none() && self.clusters.is_empty()
+    }
+
+    ///
+    /// 返回给控制台时隐藏mysql密码
+    pub fn without_secret(&self) -> Self {
+        let mut v = self.clone();
+        for checker in v.checker.iter_mut().chain(v.clusters.values_mut()) {
+            checker.pwd = String::new();
+        }
+        v
+    }
+
+    ///
+    /// 控制台更新时未填写mysql密码则沿用原配置中的密码
+    pub fn inherit_secret(&mut self, old: &Self) {
+        if let (Some(checker), Some(old_checker)) = (self.checker.as_mut(), old.checker.as_ref()) {
+            if checker.pwd.is_empty() {
+                checker.pwd = old_checker.pwd.clone();
+            }
+        }
+        for (name, checker) in self.clusters.iter_mut() {
+            if let (true, Some(old_checker)) = (checker.pwd.is_empty(), old.clusters.get(name)) {
+                checker.pwd = old_checker.pwd.clone();
+            }
+        }
+    }
+
+    pub fn get_checker(&self, cluster_name: &str) -> Option<&HealthChecker> {
+        let cluster_name = if cluster_name.is_empty() {
+            DEFAULT_CLUSTER_NAME
+        } else {
+            cluster_name
+        };
+        self.clusters.get(cluster_name).or(self.checker.as_ref())
+    }
+}
+
+///
+/// 实例最近的检测状态,用于按间隔调度与连续成功/失败阈值判断
+#[derive(Debug, Clone, Default)]
+pub struct HealthCheckStatus {
+    pub last_check_time: i32,
+    pub success_count: u32,
+    pub fail_count: u32,
+}
+
+impl HealthCheckStatus {
+    ///
+    /// 记录一次检测结果,达到阈值时返回新的健康状态
+    pub fn record(&mut self, success: bool, thresholds: (u32, u32)) -> Option<bool> {
+        if success {
+            self.fail_count = 0;
+            self.success_count = self.success_count.saturating_add(1);
+            (self.success_count >= thresholds.0).then_some(true)
+        } else {
+            self.success_count = 0;
+            self.fail_count = self.fail_count.saturating_add(1);
+            (self.fail_count >= thresholds.1).then_some(false)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn parse_and_threshold() {
+        let health_check = ServiceHealthCheck::parse(
+            r#"{"checker":{"type":"HTTP","path":"/health","healthyThreshold":2,"unhealthyThreshold":3},
+            "clusters":{"db":{"type":"MYSQL","user":"root"}}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            health_check.get_checker("db").unwrap().check_type,
+            HealthCheckType::Mysql
+        );
+        let checker = health_check.get_checker("").unwrap();
+        assert_eq!(checker.check_type, HealthCheckType::Http);
+        assert_eq!(checker.get_interval_second(5), 5);
+        assert!(ServiceHealthCheck::parse("").unwrap().is_empty());
+        assert!(
+            ServiceHealthCheck::parse(r#"{"checker":{"type":"HTTP","path":"health"}}"#).is_err()
+        );
+        assert!(ServiceHealthCheck::parse(r#"{"checker":{"type":"UDP"}}"#).is_err());
+
+        let old = ServiceHealthCheck::parse(
+            r#"{"clusters":{"db":{"type":"MYSQL","user":"root","pwd":"p"}}}"#,
+        )
+        .unwrap();
+        let mut new = old.without_secret();
+        assert!(new.clusters["db"].pwd.is_empty());
+        new.inherit_secret(&old);
+        assert_eq!(new, old);
+
+        let thresholds = checker.get_thresholds();
+        let mut status = HealthCheckStatus::default();
+        assert_eq!(status.record(false, thresholds), None);
+        assert_eq!(status.record(false, thresholds), None);
+        assert_eq!(status.record(false, thresholds), Some(false));
+        assert_eq!(status.record(true, thresholds), None);
+        assert_eq!(status.record(true, thresholds), Some(true));
+    }
+
+    async fn start_stub(status_line: &'static str) -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut buf = [0u8; 1024];
+                let n = stream.read(&mut buf).await.unwrap_or(0);
+                let request = String::from_utf8_lossy(&buf[..n]).to_lowercase();
+                let status =
+                    if request.starts_with("get /health ") && request.contains("x-check: 1") {
+                        status_line
+                    } else {
+                        "404 Not Found"
+                    };
+                let resp = format!(
+                    "HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                    status
+                );
+                stream.write_all(resp.as_bytes()).await.ok();
+            }
+        });
+        port
+    }
+
+    #[tokio::test]
+    async fn http_check() {
+        let client = Client::new();
+        let port = start_stub("204 No Content").await;
+        let host = InstanceShortKey::new(Arc::new("127.0.0.1".to_owned()), port as u32);
+        let mut checker = HealthChecker {
+            check_type: HealthCheckType::Http,
+            path: "/health".to_owned(),
+            headers: HashMap::from([("X-Check".to_owned(), "1".to_owned())]),
+            expected_response_code: Some(204),
+            ..Default::default()
+        };
+        assert!(checker.check(&client, &host).await.is_ok());
+        checker.expected_response_code = None;
+        assert!(checker.check(&client, &host).await.is_err());
+        checker.expected_response_code = Some(204);
+        checker.path = "/other".to_owned();
+        assert!(checker.check(&client, &host).await.is_err());
+    }
+}
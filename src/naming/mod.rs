@@ -1,13 +1,17 @@
 use std::collections::HashMap;
 
 pub mod api_model;
+pub mod balancer;
 pub mod core;
 pub(crate) mod filter;
+pub mod health_check;
 pub mod instance_meta_manager;
 pub mod instance_meta_repository;
 pub mod listener;
 pub mod model;
 pub mod naming_delay_nofity;
+pub mod naming_fuzzy_watch;
+pub mod naming_sse;
 pub mod naming_subscriber;
 pub mod service;
 pub mod transfer;
@@ -16,6 +20,8 @@ pub mod udp_actor;
 pub mod cluster;
 pub mod metrics;
 pub mod ops;
+pub mod route_rule;
+pub mod selector;
 pub mod service_index;
 
 #[cfg(feature = "debug")]
@@ -28,6 +34,15 @@ pub const RESPONSE_CODE_KEY: &str = "code";
 pub const RESPONSE_CODE_OK: i32 = 10200;
 pub const CLIENT_BEAT_INTERVAL_KEY: &str = "clientBeatInterval";
 pub const LIGHT_BEAT_ENABLED_KEY: &str = "lightBeatEnabled";
+pub const DEFAULT_CLIENT_BEAT_INTERVAL: i64 = 5000;
+
+/// 实例metadata中的租约配置(毫秒),与nacos保持一致
+pub const PRESERVED_HEART_BEAT_INTERVAL: &str = "preserved.heart.beat.interval";
+pub const PRESERVED_HEART_BEAT_TIMEOUT: &str = "preserved.heart.beat.timeout";
+pub const PRESERVED_IP_DELETE_TIMEOUT: &str = "preserved.ip.delete.timeout";
+/// 租约配置由客户端指定,限制在合理范围内
+pub const PRESERVED_LEASE_MIN_MILLIS: i64 = 1000;
+pub const PRESERVED_LEASE_MAX_MILLIS: i64 = 24 * 60 * 60 * 1000;
 
 pub const DEFAULT_NAMESPACE: &str = "public";
 pub const DEFAULT_CLUSTER: &str = "DEFAULT";
@@ -3,7 +3,12 @@ use std::collections::HashSet;
 use std::{collections::HashMap, sync::Arc};
 
 use crate::common::pb::data_object::InstanceDo;
-use crate::naming::NamingUtils;
+use crate::naming::health_check::ServiceHealthCheck;
+use crate::naming::selector::{LabelSelector, ServiceSelector};
+use crate::naming::{
+    NamingUtils, PRESERVED_HEART_BEAT_INTERVAL, PRESERVED_HEART_BEAT_TIMEOUT,
+    PRESERVED_IP_DELETE_TIMEOUT, PRESERVED_LEASE_MAX_MILLIS, PRESERVED_LEASE_MIN_MILLIS,
+};
 use crate::now_millis_i64;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -49,6 +54,34 @@ impl Instance {
         self.ephemeral && !self.from_grpc && !self.is_from_cluster()
     }
 
+    ///
+    /// 客户端心跳间隔,未在metadata中指定时返回None
+    pub fn get_heart_beat_interval(&self) -> Option<i64> {
+        self.get_metadata_millis(PRESERVED_HEART_BEAT_INTERVAL)
+    }
+
+    ///
+    /// 心跳超时时间,超过后实例标记为不健康
+    pub fn get_heart_beat_timeout(&self, default_value: i64) -> i64 {
+        self.get_metadata_millis(PRESERVED_HEART_BEAT_TIMEOUT)
+            .unwrap_or(default_value)
+    }
+
+    ///
+    /// 实例删除超时时间,从最后一次心跳开始计算
+    pub fn get_ip_delete_timeout(&self, default_value: i64) -> i64 {
+        self.get_metadata_millis(PRESERVED_IP_DELETE_TIMEOUT)
+            .unwrap_or(default_value)
+    }
+
+    fn get_metadata_millis(&self, key: &str) -> Option<i64> {
+        self.metadata
+            .get(key)
+            .and_then(|v| v.trim().parse::<i64>().ok())
+            .filter(|v| *v > 0)
+            .map(|v| v.clamp(PRESERVED_LEASE_MIN_MILLIS, PRESERVED_LEASE_MAX_MILLIS))
+    }
+
     pub fn generate_key(&mut self) {
         //self.id = format!("{}#{}#{}#{}#{}",&self.ip,&self.port,&self.cluster_name,&self.service_name,&self.group_name)
         self.id = Arc::new(format!("{}#{}", &self.ip, &self.port))
@@ -209,6 +242,9 @@ pub struct ServiceInfo {
     pub all_ips: bool,
     pub reach_protection_threshold: bool,
     //pub metadata:Option<HashMap<String,String>>,
+    /// 依赖消费者标签的选择器,在推送或响应具体消费者时再过滤
+    #[serde(skip)]
+    pub selector: Option<Arc<LabelSelector>>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Default, Clone)]
@@ -220,6 +256,10 @@ pub struct ServiceDetailDto {
     pub metadata: Option<Arc<HashMap<String, String>>>,
     pub protect_threshold: Option<f32>,
     pub grpc_instance_count: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selector: Option<ServiceSelector>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub health_check: Option<Box<ServiceHealthCheck>>,
 }
 
 impl ServiceDetailDto {
@@ -1,4 +1,5 @@
 use crate::naming::model::{Instance, InstanceKey};
+use crate::naming::route_rule::ServiceRouteRuleParam;
 use crate::raft::filestore::model::SnapshotRecordDto;
 use crate::raft::filestore::raftsnapshot::SnapshotWriterActor;
 use actix::prelude::*;
@@ -15,6 +16,8 @@ pub enum NamingRaftReq {
     UpdateInstance { param: InstanceRegisterParam },
     /// 删除永久实例
     RemoveInstance(InstanceKey),
+    /// 设置或删除服务路由规则
+    UpdateRouteRule(ServiceRouteRuleParam),
 }
 
 impl Message for NamingRaftReq {
@@ -14,6 +14,7 @@ use crate::{
 use super::{
     core::{NamingActor, NamingCmd, NamingResult},
     model::{ServiceInfo, ServiceKey},
+    naming_sse::{NamingSseCmd, NamingSseManage},
 };
 
 #[derive(Clone, Default)]
@@ -22,16 +23,32 @@ pub struct NamingDelayEvent {
     pub client_id_set: HashSet<Arc<String>>,
     pub service_info: Option<ServiceInfo>,
     pub conn_manage: Option<Addr<BiStreamManage>>,
+    pub sse_manage: Option<Addr<NamingSseManage>>,
 }
 
 impl NotifyEvent for NamingDelayEvent {
     fn on_event(self) -> anyhow::Result<()> {
-        if let (Some(conn_manage), Some(service_info)) =
-            (self.conn_manage.as_ref(), self.service_info)
+        let service_info = if let Some(service_info) = self.service_info {
+            service_info
+        } else {
+            return Ok(());
+        };
+        let (sse_client_id_set, client_id_set): (HashSet<_>, HashSet<_>) = self
+            .client_id_set
+            .into_iter()
+            .partition(|e| NamingSseManage::is_sse_client(e));
+        if let (Some(sse_manage), false) = (self.sse_manage.as_ref(), sse_client_id_set.is_empty())
         {
+            sse_manage.do_send(NamingSseCmd::Notify(
+                self.key.clone(),
+                sse_client_id_set,
+                service_info.clone(),
+            ));
+        }
+        if let (Some(conn_manage), false) = (self.conn_manage.as_ref(), client_id_set.is_empty()) {
             conn_manage.do_send(BiStreamManageCmd::NotifyNaming(
                 self.key,
-                self.client_id_set,
+                client_id_set,
                 service_info,
             ));
         }
@@ -42,6 +59,7 @@ impl NotifyEvent for NamingDelayEvent {
         self.service_info = other.service_info;
         self.client_id_set = other.client_id_set;
         self.conn_manage = other.conn_manage;
+        self.sse_manage = other.sse_manage;
         Ok(())
     }
 }
@@ -50,6 +68,7 @@ impl NotifyEvent for NamingDelayEvent {
 pub struct DelayNotifyActor {
     inner_delay_notify: DelayNotify<ServiceKey, NamingDelayEvent>,
     conn_manage: Option<Addr<BiStreamManage>>,
+    sse_manage: Option<Addr<NamingSseManage>>,
     naming_addr: Option<Addr<NamingActor>>,
     delay: u64,
 }
@@ -65,6 +84,7 @@ impl DelayNotifyActor {
         Self {
             inner_delay_notify: Default::default(),
             conn_manage: None,
+            sse_manage: None,
             naming_addr: None,
             delay: 500,
         }
@@ -130,6 +150,7 @@ impl Inject for DelayNotifyActor {
         _ctx: &mut Self::Context,
     ) {
         self.conn_manage = factory_data.get_actor();
+        self.sse_manage = factory_data.get_actor();
         self.naming_addr = factory_data.get_actor();
         log::info!(" DelayNotifyActor inject complete");
     }
@@ -162,6 +183,7 @@ impl Handler<DelayNotifyCmd> for DelayNotifyActor {
                     client_id_set,
                     service_info: None,
                     conn_manage: self.conn_manage.to_owned(),
+                    sse_manage: self.sse_manage.to_owned(),
                 };
                 self.inner_delay_notify
                     .add_event(self.delay, event.key.clone(), event)?;
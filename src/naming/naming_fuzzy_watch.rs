@@ -0,0 +1,88 @@
+use crate::common::fuzzy_watch_utils::{FuzzyWatchUtils, FUZZY_WATCH_PATTERN_SPLITTER};
+use crate::naming::model::ServiceKey;
+use crate::naming::NamingUtils;
+use std::sync::Arc;
+
+pub const ADD_SERVICE: &str = "ADD_SERVICE";
+pub const DELETE_SERVICE: &str = "DELETE_SERVICE";
+
+///
+/// 服务模糊监听表达式
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FuzzyServicePattern {
+    pub pattern: Arc<String>,
+    pub namespace_id: Arc<String>,
+    pub group: Arc<String>,
+    pub service: Arc<String>,
+}
+
+impl FuzzyServicePattern {
+    pub fn parse(pattern: &str) -> anyhow::Result<Self> {
+        let (namespace, group, service) = FuzzyWatchUtils::split_pattern(pattern)?;
+        Ok(Self {
+            pattern: Arc::new(pattern.to_owned()),
+            namespace_id: Arc::new(NamingUtils::default_namespace(namespace.to_owned())),
+            group: Arc::new(group.to_owned()),
+            service: Arc::new(service.to_owned()),
+        })
+    }
+
+    pub fn matches(&self, key: &ServiceKey) -> bool {
+        self.namespace_id.as_str() == key.namespace_id.as_str()
+            && FuzzyWatchUtils::match_item(
+                &self.group,
+                &self.service,
+                &key.group_name,
+                &key.service_name,
+            )
+    }
+
+    ///
+    /// 与nacos NamingUtils.getServiceKey格式一致: namespace>>group>>serviceName
+    pub fn build_service_key(key: &ServiceKey) -> String {
+        format!(
+            "{}{}{}{}{}",
+            key.namespace_id,
+            FUZZY_WATCH_PATTERN_SPLITTER,
+            key.group_name,
+            FUZZY_WATCH_PATTERN_SPLITTER,
+            key.service_name
+        )
+    }
+}
+
+///
+/// 服务模糊监听推送内容
+#[derive(Debug, Clone)]
+pub enum NamingFuzzyWatchNotify {
+    /// 监听建立时的全量/差异同步,contexts为(serviceKey,changedType)
+    Sync {
+        pattern: Arc<String>,
+        sync_type: &'static str,
+        contexts: Vec<(String, &'static str)>,
+    },
+    /// 匹配表达式的服务新增或删除
+    Change {
+        service_key: String,
+        changed_type: &'static str,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_match() {
+        let pattern = FuzzyServicePattern::parse(">>DEFAULT_GROUP>>order-*").unwrap();
+        assert_eq!(pattern.namespace_id.as_str(), "public");
+        assert!(pattern.matches(&ServiceKey::new("public", "DEFAULT_GROUP", "order-api")));
+        assert!(!pattern.matches(&ServiceKey::new("dev", "DEFAULT_GROUP", "order-api")));
+        assert!(!pattern.matches(&ServiceKey::new("public", "G1", "order-api")));
+        assert_eq!(
+            FuzzyServicePattern::build_service_key(&ServiceKey::new("public", "G1", "s1")),
+            "public>>G1>>s1"
+        );
+        assert!(FuzzyServicePattern::parse("public>>*").is_err());
+    }
+}
@@ -0,0 +1,394 @@
+use crate::naming::api_model::InstanceVO;
+use crate::naming::core::{NamingActor, NamingCmd};
+use crate::naming::filter::InstanceFilterUtils;
+use crate::naming::model::{Instance, InstanceShortKey, ServiceInfo, ServiceKey};
+use crate::naming::naming_subscriber::NamingListenerItem;
+use crate::now_millis_i64;
+use actix::prelude::*;
+use bean_factory::{bean, Inject};
+use bytes::Bytes;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc::error::TrySendError;
+
+/// SSE订阅者的client_id前缀,用于和grpc长链接区分推送通道
+pub const NAMING_SSE_CLIENT_PREFIX: &str = "sse_";
+/// 单个连接未发送消息的最大缓存数,超过时断开连接由客户端重连后重新获取全量
+pub const NAMING_SSE_CHANNEL_SIZE: usize = 64;
+
+pub type NamingSseSender = tokio::sync::mpsc::Sender<anyhow::Result<Bytes>>;
+
+type InstanceSnapshot = HashMap<InstanceShortKey, Arc<Instance>>;
+
+///
+/// 推送给SSE订阅者的服务实例变更事件;
+/// 首次推送full事件携带全量实例,后续只推送delta事件
+#[derive(Debug, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct NamingSseEvent {
+    pub namespace_id: Arc<String>,
+    pub group_name: Arc<String>,
+    pub service_name: Arc<String>,
+    pub reach_protection_threshold: bool,
+    pub last_ref_time: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hosts: Option<Vec<InstanceVO>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub added: Vec<InstanceVO>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub updated: Vec<InstanceVO>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub removed: Vec<InstanceVO>,
+}
+
+impl NamingSseEvent {
+    fn new(key: &ServiceKey, service_info: &ServiceInfo) -> Self {
+        Self {
+            namespace_id: key.namespace_id.clone(),
+            group_name: key.group_name.clone(),
+            service_name: key.service_name.clone(),
+            reach_protection_threshold: service_info.reach_protection_threshold,
+            last_ref_time: now_millis_i64(),
+            ..Default::default()
+        }
+    }
+
+    pub fn is_empty_delta(&self) -> bool {
+        self.hosts.is_none()
+            && self.added.is_empty()
+            && self.updated.is_empty()
+            && self.removed.is_empty()
+    }
+
+    pub fn to_message(&self) -> String {
+        let event_name = if self.hosts.is_some() {
+            "full"
+        } else {
+            "delta"
+        };
+        format!(
+            "event: {}\ndata: {}\n\n",
+            event_name,
+            serde_json::to_string(self).unwrap_or_default()
+        )
+    }
+}
+
+pub struct NamingSseUtils;
+
+impl NamingSseUtils {
+    pub fn build_snapshot(service_info: &ServiceInfo) -> InstanceSnapshot {
+        let mut snapshot = HashMap::new();
+        if let Some(hosts) = service_info.hosts.as_ref() {
+            for instance in hosts {
+                snapshot.insert(
+                    InstanceShortKey::new(instance.ip.clone(), instance.port),
+                    instance.clone(),
+                );
+            }
+        }
+        snapshot
+    }
+
+    ///
+    /// 对比前后两次推送的实例,生成新增、变更、删除列表
+    pub fn diff(old: &InstanceSnapshot, new: &InstanceSnapshot, event: &mut NamingSseEvent) {
+        for (key, instance) in new {
+            match old.get(key) {
+                Some(old_instance) => {
+                    if Self::is_changed(old_instance, instance) {
+                        event.updated.push(InstanceVO::from_instance(instance));
+                    }
+                }
+                None => event.added.push(InstanceVO::from_instance(instance)),
+            }
+        }
+        for (key, instance) in old {
+            if !new.contains_key(key) {
+                event.removed.push(InstanceVO::from_instance(instance));
+            }
+        }
+    }
+
+    fn is_changed(a: &Instance, b: &Instance) -> bool {
+        a.healthy != b.healthy
+            || a.enabled != b.enabled
+            || a.weight != b.weight
+            || a.ephemeral != b.ephemeral
+            || a.cluster_name != b.cluster_name
+            || a.metadata != b.metadata
+    }
+
+    pub fn create_heartbeat_message() -> String {
+        format!(": ping - {}\n\n", now_millis_i64())
+    }
+}
+
+struct NamingSseConn {
+    sender: NamingSseSender,
+    /// 消费者标签,用于依赖消费者标签的服务选择器
+    labels: HashMap<String, String>,
+    /// 已推送的实例快照,None表示还未推送全量
+    services: HashMap<ServiceKey, Option<InstanceSnapshot>>,
+}
+
+///
+/// 基于SSE的服务实例变更推送管理,供不支持grpc的客户端使用
+#[bean(inject)]
+#[derive(Default)]
+pub struct NamingSseManage {
+    conn_cache: HashMap<Arc<String>, NamingSseConn>,
+    naming_addr: Option<Addr<NamingActor>>,
+    heartbeat_second: u64,
+}
+
+impl NamingSseManage {
+    pub fn new() -> Self {
+        Self {
+            heartbeat_second: 15,
+            ..Default::default()
+        }
+    }
+
+    pub fn is_sse_client(client_id: &str) -> bool {
+        client_id.starts_with(NAMING_SSE_CLIENT_PREFIX)
+    }
+
+    fn notify(&mut self, key: &ServiceKey, client_id_set: HashSet<Arc<String>>, info: ServiceInfo) {
+        let mut close_clients = vec![];
+        for client_id in client_id_set {
+            let conn = if let Some(conn) = self.conn_cache.get_mut(&client_id) {
+                conn
+            } else {
+                continue;
+            };
+            let last_snapshot = if let Some(v) = conn.services.get_mut(key) {
+                v
+            } else {
+                continue;
+            };
+            let service_info = if info.selector.is_some() {
+                InstanceFilterUtils::consumer_service_filter(info.clone(), &conn.labels)
+            } else {
+                info.clone()
+            };
+            let snapshot = NamingSseUtils::build_snapshot(&service_info);
+            let mut event = NamingSseEvent::new(key, &service_info);
+            if let Some(old) = last_snapshot.as_ref() {
+                NamingSseUtils::diff(old, &snapshot, &mut event);
+                if event.is_empty_delta() {
+                    continue;
+                }
+            } else {
+                event.hosts = Some(
+                    snapshot
+                        .values()
+                        .map(|e| InstanceVO::from_instance(e))
+                        .collect(),
+                );
+            }
+            *last_snapshot = Some(snapshot);
+            if let Err(err) = conn.sender.try_send(Ok(event.to_message().into())) {
+                log::warn!("naming sse send error,client_id:{},{}", &client_id, err);
+                close_clients.push(client_id);
+            }
+        }
+        for client_id in close_clients {
+            self.remove_conn(client_id);
+        }
+    }
+
+    fn remove_conn(&mut self, client_id: Arc<String>) {
+        if let Some(conn) = self.conn_cache.remove(&client_id) {
+            log::info!("naming sse remove conn,client_id:{}", &client_id);
+            if let Some(naming_addr) = self.naming_addr.as_ref() {
+                let items = conn
+                    .services
+                    .into_keys()
+                    .map(|service_key| NamingListenerItem {
+                        service_key,
+                        clusters: None,
+                    })
+                    .collect();
+                naming_addr.do_send(NamingCmd::RemoveSubscribe(items, client_id));
+            }
+        }
+    }
+
+    fn heartbeat(&mut self, ctx: &mut Context<Self>) {
+        ctx.run_later(Duration::from_secs(self.heartbeat_second), |act, ctx| {
+            let message = NamingSseUtils::create_heartbeat_message();
+            let mut close_clients = vec![];
+            for (client_id, conn) in &act.conn_cache {
+                match conn.sender.try_send(Ok(message.clone().into())) {
+                    Ok(_) | Err(TrySendError::Full(_)) => {}
+                    Err(TrySendError::Closed(_)) => close_clients.push(client_id.clone()),
+                }
+            }
+            for client_id in close_clients {
+                act.remove_conn(client_id);
+            }
+            act.heartbeat(ctx);
+        });
+    }
+}
+
+impl Actor for NamingSseManage {
+    type Context = Context<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        log::info!("NamingSseManage started");
+        self.heartbeat(ctx);
+    }
+}
+
+impl Inject for NamingSseManage {
+    type Context = Context<Self>;
+
+    fn inject(
+        &mut self,
+        factory_data: bean_factory::FactoryData,
+        _factory: bean_factory::BeanFactory,
+        _ctx: &mut Self::Context,
+    ) {
+        self.naming_addr = factory_data.get_actor();
+        log::info!("NamingSseManage inject complete");
+    }
+}
+
+#[derive(Message)]
+#[rtype(result = "anyhow::Result<NamingSseResult>")]
+pub enum NamingSseCmd {
+    AddConn {
+        client_id: Arc<String>,
+        sender: NamingSseSender,
+        labels: HashMap<String, String>,
+        service_keys: Vec<ServiceKey>,
+    },
+    Notify(ServiceKey, HashSet<Arc<String>>, ServiceInfo),
+    QueryConnCount,
+}
+
+pub enum NamingSseResult {
+    ConnCount(usize),
+    None,
+}
+
+impl Handler<NamingSseCmd> for NamingSseManage {
+    type Result = anyhow::Result<NamingSseResult>;
+
+    fn handle(&mut self, msg: NamingSseCmd, _ctx: &mut Context<Self>) -> Self::Result {
+        match msg {
+            NamingSseCmd::AddConn {
+                client_id,
+                sender,
+                labels,
+                service_keys,
+            } => {
+                log::info!("naming sse add conn,client_id:{}", &client_id);
+                let services = service_keys.into_iter().map(|e| (e, None)).collect();
+                self.conn_cache.insert(
+                    client_id,
+                    NamingSseConn {
+                        sender,
+                        labels,
+                        services,
+                    },
+                );
+            }
+            NamingSseCmd::Notify(key, client_id_set, service_info) => {
+                self.notify(&key, client_id_set, service_info);
+            }
+            NamingSseCmd::QueryConnCount => {
+                return Ok(NamingSseResult::ConnCount(self.conn_cache.len()));
+            }
+        }
+        Ok(NamingSseResult::None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_instance(ip: &str, healthy: bool) -> Arc<Instance> {
+        Arc::new(Instance {
+            ip: Arc::new(ip.to_owned()),
+            port: 80,
+            healthy,
+            ..Default::default()
+        })
+    }
+
+    fn build_service_info(hosts: Vec<Arc<Instance>>) -> ServiceInfo {
+        ServiceInfo {
+            hosts: Some(hosts),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn diff_instances() {
+        let old = NamingSseUtils::build_snapshot(&build_service_info(vec![
+            build_instance("10.0.0.1", true),
+            build_instance("10.0.0.2", true),
+        ]));
+        let new = NamingSseUtils::build_snapshot(&build_service_info(vec![
+            build_instance("10.0.0.2", false),
+            build_instance("10.0.0.3", true),
+        ]));
+        let mut event = NamingSseEvent::default();
+        NamingSseUtils::diff(&old, &new, &mut event);
+        assert_eq!(event.added[0].ip.as_str(), "10.0.0.3");
+        assert_eq!(event.updated[0].ip.as_str(), "10.0.0.2");
+        assert_eq!(event.removed[0].ip.as_str(), "10.0.0.1");
+        assert!(event.to_message().starts_with("event: delta\n"));
+
+        let mut event = NamingSseEvent::default();
+        NamingSseUtils::diff(&new, &new, &mut event);
+        assert!(event.is_empty_delta());
+    }
+
+    #[actix_rt::test]
+    async fn notify_full_then_delta() {
+        let addr = NamingSseManage::new().start();
+        let (tx, mut rx) = tokio::sync::mpsc::channel(NAMING_SSE_CHANNEL_SIZE);
+        let client_id = Arc::new(format!("{}1", NAMING_SSE_CLIENT_PREFIX));
+        let key = ServiceKey::new("public", "DEFAULT_GROUP", "foo");
+        addr.send(NamingSseCmd::AddConn {
+            client_id: client_id.clone(),
+            sender: tx,
+            labels: HashMap::new(),
+            service_keys: vec![key.clone()],
+        })
+        .await
+        .unwrap()
+        .unwrap();
+        let mut client_id_set = HashSet::new();
+        client_id_set.insert(client_id.clone());
+        let info = build_service_info(vec![build_instance("10.0.0.1", true)]);
+        for _ in 0..2 {
+            addr.send(NamingSseCmd::Notify(
+                key.clone(),
+                client_id_set.clone(),
+                info.clone(),
+            ))
+            .await
+            .unwrap()
+            .unwrap();
+        }
+        let info = build_service_info(vec![]);
+        addr.send(NamingSseCmd::Notify(key, client_id_set, info))
+            .await
+            .unwrap()
+            .unwrap();
+        let full = String::from_utf8(rx.recv().await.unwrap().unwrap().to_vec()).unwrap();
+        assert!(full.starts_with("event: full\n"));
+        // 实例未变化时不推送
+        let delta = String::from_utf8(rx.recv().await.unwrap().unwrap().to_vec()).unwrap();
+        assert!(delta.starts_with("event: delta\n"));
+        assert!(delta.contains("\"removed\""));
+    }
+}
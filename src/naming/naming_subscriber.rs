@@ -3,8 +3,11 @@
 use super::{
     model::ServiceKey,
     naming_delay_nofity::{DelayNotifyActor, DelayNotifyCmd},
+    naming_fuzzy_watch::{FuzzyServicePattern, NamingFuzzyWatchNotify},
 };
 use crate::common::constant::EMPTY_ARC_STRING;
+use crate::common::model::privilege::{ResourceAction, ResourcePrivilege};
+use crate::grpc::bistream_manage::{BiStreamManage, BiStreamManageCmd};
 use crate::naming::service::SubscriberInfoDto;
 use crate::naming::service_index::ServiceQueryParam;
 use actix::prelude::*;
@@ -40,11 +43,21 @@ pub struct NamingListenerItem {
     pub clusters: Option<HashSet<String>>,
 }
 
+/// (表达式, 监听的客户端 -> 客户端用户的细粒度数据权限)
+type FuzzyWatchClients = (
+    Arc<FuzzyServicePattern>,
+    HashMap<Arc<String>, ResourcePrivilege>,
+);
+
 #[derive(Default)]
 pub struct Subscriber {
     listener: BTreeMap<ServiceKey, BTreeMap<Arc<String>, Option<HashSet<String>>>>,
     client_keys: HashMap<Arc<String>, HashSet<ServiceKey>>,
+    /// 模糊监听: pattern -> (表达式, 监听的客户端)
+    fuzzy_listener: HashMap<Arc<String>, FuzzyWatchClients>,
+    client_fuzzy_patterns: HashMap<Arc<String>, HashSet<Arc<String>>>,
     notify_addr: Option<Addr<DelayNotifyActor>>,
+    conn_manage: Option<Addr<BiStreamManage>>,
 }
 
 impl Subscriber {
@@ -52,7 +65,10 @@ impl Subscriber {
         Self {
             listener: Default::default(),
             client_keys: Default::default(),
+            fuzzy_listener: Default::default(),
+            client_fuzzy_patterns: Default::default(),
             notify_addr: Default::default(),
+            conn_manage: Default::default(),
         }
     }
 
@@ -60,6 +76,10 @@ impl Subscriber {
         self.notify_addr = Some(notify_addr);
     }
 
+    pub fn set_conn_manage(&mut self, conn_manage: Addr<BiStreamManage>) {
+        self.conn_manage = Some(conn_manage);
+    }
+
     pub fn add_subscribe(&mut self, client_id: Arc<String>, items: Vec<NamingListenerItem>) {
         match self.client_keys.get_mut(&client_id) {
             Some(set) => {
@@ -118,6 +138,11 @@ impl Subscriber {
     }
 
     pub fn remove_client_subscribe(&mut self, client_id: Arc<String>) {
+        if let Some(patterns) = self.client_fuzzy_patterns.remove(&client_id) {
+            for pattern in patterns {
+                self.remove_fuzzy_listener(&pattern, &client_id);
+            }
+        }
         if let Some(set) = self.client_keys.remove(&client_id) {
             let mut remove_keys = vec![];
             for key in set {
@@ -164,6 +189,88 @@ impl Subscriber {
         }
     }
 
+    pub fn add_fuzzy_watch(
+        &mut self,
+        client_id: Arc<String>,
+        pattern: Arc<FuzzyServicePattern>,
+        resource_privilege: ResourcePrivilege,
+    ) {
+        self.fuzzy_listener
+            .entry(pattern.pattern.clone())
+            .or_insert_with(|| (pattern.clone(), HashMap::new()))
+            .1
+            .insert(client_id.clone(), resource_privilege);
+        self.client_fuzzy_patterns
+            .entry(client_id)
+            .or_default()
+            .insert(pattern.pattern.clone());
+    }
+
+    pub fn remove_fuzzy_watch(&mut self, client_id: Arc<String>, pattern: &Arc<String>) {
+        let mut remove_empty_client = false;
+        if let Some(set) = self.client_fuzzy_patterns.get_mut(&client_id) {
+            set.remove(pattern);
+            remove_empty_client = set.is_empty();
+        }
+        if remove_empty_client {
+            self.client_fuzzy_patterns.remove(&client_id);
+        }
+        self.remove_fuzzy_listener(pattern, &client_id);
+    }
+
+    fn remove_fuzzy_listener(&mut self, pattern: &Arc<String>, client_id: &Arc<String>) {
+        let mut remove_empty_pattern = false;
+        if let Some((_, set)) = self.fuzzy_listener.get_mut(pattern) {
+            set.remove(client_id);
+            remove_empty_pattern = set.is_empty();
+        }
+        if remove_empty_pattern {
+            self.fuzzy_listener.remove(pattern);
+        }
+    }
+
+    pub fn notify_fuzzy_watch_sync(&self, client_id: Arc<String>, notify: NamingFuzzyWatchNotify) {
+        if let Some(conn_manage) = &self.conn_manage {
+            let mut set = HashSet::new();
+            set.insert(client_id);
+            conn_manage.do_send(BiStreamManageCmd::NotifyNamingFuzzyWatch(notify, set));
+        }
+    }
+
+    ///
+    /// 服务新增或删除时通知匹配且有读权限的模糊监听客户端
+    pub fn notify_fuzzy_watch(&self, key: &ServiceKey, changed_type: &'static str) {
+        if let Some(conn_manage) = &self.conn_manage {
+            let mut client_id_set = HashSet::new();
+            for (pattern, clients) in self.fuzzy_listener.values() {
+                if !pattern.matches(key) {
+                    continue;
+                }
+                for (client_id, privilege) in clients {
+                    if privilege.check_permission(
+                        &key.namespace_id,
+                        &key.group_name,
+                        &key.service_name,
+                        ResourceAction::Read,
+                    ) {
+                        client_id_set.insert(client_id.clone());
+                    }
+                }
+            }
+            if client_id_set.is_empty() {
+                return;
+            }
+            let notify = NamingFuzzyWatchNotify::Change {
+                service_key: FuzzyServicePattern::build_service_key(key),
+                changed_type,
+            };
+            conn_manage.do_send(BiStreamManageCmd::NotifyNamingFuzzyWatch(
+                notify,
+                client_id_set,
+            ));
+        }
+    }
+
     pub fn get_listener_key_size(&self) -> usize {
         self.listener.len()
     }
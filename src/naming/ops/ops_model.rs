@@ -8,7 +8,7 @@ use std::{collections::HashMap, sync::Arc};
 use crate::naming::api_model::InstanceVO;
 use crate::naming::model::ServiceKey;
 use crate::naming::{service::ServiceInfoDto, service_index::ServiceQueryParam, NamingUtils};
-use crate::user_namespace_privilege;
+use crate::{user_namespace_privilege, user_resource_privilege};
 
 #[derive(Debug, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
@@ -29,6 +29,7 @@ impl OpsServiceQueryListRequest {
             limit,
             offset,
             namespace_privilege,
+            resource_privilege: user_resource_privilege!(req),
             ..Default::default()
         };
         if let Some(namespace_id) = self.namespace_id {
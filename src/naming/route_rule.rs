@@ -0,0 +1,378 @@
+use crate::naming::model::{Instance, ServiceKey};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// header取值为该值时表示只要求header存在
+pub const ANY_HEADER_VALUE: &str = "*";
+
+///
+/// 路由目标,按实例metadata标签匹配一组实例
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RouteDestination {
+    #[serde(default)]
+    pub labels: HashMap<String, String>,
+    /// 流量权重,同一规则内多个目标按权重比例分配
+    #[serde(default = "default_route_weight")]
+    pub weight: u32,
+}
+
+fn default_route_weight() -> u32 {
+    100
+}
+
+impl RouteDestination {
+    fn match_instance(&self, instance: &Instance) -> bool {
+        self.labels
+            .iter()
+            .all(|(k, v)| instance.metadata.get(k) == Some(v))
+    }
+}
+
+///
+/// 单条路由规则;请求header全部满足时命中,header条件为空时匹配所有请求
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RouteRuleItem {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    pub destinations: Vec<RouteDestination>,
+}
+
+impl RouteRuleItem {
+    fn match_headers(&self, headers: &HashMap<String, String>) -> bool {
+        self.headers.iter().all(|(k, v)| {
+            headers
+                .get(&k.to_lowercase())
+                .map(|e| v == ANY_HEADER_VALUE || e == v)
+                .unwrap_or(false)
+        })
+    }
+
+    fn select_destination_index(&self) -> Option<usize> {
+        let total: u32 = self.destinations.iter().map(|e| e.weight).sum();
+        if total == 0 {
+            return None;
+        }
+        let mut point = rand::thread_rng().gen_range(0..total);
+        for (i, item) in self.destinations.iter().enumerate() {
+            if point < item.weight {
+                return Some(i);
+            }
+            point -= item.weight;
+        }
+        None
+    }
+}
+
+///
+/// 服务级流量路由规则,按顺序匹配,第一条命中的规则生效;
+/// 命中目标没有可用实例时依次回退到规则内其它目标,仍没有时不做路由过滤
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServiceRouteRule {
+    #[serde(default)]
+    pub rules: Vec<RouteRuleItem>,
+}
+
+impl ServiceRouteRule {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        for rule in &self.rules {
+            if rule.destinations.is_empty() {
+                return Err(anyhow::anyhow!(
+                    "route rule {} destinations is empty",
+                    &rule.name
+                ));
+            }
+            if rule.destinations.iter().all(|e| e.weight == 0) {
+                return Err(anyhow::anyhow!(
+                    "route rule {} destinations weight is zero",
+                    &rule.name
+                ));
+            }
+            if rule.headers.keys().any(|e| e.is_empty()) {
+                return Err(anyhow::anyhow!(
+                    "route rule {} header name is empty",
+                    &rule.name
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    ///
+    /// 按请求header过滤候选实例;headers的key需为小写
+    pub fn route(
+        &self,
+        candidates: Vec<Arc<Instance>>,
+        headers: &HashMap<String, String>,
+    ) -> Vec<Arc<Instance>> {
+        let rule = if let Some(rule) = self.rules.iter().find(|e| e.match_headers(headers)) {
+            rule
+        } else {
+            return candidates;
+        };
+        let first = rule.select_destination_index().unwrap_or_default();
+        let len = rule.destinations.len();
+        for i in 0..len {
+            let destination = &rule.destinations[(first + i) % len];
+            if destination.weight == 0 {
+                continue;
+            }
+            let list: Vec<Arc<Instance>> = candidates
+                .iter()
+                .filter(|e| destination.match_instance(e))
+                .cloned()
+                .collect();
+            if !list.is_empty() {
+                return list;
+            }
+        }
+        candidates
+    }
+
+    pub fn build_headers<'a>(
+        headers: impl Iterator<Item = (&'a str, &'a [u8])>,
+    ) -> HashMap<String, String> {
+        headers
+            .map(|(k, v)| {
+                (
+                    k.to_lowercase(),
+                    String::from_utf8_lossy(v).as_ref().to_owned(),
+                )
+            })
+            .collect()
+    }
+}
+
+///
+/// 路由规则raft存储参数,rule为空表示删除
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServiceRouteRuleParam {
+    pub namespace_id: Arc<String>,
+    pub group_name: Arc<String>,
+    pub service_name: Arc<String>,
+    pub rule: Option<ServiceRouteRule>,
+}
+
+impl ServiceRouteRuleParam {
+    pub fn new(key: &ServiceKey, rule: Option<ServiceRouteRule>) -> Self {
+        Self {
+            namespace_id: key.namespace_id.clone(),
+            group_name: key.group_name.clone(),
+            service_name: key.service_name.clone(),
+            rule,
+        }
+    }
+
+    pub fn to_service_key(&self) -> ServiceKey {
+        ServiceKey::new_by_arc(
+            self.namespace_id.clone(),
+            self.group_name.clone(),
+            self.service_name.clone(),
+        )
+    }
+}
+
+///
+/// 路由规则快照存储对象
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ServiceRouteRuleDO {
+    #[prost(string, tag = "1")]
+    pub namespace_id: String,
+    #[prost(string, tag = "2")]
+    pub group_name: String,
+    #[prost(string, tag = "3")]
+    pub service_name: String,
+    #[prost(message, repeated, tag = "4")]
+    pub rules: Vec<RouteRuleItemDO>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct RouteRuleItemDO {
+    #[prost(string, tag = "1")]
+    pub name: String,
+    #[prost(map = "string, string", tag = "2")]
+    pub headers: HashMap<String, String>,
+    #[prost(message, repeated, tag = "3")]
+    pub destinations: Vec<RouteDestinationDO>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct RouteDestinationDO {
+    #[prost(map = "string, string", tag = "1")]
+    pub labels: HashMap<String, String>,
+    #[prost(uint32, tag = "2")]
+    pub weight: u32,
+}
+
+impl ServiceRouteRuleDO {
+    pub fn to_bytes(&self) -> anyhow::Result<Vec<u8>> {
+        use prost::Message;
+        let mut v = Vec::new();
+        self.encode(&mut v)?;
+        Ok(v)
+    }
+
+    pub fn from_bytes(data: &[u8]) -> anyhow::Result<Self> {
+        use prost::Message;
+        let s = Self::decode(data)?;
+        Ok(s)
+    }
+}
+
+impl From<&ServiceRouteRuleParam> for ServiceRouteRuleDO {
+    fn from(value: &ServiceRouteRuleParam) -> Self {
+        let rules = value
+            .rule
+            .as_ref()
+            .map(|rule| {
+                rule.rules
+                    .iter()
+                    .map(|item| RouteRuleItemDO {
+                        name: item.name.clone(),
+                        headers: item.headers.clone(),
+                        destinations: item
+                            .destinations
+                            .iter()
+                            .map(|e| RouteDestinationDO {
+                                labels: e.labels.clone(),
+                                weight: e.weight,
+                            })
+                            .collect(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self {
+            namespace_id: value.namespace_id.as_ref().to_owned(),
+            group_name: value.group_name.as_ref().to_owned(),
+            service_name: value.service_name.as_ref().to_owned(),
+            rules,
+        }
+    }
+}
+
+impl From<ServiceRouteRuleDO> for ServiceRouteRuleParam {
+    fn from(value: ServiceRouteRuleDO) -> Self {
+        let rules: Vec<RouteRuleItem> = value
+            .rules
+            .into_iter()
+            .map(|item| RouteRuleItem {
+                name: item.name,
+                headers: item.headers,
+                destinations: item
+                    .destinations
+                    .into_iter()
+                    .map(|e| RouteDestination {
+                        labels: e.labels,
+                        weight: e.weight,
+                    })
+                    .collect(),
+            })
+            .collect();
+        Self {
+            namespace_id: Arc::new(value.namespace_id),
+            group_name: Arc::new(value.group_name),
+            service_name: Arc::new(value.service_name),
+            rule: if rules.is_empty() {
+                None
+            } else {
+                Some(ServiceRouteRule { rules })
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_instance(ip: &str, version: &str, canary: bool) -> Arc<Instance> {
+        let mut metadata = HashMap::new();
+        metadata.insert("version".to_owned(), version.to_owned());
+        if canary {
+            metadata.insert("canary".to_owned(), "true".to_owned());
+        }
+        Arc::new(Instance {
+            ip: Arc::new(ip.to_owned()),
+            port: 80,
+            metadata: Arc::new(metadata),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn route_by_header_and_weight() {
+        let rule: ServiceRouteRule = serde_json::from_str(
+            r#"{"rules":[
+                {"name":"canary","headers":{"X-Canary":"*"},"destinations":[{"labels":{"canary":"true"}}]},
+                {"name":"version","destinations":[
+                    {"labels":{"version":"v2"},"weight":70},
+                    {"labels":{"version":"v1"},"weight":30}]}
+            ]}"#,
+        )
+        .unwrap();
+        assert!(rule.validate().is_ok());
+        let candidates = vec![
+            build_instance("10.0.0.1", "v1", false),
+            build_instance("10.0.0.2", "v2", false),
+            build_instance("10.0.0.3", "v2", true),
+        ];
+        let mut headers = HashMap::new();
+        headers.insert("x-canary".to_owned(), "1".to_owned());
+        let list = rule.route(candidates.clone(), &headers);
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0].ip.as_str(), "10.0.0.3");
+
+        let mut v2_count = 0;
+        for _ in 0..200 {
+            let list = rule.route(candidates.clone(), &HashMap::new());
+            if list[0].metadata.get("version").unwrap() == "v2" {
+                assert_eq!(list.len(), 2);
+                v2_count += 1;
+            } else {
+                assert_eq!(list.len(), 1);
+            }
+        }
+        assert!(v2_count > 100 && v2_count < 200);
+
+        // 命中的目标没有实例时回退
+        let list = rule.route(vec![build_instance("10.0.0.1", "v1", false)], &headers);
+        assert_eq!(list.len(), 1);
+
+        let invalid: ServiceRouteRule =
+            serde_json::from_str(r#"{"rules":[{"destinations":[]}]}"#).unwrap();
+        assert!(invalid.validate().is_err());
+    }
+
+    #[test]
+    fn route_rule_do_round_trip() {
+        let rule: ServiceRouteRule = serde_json::from_str(
+            r#"{"rules":[{"name":"v2","headers":{"x-env":"gray"},"destinations":[{"labels":{"version":"v2"},"weight":80}]}]}"#,
+        )
+        .unwrap();
+        let key = ServiceKey::new("public", "DEFAULT_GROUP", "order");
+        let param = ServiceRouteRuleParam::new(&key, Some(rule.clone()));
+        let bytes = ServiceRouteRuleDO::from(&param).to_bytes().unwrap();
+        let param: ServiceRouteRuleParam = ServiceRouteRuleDO::from_bytes(&bytes).unwrap().into();
+        assert_eq!(param.to_service_key(), key);
+        assert_eq!(param.rule, Some(rule));
+        let empty = ServiceRouteRuleParam::new(&key, None);
+        let bytes = ServiceRouteRuleDO::from(&empty).to_bytes().unwrap();
+        assert!(
+            ServiceRouteRuleParam::from(ServiceRouteRuleDO::from_bytes(&bytes).unwrap())
+                .rule
+                .is_none()
+        );
+    }
+}
@@ -0,0 +1,294 @@
+use super::model::Instance;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+const CONSUMER_LABEL_PREFIX: &str = "CONSUMER.label.";
+const PROVIDER_LABEL_PREFIX: &str = "PROVIDER.label.";
+
+///
+/// 服务实例选择器,与nacos selector json格式兼容: {"type":"label","expression":"..."}
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ServiceSelector {
+    #[default]
+    None,
+    /// 实例元数据标签表达式,多个条件用&连接,如: env = prod & zone != a;
+    /// 兼容nacos的 CONSUMER.label.zone = PROVIDER.label.zone 写法
+    Label { expression: String },
+    /// 只选择指定标签与消费者标签相同的实例
+    SameLabel { labels: Vec<String> },
+}
+
+impl ServiceSelector {
+    ///
+    /// 解析nacos接口中的selector json字符串,空字符串表示不设置选择器
+    pub fn parse(selector_str: &str) -> anyhow::Result<Self> {
+        if selector_str.trim().is_empty() {
+            return Ok(Self::None);
+        }
+        let selector: Self = serde_json::from_str(selector_str)?;
+        selector.validate()?;
+        Ok(selector)
+    }
+
+    ///
+    /// 校验选择器表达式是否合法
+    pub fn validate(&self) -> anyhow::Result<()> {
+        LabelSelector::build(self.clone())?;
+        Ok(())
+    }
+
+    pub fn to_map(&self) -> HashMap<String, String> {
+        let mut map = HashMap::new();
+        match self {
+            ServiceSelector::None => {
+                map.insert("type".to_owned(), "none".to_owned());
+                map.insert("contextType".to_owned(), "NONE".to_owned());
+            }
+            ServiceSelector::Label { expression } => {
+                map.insert("type".to_owned(), "label".to_owned());
+                map.insert("expression".to_owned(), expression.to_owned());
+                map.insert("contextType".to_owned(), "CMDB".to_owned());
+            }
+            ServiceSelector::SameLabel { labels } => {
+                map.insert("type".to_owned(), "sameLabel".to_owned());
+                map.insert("labels".to_owned(), labels.join(","));
+                map.insert("contextType".to_owned(), "CMDB".to_owned());
+            }
+        }
+        map
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum LabelTerm {
+    Eq(String, String),
+    NotEq(String, String),
+    SameAsConsumer(String),
+}
+
+impl LabelTerm {
+    fn parse(term: &str) -> anyhow::Result<Self> {
+        let (key, value, not_eq) = if let Some((k, v)) = term.split_once("!=") {
+            (k.trim(), v.trim(), true)
+        } else if let Some((k, v)) = term.split_once('=') {
+            (k.trim(), v.trim(), false)
+        } else {
+            return Err(anyhow::anyhow!(
+                "invalid selector expression term: {}",
+                term
+            ));
+        };
+        if key.is_empty() {
+            return Err(anyhow::anyhow!(
+                "invalid selector expression term: {}",
+                term
+            ));
+        }
+        if let Some(consumer_key) = key.strip_prefix(CONSUMER_LABEL_PREFIX) {
+            return match value.strip_prefix(PROVIDER_LABEL_PREFIX) {
+                Some(provider_key) if !not_eq && provider_key == consumer_key => {
+                    Ok(Self::SameAsConsumer(consumer_key.to_owned()))
+                }
+                _ => Err(anyhow::anyhow!(
+                    "consumer label must equal the same provider label: {}",
+                    term
+                )),
+            };
+        }
+        if not_eq {
+            Ok(Self::NotEq(key.to_owned(), value.to_owned()))
+        } else {
+            Ok(Self::Eq(key.to_owned(), value.to_owned()))
+        }
+    }
+
+    fn is_match(
+        &self,
+        metadata: &HashMap<String, String>,
+        consumer_labels: Option<&HashMap<String, String>>,
+    ) -> bool {
+        match self {
+            LabelTerm::Eq(k, v) => metadata.get(k).is_some_and(|e| e == v),
+            LabelTerm::NotEq(k, v) => metadata.get(k).is_none_or(|e| e != v),
+            LabelTerm::SameAsConsumer(k) => match consumer_labels.and_then(|c| c.get(k)) {
+                Some(v) => metadata.get(k).is_some_and(|e| e == v),
+                None => true,
+            },
+        }
+    }
+}
+
+///
+/// 解析后的选择器
+#[derive(Debug, Clone, PartialEq)]
+pub struct LabelSelector {
+    pub source: ServiceSelector,
+    terms: Vec<LabelTerm>,
+}
+
+impl LabelSelector {
+    ///
+    /// ServiceSelector::None 返回 None
+    pub fn build(selector: ServiceSelector) -> anyhow::Result<Option<Self>> {
+        let terms = match &selector {
+            ServiceSelector::None => return Ok(None),
+            ServiceSelector::Label { expression } => {
+                let mut terms = vec![];
+                for item in expression.split('&') {
+                    let item = item.trim();
+                    if !item.is_empty() {
+                        terms.push(LabelTerm::parse(item)?);
+                    }
+                }
+                terms
+            }
+            ServiceSelector::SameLabel { labels } => labels
+                .iter()
+                .filter(|e| !e.is_empty())
+                .map(|e| LabelTerm::SameAsConsumer(e.to_owned()))
+                .collect(),
+        };
+        if terms.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(Self {
+            source: selector,
+            terms,
+        }))
+    }
+
+    ///
+    /// 是否依赖消费者标签
+    pub fn need_consumer_labels(&self) -> bool {
+        self.terms
+            .iter()
+            .any(|e| matches!(e, LabelTerm::SameAsConsumer(_)))
+    }
+
+    pub fn is_match(
+        &self,
+        instance: &Instance,
+        consumer_labels: Option<&HashMap<String, String>>,
+    ) -> bool {
+        self.terms
+            .iter()
+            .all(|e| e.is_match(&instance.metadata, consumer_labels))
+    }
+
+    ///
+    /// 按选择器过滤实例;依赖消费者标签且没有匹配实例时与nacos一致返回全部实例
+    pub fn select(
+        &self,
+        instances: Vec<Arc<Instance>>,
+        consumer_labels: Option<&HashMap<String, String>>,
+    ) -> Vec<Arc<Instance>> {
+        let list: Vec<Arc<Instance>> = instances
+            .iter()
+            .filter(|e| self.is_match(e, consumer_labels))
+            .cloned()
+            .collect();
+        if list.is_empty() && consumer_labels.is_some() && self.need_consumer_labels() {
+            return instances;
+        }
+        list
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_instance(ip: &str, labels: &[(&str, &str)]) -> Arc<Instance> {
+        let mut metadata = HashMap::new();
+        for (k, v) in labels {
+            metadata.insert(k.to_string(), v.to_string());
+        }
+        Arc::new(Instance {
+            ip: Arc::new(ip.to_owned()),
+            metadata: Arc::new(metadata),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn label_expression() {
+        let selector = LabelSelector::build(ServiceSelector::Label {
+            expression: "env = prod & zone != a".to_owned(),
+        })
+        .unwrap()
+        .unwrap();
+        let instances = vec![
+            build_instance("1", &[("env", "prod"), ("zone", "a")]),
+            build_instance("2", &[("env", "prod"), ("zone", "b")]),
+            build_instance("3", &[("env", "prod")]),
+            build_instance("4", &[("env", "dev")]),
+        ];
+        let list = selector.select(instances, None);
+        assert_eq!(list.len(), 2);
+        assert_eq!(list[0].ip.as_str(), "2");
+        assert_eq!(list[1].ip.as_str(), "3");
+        assert!(LabelSelector::build(ServiceSelector::Label {
+            expression: "env".to_owned()
+        })
+        .is_err());
+        assert!(LabelSelector::build(ServiceSelector::None)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn same_label_as_consumer() {
+        let selector = LabelSelector::build(ServiceSelector::Label {
+            expression: "CONSUMER.label.zone = PROVIDER.label.zone".to_owned(),
+        })
+        .unwrap()
+        .unwrap();
+        let same_label = LabelSelector::build(ServiceSelector::SameLabel {
+            labels: vec!["zone".to_owned()],
+        })
+        .unwrap()
+        .unwrap();
+        assert!(selector.need_consumer_labels() && same_label.need_consumer_labels());
+        let instances = vec![
+            build_instance("1", &[("zone", "a")]),
+            build_instance("2", &[("zone", "b")]),
+        ];
+        let mut consumer = HashMap::new();
+        consumer.insert("zone".to_owned(), "b".to_owned());
+        let list = selector.select(instances.clone(), Some(&consumer));
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0].ip.as_str(), "2");
+        // 消费者没有对应标签或没有匹配实例时返回全部
+        assert_eq!(selector.select(instances.clone(), None).len(), 2);
+        consumer.insert("zone".to_owned(), "c".to_owned());
+        assert_eq!(selector.select(instances, Some(&consumer)).len(), 2);
+    }
+
+    #[test]
+    fn selector_json() {
+        let selector: ServiceSelector =
+            serde_json::from_str(r#"{"type":"label","expression":"env=prod"}"#).unwrap();
+        assert_eq!(
+            selector,
+            ServiceSelector::Label {
+                expression: "env=prod".to_owned()
+            }
+        );
+        let selector: ServiceSelector = serde_json::from_str(r#"{"type":"none"}"#).unwrap();
+        assert_eq!(selector, ServiceSelector::None);
+        assert_eq!(ServiceSelector::parse("").unwrap(), ServiceSelector::None);
+        assert!(ServiceSelector::parse(r#"{"type":"label","expression":"env"}"#).is_err());
+        assert!(ServiceSelector::Label {
+            expression: "env = prod & zone".to_owned()
+        }
+        .validate()
+        .is_err());
+        assert!(ServiceSelector::Label {
+            expression: "env = prod".to_owned()
+        }
+        .validate()
+        .is_ok());
+    }
+}
@@ -8,10 +8,16 @@ use super::{
     },
 };
 use crate::common::constant::EMPTY_ARC_STRING;
+use crate::naming::balancer::{InstanceSelectParam, InstanceSelectState};
 use crate::naming::cluster::model::ProcessRange;
+use crate::naming::health_check::{
+    HealthCheckStatus, HealthCheckType, HealthChecker, ServiceHealthCheck,
+};
 use crate::naming::instance_meta_manager::{InstanceMetaManager, InstanceMetaManagerReq};
 use crate::naming::instance_meta_repository::InstanceMetaDto;
 use crate::naming::model::UpdatePerpetualType;
+use crate::naming::route_rule::ServiceRouteRule;
+use crate::naming::selector::{LabelSelector, ServiceSelector};
 use crate::now_millis;
 use actix::Addr;
 use actix_web::rt;
@@ -28,10 +34,49 @@ use std::{
 #[derive(Debug, Clone, Default)]
 pub struct ServiceMetadata {
     pub protect_threshold: f32,
+    pub selector: Option<Arc<LabelSelector>>,
 }
 
 type InstanceMetaData = Arc<HashMap<String, String>>;
 
+///
+/// 临时实例默认租约配置(毫秒),实例metadata未指定时使用
+#[derive(Debug, Clone, Copy)]
+pub struct InstanceLeaseConfig {
+    pub heart_beat_timeout: i64,
+    pub ip_delete_timeout: i64,
+}
+
+impl Default for InstanceLeaseConfig {
+    fn default() -> Self {
+        Self {
+            heart_beat_timeout: 18000,
+            ip_delete_timeout: 33000,
+        }
+    }
+}
+
+impl InstanceLeaseConfig {
+    pub fn new(heart_beat_timeout: i64, ip_delete_timeout: i64) -> Self {
+        Self {
+            heart_beat_timeout,
+            ip_delete_timeout,
+        }
+    }
+
+    fn healthy_deadline(&self, instance: &Instance) -> i64 {
+        instance
+            .last_modified_millis
+            .saturating_add(instance.get_heart_beat_timeout(self.heart_beat_timeout))
+    }
+
+    fn delete_deadline(&self, instance: &Instance) -> i64 {
+        instance
+            .last_modified_millis
+            .saturating_add(instance.get_ip_delete_timeout(self.ip_delete_timeout))
+    }
+}
+
 #[derive(Default)]
 pub struct Service {
     pub service_name: Arc<String>,
@@ -44,6 +89,7 @@ pub struct Service {
     pub namespace_id: Arc<String>,
     pub app_name: String,
     pub check_sum: String,
+    pub(crate) selector: Option<Arc<LabelSelector>>,
     pub(crate) last_empty_times: u64,
     pub(crate) instance_size: i64,
     pub(crate) healthy_instance_size: i64,
@@ -54,7 +100,16 @@ pub struct Service {
     pub(crate) healthy_timeout_set: TimeoutSet<InstanceShortKey>,
     /// 不健康状态过期记录，过期后反实例删除
     pub(crate) unhealthy_timeout_set: TimeoutSet<InstanceShortKey>,
+    /// 过期记录按实例各自的租约到期时间登记
+    pub(crate) lease_config: InstanceLeaseConfig,
     pub(crate) perpetual_host_set: HashSet<InstanceShortKey>,
+    /// 自定义的持久实例主动健康检测配置
+    pub(crate) health_check: Option<Arc<ServiceHealthCheck>>,
+    pub(crate) health_check_status: HashMap<InstanceShortKey, HealthCheckStatus>,
+    /// 单实例选择状态(轮询、最久未选中)
+    pub(crate) select_state: InstanceSelectState,
+    /// 服务级流量路由规则,通过raft持久化
+    pub(crate) route_rule: Option<Arc<ServiceRouteRule>>,
 }
 
 impl Service {
@@ -210,7 +265,7 @@ impl Service {
         // 非来自集群的更新才维护实例心跳检测
         if new_instance.is_enable_timeout() && !from_sync {
             self.healthy_timeout_set.add(
-                new_instance.last_modified_millis as u64,
+                self.lease_config.healthy_deadline(&new_instance) as u64,
                 new_instance.get_short_key(),
             );
         }
@@ -270,27 +325,26 @@ impl Service {
             );
              */
             self.healthy_timeout_set.add(
-                instance.last_modified_millis as u64,
+                self.lease_config.healthy_deadline(instance) as u64,
                 instance.get_short_key(),
             );
         }
     }
 
+    ///
+    /// 按实例各自的租约检查过期,到期前有心跳续约的实例跳过
     pub(crate) fn time_check(
         &mut self,
-        healthy_time: i64,
-        offline_time: i64,
+        current_time: i64,
     ) -> (Vec<InstanceShortKey>, Vec<InstanceShortKey>) {
         let mut remove_list = vec![];
         #[cfg(feature = "debug")]
-        log::info!(
-            "time_check,healthy_time:{},offline_time:{}",
-            healthy_time,
-            offline_time
-        );
-        for key in self.unhealthy_timeout_set.timeout(offline_time as u64) {
+        log::info!("time_check,current_time:{}", current_time);
+        for key in self.unhealthy_timeout_set.timeout(current_time as u64) {
             if let Some(instance) = self.instances.get(&key) {
-                if !instance.is_enable_timeout() || instance.last_modified_millis > offline_time {
+                if !instance.is_enable_timeout()
+                    || self.lease_config.delete_deadline(instance) > current_time
+                {
                     continue;
                 }
             }
@@ -298,9 +352,11 @@ impl Service {
             remove_list.push(key);
         }
         let mut update_list = vec![];
-        for key in self.healthy_timeout_set.timeout(healthy_time as u64) {
+        for key in self.healthy_timeout_set.timeout(current_time as u64) {
             if let Some(instance) = self.instances.get(&key) {
-                if !instance.is_enable_timeout() || instance.last_modified_millis > healthy_time {
+                if !instance.is_enable_timeout()
+                    || self.lease_config.healthy_deadline(instance) > current_time
+                {
                     continue;
                 }
             }
@@ -333,6 +389,7 @@ impl Service {
             }
         }
         if let Some(old) = self.instances.remove(instance_key) {
+            self.select_state.remove(instance_key);
             if !old.ephemeral {
                 // 删除永久实例
                 self.perpetual_host_set.remove(instance_key);
@@ -360,8 +417,10 @@ impl Service {
             }
             let mut i = i.as_ref().clone();
             i.healthy = false;
-            self.unhealthy_timeout_set
-                .add(i.last_modified_millis as u64, instance_id.clone());
+            self.unhealthy_timeout_set.add(
+                self.lease_config.delete_deadline(&i) as u64,
+                instance_id.clone(),
+            );
             self.instances.insert(instance_id.clone(), Arc::new(i));
         }
     }
@@ -400,16 +459,17 @@ impl Service {
     }
 
     pub(crate) fn select_one_instance(
-        &self,
+        &mut self,
         only_healthy: bool,
         only_enable: bool,
+        param: &InstanceSelectParam,
+        headers: &HashMap<String, String>,
     ) -> Option<Arc<Instance>> {
-        //后续可以考虑支持按权重随机选择
-        self.instances
-            .values()
-            .filter(|x| (x.enabled || !only_enable) && (x.healthy || !only_healthy))
-            .cloned()
-            .choose(&mut rand::thread_rng())
+        let mut candidates = self.get_all_instances(only_healthy, only_enable);
+        if let Some(route_rule) = self.route_rule.as_ref() {
+            candidates = route_rule.route(candidates, headers);
+        }
+        self.select_state.select(candidates, param)
     }
 
     /*
@@ -443,9 +503,110 @@ impl Service {
         )
     }
 
+    pub(crate) fn update_selector(&mut self, selector: ServiceSelector) {
+        match LabelSelector::build(selector) {
+            Ok(selector) => self.selector = selector.map(Arc::new),
+            Err(err) => log::warn!(
+                "service {} ignore invalid selector: {}",
+                &self.group_service,
+                err
+            ),
+        }
+    }
+
+    pub(crate) fn update_health_check(&mut self, mut health_check: ServiceHealthCheck) {
+        if let Some(old) = &self.health_check {
+            health_check.inherit_secret(old);
+        }
+        self.health_check = if health_check.is_empty() {
+            None
+        } else {
+            Some(Arc::new(health_check))
+        };
+        self.health_check_status.clear();
+    }
+
+    ///
+    /// 实例所在集群的自定义检测配置,没有配置时使用全局tcp检测
+    pub(crate) fn get_health_checker(&self, key: &InstanceShortKey) -> Option<&HealthChecker> {
+        Self::find_health_checker(&self.health_check, &self.instances, key)
+    }
+
+    fn find_health_checker<'a>(
+        health_check: &'a Option<Arc<ServiceHealthCheck>>,
+        instances: &HashMap<InstanceShortKey, Arc<Instance>>,
+        key: &InstanceShortKey,
+    ) -> Option<&'a HealthChecker> {
+        let cluster_name = instances
+            .get(key)
+            .map(|e| e.cluster_name.as_str())
+            .unwrap_or_default();
+        health_check.as_ref()?.get_checker(cluster_name)
+    }
+
+    ///
+    /// 收集本轮需要检测的持久实例,返回(使用全局tcp检测的实例,按自定义配置检测的实例)
+    pub(crate) fn collect_health_check_hosts(
+        &mut self,
+        now: i32,
+        default_interval: i32,
+        default_due: bool,
+    ) -> (
+        Vec<InstanceShortKey>,
+        Vec<(InstanceShortKey, HealthChecker)>,
+    ) {
+        let mut default_hosts = vec![];
+        let mut custom_hosts = vec![];
+        for key in &self.perpetual_host_set {
+            match Self::find_health_checker(&self.health_check, &self.instances, key) {
+                Some(checker) => {
+                    if checker.check_type == HealthCheckType::None {
+                        continue;
+                    }
+                    let status = self.health_check_status.entry(key.clone()).or_default();
+                    if now - status.last_check_time >= checker.get_interval_second(default_interval)
+                    {
+                        status.last_check_time = now;
+                        custom_hosts.push((key.clone(), checker.clone()));
+                    }
+                }
+                None => {
+                    if default_due {
+                        default_hosts.push(key.clone());
+                    }
+                }
+            }
+        }
+        if self.health_check_status.len() > self.perpetual_host_set.len() {
+            let perpetual_host_set = &self.perpetual_host_set;
+            self.health_check_status
+                .retain(|k, _| perpetual_host_set.contains(k));
+        }
+        (default_hosts, custom_hosts)
+    }
+
+    ///
+    /// 记录主动检测结果,连续成功或失败次数达到阈值后再更新实例健康状态
+    pub(crate) fn update_health_check_result(&mut self, key: &InstanceShortKey, success: bool) {
+        if !self.perpetual_host_set.contains(key) {
+            return;
+        }
+        let thresholds = self
+            .get_health_checker(key)
+            .map(|e| e.get_thresholds())
+            .unwrap_or((1, 1));
+        let status = self.health_check_status.entry(key.clone()).or_default();
+        match status.record(success, thresholds) {
+            Some(true) => self.update_perpetual_instance_healthy_valid(key),
+            Some(false) => self.update_instance_healthy_invalid(key),
+            None => {}
+        }
+    }
+
     pub fn get_metadata(&self) -> ServiceMetadata {
         ServiceMetadata {
             protect_threshold: self.protect_threshold,
+            selector: self.selector.clone(),
         }
     }
 
@@ -459,6 +620,9 @@ impl Service {
             trigger_flag: false,
             metadata: Some(self.metadata.clone()),
             protect_threshold: Some(self.protect_threshold),
+            selector: self.selector.as_ref().map(|e| e.source.clone()),
+            health_check: self.health_check.clone(),
+            route_rule: self.route_rule.clone(),
         }
     }
 
@@ -474,6 +638,11 @@ impl Service {
             group_name: self.group_name.clone(),
             metadata,
             protect_threshold: Some(self.protect_threshold),
+            selector: self.selector.as_ref().map(|e| e.source.clone()),
+            health_check: self
+                .health_check
+                .as_ref()
+                .map(|e| Box::new(e.as_ref().clone())),
             ..Default::default()
         }
     }
@@ -509,6 +678,9 @@ pub struct ServiceInfoDto {
     pub trigger_flag: bool,
     pub metadata: Option<Arc<HashMap<String, String>>>,
     pub protect_threshold: Option<f32>,
+    pub selector: Option<ServiceSelector>,
+    pub health_check: Option<Arc<ServiceHealthCheck>>,
+    pub route_rule: Option<Arc<ServiceRouteRule>>,
 }
 
 #[derive(Debug, Default, Serialize, Deserialize, Clone)]
@@ -1,5 +1,6 @@
 use super::model::ServiceKey;
-use crate::common::model::privilege::NamespacePrivilegeGroup;
+use super::naming_fuzzy_watch::FuzzyServicePattern;
+use crate::common::model::privilege::{NamespacePrivilegeGroup, ResourceAction, ResourcePrivilege};
 use crate::common::string_utils::StringUtils;
 use crate::namespace::model::{NamespaceActorReq, WeakNamespaceFromType, WeakNamespaceParam};
 use crate::namespace::NamespaceActor;
@@ -18,6 +19,9 @@ pub struct ServiceQueryParam {
     pub like_group: Option<String>,
     pub like_service: Option<String>,
     pub namespace_privilege: NamespacePrivilegeGroup,
+    /// 用户细粒度数据权限,只返回有读权限的服务
+    #[serde(default)]
+    pub resource_privilege: ResourcePrivilege,
     pub offset: usize,
     pub limit: usize,
 }
@@ -49,6 +53,11 @@ impl ServiceQueryParam {
             true
         }
     }
+
+    pub fn match_resource(&self, namespace_id: &str, group: &str, service: &str) -> bool {
+        self.resource_privilege
+            .check_permission(namespace_id, group, service, ResourceAction::Read)
+    }
 }
 
 #[derive(Debug, Clone, Default)]
@@ -129,7 +138,7 @@ impl ServiceIndex {
         for (g, set) in &self.group_service {
             if param.match_group(g) {
                 for s in set {
-                    if param.match_service(s) {
+                    if param.match_service(s) && param.match_resource(namespace_id, g, s) {
                         if index >= param.offset && index < end_index {
                             let service_key =
                                 ServiceKey::new_by_arc(namespace_id.clone(), g.clone(), s.clone());
@@ -143,6 +152,29 @@ impl ServiceIndex {
         (index, rlist)
     }
 
+    pub(crate) fn query_fuzzy_watch_keys(
+        &self,
+        namespace_id: &Arc<String>,
+        pattern: &FuzzyServicePattern,
+    ) -> Vec<ServiceKey> {
+        let mut rlist = vec![];
+        for (g, set) in &self.group_service {
+            if !StringUtils::wildcard_match(&pattern.group, g) {
+                continue;
+            }
+            for s in set {
+                if StringUtils::wildcard_match(&pattern.service, s) {
+                    rlist.push(ServiceKey::new_by_arc(
+                        namespace_id.clone(),
+                        g.clone(),
+                        s.clone(),
+                    ));
+                }
+            }
+        }
+        rlist
+    }
+
     pub fn get_service_count(&self) -> usize {
         let mut sum = 0;
         for set in self.group_service.values() {
@@ -253,6 +285,14 @@ impl NamespaceIndex {
         (size, rlist)
     }
 
+    pub fn query_fuzzy_watch_keys(&self, pattern: &FuzzyServicePattern) -> Vec<ServiceKey> {
+        if let Some(index) = self.namespace_group.get(&pattern.namespace_id) {
+            index.query_fuzzy_watch_keys(&pattern.namespace_id, pattern)
+        } else {
+            vec![]
+        }
+    }
+
     fn notify_namespace_change(&self, param: WeakNamespaceParam, is_remove: bool) {
         if let Some(act) = &self.namespace_actor {
             if is_remove {
@@ -343,3 +383,18 @@ fn query_service() {
     assert!(size == 0);
     assert!(list.is_empty());
 }
+
+#[test]
+fn query_fuzzy_watch_keys() {
+    let mut index = NamespaceIndex::new();
+    index.insert_service(ServiceKey::new("public", "G1", "order-api"));
+    index.insert_service(ServiceKey::new("public", "G2", "order-job"));
+    index.insert_service(ServiceKey::new("public", "G1", "user-api"));
+    index.insert_service(ServiceKey::new("dev", "G1", "order-api"));
+    let pattern = FuzzyServicePattern::parse("public>>G*>>order-*").unwrap();
+    let keys = index.query_fuzzy_watch_keys(&pattern);
+    assert_eq!(keys.len(), 2);
+    assert!(keys.iter().all(|k| k.namespace_id.as_str() == "public"));
+    let pattern = FuzzyServicePattern::parse("dev>>*>>*").unwrap();
+    assert_eq!(index.query_fuzzy_watch_keys(&pattern).len(), 1);
+}
@@ -1,7 +1,10 @@
 use crate::naming::core::{NamingActor, NamingCmd};
+use crate::naming::health_check::HealthChecker;
 use crate::naming::model::{InstanceShortKey, ServiceKey};
 use actix::prelude::*;
 use bean_factory::{bean, BeanFactory, FactoryData, Inject};
+use reqwest::Client;
+use std::sync::Arc;
 use std::time::Duration;
 use tokio::net::TcpStream;
 use tokio::time::timeout;
@@ -18,6 +21,7 @@ pub struct NetSniffing {
     pub naming_actor: Option<Addr<NamingActor>>,
     pub timeout_duration: Duration,
     pub retry_interval: Duration,
+    pub http_client: Client,
 }
 
 impl Actor for NetSniffing {
@@ -45,6 +49,7 @@ impl NetSniffing {
             timeout_duration,
             retry_interval,
             naming_actor: None,
+            http_client: Client::new(),
         }
     }
 
@@ -72,6 +77,22 @@ impl NetSniffing {
                 }
                 Ok(NetSniffingResult::None)
             }
+            NetSniffingCmd::CheckServiceHost(host, checker, service_key) => {
+                //自定义检测不在这里重试,由连续失败阈值控制
+                let result = checker.check(&self.http_client, &host).await;
+                #[cfg(feature = "debug")]
+                if let Err(err) = &result {
+                    log::info!("health check {:?} failed: {}", &host, err);
+                }
+                if let Some(naming_actor) = &self.naming_actor {
+                    naming_actor.do_send(NamingCmd::PerpetualHostSniffing {
+                        host,
+                        service_keys: vec![service_key],
+                        success: result.is_ok(),
+                    })
+                }
+                Ok(NetSniffingResult::None)
+            }
         }
     }
 
@@ -88,6 +109,8 @@ impl NetSniffing {
 pub enum NetSniffingCmd {
     ProbeHost(InstanceShortKey),
     ProbeServiceHost(InstanceShortKey, Vec<ServiceKey>),
+    /// 按服务自定义的检测配置检测持久实例
+    CheckServiceHost(InstanceShortKey, Arc<HealthChecker>, ServiceKey),
 }
 
 #[derive(Debug)]
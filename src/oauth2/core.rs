@@ -5,6 +5,11 @@ use crate::user::UserManager;
 use actix::prelude::*;
 use bean_factory::{bean, BeanFactory, FactoryData, Inject};
 use std::sync::Arc;
+use std::time::Duration;
+
+/// 初始化失败后的重试间隔,按倍数递增到上限
+const INIT_RETRY_MIN_SECOND: u64 = 5;
+const INIT_RETRY_MAX_SECOND: u64 = 300;
 
 #[bean(inject)]
 pub struct OAuth2Manager {
@@ -12,6 +17,7 @@ pub struct OAuth2Manager {
     oauth2_msg_actor: Option<Addr<OAuth2MsgActor>>,
     enable_oauth2: bool,
     user_manager_addr: Option<Addr<UserManager>>,
+    init_retry_second: u64,
 }
 
 impl OAuth2Manager {
@@ -21,24 +27,32 @@ impl OAuth2Manager {
             oauth2_msg_actor: None,
             enable_oauth2,
             user_manager_addr: None,
+            init_retry_second: INIT_RETRY_MIN_SECOND,
         }
     }
 
-    pub fn init(&mut self, _ctx: &mut Context<Self>) {
+    pub fn init(&mut self, ctx: &mut Context<Self>) {
         if !self.enable_oauth2 {
             return;
         }
         let oauth2_config = self.oauth2_config.clone();
         let user_manager_addr = self.user_manager_addr.clone();
-        match OAuth2MsgActor::new(oauth2_config, user_manager_addr) {
-            Ok(actor) => {
-                let oauth2_msg_actor = actor.start();
-                self.oauth2_msg_actor = Some(oauth2_msg_actor);
-            }
-            Err(err) => {
-                log::error!("init oauth2 error:{}", err);
-            }
-        }
+        async move { OAuth2MsgActor::new(oauth2_config, user_manager_addr).await }
+            .into_actor(self)
+            .map(|res, act, ctx| match res {
+                Ok(actor) => {
+                    act.oauth2_msg_actor = Some(actor.start());
+                    act.init_retry_second = INIT_RETRY_MIN_SECOND;
+                }
+                Err(err) => {
+                    // OIDC服务发现失败时按退避间隔重试,避免需要重启才能启用
+                    let delay = act.init_retry_second;
+                    act.init_retry_second = (delay * 2).min(INIT_RETRY_MAX_SECOND);
+                    log::error!("init oauth2 error:{},retry after {}s", err, delay);
+                    ctx.run_later(Duration::from_secs(delay), |act, ctx| act.init(ctx));
+                }
+            })
+            .wait(ctx);
     }
 
     async fn handle_req(
@@ -1,4 +1,7 @@
 use crate::common::model::privilege::PrivilegeGroup;
+use crate::user::permission;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
 pub mod actor_model;
@@ -16,6 +19,107 @@ pub struct OAuth2Config {
     pub oauth2_username_claim_name: Arc<String>,
     pub oauth2_nickname_claim_name: Arc<String>,
     pub oauth2_user_default_role: Arc<String>,
+    /// 开启后以oauth2_server_url作为issuer进行OIDC服务发现,并校验ID token
+    pub oauth2_oidc_discovery: bool,
+    /// 用户组claim名称,支持用.访问嵌套字段
+    pub oauth2_groups_claim_name: Arc<String>,
+    pub oauth2_user_developer_groups: Arc<HashSet<String>>,
+    pub oauth2_user_admin_groups: Arc<HashSet<String>>,
+    /// 用户组对应的命名空间白名单
+    pub oauth2_group_namespaces: Arc<HashMap<String, HashSet<Arc<String>>>>,
+}
+
+impl OAuth2Config {
+    ///
+    /// 解析用户组与命名空间映射,格式: group1:ns1,ns2;group2:ns3
+    pub fn parse_group_namespaces(input: &str) -> HashMap<String, HashSet<Arc<String>>> {
+        let mut result: HashMap<String, HashSet<Arc<String>>> = HashMap::new();
+        for item in input.split(';') {
+            let (group, namespaces) = if let Some(v) = item.split_once(':') {
+                v
+            } else {
+                continue;
+            };
+            let group = group.trim();
+            if group.is_empty() {
+                continue;
+            }
+            let set = result.entry(group.to_owned()).or_default();
+            for namespace in namespaces.split(',') {
+                let namespace = namespace.trim();
+                if !namespace.is_empty() {
+                    set.insert(Arc::new(namespace.to_owned()));
+                }
+            }
+        }
+        result
+    }
+
+    ///
+    /// 从claims中提取用户组,支持数组或逗号分隔的字符串
+    pub fn extract_groups(&self, claims: &Value) -> Vec<String> {
+        let mut value = claims;
+        for key in self.oauth2_groups_claim_name.split('.') {
+            if let Some(v) = value.get(key) {
+                value = v;
+            } else {
+                return vec![];
+            }
+        }
+        match value {
+            Value::Array(list) => list
+                .iter()
+                .filter_map(|v| v.as_str().map(|s| s.to_owned()))
+                .collect(),
+            Value::String(v) => v
+                .split(',')
+                .map(|s| s.trim().to_owned())
+                .filter(|s| !s.is_empty())
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    ///
+    /// 按用户组映射角色与命名空间权限;管理员组优先于开发者组,没有命中命名空间映射时返回None
+    pub fn map_groups(
+        &self,
+        groups: &[String],
+    ) -> (Arc<String>, Option<PrivilegeGroup<Arc<String>>>) {
+        let mut role = self.oauth2_user_default_role.clone();
+        if groups
+            .iter()
+            .any(|g| self.oauth2_user_developer_groups.contains(g))
+        {
+            role = permission::USER_ROLE_DEVELOPER.clone();
+        }
+        if groups
+            .iter()
+            .any(|g| self.oauth2_user_admin_groups.contains(g))
+        {
+            role = permission::USER_ROLE_MANAGER.clone();
+        }
+        let mut whitelist = HashSet::new();
+        let mut matched = false;
+        for group in groups {
+            if let Some(namespaces) = self.oauth2_group_namespaces.get(group) {
+                matched = true;
+                whitelist.extend(namespaces.iter().cloned());
+            }
+        }
+        let namespace_privilege = if matched {
+            Some(PrivilegeGroup {
+                enabled: true,
+                whitelist_is_all: false,
+                whitelist: Some(Arc::new(whitelist)),
+                blacklist_is_all: false,
+                blacklist: None,
+            })
+        } else {
+            None
+        };
+        (role, namespace_privilege)
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -44,3 +148,36 @@ impl OAuth2UserMeta {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn map_groups() {
+        let config = OAuth2Config {
+            oauth2_user_default_role: permission::USER_ROLE_VISITOR.clone(),
+            oauth2_groups_claim_name: Arc::new("realm_access.groups".to_owned()),
+            oauth2_user_developer_groups: Arc::new(HashSet::from(["dev".to_owned()])),
+            oauth2_user_admin_groups: Arc::new(HashSet::from(["ops".to_owned()])),
+            oauth2_group_namespaces: Arc::new(OAuth2Config::parse_group_namespaces(
+                "dev: public,test ;ops:prod;bad",
+            )),
+            ..Default::default()
+        };
+        let claims = serde_json::json!({"realm_access": {"groups": ["dev", "other"]}});
+        let groups = config.extract_groups(&claims);
+        assert_eq!(groups, vec!["dev".to_owned(), "other".to_owned()]);
+        let (role, privilege) = config.map_groups(&groups);
+        assert_eq!(role, permission::USER_ROLE_DEVELOPER.clone());
+        let privilege = privilege.unwrap();
+        assert!(privilege.check_permission(&Arc::new("test".to_owned())));
+        assert!(!privilege.check_permission(&Arc::new("prod".to_owned())));
+
+        let (role, _) = config.map_groups(&["dev".to_owned(), "ops".to_owned()]);
+        assert_eq!(role, permission::USER_ROLE_MANAGER.clone());
+        let (role, privilege) = config.map_groups(&[]);
+        assert_eq!(role, permission::USER_ROLE_VISITOR.clone());
+        assert!(privilege.is_none());
+    }
+}
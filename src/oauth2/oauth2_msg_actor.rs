@@ -1,40 +1,73 @@
 use crate::common::get_app_version;
+use crate::common::model::privilege::PrivilegeGroupOptionParam;
 use crate::oauth2::model::actor_model::{OAuth2MsgReq, OAuth2MsgResult};
 use crate::oauth2::model::OAuth2Config;
+use crate::oauth2::oidc::{
+    AuthorizeStateSigner, IdTokenValidator, JwkSet, OidcClient, OidcContext, OidcProviderMetadata,
+};
 use crate::user::model::{UserDto, UserSourceType};
 use crate::user::{UserManager, UserManagerReq, UserManagerResult};
 use actix::prelude::*;
 use oauth2::{
-    basic::BasicClient, reqwest::async_http_client, AuthorizationCode, ClientId, ClientSecret,
-    RedirectUrl, Scope, TokenResponse,
+    reqwest::async_http_client, AuthorizationCode, ClientId, ClientSecret, RedirectUrl, Scope,
+    TokenResponse,
 };
 use reqwest::Client;
-use serde_json::Value;
+use serde_json::{Map, Value};
 use std::sync::Arc;
+use std::time::Duration;
+
+const HTTP_TIMEOUT_SECOND: u64 = 10;
 
 #[derive(Clone)]
 pub struct OAuth2MsgActor {
     oauth2_config: Arc<OAuth2Config>,
     user_manager_addr: Option<Addr<UserManager>>,
     http_client: Client,
-    oauth2_client: BasicClient,
+    oauth2_client: OidcClient,
     scopes: Vec<Scope>,
+    userinfo_url: Option<String>,
+    oidc_context: Option<Arc<OidcContext>>,
+    state_signer: Arc<AuthorizeStateSigner>,
 }
 
 impl OAuth2MsgActor {
-    pub(crate) fn new(
+    pub(crate) async fn new(
         oauth2_config: Arc<OAuth2Config>,
         user_manager_addr: Option<Addr<UserManager>>,
     ) -> anyhow::Result<Self> {
+        // 服务发现、JWKS与userinfo都需要校验证书,否则签名公钥可被中间人替换
+        let http_client = Client::builder()
+            .timeout(Duration::from_secs(HTTP_TIMEOUT_SECOND))
+            .build()?;
         let client_id = ClientId::new(oauth2_config.oauth2_client_id.as_ref().clone());
         let client_secret = ClientSecret::new(oauth2_config.oauth2_client_secret.as_ref().clone());
         // Use full URLs directly
-        let auth_url = oauth2_config.oauth2_authorization_url.as_ref().clone();
-        let token_url = oauth2_config.oauth2_token_url.as_ref().clone();
+        let mut auth_url = oauth2_config.oauth2_authorization_url.as_ref().clone();
+        let mut token_url = oauth2_config.oauth2_token_url.as_ref().clone();
+        let mut userinfo_url = Some(oauth2_config.oauth2_userinfo_url.as_ref().clone());
+        let mut oidc_context = None;
+        if oauth2_config.oauth2_oidc_discovery {
+            // OIDC discovery, endpoints come from the provider metadata
+            let metadata =
+                OidcProviderMetadata::discover(&http_client, &oauth2_config.oauth2_server_url)
+                    .await?;
+            let jwks = JwkSet::fetch(&http_client, &metadata.jwks_uri).await?;
+            log::info!("OIDC discovery success, issuer: {}", &metadata.issuer);
+            auth_url = metadata.authorization_endpoint;
+            token_url = metadata.token_endpoint;
+            userinfo_url = metadata.userinfo_endpoint;
+            let validator = IdTokenValidator::new(metadata.issuer, client_id.as_str().to_owned());
+            oidc_context = Some(Arc::new(OidcContext::new(
+                validator,
+                metadata.jwks_uri,
+                jwks,
+            )));
+        }
 
         let redirect_url = RedirectUrl::new(oauth2_config.oauth2_redirect_uri.as_ref().clone())?;
 
-        let oauth2_client = BasicClient::new(
+        let oauth2_client = OidcClient::new(
             client_id,
             Some(client_secret),
             oauth2::AuthUrl::new(auth_url)?,
@@ -49,31 +82,92 @@ impl OAuth2MsgActor {
             .map(|s| Scope::new(s.to_string()))
             .collect();
 
+        let state_signer = Arc::new(AuthorizeStateSigner::new(
+            oauth2_config.oauth2_client_secret.as_str(),
+        ));
         Ok(Self {
             oauth2_config,
             user_manager_addr,
-            http_client: Client::builder()
-                .danger_accept_invalid_certs(true)
-                .build()
-                .unwrap_or_else(|_| Client::new()),
+            http_client,
             oauth2_client,
             scopes,
+            userinfo_url,
+            oidc_context,
+            state_signer,
+        })
+    }
+
+    async fn fetch_userinfo(
+        &self,
+        userinfo_url: &str,
+        access_token: &str,
+    ) -> anyhow::Result<Value> {
+        // Build request with bearer token
+        // Use bearer_auth which sets: Authorization: Bearer <token>
+        // GitHub and some OAuth2 providers require User-Agent header
+        let user_agent = format!("r-nacos/{}", get_app_version());
+        let request = self
+            .http_client
+            .get(userinfo_url)
+            .bearer_auth(access_token)
+            .header("Accept", "application/json")
+            .header("User-Agent", user_agent);
+
+        let userinfo_response = request.send().await?;
+
+        // Check response status
+        let status = userinfo_response.status();
+        if !status.is_success() {
+            let error_text = userinfo_response.text().await.unwrap_or_default();
+            log::error!(
+                "OAuth2 userinfo request failed: status={}, url={}, body={}",
+                status,
+                userinfo_url,
+                error_text
+            );
+            return Err(anyhow::anyhow!(
+                "OAuth2 userinfo request failed: status={}, body={}",
+                status,
+                error_text
+            ));
+        }
+
+        // Parse JSON directly
+        userinfo_response.json().await.map_err(|e| {
+            log::error!("OAuth2 userinfo JSON parse error: {}", e);
+            anyhow::anyhow!("Failed to parse OAuth2 userinfo response: {}", e)
         })
     }
 
     async fn handle_req(&self, msg: OAuth2MsgReq) -> anyhow::Result<OAuth2MsgResult> {
         match msg {
             OAuth2MsgReq::GetAuthorizeUrl => {
-                let (auth_url, _csrf_state) = self
+                let (state, nonce) = self.state_signer.generate(crate::now_second_i32() as i64);
+                let mut request = self
                     .oauth2_client
-                    .authorize_url(oauth2::CsrfToken::new_random)
-                    .add_scopes(self.scopes.clone())
-                    .url();
+                    .authorize_url(move || oauth2::CsrfToken::new(state))
+                    .add_scopes(self.scopes.clone());
+                if self.oidc_context.is_some() {
+                    request = request.add_extra_param("nonce", nonce);
+                }
+                let (auth_url, _csrf_state) = request.url();
 
                 Ok(OAuth2MsgResult::AuthorizeUrl(auth_url.to_string()))
             }
             OAuth2MsgReq::Authenticate(param) => {
+                let state = param
+                    .state
+                    .as_ref()
+                    .ok_or_else(|| anyhow::anyhow!("OAuth2 callback state is empty"))?;
+                let nonce = self
+                    .state_signer
+                    .verify(state, crate::now_second_i32() as i64)
+                    .map_err(|e| {
+                        log::error!("OAuth2 state check failed: {}", e);
+                        e
+                    })?;
                 // Exchange authorization code for token
+                // async_http_client使用默认校验证书的reqwest客户端
                 let token = self
                     .oauth2_client
                     .exchange_code(AuthorizationCode::new(param.code.clone()))
@@ -86,44 +180,34 @@ impl OAuth2MsgActor {
 
                 let access_token = token.access_token().secret();
 
-                // Get user info - use full URL directly
-                let userinfo_url = self.oauth2_config.oauth2_userinfo_url.as_ref().clone();
-
-                // Build request with bearer token
-                // Use bearer_auth which sets: Authorization: Bearer <token>
-                // GitHub and some OAuth2 providers require User-Agent header
-                let user_agent = format!("r-nacos/{}", get_app_version());
-                let request = self
-                    .http_client
-                    .get(&userinfo_url)
-                    .bearer_auth(access_token.clone())
-                    .header("Accept", "application/json")
-                    .header("User-Agent", user_agent);
-
-                let userinfo_response = request.send().await?;
-
-                // Check response status
-                let status = userinfo_response.status();
-                if !status.is_success() {
-                    let error_text = userinfo_response.text().await.unwrap_or_default();
-                    log::error!(
-                        "OAuth2 userinfo request failed: status={}, url={}, body={}",
-                        status,
-                        userinfo_url,
-                        error_text
-                    );
-                    return Err(anyhow::anyhow!(
-                        "OAuth2 userinfo request failed: status={}, body={}",
-                        status,
-                        error_text
-                    ));
+                let mut claims = Map::new();
+                if let Some(oidc_context) = &self.oidc_context {
+                    let id_token = token.extra_fields().id_token.as_ref().ok_or_else(|| {
+                        log::error!("OIDC token response has no id_token");
+                        anyhow::anyhow!("OIDC token response has no id_token")
+                    })?;
+                    claims = oidc_context
+                        .validate(&self.http_client, id_token, &nonce)
+                        .await
+                        .map_err(|e| {
+                            log::error!("OIDC id_token validate failed: {}", e);
+                            e
+                        })?;
                 }
-
-                // Parse JSON directly
-                let userinfo: Value = userinfo_response.json().await.map_err(|e| {
-                    log::error!("OAuth2 userinfo JSON parse error: {}", e);
-                    anyhow::anyhow!("Failed to parse OAuth2 userinfo response: {}", e)
-                })?;
+                if let Some(userinfo_url) = &self.userinfo_url {
+                    if let Value::Object(info) =
+                        self.fetch_userinfo(userinfo_url, access_token).await?
+                    {
+                        if claims.contains_key("sub") && info.get("sub") != claims.get("sub") {
+                            return Err(anyhow::anyhow!("OIDC userinfo sub not match id_token"));
+                        }
+                        // Verified id_token claims take precedence
+                        for (key, value) in info {
+                            claims.entry(key).or_insert(value);
+                        }
+                    }
+                }
+                let userinfo = Value::Object(claims);
 
                 // Log the complete userinfo response for debugging
                 log::info!(
@@ -154,9 +238,19 @@ impl OAuth2MsgActor {
                         anyhow::anyhow!("nickname claim '{}' not found", nickname_claim_name)
                     })?;
 
-                // Use configured default role
-                let role = self.oauth2_config.oauth2_user_default_role.clone();
-                let mut namespace_privilege = None;
+                // Map groups claim to role and namespace privilege, fallback to default role
+                let groups = self.oauth2_config.extract_groups(&userinfo);
+                let (role, group_namespace_privilege) = self.oauth2_config.map_groups(&groups);
+                let namespace_privilege_param =
+                    group_namespace_privilege
+                        .as_ref()
+                        .map(|v| PrivilegeGroupOptionParam {
+                            whitelist_is_all: Some(v.whitelist_is_all),
+                            whitelist: v.whitelist.clone(),
+                            blacklist_is_all: Some(v.blacklist_is_all),
+                            blacklist: None,
+                        });
+                let mut namespace_privilege = group_namespace_privilege;
 
                 // Initialize user
                 if let Some(user_manager_addr) = &self.user_manager_addr {
@@ -170,11 +264,13 @@ impl OAuth2MsgActor {
                     if let Ok(Ok(UserManagerResult::QueryUser(Some(user_dto)))) = user_manager_addr
                         .send(UserManagerReq::InitUser {
                             user,
-                            namespace_privilege_param: None,
+                            namespace_privilege_param,
                         })
                         .await
                     {
-                        namespace_privilege = user_dto.namespace_privilege;
+                        if namespace_privilege.is_none() {
+                            namespace_privilege = user_dto.namespace_privilege;
+                        }
                     }
                 }
 
@@ -0,0 +1,496 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use oauth2::basic::{
+    BasicErrorResponse, BasicRevocationErrorResponse, BasicTokenIntrospectionResponse,
+    BasicTokenType,
+};
+use oauth2::{CsrfToken, ExtraTokenFields, StandardRevocableToken, StandardTokenResponse};
+use reqwest::Client;
+use ring::rand::SecureRandom;
+use ring::{hmac, signature};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::sync::{Arc, RwLock};
+
+pub const OIDC_DISCOVERY_PATH: &str = "/.well-known/openid-configuration";
+/// 校验过期时间时允许的时钟偏差
+const CLOCK_SKEW_SECOND: i64 = 60;
+/// 授权请求state有效期
+const AUTHORIZE_STATE_TIMEOUT_SECOND: i64 = 600;
+
+///
+/// token响应中的OIDC扩展字段
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct IdTokenFields {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id_token: Option<String>,
+}
+
+impl ExtraTokenFields for IdTokenFields {}
+
+pub type OidcTokenResponse = StandardTokenResponse<IdTokenFields, BasicTokenType>;
+
+pub type OidcClient = oauth2::Client<
+    BasicErrorResponse,
+    OidcTokenResponse,
+    BasicTokenType,
+    BasicTokenIntrospectionResponse,
+    StandardRevocableToken,
+    BasicRevocationErrorResponse,
+>;
+
+///
+/// OIDC服务发现元数据
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct OidcProviderMetadata {
+    pub issuer: String,
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    #[serde(default)]
+    pub userinfo_endpoint: Option<String>,
+    pub jwks_uri: String,
+}
+
+impl OidcProviderMetadata {
+    pub fn build_discovery_url(issuer: &str) -> String {
+        format!("{}{}", issuer.trim_end_matches('/'), OIDC_DISCOVERY_PATH)
+    }
+
+    pub async fn discover(client: &Client, issuer: &str) -> anyhow::Result<Self> {
+        let url = Self::build_discovery_url(issuer);
+        let metadata: Self = client
+            .get(&url)
+            .header("Accept", "application/json")
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        if metadata.issuer.trim_end_matches('/') != issuer.trim_end_matches('/') {
+            return Err(anyhow::anyhow!(
+                "oidc issuer mismatch, expected {}, discovered {}",
+                issuer,
+                &metadata.issuer
+            ));
+        }
+        Ok(metadata)
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Jwk {
+    pub kty: String,
+    #[serde(default)]
+    pub kid: Option<String>,
+    #[serde(default)]
+    pub alg: Option<String>,
+    #[serde(default)]
+    pub n: Option<String>,
+    #[serde(default)]
+    pub e: Option<String>,
+    #[serde(default)]
+    pub crv: Option<String>,
+    #[serde(default)]
+    pub x: Option<String>,
+    #[serde(default)]
+    pub y: Option<String>,
+}
+
+impl Jwk {
+    fn decode_field(v: &Option<String>, name: &str) -> anyhow::Result<Vec<u8>> {
+        let v = v
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("jwk field {} is empty", name))?;
+        Ok(URL_SAFE_NO_PAD.decode(v)?)
+    }
+
+    ///
+    /// 按jwt头中的alg校验签名,支持RS256/RS384/RS512/ES256/ES384
+    pub fn verify(&self, alg: &str, message: &[u8], sig: &[u8]) -> anyhow::Result<()> {
+        if let Some(key_alg) = &self.alg {
+            if key_alg != alg {
+                return Err(anyhow::anyhow!("jwk alg {} not match {}", key_alg, alg));
+            }
+        }
+        let result = match (self.kty.as_str(), alg) {
+            ("RSA", "RS256" | "RS384" | "RS512") => {
+                let params = match alg {
+                    "RS256" => &signature::RSA_PKCS1_2048_8192_SHA256,
+                    "RS384" => &signature::RSA_PKCS1_2048_8192_SHA384,
+                    _ => &signature::RSA_PKCS1_2048_8192_SHA512,
+                };
+                let n = Self::decode_field(&self.n, "n")?;
+                let e = Self::decode_field(&self.e, "e")?;
+                signature::RsaPublicKeyComponents { n: &n, e: &e }.verify(params, message, sig)
+            }
+            ("EC", "ES256" | "ES384") => {
+                let (params, crv): (&signature::EcdsaVerificationAlgorithm, _) = match alg {
+                    "ES256" => (&signature::ECDSA_P256_SHA256_FIXED, "P-256"),
+                    _ => (&signature::ECDSA_P384_SHA384_FIXED, "P-384"),
+                };
+                if self.crv.as_deref() != Some(crv) {
+                    return Err(anyhow::anyhow!("jwk crv not match alg {}", alg));
+                }
+                let mut point = vec![0x04];
+                point.extend(Self::decode_field(&self.x, "x")?);
+                point.extend(Self::decode_field(&self.y, "y")?);
+                signature::UnparsedPublicKey::new(params, &point).verify(message, sig)
+            }
+            _ => return Err(anyhow::anyhow!("unsupported jwt alg {}", alg)),
+        };
+        result.map_err(|_| anyhow::anyhow!("id token signature is invalid"))
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct JwkSet {
+    pub keys: Vec<Jwk>,
+}
+
+impl JwkSet {
+    pub async fn fetch(client: &Client, url: &str) -> anyhow::Result<Self> {
+        Ok(client
+            .get(url)
+            .header("Accept", "application/json")
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?)
+    }
+
+    ///
+    /// jwt头没有kid时只在唯一一个key的情况下使用
+    pub fn find(&self, kid: Option<&str>) -> Option<&Jwk> {
+        match kid {
+            Some(kid) => self.keys.iter().find(|e| e.kid.as_deref() == Some(kid)),
+            None if self.keys.len() == 1 => self.keys.first(),
+            None => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct JwtHeader {
+    pub alg: String,
+    #[serde(default)]
+    pub kid: Option<String>,
+}
+
+///
+/// ID token校验:签名、签发者、接收方与有效期
+#[derive(Clone, Debug)]
+pub struct IdTokenValidator {
+    pub issuer: String,
+    pub client_id: String,
+}
+
+impl IdTokenValidator {
+    pub fn new(issuer: String, client_id: String) -> Self {
+        Self { issuer, client_id }
+    }
+
+    pub fn parse_header(id_token: &str) -> anyhow::Result<JwtHeader> {
+        let header = id_token
+            .split('.')
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("invalid id token"))?;
+        Ok(serde_json::from_slice(&URL_SAFE_NO_PAD.decode(header)?)?)
+    }
+
+    pub fn validate(
+        &self,
+        id_token: &str,
+        jwks: &JwkSet,
+        nonce: Option<&str>,
+        now_second: i64,
+    ) -> anyhow::Result<Map<String, Value>> {
+        let parts: Vec<&str> = id_token.split('.').collect();
+        if parts.len() != 3 {
+            return Err(anyhow::anyhow!("invalid id token"));
+        }
+        let header = Self::parse_header(id_token)?;
+        let jwk = jwks
+            .find(header.kid.as_deref())
+            .ok_or_else(|| anyhow::anyhow!("not found jwk, kid: {:?}", &header.kid))?;
+        let message_len = parts[0].len() + 1 + parts[1].len();
+        let sig = URL_SAFE_NO_PAD.decode(parts[2])?;
+        jwk.verify(&header.alg, &id_token.as_bytes()[..message_len], &sig)?;
+        let claims: Map<String, Value> =
+            serde_json::from_slice(&URL_SAFE_NO_PAD.decode(parts[1])?)?;
+        let issuer = claims
+            .get("iss")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default();
+        if issuer.trim_end_matches('/') != self.issuer.trim_end_matches('/') {
+            return Err(anyhow::anyhow!("id token issuer mismatch: {}", issuer));
+        }
+        let audience_match = match claims.get("aud") {
+            Some(Value::String(v)) => v == &self.client_id,
+            Some(Value::Array(list)) => list.iter().any(|v| v.as_str() == Some(&self.client_id)),
+            _ => false,
+        };
+        if !audience_match {
+            return Err(anyhow::anyhow!("id token audience mismatch"));
+        }
+        let exp = claims
+            .get("exp")
+            .and_then(|v| v.as_i64())
+            .ok_or_else(|| anyhow::anyhow!("id token exp is empty"))?;
+        if exp + CLOCK_SKEW_SECOND < now_second {
+            return Err(anyhow::anyhow!("id token is expired"));
+        }
+        if let Some(nbf) = claims.get("nbf").and_then(|v| v.as_i64()) {
+            if nbf - CLOCK_SKEW_SECOND > now_second {
+                return Err(anyhow::anyhow!("id token is not yet valid"));
+            }
+        }
+        if let Some(nonce) = nonce {
+            if claims.get("nonce").and_then(|v| v.as_str()) != Some(nonce) {
+                return Err(anyhow::anyhow!("id token nonce mismatch"));
+            }
+        }
+        Ok(claims)
+    }
+}
+
+///
+/// 通过服务发现得到的OIDC校验上下文;遇到未知kid时重新拉取JWKS以支持密钥轮换
+#[derive(Debug)]
+pub struct OidcContext {
+    pub validator: IdTokenValidator,
+    pub jwks_uri: String,
+    jwks: RwLock<Arc<JwkSet>>,
+}
+
+impl OidcContext {
+    pub fn new(validator: IdTokenValidator, jwks_uri: String, jwks: JwkSet) -> Self {
+        Self {
+            validator,
+            jwks_uri,
+            jwks: RwLock::new(Arc::new(jwks)),
+        }
+    }
+
+    fn get_jwks(&self) -> Arc<JwkSet> {
+        self.jwks.read().map(|v| v.clone()).unwrap_or_default()
+    }
+
+    pub async fn validate(
+        &self,
+        client: &Client,
+        id_token: &str,
+        nonce: &str,
+    ) -> anyhow::Result<Map<String, Value>> {
+        let header = IdTokenValidator::parse_header(id_token)?;
+        let mut jwks = self.get_jwks();
+        if jwks.find(header.kid.as_deref()).is_none() {
+            jwks = Arc::new(JwkSet::fetch(client, &self.jwks_uri).await?);
+            if let Ok(mut v) = self.jwks.write() {
+                *v = jwks.clone();
+            }
+        }
+        self.validator
+            .validate(id_token, &jwks, Some(nonce), crate::now_second_i32() as i64)
+    }
+}
+
+///
+/// 授权请求的state与nonce;state带过期时间并签名,nonce由state签名派生,
+/// 回调落到集群任一节点都能校验,不依赖本地存储
+pub struct AuthorizeStateSigner {
+    key: hmac::Key,
+}
+
+impl AuthorizeStateSigner {
+    ///
+    /// secret为空时使用随机密钥,此时只有签发节点能校验
+    pub fn new(secret: &str) -> Self {
+        let key = if secret.is_empty() {
+            let mut buf = [0u8; 32];
+            ring::rand::SystemRandom::new()
+                .fill(&mut buf)
+                .expect("generate random key failed");
+            hmac::Key::new(hmac::HMAC_SHA256, &buf)
+        } else {
+            hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes())
+        };
+        Self { key }
+    }
+
+    fn sign(&self, data: &str) -> String {
+        URL_SAFE_NO_PAD.encode(hmac::sign(&self.key, data.as_bytes()).as_ref())
+    }
+
+    fn build_nonce(&self, state: &str) -> String {
+        self.sign(&format!("nonce.{}", state))
+    }
+
+    ///
+    /// 生成授权请求的(state, nonce)
+    pub fn generate(&self, now_second: i64) -> (String, String) {
+        let payload = format!(
+            "{}.{}",
+            CsrfToken::new_random().secret(),
+            now_second + AUTHORIZE_STATE_TIMEOUT_SECOND
+        );
+        let state = format!("{}.{}", &payload, self.sign(&payload));
+        let nonce = self.build_nonce(&state);
+        (state, nonce)
+    }
+
+    ///
+    /// 校验回调中的state,返回授权请求时对应的nonce
+    pub fn verify(&self, state: &str, now_second: i64) -> anyhow::Result<String> {
+        let (payload, sig) = state
+            .rsplit_once('.')
+            .ok_or_else(|| anyhow::anyhow!("invalid oauth2 state"))?;
+        let sig = URL_SAFE_NO_PAD
+            .decode(sig)
+            .map_err(|_| anyhow::anyhow!("invalid oauth2 state"))?;
+        hmac::verify(&self.key, payload.as_bytes(), &sig)
+            .map_err(|_| anyhow::anyhow!("oauth2 state signature is invalid"))?;
+        let expire = payload
+            .rsplit_once('.')
+            .and_then(|(_, v)| v.parse::<i64>().ok())
+            .ok_or_else(|| anyhow::anyhow!("invalid oauth2 state"))?;
+        if expire < now_second {
+            return Err(anyhow::anyhow!("oauth2 state is expired"));
+        }
+        Ok(self.build_nonce(state))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ring::rand::SystemRandom;
+    use ring::signature::{EcdsaKeyPair, KeyPair, ECDSA_P256_SHA256_FIXED_SIGNING};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    const CLIENT_ID: &str = "rnacos";
+
+    struct MockIdp {
+        key_pair: EcdsaKeyPair,
+        rng: SystemRandom,
+    }
+
+    impl MockIdp {
+        fn new() -> Self {
+            let rng = SystemRandom::new();
+            let pkcs8 =
+                EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, &rng).unwrap();
+            let key_pair =
+                EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, pkcs8.as_ref(), &rng)
+                    .unwrap();
+            Self { key_pair, rng }
+        }
+
+        fn jwks(&self) -> JwkSet {
+            let point = self.key_pair.public_key().as_ref();
+            JwkSet {
+                keys: vec![Jwk {
+                    kty: "EC".to_owned(),
+                    kid: Some("k1".to_owned()),
+                    alg: Some("ES256".to_owned()),
+                    crv: Some("P-256".to_owned()),
+                    x: Some(URL_SAFE_NO_PAD.encode(&point[1..33])),
+                    y: Some(URL_SAFE_NO_PAD.encode(&point[33..65])),
+                    ..Default::default()
+                }],
+            }
+        }
+
+        fn sign(&self, claims: Value) -> String {
+            let header = URL_SAFE_NO_PAD.encode(r#"{"alg":"ES256","kid":"k1","typ":"JWT"}"#);
+            let payload = URL_SAFE_NO_PAD.encode(claims.to_string());
+            let message = format!("{}.{}", header, payload);
+            let sig = self.key_pair.sign(&self.rng, message.as_bytes()).unwrap();
+            format!("{}.{}", message, URL_SAFE_NO_PAD.encode(sig.as_ref()))
+        }
+
+        /// 本地http桩,依次响应discovery与jwks请求
+        async fn serve(&self) -> String {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let issuer = format!("http://{}", listener.local_addr().unwrap());
+            let discovery = serde_json::json!({
+                "issuer": &issuer,
+                "authorization_endpoint": format!("{}/authorize", &issuer),
+                "token_endpoint": format!("{}/token", &issuer),
+                "jwks_uri": format!("{}/jwks", &issuer),
+            })
+            .to_string();
+            let jwks = serde_json::to_string(&self.jwks()).unwrap();
+            tokio::spawn(async move {
+                for body in [discovery, jwks] {
+                    let (mut stream, _) = listener.accept().await.unwrap();
+                    let mut buf = [0u8; 1024];
+                    stream.read(&mut buf).await.unwrap();
+                    let resp = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        body.len(),
+                        body
+                    );
+                    stream.write_all(resp.as_bytes()).await.unwrap();
+                }
+            });
+            issuer
+        }
+    }
+
+    #[tokio::test]
+    async fn discover_and_validate_id_token() {
+        let idp = MockIdp::new();
+        let issuer = idp.serve().await;
+        let client = Client::new();
+        let metadata = OidcProviderMetadata::discover(&client, &issuer)
+            .await
+            .unwrap();
+        assert_eq!(metadata.token_endpoint, format!("{}/token", &issuer));
+        assert!(metadata.userinfo_endpoint.is_none());
+        let jwks = JwkSet::fetch(&client, &metadata.jwks_uri).await.unwrap();
+        let validator = IdTokenValidator::new(issuer.clone(), CLIENT_ID.to_owned());
+        let now = 1_700_000_000;
+        let token = idp.sign(serde_json::json!({
+            "iss": &issuer, "aud": [CLIENT_ID], "exp": now + 300, "sub": "u1", "groups": ["dev"],
+            "nonce": "n1"
+        }));
+        let claims = validator.validate(&token, &jwks, Some("n1"), now).unwrap();
+        assert_eq!(claims["sub"], "u1");
+        // 过期、nonce不一致、接收方不一致与签名被篡改
+        assert!(validator
+            .validate(&token, &jwks, Some("n1"), now + 400)
+            .is_err());
+        assert!(validator.validate(&token, &jwks, Some("n2"), now).is_err());
+        let other_aud =
+            idp.sign(serde_json::json!({"iss": &issuer, "aud": "other", "exp": now + 300}));
+        assert!(validator.validate(&other_aud, &jwks, None, now).is_err());
+        let mut tampered = token.clone();
+        tampered.insert_str(token.find('.').unwrap() + 1, "e30");
+        assert!(validator.validate(&tampered, &jwks, None, now).is_err());
+        assert!(validator
+            .validate(&token, &MockIdp::new().jwks(), None, now)
+            .is_err());
+    }
+
+    #[test]
+    fn authorize_state_and_nonce() {
+        let signer = AuthorizeStateSigner::new("client_secret");
+        let now = 1_700_000_000;
+        let (state, nonce) = signer.generate(now);
+        assert_eq!(signer.verify(&state, now + 10).unwrap(), nonce);
+        // 其它节点使用相同密钥也能校验
+        let other = AuthorizeStateSigner::new("client_secret");
+        assert_eq!(other.verify(&state, now).unwrap(), nonce);
+        assert!(signer
+            .verify(&state, now + AUTHORIZE_STATE_TIMEOUT_SECOND + 1)
+            .is_err());
+        assert!(AuthorizeStateSigner::new("other")
+            .verify(&state, now)
+            .is_err());
+        let forged = format!("abc.{}", now + 100);
+        assert!(signer
+            .verify(&format!("{}.{}", forged, "AAAA"), now)
+            .is_err());
+    }
+}
@@ -101,9 +101,11 @@ async fn do_login(
                     + &uuid::Uuid::new_v4().to_string().replace('-', ""),
             );
             let session = Arc::new(TokenSession {
+                resource_privilege: user.build_resource_privilege(),
                 username: user.username.clone(),
                 roles: user.roles.unwrap_or_default(),
                 extend_infos: user.extend_info.unwrap_or_default(),
+                namespace_privilege: user.namespace_privilege,
             });
             /*
             let cache_req = CacheManagerReq::Set {
@@ -2,27 +2,29 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use actix::Addr;
-use actix_web::{web, HttpRequest, HttpResponse, Responder, Scope};
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse, Responder, Scope};
 use chrono::Local;
 use serde::{Deserialize, Serialize};
 
 use crate::common::appdata::AppShareData;
+use crate::common::model::privilege::ResourceAction;
 use crate::common::model::ApiResult;
 use crate::common::option_utils::OptionUtils;
 use crate::common::string_utils::StringUtils;
-use crate::common::web_utils::get_req_body;
+use crate::common::web_utils::{get_client_ip, get_req_body};
 use crate::config::config_index::ConfigQueryParam;
 use crate::config::config_type::ConfigType;
 use crate::config::core::{
     ConfigActor, ConfigCmd, ConfigInfoDto, ConfigKey, ConfigResult, ListenerItem, ListenerResult,
 };
+use crate::config::model::ConfigClientInfo;
 use crate::config::utils::param_utils;
 use crate::config::ConfigUtils;
 use crate::console::v2::ERROR_CODE_SYSTEM_ERROR;
-use crate::merge_web_param;
 use crate::openapi::constant::EMPTY;
 use crate::raft::cluster::model::{DelConfigReq, SetConfigReq};
 use crate::utils::select_option_by_clone;
+use crate::{merge_web_param, user_namespace_privilege, user_resource_privilege};
 
 pub(super) fn service() -> Scope {
     web::scope("/configs")
@@ -45,6 +47,8 @@ pub struct ConfigWebParams {
     pub content: Option<String>,
     pub desc: Option<String>,
     pub r#type: Option<String>,
+    pub config_tags: Option<String>,
+    pub encrypted_data_key: Option<String>,
     pub search: Option<String>,   //search type
     pub page_no: Option<usize>,   //use at search
     pub page_size: Option<usize>, //use at search
@@ -83,6 +87,7 @@ impl From<ConfigInfoDto> for ConfigInfo {
             data_id: v.data_id,
             content: v.content,
             md5: v.md5,
+            encrypted_data_key: v.encrypted_data_key.map(|e| e.as_ref().to_owned()),
             ..Default::default()
         }
     }
@@ -97,6 +102,11 @@ impl ConfigWebParams {
             content: OptionUtils::select(self.content, other.content),
             desc: OptionUtils::select(self.desc, other.desc),
             r#type: OptionUtils::select(self.r#type, other.r#type),
+            config_tags: OptionUtils::select(self.config_tags, other.config_tags),
+            encrypted_data_key: OptionUtils::select(
+                self.encrypted_data_key,
+                other.encrypted_data_key,
+            ),
             search: OptionUtils::select(self.search, other.search),
             page_no: OptionUtils::select(self.page_no, other.page_no),
             page_size: OptionUtils::select(self.page_size, other.page_size),
@@ -139,6 +149,10 @@ impl ConfigWebParams {
             offset,
             like_group: self.group,
             like_data_id: self.data_id,
+            tags: self
+                .config_tags
+                .map(|v| ConfigUtils::parse_tags(&v))
+                .unwrap_or_default(),
             query_context: true,
             ..Default::default()
         };
@@ -156,6 +170,10 @@ impl ConfigWebParams {
             offset,
             group: self.group.map(Arc::new),
             data_id: self.data_id.map(Arc::new),
+            tags: self
+                .config_tags
+                .map(|v| ConfigUtils::parse_tags(&v))
+                .unwrap_or_default(),
             query_context: true,
             ..Default::default()
         };
@@ -174,7 +192,47 @@ pub struct ConfigWebConfirmedParam {
     pub content: String,
 }
 
+///
+/// 校验用户命名空间与细粒度数据权限,没有权限时返回403
+fn check_resource_permission(
+    req: &HttpRequest,
+    param: &ConfigWebConfirmedParam,
+    action: ResourceAction,
+) -> Option<HttpResponse> {
+    check_config_key_permission(
+        req,
+        &ConfigKey::new(&param.data_id, &param.group, &param.tenant),
+        action,
+    )
+}
+
+fn check_config_key_permission(
+    req: &HttpRequest,
+    key: &ConfigKey,
+    action: ResourceAction,
+) -> Option<HttpResponse> {
+    if !user_namespace_privilege!(req).check_permission(&key.tenant) {
+        Some(HttpResponse::Forbidden().body(format!(
+            "user no such namespace permission: {}",
+            &key.tenant
+        )))
+    } else if !user_resource_privilege!(req).check_permission(
+        &key.tenant,
+        &key.group,
+        &key.data_id,
+        action,
+    ) {
+        Some(HttpResponse::Forbidden().body(format!(
+            "user no such resource permission: {}/{}/{}",
+            &key.tenant, &key.group, &key.data_id
+        )))
+    } else {
+        None
+    }
+}
+
 pub(crate) async fn add_config(
+    req: HttpRequest,
     a: web::Query<ConfigWebParams>,
     payload: web::Payload,
     appdata: web::Data<Arc<AppShareData>>,
@@ -201,15 +259,42 @@ pub(crate) async fn add_config(
 
     let config_type = StringUtils::map_not_empty(selected_param.r#type.clone());
     let desc = StringUtils::map_not_empty(selected_param.desc.clone());
+    let tags = selected_param
+        .config_tags
+        .as_ref()
+        .map(|v| ConfigUtils::parse_tags(v));
+    let encrypted_data_key = StringUtils::map_not_empty(selected_param.encrypted_data_key.clone());
     let param = selected_param.to_confirmed_param();
     match param {
         Ok(p) => {
+            if let Some(res) = check_resource_permission(&req, &p, ResourceAction::Write) {
+                return res;
+            }
             let mut req = SetConfigReq::new(
                 ConfigKey::new(&p.data_id, &p.group, &p.tenant),
                 Arc::new(p.content.to_owned()),
             );
             req.config_type = config_type.map(|v| ConfigType::new_by_value(v.as_ref()).get_value());
             req.desc = desc.map(Arc::new);
+            req.tags = tags;
+            req.encrypted_data_key = encrypted_data_key.map(Arc::new);
+            //客户端已加密的内容无法按类型校验
+            let validate_result = if req.encrypted_data_key.is_none() {
+                appdata
+                    .config_validator
+                    .validate_by_namespace(
+                        &appdata.namespace_addr,
+                        &req.config_key.tenant,
+                        req.config_type.as_ref().map(|v| v.as_str()),
+                        &req.value,
+                    )
+                    .await
+            } else {
+                Ok(())
+            };
+            if let Err(err) = validate_result {
+                return HttpResponse::BadRequest().json(err);
+            }
             match appdata.config_route.set_config(req).await {
                 Ok(_) => HttpResponse::Ok()
                     .content_type("text/html; charset=utf-8")
@@ -222,6 +307,7 @@ pub(crate) async fn add_config(
 }
 
 pub(crate) async fn del_config(
+    req: HttpRequest,
     a: web::Query<ConfigWebParams>,
     payload: web::Payload,
     appdata: web::Data<Arc<AppShareData>>,
@@ -249,6 +335,9 @@ pub(crate) async fn del_config(
     let param = selected_param.to_confirmed_param();
     match param {
         Ok(p) => {
+            if let Some(res) = check_resource_permission(&req, &p, ResourceAction::Write) {
+                return res;
+            }
             let req = DelConfigReq::new(ConfigKey::new(&p.data_id, &p.group, &p.tenant));
             match appdata.config_route.del_config(req).await {
                 Ok(_) => HttpResponse::Ok()
@@ -262,22 +351,30 @@ pub(crate) async fn del_config(
 }
 
 pub(crate) async fn get_config(
+    req: HttpRequest,
     web_param: web::Query<ConfigWebParams>,
     appdata: web::Data<Arc<AppShareData>>,
 ) -> impl Responder {
     if let Some(search) = web_param.search.as_ref() {
         if search == "blur" {
-            let query_param = web_param.0.build_like_search_param();
+            let mut query_param = web_param.0.build_like_search_param();
+            query_param.resource_privilege = user_resource_privilege!(req);
             return do_search_config(query_param, appdata).await;
         } else if search == "accurate" {
-            let query_param = web_param.0.build_search_param();
+            let mut query_param = web_param.0.build_search_param();
+            query_param.resource_privilege = user_resource_privilege!(req);
             return do_search_config(query_param, appdata).await;
         }
     };
     let param = web_param.to_confirmed_param();
     match param {
         Ok(p) => {
-            let cmd = ConfigCmd::GET(ConfigKey::new(&p.data_id, &p.group, &p.tenant));
+            if let Some(res) = check_resource_permission(&req, &p, ResourceAction::Read) {
+                return res;
+            }
+            let client = ConfigClientInfo::new_by_ip(Arc::new(get_client_ip(&req)));
+            let cmd =
+                ConfigCmd::GetByClient(ConfigKey::new(&p.data_id, &p.group, &p.tenant), client);
             match appdata.config_addr.send(cmd).await {
                 Ok(res) => {
                     let r: ConfigResult = res.unwrap();
@@ -286,6 +383,8 @@ pub(crate) async fn get_config(
                             value: v,
                             md5,
                             config_type,
+                            encrypted_data_key,
+                            gray,
                             ..
                         } => HttpResponse::Ok()
                             .content_type(
@@ -295,6 +394,13 @@ pub(crate) async fn get_config(
                                     .get_media_type(),
                             )
                             .insert_header(("content-md5", md5.as_ref().to_string()))
+                            .insert_header(("isBeta", gray.to_string()))
+                            .insert_header((
+                                "Encrypted-Data-Key",
+                                encrypted_data_key
+                                    .map(|v| v.as_ref().to_owned())
+                                    .unwrap_or_default(),
+                            ))
                             .body(v.as_ref().as_bytes().to_vec()),
                         _ => HttpResponse::NotFound().body("config data not exist"),
                     }
@@ -377,6 +483,11 @@ pub(super) async fn listener_config(
         }
     };
     let list = a.select_option(&b).to_items();
+    for item in &list {
+        if let Some(res) = check_config_key_permission(&_req, &item.key, ResourceAction::Read) {
+            return res;
+        }
+    }
     if list.is_empty() {
         //println!("listener_config error: listener item len == 0");
         return HttpResponse::NoContent()
@@ -397,7 +508,8 @@ pub(super) async fn listener_config(
         }
     }
     //println!("timeout header:{:?},time_out:{}",_req.headers().get("Long-Pulling-Timeout") ,time_out);
-    let cmd = ConfigCmd::LISTENER(list, tx, time_out);
+    let client = ConfigClientInfo::new_by_ip(Arc::new(get_client_ip(&_req)));
+    let cmd = ConfigCmd::LISTENER(list, tx, time_out, client);
     let _ = config_addr.send(cmd).await;
     let res = rx.await.unwrap();
     let v = match res {
@@ -418,3 +530,37 @@ pub(super) async fn listener_config(
         .content_type("text/html; charset=utf-8")
         .body(v)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::model::privilege::PrivilegeGroup;
+    use crate::common::model::TokenSession;
+    use actix_web::http::StatusCode;
+    use actix_web::test::TestRequest;
+    use std::collections::HashSet;
+
+    #[test]
+    fn api_key_namespace_scope() {
+        let mut namespace_privilege = PrivilegeGroup::empty();
+        namespace_privilege.whitelist = Some(Arc::new(HashSet::from([Arc::new("dev".to_owned())])));
+        let req = TestRequest::default().to_http_request();
+        req.extensions_mut().insert(Arc::new(TokenSession {
+            username: Arc::new("test".to_owned()),
+            namespace_privilege: Some(namespace_privilege),
+            ..Default::default()
+        }));
+        let build_param = |tenant: &str| ConfigWebConfirmedParam {
+            data_id: "app.yaml".to_owned(),
+            group: "DEFAULT_GROUP".to_owned(),
+            tenant: tenant.to_owned(),
+            content: String::new(),
+        };
+        assert!(
+            check_resource_permission(&req, &build_param("dev"), ResourceAction::Write).is_none()
+        );
+        let res =
+            check_resource_permission(&req, &build_param("prod"), ResourceAction::Write).unwrap();
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+    }
+}
@@ -29,3 +29,17 @@ pub fn config_v1_route(config: &mut ServiceConfig) {
             .service(web::resource("/listener").route(web::post().to(listener_config))),
     );
 }
+
+pub fn config_v2_route(config: &mut ServiceConfig) {
+    config.service(
+        scope("/nacos/v2/cs/config")
+            .service(web::resource("/list").route(web::get().to(v2::api::query_config_list)))
+            .service(
+                web::resource("/history/diff").route(web::get().to(v2::api::diff_history_config)),
+            )
+            .service(
+                web::resource("/history/rollback")
+                    .route(web::post().to(v2::api::rollback_history_config)),
+            ),
+    );
+}
@@ -1 +1,309 @@
+use std::sync::Arc;
 
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse, Responder};
+use serde::{Deserialize, Serialize};
+
+use crate::common::appdata::AppShareData;
+use crate::common::model::privilege::ResourceAction;
+use crate::common::model::{PageResult, TokenSession};
+use crate::common::option_utils::OptionUtils;
+use crate::config::config_cipher::ConfigCipher;
+use crate::config::config_index::ConfigQueryParam;
+use crate::config::core::{ConfigCmd, ConfigKey, ConfigResult};
+use crate::config::ConfigUtils;
+use crate::merge_web_param;
+use crate::openapi::v2::model::ApiResult;
+use crate::raft::cluster::model::SetConfigReq;
+use crate::user::permission::UserRole;
+use crate::{user_namespace_privilege, user_resource_privilege};
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigListParams {
+    #[serde(alias = "tenant")]
+    pub namespace_id: Option<String>,
+    pub group: Option<String>,
+    pub data_id: Option<String>,
+    /// blur: 按group、dataId模糊匹配; 其它: 精确匹配
+    pub search: Option<String>,
+    /// 按配置内容检索
+    pub content: Option<String>,
+    /// 按配置标签过滤,多个标签用逗号分隔
+    pub config_tags: Option<String>,
+    pub page_no: Option<usize>,
+    pub page_size: Option<usize>,
+}
+
+impl ConfigListParams {
+    pub fn merge(self, other: Self) -> Self {
+        Self {
+            namespace_id: OptionUtils::select(self.namespace_id, other.namespace_id),
+            group: OptionUtils::select(self.group, other.group),
+            data_id: OptionUtils::select(self.data_id, other.data_id),
+            search: OptionUtils::select(self.search, other.search),
+            content: OptionUtils::select(self.content, other.content),
+            config_tags: OptionUtils::select(self.config_tags, other.config_tags),
+            page_no: OptionUtils::select(self.page_no, other.page_no),
+            page_size: OptionUtils::select(self.page_size, other.page_size),
+        }
+    }
+
+    fn build_query_param(self, req: &HttpRequest) -> ConfigQueryParam {
+        let limit = self.page_size.unwrap_or(100).max(1);
+        let offset = (self.page_no.unwrap_or(1).max(1) - 1) * limit;
+        let mut param = ConfigQueryParam {
+            limit,
+            offset,
+            content_search: self.content,
+            tags: self
+                .config_tags
+                .map(|v| ConfigUtils::parse_tags(&v))
+                .unwrap_or_default(),
+            namespace_privilege: user_namespace_privilege!(req),
+            resource_privilege: user_resource_privilege!(req),
+            query_context: true,
+            ..Default::default()
+        };
+        if self.search.as_deref() == Some("blur") {
+            param.like_group = self.group;
+            param.like_data_id = self.data_id;
+        } else {
+            param.group = self.group.map(Arc::new);
+            param.data_id = self.data_id.map(Arc::new);
+        }
+        param.tenant = Some(Arc::new(ConfigUtils::default_tenant(
+            self.namespace_id.unwrap_or_default(),
+        )));
+        param
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigHistoryParams {
+    #[serde(alias = "tenant")]
+    pub namespace_id: Option<String>,
+    pub group: Option<String>,
+    pub data_id: Option<String>,
+    /// 差异比较的起始历史版本
+    pub from_id: Option<u64>,
+    /// 差异比较的目标历史版本,为空时与当前配置比较
+    pub to_id: Option<u64>,
+    /// 回滚的目标历史版本
+    pub history_id: Option<u64>,
+}
+
+impl ConfigHistoryParams {
+    pub fn merge(self, other: Self) -> Self {
+        Self {
+            namespace_id: OptionUtils::select(self.namespace_id, other.namespace_id),
+            group: OptionUtils::select(self.group, other.group),
+            data_id: OptionUtils::select(self.data_id, other.data_id),
+            from_id: OptionUtils::select(self.from_id, other.from_id),
+            to_id: OptionUtils::select(self.to_id, other.to_id),
+            history_id: OptionUtils::select(self.history_id, other.history_id),
+        }
+    }
+
+    fn to_key(&self) -> Result<ConfigKey, String> {
+        let data_id = self.data_id.clone().unwrap_or_default();
+        if data_id.is_empty() {
+            return Err("dataId is empty".to_owned());
+        }
+        let group = self
+            .group
+            .clone()
+            .filter(|v| !v.is_empty())
+            .unwrap_or("DEFAULT_GROUP".to_owned());
+        let tenant = ConfigUtils::default_tenant(self.namespace_id.clone().unwrap_or_default());
+        Ok(ConfigKey::new(&data_id, &group, &tenant))
+    }
+}
+
+///
+/// 校验用户命名空间及细粒度数据权限,没有权限时返回403
+fn check_config_permission(
+    req: &HttpRequest,
+    key: &ConfigKey,
+    action: ResourceAction,
+) -> Option<HttpResponse> {
+    if !user_namespace_privilege!(req).check_permission(&key.tenant) {
+        return Some(
+            HttpResponse::Forbidden().json(ApiResult::<Option<()>>::error(
+                403,
+                format!("user no such namespace permission: {}", &key.tenant),
+                None,
+            )),
+        );
+    }
+    if !user_resource_privilege!(req).check_permission(
+        &key.tenant,
+        &key.group,
+        &key.data_id,
+        action,
+    ) {
+        return Some(
+            HttpResponse::Forbidden().json(ApiResult::<Option<()>>::error(
+                403,
+                format!(
+                    "user no such resource permission: {}/{}/{}",
+                    &key.tenant, &key.group, &key.data_id
+                ),
+                None,
+            )),
+        );
+    }
+    None
+}
+
+fn param_error(msg: String) -> HttpResponse {
+    HttpResponse::BadRequest().json(ApiResult::<Option<()>>::error(400, msg, None))
+}
+
+fn server_error(msg: String) -> HttpResponse {
+    HttpResponse::InternalServerError().json(ApiResult::server_error(msg))
+}
+
+pub(crate) async fn query_config_list(
+    req: HttpRequest,
+    web::Query(param): web::Query<ConfigListParams>,
+    payload: web::Payload,
+    appdata: web::Data<Arc<AppShareData>>,
+) -> impl Responder {
+    let param = merge_web_param!(param, payload).build_query_param(&req);
+    if !param
+        .namespace_privilege
+        .check_option_value_permission(&param.tenant, true)
+    {
+        return HttpResponse::Forbidden().json(ApiResult::<Option<()>>::error(
+            403,
+            format!(
+                "user no such namespace permission: {}",
+                param
+                    .tenant
+                    .as_ref()
+                    .map(|v| v.as_str())
+                    .unwrap_or_default()
+            ),
+            None,
+        ));
+    }
+    match appdata
+        .config_addr
+        .send(ConfigCmd::QueryPageInfo(Box::new(param)))
+        .await
+    {
+        Ok(Ok(ConfigResult::ConfigInfoPage(total_count, list))) => {
+            HttpResponse::Ok().json(ApiResult::success(PageResult { total_count, list }))
+        }
+        Ok(Ok(_)) => {
+            HttpResponse::InternalServerError().json(ApiResult::server_error(String::new()))
+        }
+        Ok(Err(err)) => {
+            HttpResponse::InternalServerError().json(ApiResult::server_error(err.to_string()))
+        }
+        Err(err) => {
+            HttpResponse::InternalServerError().json(ApiResult::server_error(err.to_string()))
+        }
+    }
+}
+
+///
+/// 比较配置历史版本差异,加密配置有解密权限时解密后再比较
+pub(crate) async fn diff_history_config(
+    req: HttpRequest,
+    web::Query(param): web::Query<ConfigHistoryParams>,
+    payload: web::Payload,
+    appdata: web::Data<Arc<AppShareData>>,
+) -> impl Responder {
+    let param = merge_web_param!(param, payload);
+    let config_key = match param.to_key() {
+        Ok(v) => v,
+        Err(msg) => return param_error(msg),
+    };
+    let from_id = match param.from_id {
+        Some(v) => v,
+        None => return param_error("fromId is empty".to_owned()),
+    };
+    if let Some(res) = check_config_permission(&req, &config_key, ResourceAction::Read) {
+        return res;
+    }
+    //加密配置需要与控制台解密接口相同的权限及写权限才返回明文差异
+    let can_decrypt = req
+        .extensions()
+        .get::<Arc<TokenSession>>()
+        .map(|session| UserRole::can_decrypt_config_by_roles(&session.roles))
+        .unwrap_or(false)
+        && check_config_permission(&req, &config_key, ResourceAction::Write).is_none();
+    let cmd = ConfigCmd::DiffHistory {
+        key: config_key.clone(),
+        from_id,
+        to_id: param.to_id,
+    };
+    match appdata.config_addr.send(cmd).await {
+        Ok(Ok(ConfigResult::ConfigHistoryDiff(dto))) => {
+            let is_cipher = ConfigCipher::is_cipher_data_id(&config_key.data_id);
+            match (*dto)
+                .build_diff(&appdata.config_cipher, is_cipher, can_decrypt)
+                .await
+            {
+                Ok(dto) => HttpResponse::Ok().json(ApiResult::success(dto)),
+                Err(err) => server_error(err.to_string()),
+            }
+        }
+        Ok(Ok(_)) => HttpResponse::NotFound().json(ApiResult::<Option<()>>::error(
+            404,
+            format!("config history not found: {}", config_key.build_key()),
+            None,
+        )),
+        Ok(Err(err)) => server_error(err.to_string()),
+        Err(err) => server_error(err.to_string()),
+    }
+}
+
+///
+/// 回滚到指定历史版本,以当前登录用户记录新的历史
+pub(crate) async fn rollback_history_config(
+    req: HttpRequest,
+    web::Query(param): web::Query<ConfigHistoryParams>,
+    payload: web::Payload,
+    appdata: web::Data<Arc<AppShareData>>,
+) -> impl Responder {
+    let param = merge_web_param!(param, payload);
+    let config_key = match param.to_key() {
+        Ok(v) => v,
+        Err(msg) => return param_error(msg),
+    };
+    let history_id = match param.history_id {
+        Some(v) => v,
+        None => return param_error("historyId is empty".to_owned()),
+    };
+    if let Some(res) = check_config_permission(&req, &config_key, ResourceAction::Write) {
+        return res;
+    }
+    let op_user = req
+        .extensions()
+        .get::<Arc<TokenSession>>()
+        .map(|session| session.username.clone());
+    let history = match appdata
+        .config_addr
+        .send(ConfigCmd::GetHistory(config_key.clone(), history_id))
+        .await
+    {
+        Ok(Ok(ConfigResult::HistoryData(history))) => history,
+        _ => {
+            return HttpResponse::NotFound().json(ApiResult::<Option<()>>::error(
+                404,
+                format!("config history not found: {}", config_key.build_key()),
+                None,
+            ))
+        }
+    };
+    let mut set_req = SetConfigReq::new(config_key, history.content);
+    set_req.encrypted_data_key = history.encrypted_data_key;
+    set_req.op_user = op_user;
+    match appdata.config_route.set_config(set_req).await {
+        Ok(_) => HttpResponse::Ok().json(ApiResult::success(true)),
+        Err(err) => server_error(err.to_string()),
+    }
+}
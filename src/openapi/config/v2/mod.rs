@@ -1 +1 @@
-mod api;
+pub mod api;
@@ -6,6 +6,7 @@ use crate::mcp::model::mcp::McpServer;
 use crate::mcp::model::tools::{ConvertType, McpTool, ToolFunctionValue};
 use crate::naming::core::{NamingCmd, NamingResult};
 use crate::naming::model::ServiceKey;
+use crate::naming::route_rule::ServiceRouteRule;
 use crate::openapi::mcp::{HandleOtherResult, IGNORE_TRASFER_HEADERS};
 use actix_web::{web, HttpRequest, HttpResponse, Result};
 use serde_json::{json, Value};
@@ -401,7 +402,9 @@ async fn handle_tools_call(
             *log_args = McpHandleLogArgs::Arg(format!("tool:{}", tool_name));
 
             let (tool, url) =
-                match select_tool_and_url(tool_name, &mcp_server, &args, app_share_data).await {
+                match select_tool_and_url(tool_name, &mcp_server, &args, app_share_data, &headers)
+                    .await
+                {
                     Ok(result) => result,
                     Err(error) => {
                         *log_args =
@@ -543,6 +546,7 @@ async fn select_tool_and_url<'a>(
     server: &'a Arc<McpServer>,
     value: &'a serde_json::Value,
     app_share_data: &Arc<AppShareData>,
+    headers: &HashMap<&str, &[u8]>,
 ) -> anyhow::Result<(&'a McpTool, String)> {
     for tool in server.release_value.tools.iter() {
         if tool.tool_name.as_str() == tool_name {
@@ -553,7 +557,11 @@ async fn select_tool_and_url<'a>(
             );
             if let Ok(Ok(NamingResult::SelectInstance(instance))) = app_share_data
                 .naming_addr
-                .send(NamingCmd::SelectOneInstance(service_key))
+                .send(NamingCmd::SelectOneInstance(
+                    service_key,
+                    tool.route_rule.select.clone(),
+                    ServiceRouteRule::build_headers(headers.iter().map(|(k, v)| (*k, *v))),
+                ))
                 .await
             {
                 let host = instance.map(|i| (i.ip.clone(), i.port as u16));
@@ -4,11 +4,31 @@ use crate::mcp::model::actor_model::{McpManagerReq, McpManagerResult};
 use crate::openapi::mcp_api::model::{
     OpenMcpServerDto, OpenMcpServerQueryParam, OpenToolFunctionValue, OpenToolSpecQueryParam,
 };
-use actix_web::{web, HttpRequest, HttpResponse, Responder};
+use crate::user_namespace_privilege;
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse, Responder};
 use std::sync::Arc;
 
+///
+/// 校验用户命名空间权限,没有权限时返回403
+fn check_namespace_permission(
+    req: &HttpRequest,
+    namespace_id: &Arc<String>,
+) -> Option<HttpResponse> {
+    if user_namespace_privilege!(req).check_permission(namespace_id) {
+        None
+    } else {
+        Some(HttpResponse::Forbidden().json(ApiResult::<String>::error(
+            "NO_PERMISSION".to_string(),
+            Some(format!(
+                "user no such namespace permission: {}",
+                namespace_id
+            )),
+        )))
+    }
+}
+
 pub async fn query_mcp_server_list(
-    _req: HttpRequest,
+    req: HttpRequest,
     query: web::Query<OpenMcpServerQueryParam>,
     appdata: web::Data<Arc<AppShareData>>,
 ) -> impl Responder {
@@ -22,6 +42,11 @@ pub async fn query_mcp_server_list(
     }
 
     let mcp_query_param = param.to_mcp_query_param();
+    if let Some(namespace_id) = &mcp_query_param.namespace_id {
+        if let Some(res) = check_namespace_permission(&req, namespace_id) {
+            return res;
+        }
+    }
     let cmd = McpManagerReq::QueryServer(mcp_query_param);
 
     match appdata.mcp_manager.send(cmd).await {
@@ -49,7 +74,7 @@ pub async fn query_mcp_server_list(
 }
 
 pub async fn query_tool_spec_list(
-    _req: HttpRequest,
+    req: HttpRequest,
     query: web::Query<OpenToolSpecQueryParam>,
     appdata: web::Data<Arc<AppShareData>>,
 ) -> impl Responder {
@@ -63,6 +88,11 @@ pub async fn query_tool_spec_list(
     }
 
     let tool_spec_query_param = param.to_tool_spec_query_param();
+    if let Some(namespace_id) = &tool_spec_query_param.namespace_id {
+        if let Some(res) = check_namespace_permission(&req, &Arc::new(namespace_id.to_owned())) {
+            return res;
+        }
+    }
     let cmd = McpManagerReq::QueryToolSpec(tool_spec_query_param);
 
     match appdata.mcp_manager.send(cmd).await {
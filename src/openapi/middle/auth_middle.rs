@@ -8,6 +8,7 @@ use crate::metrics::metrics_key::MetricsKey;
 use crate::metrics::model::{MetricsItem, MetricsRecord, MetricsRequest};
 use crate::raft::cache::model::{CacheKey, CacheType};
 use crate::raft::cluster::model::{RouterRequest, RouterResponse};
+use crate::user::api_key::{get_api_key_session, ApiKeyUtils};
 use actix::Addr;
 use actix_http::body::EitherBody;
 use actix_http::HttpMessage;
@@ -114,12 +115,7 @@ where
                 true
             } else if token.is_empty() {
                 false
-            } else if let Ok(Some(session)) = get_user_session(
-                &app_share_data,
-                CacheKey::new(CacheType::ApiTokenSession, token.clone()),
-            )
-            .await
-            {
+            } else if let Ok(Some(session)) = get_token_session(&app_share_data, token).await {
                 request.extensions_mut().insert(session);
                 true
             } else {
@@ -194,6 +190,21 @@ fn bytes_to_payload(buf: web::Bytes) -> dev::Payload {
     dev::Payload::from(pl)
 }
 
+async fn get_token_session(
+    app_share_data: &Arc<AppShareData>,
+    token: Arc<String>,
+) -> anyhow::Result<Option<Arc<TokenSession>>> {
+    if ApiKeyUtils::is_api_key(&token) {
+        get_api_key_session(&app_share_data.user_manager, &token).await
+    } else {
+        get_user_session(
+            app_share_data,
+            CacheKey::new(CacheType::ApiTokenSession, token),
+        )
+        .await
+    }
+}
+
 async fn get_user_session(
     app_share_data: &Arc<AppShareData>,
     key: CacheKey,
@@ -3,7 +3,7 @@ use actix_web::web::{scope, ServiceConfig};
 
 use crate::common::AppSysConfig;
 use crate::openapi::auth::login;
-use crate::openapi::config::config_v1_route;
+use crate::openapi::config::{config_v1_route, config_v2_route};
 use crate::openapi::constant::NACOS_PREFIX;
 use crate::openapi::naming::naming_v1_route;
 
@@ -75,6 +75,7 @@ fn openapi_service(conf: RouteConf) -> impl FnOnce(&mut ServiceConfig) {
 
 pub fn openapi_route_config(config: &mut ServiceConfig) {
     config_v1_route(config);
+    config_v2_route(config);
     naming_v1_route(config);
 }
 
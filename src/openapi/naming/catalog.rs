@@ -1,9 +1,11 @@
+use crate::common::model::privilege::ResourceAction;
 use crate::naming::api_model::InstanceVO;
 use crate::naming::core::{NamingActor, NamingCmd, NamingResult};
 use crate::naming::ops::ops_model::{
     OpsQueryServiceInstanceListRequest, OpsQueryServiceInstanceListResponse, OpsServiceDto,
     OpsServiceOptQueryListResponse, OpsServiceQueryListRequest,
 };
+use crate::openapi::naming::check_resource_permission;
 use actix::Addr;
 use actix_web::http::header;
 use actix_web::{get, web, HttpRequest, HttpResponse, Responder, Scope};
@@ -42,6 +44,7 @@ pub async fn query_opt_service_list(
 }
 
 pub async fn get_instance_page(
+    req: HttpRequest,
     param: web::Query<OpsQueryServiceInstanceListRequest>,
     naming_addr: web::Data<Addr<NamingActor>>,
 ) -> impl Responder {
@@ -49,6 +52,9 @@ pub async fn get_instance_page(
     let page_size = param.page_size.unwrap_or(20);
     match param.to_clusters_key() {
         Ok((service_key, cluster)) => {
+            if let Some(res) = check_resource_permission(&req, &service_key, ResourceAction::Read) {
+                return res;
+            }
             match naming_addr
                 .send(NamingCmd::QueryInstancePage {
                     service_key,
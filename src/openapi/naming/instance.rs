@@ -1,25 +1,36 @@
 #![allow(unused_imports, unused_assignments, unused_variables)]
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::net::SocketAddr;
 use std::sync::Arc;
 
 use actix::prelude::*;
 use actix_web::dev::HttpServiceFactory;
-use actix_web::{get, http::header, put, web, HttpResponse, Responder, Scope};
+use actix_web::{
+    get, http::header, put, web, HttpMessage, HttpRequest, HttpResponse, Responder, Scope,
+};
 use serde::{Deserialize, Serialize};
 
 use crate::common::appdata::AppShareData;
+use crate::common::model::privilege::ResourceAction;
 use crate::common::web_utils::get_req_body;
 use crate::merge_web_param;
 use crate::naming::api_model::InstanceVO;
 use crate::naming::core::{NamingActor, NamingCmd, NamingResult};
 use crate::naming::model::{Instance, InstanceUpdateTag, ServiceKey};
+use crate::naming::naming_sse::{NamingSseCmd, NAMING_SSE_CHANNEL_SIZE, NAMING_SSE_CLIENT_PREFIX};
+use crate::naming::naming_subscriber::NamingListenerItem;
+use crate::naming::route_rule::ServiceRouteRule;
 use crate::naming::{
-    NamingUtils, CLIENT_BEAT_INTERVAL_KEY, LIGHT_BEAT_ENABLED_KEY, RESPONSE_CODE_KEY,
-    RESPONSE_CODE_OK,
+    NamingUtils, CLIENT_BEAT_INTERVAL_KEY, DEFAULT_CLIENT_BEAT_INTERVAL, LIGHT_BEAT_ENABLED_KEY,
+    RESPONSE_CODE_KEY, RESPONSE_CODE_OK,
 };
 use crate::openapi::constant::EMPTY;
-use crate::openapi::naming::model::{BeatRequest, InstanceWebParams, InstanceWebQueryListParams};
+use crate::openapi::naming::check_resource_permission;
+use crate::openapi::naming::model::{
+    BeatRequest, InstanceWebParams, InstanceWebQueryListParams, InstanceWebSelectOneParams,
+    InstanceWebWatchParams,
+};
+use crate::user_namespace_privilege;
 use crate::utils::{get_bool_from_string, select_option_by_clone};
 
 pub(super) fn service() -> Scope {
@@ -34,34 +45,45 @@ pub(super) fn service() -> Scope {
         )
         .service(beat_instance)
         .service(get_instance_list)
+        .service(select_one_instance)
+        .service(watch_instances)
 }
 
 pub async fn get_instance(
+    req: HttpRequest,
     param: web::Query<InstanceWebParams>,
     naming_addr: web::Data<Addr<NamingActor>>,
 ) -> impl Responder {
     let instance = param.0.convert_to_instance();
     match instance {
-        Ok(instance) => match naming_addr.send(NamingCmd::Query(instance)).await {
-            Ok(res) => {
-                let result: NamingResult = res.unwrap();
-                match result {
-                    NamingResult::Instance(v) => {
-                        let vo = InstanceVO::from_instance(&v);
-                        HttpResponse::Ok()
-                            .insert_header(header::ContentType(mime::APPLICATION_JSON))
-                            .body(serde_json::to_string(&vo).unwrap())
+        Ok(instance) => {
+            if let Some(res) =
+                check_resource_permission(&req, &instance.get_service_key(), ResourceAction::Read)
+            {
+                return res;
+            }
+            match naming_addr.send(NamingCmd::Query(instance)).await {
+                Ok(res) => {
+                    let result: NamingResult = res.unwrap();
+                    match result {
+                        NamingResult::Instance(v) => {
+                            let vo = InstanceVO::from_instance(&v);
+                            HttpResponse::Ok()
+                                .insert_header(header::ContentType(mime::APPLICATION_JSON))
+                                .body(serde_json::to_string(&vo).unwrap())
+                        }
+                        _ => HttpResponse::InternalServerError().body("error"),
                     }
-                    _ => HttpResponse::InternalServerError().body("error"),
                 }
+                Err(_) => HttpResponse::InternalServerError().body("error"),
             }
-            Err(_) => HttpResponse::InternalServerError().body("error"),
-        },
+        }
         Err(e) => HttpResponse::InternalServerError().body(e),
     }
 }
 
 pub async fn update_instance(
+    req: HttpRequest,
     param: web::Query<InstanceWebParams>,
     payload: web::Payload,
     appdata: web::Data<Arc<AppShareData>>,
@@ -89,6 +111,11 @@ pub async fn update_instance(
     let instance = param.convert_to_instance();
     match instance {
         Ok(instance) => {
+            if let Some(res) =
+                check_resource_permission(&req, &instance.get_service_key(), ResourceAction::Write)
+            {
+                return res;
+            }
             if !instance.check_valid() {
                 HttpResponse::InternalServerError().body("instance check is invalid")
             } else {
@@ -107,6 +134,7 @@ pub async fn update_instance(
 }
 
 pub async fn del_instance(
+    req: HttpRequest,
     param: web::Query<InstanceWebParams>,
     payload: web::Payload,
     appdata: web::Data<Arc<AppShareData>>,
@@ -115,6 +143,11 @@ pub async fn del_instance(
     let instance = param.convert_to_instance();
     match instance {
         Ok(instance) => {
+            if let Some(res) =
+                check_resource_permission(&req, &instance.get_service_key(), ResourceAction::Write)
+            {
+                return res;
+            }
             if !instance.check_valid() {
                 HttpResponse::InternalServerError().body("instance check is invalid")
             } else {
@@ -130,6 +163,7 @@ pub async fn del_instance(
 
 #[put("/beat")]
 pub async fn beat_instance(
+    req: HttpRequest,
     param: web::Query<BeatRequest>,
     payload: web::Payload,
     appdata: web::Data<Arc<AppShareData>>,
@@ -140,6 +174,11 @@ pub async fn beat_instance(
     let instance = param.convert_to_instance();
     match instance {
         Ok(instance) => {
+            if let Some(res) =
+                check_resource_permission(&req, &instance.get_service_key(), ResourceAction::Write)
+            {
+                return res;
+            }
             if !instance.check_valid() {
                 HttpResponse::InternalServerError().body("instance check is invalid")
             } else {
@@ -150,15 +189,17 @@ pub async fn beat_instance(
                     metadata: false,
                     from_update: false,
                 };
+                let beat_instance = instance.clone();
                 match appdata
                     .naming_route
                     .update_instance(instance, Some(tag))
                     .await
                 {
                     Ok(_) => {
+                        let beat_interval = get_client_beat_interval(&appdata, beat_instance).await;
                         let mut result = HashMap::new();
                         result.insert(RESPONSE_CODE_KEY, serde_json::json!(RESPONSE_CODE_OK));
-                        result.insert(CLIENT_BEAT_INTERVAL_KEY, serde_json::json!(5000));
+                        result.insert(CLIENT_BEAT_INTERVAL_KEY, serde_json::json!(beat_interval));
                         result.insert(LIGHT_BEAT_ENABLED_KEY, serde_json::json!(true));
                         let v = serde_json::to_string(&result).unwrap();
                         HttpResponse::Ok()
@@ -173,8 +214,25 @@ pub async fn beat_instance(
     }
 }
 
+///
+/// 返回给客户端的心跳间隔;轻量心跳不带metadata,需要从已注册的实例中读取
+async fn get_client_beat_interval(appdata: &Arc<AppShareData>, instance: Instance) -> i64 {
+    if let Some(v) = instance.get_heart_beat_interval() {
+        return v;
+    }
+    if let Ok(Ok(NamingResult::Instance(v))) =
+        appdata.naming_addr.send(NamingCmd::Query(instance)).await
+    {
+        if let Some(v) = v.get_heart_beat_interval() {
+            return v;
+        }
+    }
+    DEFAULT_CLIENT_BEAT_INTERVAL
+}
+
 #[get("/list")]
 pub async fn get_instance_list(
+    req: HttpRequest,
     param: web::Query<InstanceWebQueryListParams>,
     naming_addr: web::Data<Addr<NamingActor>>,
 ) -> impl Responder {
@@ -182,6 +240,9 @@ pub async fn get_instance_list(
     let addr = param.get_addr();
     match param.to_clusters_key() {
         Ok((key, clusters)) => {
+            if let Some(res) = check_resource_permission(&req, &key, ResourceAction::Read) {
+                return res;
+            }
             match naming_addr
                 .send(NamingCmd::QueryListString(
                     key.clone(),
@@ -206,3 +267,123 @@ pub async fn get_instance_list(
         Err(err) => HttpResponse::InternalServerError().body(err),
     }
 }
+
+///
+/// 按服务路由规则、策略(随机、权重随机、轮询、最久未选中)与就近可用区选择一个健康实例
+#[get("/selectOne")]
+pub async fn select_one_instance(
+    req: HttpRequest,
+    param: web::Query<InstanceWebSelectOneParams>,
+    naming_addr: web::Data<Addr<NamingActor>>,
+) -> impl Responder {
+    let key = match param.to_service_key() {
+        Ok(key) => key,
+        Err(err) => return HttpResponse::InternalServerError().body(err),
+    };
+    if let Some(res) = check_resource_permission(&req, &key, ResourceAction::Read) {
+        return res;
+    }
+    match naming_addr
+        .send(NamingCmd::SelectOneInstance(
+            key,
+            param.to_select_param(),
+            ServiceRouteRule::build_headers(
+                req.headers()
+                    .iter()
+                    .map(|(k, v)| (k.as_str(), v.as_bytes())),
+            ),
+        ))
+        .await
+    {
+        Ok(Ok(NamingResult::SelectInstance(Some(instance)))) => {
+            let vo = InstanceVO::from_instance(&instance);
+            HttpResponse::Ok()
+                .insert_header(header::ContentType(mime::APPLICATION_JSON))
+                .body(serde_json::to_string(&vo).unwrap())
+        }
+        Ok(Ok(NamingResult::SelectInstance(None))) => {
+            HttpResponse::NotFound().body("no healthy instance")
+        }
+        Ok(_) => HttpResponse::InternalServerError().body("error"),
+        Err(err) => HttpResponse::InternalServerError().body(err.to_string()),
+    }
+}
+
+///
+/// 以SSE方式订阅服务实例变化;连接后先推送各服务全量实例(full事件),之后只推送变化(delta事件)
+#[get("/watch")]
+pub async fn watch_instances(
+    req: HttpRequest,
+    param: web::Query<InstanceWebWatchParams>,
+    appdata: web::Data<Arc<AppShareData>>,
+) -> impl Responder {
+    let keys = match param.to_service_keys() {
+        Ok(keys) => keys,
+        Err(err) => return HttpResponse::BadRequest().body(err),
+    };
+    let namespace_privilege = user_namespace_privilege!(req);
+    for key in &keys {
+        if !namespace_privilege.check_permission(&key.namespace_id) {
+            return HttpResponse::Forbidden().body(format!(
+                "user no such namespace permission: {}",
+                &key.namespace_id
+            ));
+        }
+        if let Some(res) = check_resource_permission(&req, key, ResourceAction::Read) {
+            return res;
+        }
+    }
+    let (tx, rx) = tokio::sync::mpsc::channel(NAMING_SSE_CHANNEL_SIZE);
+    let client_id = Arc::new(format!(
+        "{}{}",
+        NAMING_SSE_CLIENT_PREFIX,
+        uuid::Uuid::new_v4().simple()
+    ));
+    appdata
+        .naming_sse_manage
+        .send(NamingSseCmd::AddConn {
+            client_id: client_id.clone(),
+            sender: tx,
+            labels: param.get_labels(),
+            service_keys: keys.clone(),
+        })
+        .await
+        .ok();
+    let items = keys
+        .iter()
+        .map(|key| NamingListenerItem {
+            service_key: key.clone(),
+            clusters: None,
+        })
+        .collect();
+    appdata
+        .naming_addr
+        .send(NamingCmd::Subscribe(items, client_id.clone()))
+        .await
+        .ok();
+    //连接建立后立即推送全量,不等待延迟通知
+    for key in keys {
+        if let Ok(Ok(NamingResult::ServiceInfo(service_info))) = appdata
+            .naming_addr
+            .send(NamingCmd::QueryServiceInfo(
+                key.clone(),
+                "".to_owned(),
+                true,
+            ))
+            .await
+        {
+            let mut client_id_set = HashSet::new();
+            client_id_set.insert(client_id.clone());
+            appdata.naming_sse_manage.do_send(NamingSseCmd::Notify(
+                key,
+                client_id_set,
+                service_info,
+            ));
+        }
+    }
+    HttpResponse::Ok()
+        .insert_header(("content-type", "text/event-stream; charset=utf-8"))
+        .insert_header(("cache-control", "no-cache"))
+        .insert_header(("x-accel-buffering", "no"))
+        .streaming(tokio_stream::wrappers::ReceiverStream::new(rx))
+}
@@ -1,7 +1,10 @@
+use crate::common::model::privilege::ResourceAction;
+use crate::naming::model::ServiceKey;
 use crate::openapi::constant::{EMPTY, NAMING_V1_BASE_PATH};
 use crate::openapi::naming::catalog::{get_instance_page, query_opt_service_list};
 use crate::openapi::naming::instance::{
-    beat_instance, del_instance, get_instance, get_instance_list, update_instance,
+    beat_instance, del_instance, get_instance, get_instance_list, select_one_instance,
+    update_instance, watch_instances,
 };
 use crate::openapi::naming::operator::{
     mock_get_switches, mock_operator_metrics, mock_put_switches,
@@ -11,7 +14,8 @@ use crate::openapi::naming::service::{
 };
 use crate::openapi::RouteConf;
 use actix_web::web::{scope, ServiceConfig};
-use actix_web::{web, Scope};
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse, Scope};
+use std::sync::Arc;
 
 mod catalog;
 pub(crate) mod instance;
@@ -20,6 +24,47 @@ mod operator;
 pub(crate) mod service;
 mod v2;
 
+///
+/// 校验用户命名空间与细粒度数据权限,没有权限时返回403;
+/// api key限定的命名空间只记录在会话的命名空间权限中
+pub(crate) fn check_resource_permission(
+    req: &HttpRequest,
+    key: &ServiceKey,
+    action: ResourceAction,
+) -> Option<HttpResponse> {
+    if let Some(res) = check_namespace_permission(req, &key.namespace_id) {
+        Some(res)
+    } else if crate::user_resource_privilege!(req).check_permission(
+        &key.namespace_id,
+        &key.group_name,
+        &key.service_name,
+        action,
+    ) {
+        None
+    } else {
+        Some(HttpResponse::Forbidden().body(format!(
+            "user no such resource permission: {}/{}/{}",
+            &key.namespace_id, &key.group_name, &key.service_name
+        )))
+    }
+}
+
+///
+/// 校验用户命名空间权限,没有权限时返回403
+pub(crate) fn check_namespace_permission(
+    req: &HttpRequest,
+    namespace_id: &Arc<String>,
+) -> Option<HttpResponse> {
+    if crate::user_namespace_privilege!(req).check_permission(namespace_id) {
+        None
+    } else {
+        Some(HttpResponse::Forbidden().body(format!(
+            "user no such namespace permission: {}",
+            namespace_id
+        )))
+    }
+}
+
 pub fn openapi_service(conf: RouteConf) -> Vec<Scope> {
     vec![openapi_v1_route(conf)]
 }
@@ -45,7 +90,9 @@ pub fn naming_v1_route(config: &mut ServiceConfig) {
                         .route(web::delete().to(del_instance)),
                 )
                 .service(beat_instance)
-                .service(get_instance_list),
+                .service(get_instance_list)
+                .service(select_one_instance)
+                .service(watch_instances),
         )
         .service(
             scope("/nacos/v1/ns/service")
@@ -76,3 +123,33 @@ pub fn naming_v1_route(config: &mut ServiceConfig) {
                 .service(web::resource("instances").route(web::get().to(get_instance_page))),
         );
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::model::privilege::PrivilegeGroup;
+    use crate::common::model::TokenSession;
+    use actix_web::http::StatusCode;
+    use actix_web::test::TestRequest;
+    use std::collections::HashSet;
+
+    #[test]
+    fn api_key_namespace_scope() {
+        //api key限定命名空间后只保留在会话的命名空间白名单中
+        let mut namespace_privilege = PrivilegeGroup::empty();
+        namespace_privilege.whitelist = Some(Arc::new(HashSet::from([Arc::new("dev".to_owned())])));
+        let req = TestRequest::default().to_http_request();
+        req.extensions_mut().insert(Arc::new(TokenSession {
+            username: Arc::new("test".to_owned()),
+            namespace_privilege: Some(namespace_privilege),
+            ..Default::default()
+        }));
+        let dev_key = ServiceKey::new("dev", "DEFAULT_GROUP", "foo");
+        assert!(check_resource_permission(&req, &dev_key, ResourceAction::Write).is_none());
+        let prod_key = ServiceKey::new("prod", "DEFAULT_GROUP", "foo");
+        let res = check_resource_permission(&req, &prod_key, ResourceAction::Read).unwrap();
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+        let res = check_namespace_permission(&req, &prod_key.namespace_id).unwrap();
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+    }
+}
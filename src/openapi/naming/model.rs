@@ -1,6 +1,8 @@
 #![allow(unused_imports, unused_assignments, unused_variables)]
 use crate::common::option_utils::OptionUtils;
 use crate::common::string_utils::StringUtils;
+use crate::naming::balancer::{InstanceSelectParam, SelectStrategy};
+use crate::naming::health_check::DEFAULT_CLUSTER_NAME;
 use crate::naming::model::{Instance, ServiceKey};
 use crate::naming::service::{ServiceInfoDto, SubscriberInfoDto};
 use crate::naming::NamingUtils;
@@ -174,6 +176,94 @@ impl InstanceWebQueryListParams {
     }
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstanceWebSelectOneParams {
+    pub namespace_id: Option<String>,
+    pub service_name: Option<String>,
+    pub group_name: Option<String>,
+    pub strategy: Option<String>,
+    pub zone: Option<String>,
+    pub region: Option<String>,
+}
+
+impl InstanceWebSelectOneParams {
+    pub(crate) fn to_service_key(&self) -> Result<ServiceKey, String> {
+        let grouped_name = self.service_name.clone().unwrap_or_default();
+        let (mut group_name, service_name) =
+            match NamingUtils::split_group_and_service_name(&grouped_name) {
+                Some(v) => v,
+                None => return Err("serviceName is invalid!".to_owned()),
+            };
+        if let Some(_group_name) = self.group_name.as_ref() {
+            if !_group_name.is_empty() {
+                _group_name.clone_into(&mut group_name)
+            }
+        }
+        let namespace_id =
+            NamingUtils::default_namespace(self.namespace_id.clone().unwrap_or_default());
+        Ok(ServiceKey::new(&namespace_id, &group_name, &service_name))
+    }
+
+    pub(crate) fn to_select_param(&self) -> InstanceSelectParam {
+        InstanceSelectParam {
+            strategy: self
+                .strategy
+                .as_ref()
+                .map(|e| SelectStrategy::from_str_or_default(e))
+                .unwrap_or_default(),
+            zone: StringUtils::map_not_empty(self.zone.clone()).map(Arc::new),
+            region: StringUtils::map_not_empty(self.region.clone()).map(Arc::new),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstanceWebWatchParams {
+    pub namespace_id: Option<String>,
+    /// 多个服务用逗号分隔,支持group@@service格式
+    pub service_names: Option<String>,
+    pub group_name: Option<String>,
+    /// 消费者标签,用于服务选择器过滤;支持json与k1=v1,k2=v2格式
+    pub labels: Option<String>,
+}
+
+impl InstanceWebWatchParams {
+    pub(crate) fn to_service_keys(&self) -> Result<Vec<ServiceKey>, String> {
+        let namespace_id =
+            NamingUtils::default_namespace(self.namespace_id.clone().unwrap_or_default());
+        let default_group = NamingUtils::default_group(self.group_name.clone().unwrap_or_default());
+        let mut keys = vec![];
+        for grouped_name in
+            NamingUtils::split_filters(self.service_names.as_deref().unwrap_or_default())
+        {
+            let (group_name, service_name) = match grouped_name.split_once("@@") {
+                Some((group_name, service_name)) if !group_name.is_empty() => {
+                    (group_name, service_name)
+                }
+                Some((_, service_name)) => (default_group.as_str(), service_name),
+                None => (default_group.as_str(), grouped_name.as_str()),
+            };
+            if service_name.is_empty() {
+                return Err("serviceNames is invalid!".to_owned());
+            }
+            keys.push(ServiceKey::new(&namespace_id, group_name, service_name));
+        }
+        if keys.is_empty() {
+            return Err("serviceNames is empty!".to_owned());
+        }
+        Ok(keys)
+    }
+
+    pub(crate) fn get_labels(&self) -> HashMap<String, String> {
+        self.labels
+            .as_ref()
+            .and_then(|e| NamingUtils::parse_metadata(e).ok())
+            .unwrap_or_default()
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct BeatRequest {
@@ -386,11 +476,26 @@ pub struct ClusterVo {
 impl ServiceInfoVo {
     /// 从 ServiceInfoDto 创建 ServiceInfoVo，使用默认的命名空间和集群信息
     pub fn from_dto(dto: ServiceInfoDto, namespace_id: Arc<String>) -> Self {
-        let mut selector = HashMap::new();
-        selector.insert("type".to_owned(), "none".to_string());
-        selector.insert("contextType".to_owned(), "NONE".to_string());
-        let mut health_checker = HashMap::new();
-        health_checker.insert("type".to_owned(), "TCP".to_string());
+        let selector = dto.selector.unwrap_or_default().to_map();
+        let health_check = dto.health_check.unwrap_or_default();
+        let default_checker = health_check.checker.clone().unwrap_or_default();
+        let mut clusters = vec![ClusterVo {
+            name: DEFAULT_CLUSTER_NAME.to_string(),
+            health_checker: health_check
+                .get_checker(DEFAULT_CLUSTER_NAME)
+                .unwrap_or(&default_checker)
+                .to_map(),
+            metadata: HashMap::new(),
+        }];
+        for (name, checker) in &health_check.clusters {
+            if name != DEFAULT_CLUSTER_NAME {
+                clusters.push(ClusterVo {
+                    name: name.to_owned(),
+                    health_checker: checker.to_map(),
+                    metadata: HashMap::new(),
+                });
+            }
+        }
 
         Self {
             namespace_id,
@@ -399,11 +504,7 @@ impl ServiceInfoVo {
             protect_threshold: dto.protect_threshold.unwrap_or(0.0),
             metadata: dto.metadata,
             selector,
-            clusters: vec![ClusterVo {
-                name: "DEFAULT".to_string(),
-                health_checker,
-                metadata: HashMap::new(),
-            }],
+            clusters,
         }
     }
 }
@@ -1,3 +1,4 @@
+use crate::common::model::privilege::ResourceAction;
 use crate::merge_web_param;
 use crate::naming::api_model::ServiceInfoParam;
 use crate::naming::core::{NamingActor, NamingCmd, NamingResult};
@@ -8,9 +9,10 @@ use crate::openapi::naming::model::{
     ServiceInfoVo, ServiceQueryListRequest, ServiceQueryListResponce,
     ServiceQuerySubscribersListResponce,
 };
+use crate::openapi::naming::{check_namespace_permission, check_resource_permission};
 use actix::Addr;
 use actix_web::http::header;
-use actix_web::{web, HttpResponse, Responder, Scope};
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse, Responder, Scope};
 
 pub(super) fn service() -> Scope {
     web::scope("/service")
@@ -26,6 +28,7 @@ pub(super) fn service() -> Scope {
 }
 
 pub async fn query_service(
+    req: HttpRequest,
     param: web::Query<ServiceQueryListRequest>,
     naming_addr: web::Data<Addr<NamingActor>>,
 ) -> impl Responder {
@@ -40,6 +43,9 @@ pub async fn query_service(
                 .to_owned(),
         );
         let service_key = ServiceKey::new(&namespace_id, &group, &service_name);
+        if let Some(res) = check_resource_permission(&req, &service_key, ResourceAction::Read) {
+            return res;
+        }
         match naming_addr
             .send(NamingCmd::QueryServiceOnly(service_key.clone()))
             .await
@@ -69,6 +75,7 @@ pub async fn query_service(
 }
 
 pub async fn update_service(
+    req: HttpRequest,
     param: web::Query<ServiceInfoParam>,
     payload: web::Payload,
     naming_addr: web::Data<Addr<NamingActor>>,
@@ -76,6 +83,13 @@ pub async fn update_service(
     let param = merge_web_param!(param.0, payload);
     match param.build_service_info() {
         Ok(service_info) => {
+            if let Some(res) = check_resource_permission(
+                &req,
+                &service_info.to_service_key(),
+                ResourceAction::Write,
+            ) {
+                return res;
+            }
             let _ = naming_addr
                 .send(NamingCmd::UpdateService(service_info))
                 .await;
@@ -86,6 +100,7 @@ pub async fn update_service(
 }
 
 pub async fn remove_service(
+    req: HttpRequest,
     param: web::Query<ServiceInfoParam>,
     payload: web::Payload,
     naming_addr: web::Data<Addr<NamingActor>>,
@@ -94,6 +109,9 @@ pub async fn remove_service(
     match param.build_service_info() {
         Ok(service_info) => {
             let key = service_info.to_service_key();
+            if let Some(res) = check_resource_permission(&req, &key, ResourceAction::Write) {
+                return res;
+            }
             match naming_addr.send(NamingCmd::RemoveService(key)).await {
                 Ok(res) => {
                     let res: anyhow::Result<NamingResult> = res;
@@ -110,6 +128,7 @@ pub async fn remove_service(
 }
 
 pub async fn query_service_list(
+    req: HttpRequest,
     param: web::Query<ServiceQueryListRequest>,
     naming_addr: web::Data<Addr<NamingActor>>,
 ) -> impl Responder {
@@ -130,8 +149,16 @@ pub async fn query_service_list(
             .to_owned(),
     );
     let key = ServiceKey::new(&namespace_id, &group, "");
+    if let Some(res) = check_namespace_permission(&req, &key.namespace_id) {
+        return res;
+    }
     match naming_addr
-        .send(NamingCmd::QueryServicePage(key, page_size, page_index))
+        .send(NamingCmd::QueryServicePage(
+            key,
+            page_size,
+            page_index,
+            crate::user_resource_privilege!(req),
+        ))
         .await
     {
         Ok(res) => {
@@ -151,6 +178,7 @@ pub async fn query_service_list(
 /// 控制台的接口应该走v2的接口,标记废弃
 /// #[deprecated]
 pub async fn query_subscribers_list(
+    req: HttpRequest,
     param: web::Query<ServiceQueryListRequest>,
     naming_addr: web::Data<Addr<NamingActor>>,
 ) -> impl Responder {
@@ -177,6 +205,9 @@ pub async fn query_subscribers_list(
         .to_owned();
 
     let key = ServiceKey::new(&namespace_id, &group, &service);
+    if let Some(res) = check_resource_permission(&req, &key, ResourceAction::Read) {
+        return res;
+    }
     match naming_addr
         .send(NamingCmd::QueryServiceSubscribersPage(
             key, page_size, page_index,
@@ -3,8 +3,8 @@ use crate::common::option_utils::OptionUtils;
 use crate::common::string_utils::StringUtils;
 use crate::console::model::{ConsoleResult, NamespaceInfo};
 use crate::console::NamespaceUtils;
-use crate::merge_web_param;
-use actix_web::{web, HttpResponse, Responder};
+use crate::{merge_web_param, user_namespace_privilege};
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse, Responder};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use uuid::Uuid;
@@ -85,22 +85,65 @@ impl From<NamespaceParam> for NamespaceInfo {
             ),
             namespace_name: OptionUtils::select(value.namespace_show_name, value.namespace_name),
             r#type: None,
+            config_validate_enforce: None,
         }
     }
 }
 
-pub async fn query_namespace_list(app_data: web::Data<Arc<AppShareData>>) -> impl Responder {
+///
+/// 校验用户命名空间权限,没有权限时返回错误信息
+pub(crate) fn check_namespace_permission(
+    req: &HttpRequest,
+    namespace_id: &Option<Arc<String>>,
+) -> Option<String> {
+    let namespace_privilege = user_namespace_privilege!(req);
+    if namespace_privilege.check_option_value_permission(namespace_id, namespace_privilege.is_all())
+    {
+        None
+    } else {
+        Some(format!(
+            "user no such namespace permission: {}",
+            namespace_id
+                .as_ref()
+                .map(|v| v.as_str())
+                .unwrap_or_default()
+        ))
+    }
+}
+
+///
+/// 过滤出用户有权限的命名空间
+pub(crate) fn filter_namespaces(
+    req: &HttpRequest,
+    namespaces: &[NamespaceInfo],
+) -> Vec<NamespaceVO> {
+    let namespace_privilege = user_namespace_privilege!(req);
+    namespaces
+        .iter()
+        .filter(|e| {
+            namespace_privilege
+                .check_option_value_permission(&e.namespace_id, namespace_privilege.is_all())
+        })
+        .map(|e| e.clone().into())
+        .collect()
+}
+
+pub async fn query_namespace_list(
+    req: HttpRequest,
+    app_data: web::Data<Arc<AppShareData>>,
+) -> impl Responder {
     let namespaces = if let Ok(namespaces) = NamespaceUtils::get_namespaces(&app_data).await {
         namespaces
     } else {
         return HttpResponse::InternalServerError().body("system error");
     };
-    let list: Vec<NamespaceVO> = namespaces.iter().map(|e| e.clone().into()).collect();
+    let list = filter_namespaces(&req, &namespaces);
     let result = ConsoleResult::success(list);
     HttpResponse::Ok().json(result)
 }
 
 pub async fn add_namespace(
+    req: HttpRequest,
     web::Query(param): web::Query<NamespaceParam>,
     payload: web::Payload,
     app_data: web::Data<Arc<AppShareData>>,
@@ -110,6 +153,9 @@ pub async fn add_namespace(
     if StringUtils::is_option_empty_arc(&param.namespace_id) {
         param.namespace_id = Some(Arc::new(Uuid::new_v4().to_string()));
     }
+    if let Some(msg) = check_namespace_permission(&req, &param.namespace_id) {
+        return HttpResponse::Forbidden().body(msg);
+    }
     match NamespaceUtils::add_namespace(&app_data, param).await {
         Ok(_) => HttpResponse::Ok()
             .content_type("text/html; charset=utf-8")
@@ -121,12 +167,16 @@ pub async fn add_namespace(
 }
 
 pub async fn update_namespace(
+    req: HttpRequest,
     web::Query(param): web::Query<NamespaceParam>,
     payload: web::Payload,
     app_data: web::Data<Arc<AppShareData>>,
 ) -> impl Responder {
-    let param = merge_web_param!(param, payload);
-    match NamespaceUtils::update_namespace(&app_data, param.into()).await {
+    let param: NamespaceInfo = merge_web_param!(param, payload).into();
+    if let Some(msg) = check_namespace_permission(&req, &param.namespace_id) {
+        return HttpResponse::Forbidden().body(msg);
+    }
+    match NamespaceUtils::update_namespace(&app_data, param).await {
         Ok(_) => HttpResponse::Ok()
             .content_type("text/html; charset=utf-8")
             .body("true"),
@@ -137,11 +187,15 @@ pub async fn update_namespace(
 }
 
 pub async fn remove_namespace(
+    req: HttpRequest,
     web::Query(param): web::Query<NamespaceParam>,
     payload: web::Payload,
     app_data: web::Data<Arc<AppShareData>>,
 ) -> impl Responder {
     let param = merge_web_param!(param, payload);
+    if let Some(msg) = check_namespace_permission(&req, &param.namespace_id) {
+        return HttpResponse::Forbidden().body(msg);
+    }
     match NamespaceUtils::remove_namespace(&app_data, param.namespace_id).await {
         Ok(_) => HttpResponse::Ok()
             .content_type("text/html; charset=utf-8")
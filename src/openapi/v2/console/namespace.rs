@@ -3,29 +3,38 @@ use crate::common::string_utils::StringUtils;
 use crate::console::model::NamespaceInfo;
 use crate::console::NamespaceUtils;
 use crate::merge_web_param;
-use crate::openapi::v1::console::namespace::{NamespaceParam, NamespaceVO};
+use crate::openapi::v1::console::namespace::{
+    check_namespace_permission, filter_namespaces, NamespaceParam, NamespaceVO,
+};
 use crate::openapi::v2::model::ApiResult;
-use actix_web::{web, HttpResponse, Responder};
+use actix_web::{web, HttpRequest, HttpResponse, Responder};
 use std::sync::Arc;
 use uuid::Uuid;
 
-pub async fn query_namespace_list(app_data: web::Data<Arc<AppShareData>>) -> impl Responder {
+pub async fn query_namespace_list(
+    req: HttpRequest,
+    app_data: web::Data<Arc<AppShareData>>,
+) -> impl Responder {
     let namespaces = match NamespaceUtils::get_namespaces(&app_data).await {
         Ok(namespaces) => namespaces,
         Err(e) => {
             return HttpResponse::InternalServerError().json(ApiResult::server_error(e.to_string()))
         }
     };
-    let list: Vec<NamespaceVO> = namespaces.iter().map(|e| e.clone().into()).collect();
+    let list = filter_namespaces(&req, &namespaces);
     HttpResponse::Ok().json(ApiResult::success(list))
 }
 
 pub async fn query_namespace(
+    req: HttpRequest,
     web::Query(param): web::Query<NamespaceParam>,
     payload: web::Payload,
     app_data: web::Data<Arc<AppShareData>>,
 ) -> impl Responder {
     let param = merge_web_param!(param, payload);
+    if let Some(msg) = check_namespace_permission(&req, &param.namespace_id) {
+        return HttpResponse::Forbidden().json(ApiResult::<Option<()>>::error(403, msg, None));
+    }
     match NamespaceUtils::get_namespace(&app_data, param.namespace_id).await {
         Ok(namespace) => {
             let namespace: NamespaceVO = namespace.into();
@@ -36,6 +45,7 @@ pub async fn query_namespace(
 }
 
 pub async fn add_namespace(
+    req: HttpRequest,
     web::Query(param): web::Query<NamespaceParam>,
     payload: web::Payload,
     app_data: web::Data<Arc<AppShareData>>,
@@ -45,6 +55,9 @@ pub async fn add_namespace(
     if StringUtils::is_option_empty_arc(&param.namespace_id) {
         param.namespace_id = Some(Arc::new(Uuid::new_v4().to_string()));
     }
+    if let Some(msg) = check_namespace_permission(&req, &param.namespace_id) {
+        return HttpResponse::Forbidden().json(ApiResult::<Option<()>>::error(403, msg, None));
+    }
     match NamespaceUtils::add_namespace(&app_data, param).await {
         Ok(_) => HttpResponse::Ok().json(ApiResult::success(true)),
         Err(e) => HttpResponse::InternalServerError().json(ApiResult::server_error(e.to_string())),
@@ -52,23 +65,31 @@ pub async fn add_namespace(
 }
 
 pub async fn update_namespace(
+    req: HttpRequest,
     web::Query(param): web::Query<NamespaceParam>,
     payload: web::Payload,
     app_data: web::Data<Arc<AppShareData>>,
 ) -> impl Responder {
-    let param = merge_web_param!(param, payload);
-    match NamespaceUtils::update_namespace(&app_data, param.into()).await {
+    let param: NamespaceInfo = merge_web_param!(param, payload).into();
+    if let Some(msg) = check_namespace_permission(&req, &param.namespace_id) {
+        return HttpResponse::Forbidden().json(ApiResult::<Option<()>>::error(403, msg, None));
+    }
+    match NamespaceUtils::update_namespace(&app_data, param).await {
         Ok(_) => HttpResponse::Ok().json(ApiResult::success(true)),
         Err(e) => HttpResponse::InternalServerError().json(ApiResult::server_error(e.to_string())),
     }
 }
 
 pub async fn remove_namespace(
+    req: HttpRequest,
     web::Query(param): web::Query<NamespaceParam>,
     payload: web::Payload,
     app_data: web::Data<Arc<AppShareData>>,
 ) -> impl Responder {
     let param = merge_web_param!(param, payload);
+    if let Some(msg) = check_namespace_permission(&req, &param.namespace_id) {
+        return HttpResponse::Forbidden().json(ApiResult::<Option<()>>::error(403, msg, None));
+    }
     match NamespaceUtils::remove_namespace(&app_data, param.namespace_id).await {
         Ok(_) => HttpResponse::Ok().json(ApiResult::success(true)),
         Err(e) => HttpResponse::InternalServerError().json(ApiResult::server_error(e.to_string())),
@@ -34,6 +34,8 @@ pub async fn handle_route(
             op_user,
             config_type,
             desc,
+            tags,
+            encrypted_data_key,
             extend_info: _,
         } => {
             let config_key: ConfigKey = (&key as &str).into();
@@ -44,6 +46,8 @@ pub async fn handle_route(
                     op_user,
                     config_type,
                     desc,
+                    tags,
+                    encrypted_data_key,
                 })
                 .await??;
             Ok(RouterResponse::None)
@@ -58,6 +62,16 @@ pub async fn handle_route(
                 .await??;
             Ok(RouterResponse::None)
         }
+        RouterRequest::ConfigGrayPromote { key, op_user } => {
+            let config_key: ConfigKey = (&key as &str).into();
+            app.config_addr
+                .send(ConfigAsyncCmd::PromoteGray {
+                    key: config_key,
+                    op_user,
+                })
+                .await??;
+            Ok(RouterResponse::None)
+        }
         RouterRequest::JoinNode {
             node_id,
             node_addr: addr,
@@ -28,6 +28,8 @@ pub struct SetConfigReq {
     pub op_user: Option<Arc<String>>,
     pub config_type: Option<Arc<String>>,
     pub desc: Option<Arc<String>>,
+    pub tags: Option<Vec<Arc<String>>>,
+    pub encrypted_data_key: Option<Arc<String>>,
     //pub can_route_to_remote: bool,
     //pub extend_info: Option<HashMap<String,String>>,
 }
@@ -40,6 +42,8 @@ impl SetConfigReq {
             op_user: None,
             config_type: None,
             desc: None,
+            tags: None,
+            encrypted_data_key: None,
         }
     }
 
@@ -54,6 +58,8 @@ impl SetConfigReq {
             op_user: Some(op_user),
             config_type: None,
             desc: None,
+            tags: None,
+            encrypted_data_key: None,
         }
     }
 
@@ -92,12 +98,20 @@ pub enum RouterRequest {
         op_user: Option<Arc<String>>,
         config_type: Option<Arc<String>>,
         desc: Option<Arc<String>>,
+        #[serde(default)]
+        tags: Option<Vec<Arc<String>>>,
+        #[serde(default)]
+        encrypted_data_key: Option<Arc<String>>,
         extend_info: HashMap<String, String>,
     },
     ConfigDel {
         key: String,
         extend_info: HashMap<String, String>,
     },
+    ConfigGrayPromote {
+        key: String,
+        op_user: Option<Arc<String>>,
+    },
     JoinNode {
         node_id: u64,
         node_addr: Arc<String>,
@@ -131,6 +145,8 @@ impl From<SetConfigReq> for RouterRequest {
             op_user: req.op_user,
             config_type: req.config_type,
             desc: req.desc,
+            tags: req.tags,
+            encrypted_data_key: req.encrypted_data_key,
             extend_info: Default::default(),
         }
     }
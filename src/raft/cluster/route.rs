@@ -1,5 +1,6 @@
 use super::model::{DelConfigReq, RouteAddr, RouterRequest, RouterResponse, SetConfigReq};
 use crate::common::appdata::AppShareData;
+use crate::config::config_cipher::ConfigCipher;
 use crate::grpc::handler::RAFT_ROUTE_REQUEST;
 use crate::namespace::model::{NamespaceRaftReq, NamespaceRaftResult};
 use crate::raft::cluster::router_request;
@@ -8,7 +9,7 @@ use crate::raft::store::{ClientRequest, ClientResponse};
 use crate::transfer::model::{TransferImportParam, TransferImportRequest, TransferImportResponse};
 use crate::transfer::reader::TransferImportManager;
 use crate::{
-    config::core::{ConfigActor, ConfigAsyncCmd, ConfigCmd},
+    config::core::{ConfigActor, ConfigAsyncCmd, ConfigCmd, ConfigKey, ConfigResult},
     grpc::PayloadUtils,
     raft::{network::factory::RaftClusterRequestSender, NacosRaft},
 };
@@ -61,6 +62,7 @@ pub struct ConfigRoute {
     config_addr: Addr<ConfigActor>,
     raft_addr_route: Arc<RaftAddrRouter>,
     cluster_sender: Arc<RaftClusterRequestSender>,
+    config_cipher: Arc<ConfigCipher>,
 }
 
 impl ConfigRoute {
@@ -68,11 +70,13 @@ impl ConfigRoute {
         config_addr: Addr<ConfigActor>,
         raft_addr_route: Arc<RaftAddrRouter>,
         cluster_sender: Arc<RaftClusterRequestSender>,
+        config_cipher: Arc<ConfigCipher>,
     ) -> Self {
         Self {
             config_addr,
             raft_addr_route,
             cluster_sender,
+            config_cipher,
         }
     }
 
@@ -80,7 +84,41 @@ impl ConfigRoute {
         anyhow::anyhow!("unknown the raft leader addr!")
     }
 
-    pub async fn set_config(&self, req: SetConfigReq) -> anyhow::Result<()> {
+    ///
+    /// 加密配置明文未变化时沿用当前密文,避免每次发布使用新的随机数重新加密导致md5变化
+    async fn get_unchanged_cipher(&self, req: &SetConfigReq) -> Option<(Arc<String>, Arc<String>)> {
+        if let Ok(Ok(ConfigResult::Data {
+            value,
+            encrypted_data_key: Some(encrypted_data_key),
+            ..
+        })) = self
+            .config_addr
+            .send(ConfigCmd::GET(req.config_key.clone()))
+            .await
+        {
+            let plain = self
+                .config_cipher
+                .decrypt(&value, &encrypted_data_key)
+                .ok()?;
+            if plain.as_str() == req.value.as_str() {
+                return Some((value, encrypted_data_key));
+            }
+        }
+        None
+    }
+
+    pub async fn set_config(&self, mut req: SetConfigReq) -> anyhow::Result<()> {
+        //加密配置在写入raft前加密;客户端已加密(带encrypted_data_key)的内容直接保存
+        if req.encrypted_data_key.is_none()
+            && ConfigCipher::is_cipher_data_id(&req.config_key.data_id)
+        {
+            let (value, encrypted_data_key) = match self.get_unchanged_cipher(&req).await {
+                Some(v) => v,
+                None => self.config_cipher.encrypt(&req.value)?,
+            };
+            req.value = value;
+            req.encrypted_data_key = Some(encrypted_data_key);
+        }
         match self.raft_addr_route.get_route_addr().await? {
             RouteAddr::Local => {
                 let cmd = ConfigAsyncCmd::Add {
@@ -89,6 +127,8 @@ impl ConfigRoute {
                     op_user: req.op_user,
                     config_type: req.config_type,
                     desc: req.desc,
+                    tags: req.tags,
+                    encrypted_data_key: req.encrypted_data_key,
                 };
                 self.config_addr.send(cmd).await?.ok();
             }
@@ -132,6 +172,39 @@ impl ConfigRoute {
         }
         Ok(())
     }
+
+    ///
+    /// 灰度配置全量发布,由leader分配历史记录id后通过一次raft请求完成
+    pub async fn promote_gray_config(
+        &self,
+        config_key: ConfigKey,
+        op_user: Option<Arc<String>>,
+    ) -> anyhow::Result<()> {
+        match self.raft_addr_route.get_route_addr().await? {
+            RouteAddr::Local => {
+                let cmd = ConfigAsyncCmd::PromoteGray {
+                    key: config_key,
+                    op_user,
+                };
+                self.config_addr.send(cmd).await??;
+            }
+            RouteAddr::Remote(_, addr) => {
+                let req = RouterRequest::ConfigGrayPromote {
+                    key: config_key.build_key(),
+                    op_user,
+                };
+                let request = serde_json::to_string(&req).unwrap_or_default();
+                let payload = PayloadUtils::build_payload(RAFT_ROUTE_REQUEST, request);
+                let resp_payload = self.cluster_sender.send_request(addr, payload).await?;
+                let body_vec = resp_payload.body.unwrap_or_default().value;
+                let _: RouterResponse = serde_json::from_slice(&body_vec)?;
+            }
+            RouteAddr::Unknown => {
+                return Err(self.unknown_err());
+            }
+        }
+        Ok(())
+    }
 }
 
 ///
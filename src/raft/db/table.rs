@@ -1,4 +1,5 @@
 use std::collections::BTreeMap;
+use std::ops::Bound;
 use std::{
     collections::HashMap,
     sync::{Arc, Weak},
@@ -13,7 +14,7 @@ use actix::prelude::*;
 use crate::cache::actor_model::CacheManagerRaftReq;
 use crate::cache::adaptation::AdaptationUtils;
 use crate::cache::core::DirectCacheManager;
-use crate::common::constant::{CACHE_TREE_NAME, USER_TREE_NAME};
+use crate::common::constant::{API_KEY_TREE_NAME, CACHE_TREE_NAME, ROLE_TREE_NAME, USER_TREE_NAME};
 use crate::common::sequence_utils::SimpleSequence;
 use crate::raft::filestore::model::SnapshotRecordDto;
 use crate::raft::filestore::raftsnapshot::{SnapshotWriterActor, SnapshotWriterRequest};
@@ -23,7 +24,8 @@ use crate::transfer::model::{
 };
 use crate::transfer::writer::TransferWriterActor;
 use crate::user::build_password_hash;
-use crate::user::model::UserDo;
+use crate::user::model::{RoleDo, UserDo};
+use crate::user::permission::UserRoleHelper;
 use crate::{
     common::string_utils::StringUtils,
     raft::{
@@ -216,6 +218,46 @@ impl TableManager {
         }
     }
 
+    ///
+    /// 按key范围查询,start包含、end不包含;用于key有序的表按游标分批读取
+    pub(crate) fn query_range_list(
+        &self,
+        name: Arc<String>,
+        start_key: Option<Vec<u8>>,
+        end_key: Option<Vec<u8>>,
+        limit: Option<i64>,
+        is_rev: bool,
+    ) -> (usize, Vec<TableKV>) {
+        let table_info = if let Some(table_info) = self.table_map.get(&name) {
+            table_info
+        } else {
+            return (0, vec![]);
+        };
+        let total = table_info.table_data.len();
+        if let (Some(start_key), Some(end_key)) = (&start_key, &end_key) {
+            if start_key >= end_key {
+                return (total, vec![]);
+            }
+        }
+        let range = (
+            start_key.map_or(Bound::Unbounded, Bound::Included),
+            end_key.map_or(Bound::Unbounded, Bound::Excluded),
+        );
+        let limit = limit.map(|v| v as usize).unwrap_or(usize::MAX);
+        let iter = table_info.table_data.range(range);
+        let list = if is_rev {
+            iter.rev()
+                .take(limit)
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect()
+        } else {
+            iter.take(limit)
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect()
+        };
+        (total, list)
+    }
+
     pub(crate) fn query_list_count(&self, name: &Arc<String>, like_key: &Option<String>) -> usize {
         if let Some(table_info) = self.table_map.get(name) {
             if let Some(like_key) = like_key {
@@ -262,6 +304,24 @@ impl TableManager {
         ret.push((k.to_vec(), v.to_vec()));
     }
 
+    ///
+    /// 角色表变更(raft日志应用或快照加载)后,按本节点角色表重新加载自定义角色
+    fn reload_custom_roles(&self) {
+        let roles = self
+            .table_map
+            .get(&*ROLE_TREE_NAME)
+            .map(|table_info| {
+                table_info
+                    .table_data
+                    .values()
+                    .filter_map(|v| RoleDo::from_bytes(v).ok())
+                    .map(|e| (Arc::new(e.name), e.modules))
+                    .collect()
+            })
+            .unwrap_or_default();
+        UserRoleHelper::reload_custom_roles(roles);
+    }
+
     fn get_table_names(&self) -> Vec<Arc<String>> {
         self.table_map.values().map(|e| e.name.clone()).collect()
     }
@@ -303,6 +363,19 @@ impl TableManager {
                     };
                     writer.do_send(TransferWriterRequest::AddRecord(record));
                 }
+            } else if param.user
+                && (key.as_str() == ROLE_TREE_NAME.as_str()
+                    || key.as_str() == API_KEY_TREE_NAME.as_str())
+            {
+                for (key, value) in &table_info.table_data {
+                    let record = TransferRecordDto {
+                        table_name: Some(table_info.name.clone()),
+                        key: key.to_owned(),
+                        value: value.to_owned(),
+                        table_id: 0,
+                    };
+                    writer.do_send(TransferWriterRequest::AddRecord(record));
+                }
             }
         }
         Ok(())
@@ -416,6 +489,14 @@ pub enum TableManagerQueryReq {
         limit: Option<i64>,
         is_rev: bool,
     },
+    /// 按key范围查询,返回的总数为表记录数
+    QueryRangeList {
+        table_name: Arc<String>,
+        start_key: Option<Vec<u8>>,
+        end_key: Option<Vec<u8>>,
+        limit: Option<i64>,
+        is_rev: bool,
+    },
 }
 
 impl From<TableManagerQueryReq> for RouterRequest {
@@ -478,7 +559,11 @@ impl Handler<TableManagerReq> for TableManager {
                         }
                     }
                 }
+                let is_role_table = table_name.as_str() == ROLE_TREE_NAME.as_str();
                 self.insert(table_name, key, value, last_seq_id);
+                if is_role_table {
+                    self.reload_custom_roles();
+                }
                 Ok(TableManagerResult::None)
             }
             TableManagerReq::Remove { table_name, key } => {
@@ -495,13 +580,21 @@ impl Handler<TableManagerReq> for TableManager {
                         }
                     }
                 }
-                match self.remove(table_name, key) {
+                let is_role_table = table_name.as_str() == ROLE_TREE_NAME.as_str();
+                let old_value = self.remove(table_name, key);
+                if is_role_table {
+                    self.reload_custom_roles();
+                }
+                match old_value {
                     Some(v) => Ok(TableManagerResult::Value(v.to_vec())),
                     None => Ok(TableManagerResult::None),
                 }
             }
             TableManagerReq::Drop(name) => {
                 self.drop_table(&name);
+                if name.as_str() == ROLE_TREE_NAME.as_str() {
+                    self.reload_custom_roles();
+                }
                 Ok(TableManagerResult::None)
             }
             TableManagerReq::NextId {
@@ -582,6 +675,17 @@ impl Handler<TableManagerQueryReq> for TableManager {
                     self.query_page_list(table_name, like_key, offset, limit, is_rev);
                 Ok(TableManagerResult::PageListResult(size, list))
             }
+            TableManagerQueryReq::QueryRangeList {
+                table_name,
+                start_key,
+                end_key,
+                limit,
+                is_rev,
+            } => {
+                let (size, list) =
+                    self.query_range_list(table_name, start_key, end_key, limit, is_rev);
+                Ok(TableManagerResult::PageListResult(size, list))
+            }
         }
     }
 }
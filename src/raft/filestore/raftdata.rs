@@ -1,10 +1,13 @@
 use crate::cache::core::DirectCacheManager;
 use crate::common::byte_utils::bin_to_id;
 use crate::common::constant::{
-    CACHE_TREE_NAME, CONFIG_TREE_NAME, DIRECT_CACHE_TABLE_NAME, MCP_SERVER_TABLE_NAME,
-    MCP_TOOL_SPEC_TABLE_NAME, NAMESPACE_TREE_NAME, NAMING_INSTANCE_TABLE, SEQUENCE_TREE_NAME,
-    SEQ_KEY_CONFIG, USER_TREE_NAME,
+    API_KEY_TREE_NAME, API_KEY_USAGE_TREE_NAME, AUDIT_LOG_TREE_NAME, CACHE_TREE_NAME,
+    CONFIG_TREE_NAME, CONFIG_WEBHOOK_LOG_TREE_NAME, CONFIG_WEBHOOK_TREE_NAME,
+    DIRECT_CACHE_TABLE_NAME, MCP_SERVER_TABLE_NAME, MCP_TOOL_SPEC_TABLE_NAME, NAMESPACE_TREE_NAME,
+    NAMING_INSTANCE_TABLE, NAMING_ROUTE_RULE_TABLE, ROLE_TREE_NAME, SEQUENCE_TREE_NAME,
+    SEQ_KEY_CONFIG, USER_LOGIN_STATE_TREE_NAME, USER_TREE_NAME,
 };
+use crate::config::config_webhook::{ConfigWebhookDO, ConfigWebhookRaftReq};
 use crate::config::core::{ConfigActor, ConfigCmd, ConfigKey, ConfigValue};
 use crate::config::model::{ConfigRaftCmd, ConfigValueDO};
 use crate::mcp::core::McpManager;
@@ -64,6 +67,14 @@ impl RaftDataHandler {
             self.config
                 .send(ConfigCmd::SetFullValue(config_key, value_do.into()))
                 .await??;
+        } else if record.tree.as_str() == CONFIG_WEBHOOK_TREE_NAME.as_str() {
+            let value_do = ConfigWebhookDO::from_bytes(&record.value)?;
+            let req = ConfigWebhookRaftReq::Update {
+                webhook: value_do.into(),
+            };
+            self.config
+                .send(ConfigRaftCmd::ConfigWebhook { req })
+                .await??;
         } else if record.tree.as_str() == SEQUENCE_TREE_NAME.as_str() {
             let key = String::from_utf8_lossy(&record.key);
             let last_id = bin_to_id(&record.value);
@@ -75,11 +86,18 @@ impl RaftDataHandler {
                 let req = RaftApplyDataRequest::LoadSnapshotRecord(record);
                 self.sequence_db.send(req).await??;
             }
-        } else if record.tree.as_str() == USER_TREE_NAME.as_str() {
+        } else if record.tree.as_str() == USER_TREE_NAME.as_str()
+            || record.tree.as_str() == ROLE_TREE_NAME.as_str()
+            || record.tree.as_str() == API_KEY_TREE_NAME.as_str()
+            || record.tree.as_str() == API_KEY_USAGE_TREE_NAME.as_str()
+            || record.tree.as_str() == USER_LOGIN_STATE_TREE_NAME.as_str()
+            || record.tree.as_str() == AUDIT_LOG_TREE_NAME.as_str()
+            || record.tree.as_str() == CONFIG_WEBHOOK_LOG_TREE_NAME.as_str()
+        {
             let key = record.key;
             let value = record.value;
             let req = TableManagerReq::Set {
-                table_name: USER_TREE_NAME.clone(),
+                table_name: record.tree,
                 key,
                 value,
                 last_seq_id: None,
@@ -110,7 +128,9 @@ impl RaftDataHandler {
         {
             let req = RaftApplyDataRequest::LoadSnapshotRecord(record);
             self.mcp_manager.send(req).await??;
-        } else if record.tree.as_str() == NAMING_INSTANCE_TABLE.as_str() {
+        } else if record.tree.as_str() == NAMING_INSTANCE_TABLE.as_str()
+            || record.tree.as_str() == NAMING_ROUTE_RULE_TABLE.as_str()
+        {
             let req = RaftApplyDataRequest::LoadSnapshotRecord(record);
             self.naming_actor.send(req).await??;
         } else {
@@ -167,6 +187,8 @@ impl RaftDataHandler {
                 value,
                 config_type,
                 desc,
+                tags,
+                encrypted_data_key,
                 history_id,
                 history_table_id,
                 op_time,
@@ -177,6 +199,8 @@ impl RaftDataHandler {
                     value,
                     config_type,
                     desc,
+                    tags,
+                    encrypted_data_key,
                     history_id,
                     history_table_id,
                     op_time,
@@ -219,6 +243,14 @@ impl RaftDataHandler {
             ClientRequest::CacheReq { req } => {
                 self.direct_cache_manager.send(req).await.ok();
             }
+            ClientRequest::ConfigGrayReq { req } => {
+                let cmd = ConfigRaftCmd::ConfigGray { req };
+                self.config.send(cmd).await.ok();
+            }
+            ClientRequest::ConfigWebhookReq { req } => {
+                let cmd = ConfigRaftCmd::ConfigWebhook { req };
+                self.config.send(cmd).await.ok();
+            }
         }
         Ok(())
     }
@@ -251,6 +283,8 @@ impl RaftDataHandler {
                 value,
                 config_type,
                 desc,
+                tags,
+                encrypted_data_key,
                 history_id,
                 history_table_id,
                 op_time,
@@ -261,6 +295,8 @@ impl RaftDataHandler {
                     value,
                     config_type,
                     desc,
+                    tags,
+                    encrypted_data_key,
                     history_id,
                     history_table_id,
                     op_time,
@@ -311,6 +347,16 @@ impl RaftDataHandler {
                 let resp = self.direct_cache_manager.send(req).await??;
                 Ok(ClientResponse::CacheResp { resp })
             }
+            ClientRequest::ConfigGrayReq { req } => {
+                let cmd = ConfigRaftCmd::ConfigGray { req };
+                self.config.send(cmd).await??;
+                Ok(ClientResponse::Success)
+            }
+            ClientRequest::ConfigWebhookReq { req } => {
+                let cmd = ConfigRaftCmd::ConfigWebhook { req };
+                self.config.send(cmd).await??;
+                Ok(ClientResponse::Success)
+            }
         }
     }
 
@@ -338,6 +384,8 @@ impl RaftDataHandler {
                 value,
                 config_type,
                 desc,
+                tags,
+                encrypted_data_key,
                 history_id,
                 history_table_id,
                 op_time,
@@ -348,6 +396,8 @@ impl RaftDataHandler {
                     value,
                     config_type,
                     desc,
+                    tags,
+                    encrypted_data_key,
                     history_id,
                     history_table_id,
                     op_time,
@@ -390,6 +440,14 @@ impl RaftDataHandler {
             ClientRequest::CacheReq { req } => {
                 self.direct_cache_manager.do_send(req);
             }
+            ClientRequest::ConfigGrayReq { req } => {
+                let cmd = ConfigRaftCmd::ConfigGray { req };
+                self.config.do_send(cmd);
+            }
+            ClientRequest::ConfigWebhookReq { req } => {
+                let cmd = ConfigRaftCmd::ConfigWebhook { req };
+                self.config.do_send(cmd);
+            }
         };
         Ok(())
     }
@@ -2,6 +2,7 @@ use std::sync::Arc;
 
 use crate::common::AppSysConfig;
 use crate::grpc::handler::CLUSTER_TOKEN;
+use crate::grpc::tls::GrpcTlsConnector;
 use actix::prelude::*;
 use inner_mem_cache::MemCache;
 use tonic::transport::Channel;
@@ -12,17 +13,26 @@ use crate::grpc::nacos_proto::{request_client::RequestClient, Payload};
 pub struct RaftClusterRequestSender {
     conn_factory: Addr<RaftConnectionFactory>,
     sys_config: Arc<AppSysConfig>,
+    tls_connector: Option<GrpcTlsConnector>,
 }
 
 impl RaftClusterRequestSender {
-    pub fn new(conn_factory: Addr<RaftConnectionFactory>, sys_config: Arc<AppSysConfig>) -> Self {
+    pub fn new(
+        conn_factory: Addr<RaftConnectionFactory>,
+        sys_config: Arc<AppSysConfig>,
+        tls_connector: Option<GrpcTlsConnector>,
+    ) -> Self {
         Self {
             conn_factory,
             sys_config,
+            tls_connector,
         }
     }
 
     pub async fn get_node_channel(&self, addr: Arc<String>) -> anyhow::Result<Arc<Channel>> {
+        if let Some(tls_connector) = &self.tls_connector {
+            return self.get_node_tls_channel(tls_connector, addr).await;
+        }
         let res: RaftConnResponse = self
             .conn_factory
             .send(RaftConnRequest::GetChannel(addr))
@@ -33,6 +43,28 @@ impl RaftClusterRequestSender {
         }
     }
 
+    ///
+    /// tls连接不支持延迟连接,在actor外建立连接后再放入缓存
+    async fn get_node_tls_channel(
+        &self,
+        tls_connector: &GrpcTlsConnector,
+        addr: Arc<String>,
+    ) -> anyhow::Result<Arc<Channel>> {
+        let res: RaftConnResponse = self
+            .conn_factory
+            .send(RaftConnRequest::GetCacheChannel(addr.clone()))
+            .await??;
+        if let RaftConnResponse::Channel(channel) = res {
+            return Ok(channel);
+        }
+        let channel = Arc::new(tls_connector.connect_channel(&addr).await?);
+        self.conn_factory.do_send(RaftConnRequest::SetChannel {
+            key: addr,
+            channel: channel.clone(),
+        });
+        Ok(channel)
+    }
+
     pub async fn send_request(
         &self,
         addr: Arc<String>,
@@ -99,6 +131,11 @@ impl RaftConnectionFactory {
         }
     }
 
+    fn get_cache_channel(&mut self, key: &Arc<String>) -> Option<Arc<Channel>> {
+        self.channel_cache.clear_time_out();
+        self.channel_cache.get(key).ok()
+    }
+
     fn update_channel_status(&mut self, key: Arc<String>, is_active: bool) {
         if let Ok(channel) = self.channel_cache.get(&key) {
             if is_active {
@@ -125,7 +162,15 @@ impl Actor for RaftConnectionFactory {
 #[rtype(result = "anyhow::Result<RaftConnResponse>")]
 pub enum RaftConnRequest {
     GetChannel(Arc<String>),
-    UpdateChannel { key: Arc<String>, is_active: bool },
+    GetCacheChannel(Arc<String>),
+    SetChannel {
+        key: Arc<String>,
+        channel: Arc<Channel>,
+    },
+    UpdateChannel {
+        key: Arc<String>,
+        is_active: bool,
+    },
 }
 
 pub enum RaftConnResponse {
@@ -142,6 +187,14 @@ impl Handler<RaftConnRequest> for RaftConnectionFactory {
                 let c = self.build_channel(key)?;
                 Ok(RaftConnResponse::Channel(c))
             }
+            RaftConnRequest::GetCacheChannel(key) => match self.get_cache_channel(&key) {
+                Some(c) => Ok(RaftConnResponse::Channel(c)),
+                None => Ok(RaftConnResponse::None),
+            },
+            RaftConnRequest::SetChannel { key, channel } => {
+                self.channel_cache.set(key, channel, self.cache_ses);
+                Ok(RaftConnResponse::None)
+            }
             RaftConnRequest::UpdateChannel { key, is_active } => {
                 self.update_channel_status(key, is_active);
                 Ok(RaftConnResponse::None)
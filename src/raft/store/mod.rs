@@ -5,6 +5,8 @@ use std::sync::Arc;
 
 use super::db::table::TableManagerReq;
 use crate::cache::actor_model::{CacheManagerRaftReq, CacheManagerRaftResult};
+use crate::config::config_webhook::ConfigWebhookRaftReq;
+use crate::config::model::ConfigGrayRaftReq;
 use crate::mcp::model::actor_model::{McpManagerRaftReq, McpManagerRaftResult};
 use crate::namespace::model::NamespaceRaftReq;
 use crate::naming::model::actor_model::{NamingRaftReq, NamingRaftResult};
@@ -29,6 +31,10 @@ pub enum ClientRequest {
         value: Arc<String>,
         config_type: Option<Arc<String>>,
         desc: Option<Arc<String>>,
+        #[serde(default)]
+        tags: Option<Vec<Arc<String>>>,
+        #[serde(default)]
+        encrypted_data_key: Option<Arc<String>>,
         history_id: u64,
         history_table_id: Option<u64>,
         op_time: i64,
@@ -56,6 +62,12 @@ pub enum ClientRequest {
     CacheReq {
         req: CacheManagerRaftReq,
     },
+    ConfigGrayReq {
+        req: ConfigGrayRaftReq,
+    },
+    ConfigWebhookReq {
+        req: ConfigWebhookRaftReq,
+    },
 }
 
 impl AppData for ClientRequest {}
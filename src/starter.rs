@@ -1,7 +1,10 @@
 use std::{collections::HashSet, sync::Arc, time::Duration};
 
+use crate::audit::AuditManager;
 use crate::common::actor_utils::{create_actor_at_thread, create_actor_at_thread2};
+use crate::common::tls_utils::TlsUtils;
 use crate::grpc::handler::RAFT_ROUTE_REQUEST;
+use crate::grpc::tls::GrpcTlsConnector;
 use crate::health::core::HealthManager;
 use crate::ldap::core::LdapManager;
 use crate::mcp::core::McpManager;
@@ -24,6 +27,9 @@ use crate::transfer::reader::TransferImportManager;
 use crate::transfer::writer::TransferWriterManager;
 use crate::{
     common::{appdata::AppShareData, AppSysConfig},
+    config::config_cipher::{ConfigCipher, CONFIG_CIPHER_KEY_ENV},
+    config::config_validator::ConfigValidatorManager,
+    config::config_webhook::ConfigWebhookNotifyActor,
     config::core::ConfigActor,
     grpc::{bistream_manage::BiStreamManage, PayloadUtils},
     naming::{
@@ -34,6 +40,7 @@ use crate::{
         },
         core::NamingActor,
         naming_delay_nofity::DelayNotifyActor,
+        naming_sse::NamingSseManage,
     },
     raft::{
         cache::{route::CacheRoute, CacheManager},
@@ -76,13 +83,22 @@ pub async fn config_factory(sys_config: Arc<AppSysConfig>) -> anyhow::Result<Fac
     factory.register(BeanDefinition::actor_with_inject_from_obj(
         DelayNotifyActor::new().start(),
     ));
+    factory.register(BeanDefinition::actor_with_inject_from_obj(
+        NamingSseManage::new().start(),
+    ));
+    factory.register(BeanDefinition::actor_with_inject_from_obj(
+        ConfigWebhookNotifyActor::default().start(),
+    ));
 
     //raft
     let conn_factory = RaftConnectionFactory::new(60).start();
     factory.register(BeanDefinition::actor_from_obj(conn_factory.clone()));
+    let tls_connector =
+        TlsUtils::build_cluster_client_config(&sys_config)?.map(GrpcTlsConnector::new);
     let cluster_sender = Arc::new(RaftClusterRequestSender::new(
         conn_factory,
         sys_config.clone(),
+        tls_connector,
     ));
     factory.register(BeanDefinition::from_obj(cluster_sender.clone()));
 
@@ -132,10 +148,16 @@ pub async fn config_factory(sys_config: Arc<AppSysConfig>) -> anyhow::Result<Fac
         cluster_sender.clone(),
     ));
     factory.register(BeanDefinition::from_obj(table_route));
+    let config_cipher = Arc::new(ConfigCipher::new_by_key_file(
+        &sys_config.config_cipher_key_file,
+        CONFIG_CIPHER_KEY_ENV,
+    ));
+    factory.register(BeanDefinition::from_obj(config_cipher.clone()));
     let config_route = Arc::new(ConfigRoute::new(
         config_addr.clone(),
         raft_addr_router.clone(),
         cluster_sender.clone(),
+        config_cipher,
     ));
     factory.register(BeanDefinition::from_obj(config_route.clone()));
 
@@ -166,7 +188,7 @@ pub async fn config_factory(sys_config: Arc<AppSysConfig>) -> anyhow::Result<Fac
         bistream_manage_addr.clone(),
     ));
 
-    let user_manager = UserManager::new().start();
+    let user_manager = UserManager::new(&sys_config).start();
     factory.register(BeanDefinition::actor_with_inject_from_obj(user_manager));
     let cache_manager = CacheManager::new().start();
     factory.register(BeanDefinition::actor_with_inject_from_obj(
@@ -220,6 +242,8 @@ pub async fn config_factory(sys_config: Arc<AppSysConfig>) -> anyhow::Result<Fac
     factory.register(BeanDefinition::from_obj(raft_data_wrap));
     let metrics_manager = MetricsManager::new(sys_config.clone()).start();
     factory.register(BeanDefinition::actor_with_inject_from_obj(metrics_manager));
+    let audit_manager = AuditManager::new(&sys_config).start();
+    factory.register(BeanDefinition::actor_with_inject_from_obj(audit_manager));
     let transfer_writer_addr = TransferWriterManager::new(std::env::temp_dir(), 0).start();
     factory.register(BeanDefinition::actor_with_inject_from_obj(
         transfer_writer_addr,
@@ -269,6 +293,9 @@ pub fn build_share_data(factory_data: FactoryData) -> anyhow::Result<Arc<AppShar
         raft_store: factory_data.get_bean().unwrap(),
         sys_config,
         config_route: factory_data.get_bean().unwrap(),
+        config_validator: Arc::new(ConfigValidatorManager::new()),
+        config_cipher: factory_data.get_bean().unwrap(),
+        config_webhook_notify: factory_data.get_actor().unwrap(),
         cluster_sender: factory_data.get_bean().unwrap(),
         naming_route: factory_data.get_bean().unwrap(),
         naming_inner_node_manage: factory_data.get_actor().unwrap(),
@@ -280,6 +307,7 @@ pub fn build_share_data(factory_data: FactoryData) -> anyhow::Result<Arc<AppShar
         cache_manager: factory_data.get_actor().unwrap(),
         direct_cache_manager: factory_data.get_actor().unwrap(),
         metrics_manager: factory_data.get_actor().unwrap(),
+        audit_manager: factory_data.get_actor().unwrap(),
         timezone_offset: Arc::new(timezone_offset),
         namespace_addr: factory_data.get_actor().unwrap(),
         raft_request_route: factory_data.get_bean().unwrap(),
@@ -292,6 +320,7 @@ pub fn build_share_data(factory_data: FactoryData) -> anyhow::Result<Arc<AppShar
         sequence_db_manager: factory_data.get_actor().unwrap(),
         mcp_manager: factory_data.get_actor().unwrap(),
         sse_stream_manager: factory_data.get_actor().unwrap(),
+        naming_sse_manage: factory_data.get_actor().unwrap(),
         factory_data,
         common_client: reqwest_client,
     });
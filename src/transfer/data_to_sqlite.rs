@@ -1,6 +1,7 @@
 use crate::common::constant::{CONFIG_TREE_NAME, NAMESPACE_TREE_NAME, USER_TREE_NAME};
 use crate::config::core::{ConfigKey, ConfigValue};
 use crate::config::model::ConfigValueDO;
+use crate::config::ConfigUtils;
 use crate::namespace::model::NamespaceDO;
 use crate::transfer::model::TransferRecordRef;
 use crate::transfer::reader::{reader_transfer_record, TransferFileReader};
@@ -11,6 +12,7 @@ use crate::transfer::sqlite::dao::user::{UserDO, UserDao};
 use crate::transfer::sqlite::TableSeq;
 use crate::user::model::UserDo;
 use rusqlite::Connection;
+use std::sync::Arc;
 
 pub async fn data_to_sqlite(data_file: &str, db_path: &str) -> anyhow::Result<()> {
     let mut file_reader = TransferFileReader::new(data_file).await?;
@@ -67,6 +69,12 @@ fn insert_config(
         content: Some(config_value.content.clone()),
         config_type: config_value.config_type.clone(),
         config_desc: config_value.desc,
+        config_tags: if config_value.tags.is_empty() {
+            None
+        } else {
+            Some(Arc::new(ConfigUtils::join_tags(&config_value.tags)))
+        },
+        encrypted_data_key: config_value.encrypted_data_key,
         last_time: Some(config_value.last_modified),
     };
     config_dao.insert(&config_do)?;
@@ -127,6 +135,8 @@ create table if not exists tb_config(
     content text,
     config_type text,
     config_desc text,
+    config_tags text,
+    encrypted_data_key text,
     last_time long
 );
 create index if not exists tb_config_key_idx on tb_config(data_id,group_id,tenant_id);
@@ -2,6 +2,7 @@ use crate::common::constant::{
     CONFIG_TREE_NAME, EMPTY_ARC_STRING, EMPTY_STR, NAMESPACE_TREE_NAME, USER_TREE_NAME,
 };
 use crate::common::sqlx_utils::MySqlExecutor;
+use crate::common::string_utils::StringUtils;
 use crate::config::core::{ConfigKey, ConfigValue};
 use crate::config::model::ConfigValueDO;
 use crate::config::ConfigUtils;
@@ -149,6 +150,9 @@ fn build_config_record(
             None,
         );
     }
+    config_value.set_encrypted_data_key(
+        StringUtils::map_not_empty(config_do.encrypted_data_key).map(Arc::new),
+    );
     let value_do: ConfigValueDO = config_value.into();
     let record = TransferRecordDto {
         table_name: Some(CONFIG_TREE_NAME.clone()),
@@ -177,6 +181,7 @@ async fn apply_tenant(
             namespace_id: item.tenant_id,
             namespace_name: item.tenant_name,
             r#type: Some(FROM_USER_VALUE.to_string()),
+            config_validate_enforce: None,
         };
         let record = TransferRecordDto {
             table_name: Some(NAMESPACE_TREE_NAME.clone()),
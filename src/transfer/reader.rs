@@ -1,6 +1,7 @@
 use crate::common::constant::{
-    CACHE_TREE_NAME, CONFIG_TREE_NAME, EMPTY_ARC_STRING, MCP_SERVER_TABLE_NAME,
-    MCP_TOOL_SPEC_TABLE_NAME, NAMESPACE_TREE_NAME, NAMING_INSTANCE_TABLE, USER_TREE_NAME,
+    API_KEY_TREE_NAME, CACHE_TREE_NAME, CONFIG_TREE_NAME, EMPTY_ARC_STRING, MCP_SERVER_TABLE_NAME,
+    MCP_TOOL_SPEC_TABLE_NAME, NAMESPACE_TREE_NAME, NAMING_INSTANCE_TABLE, NAMING_ROUTE_RULE_TABLE,
+    ROLE_TREE_NAME, USER_TREE_NAME,
 };
 use crate::common::pb::data_object::{McpServerDo, McpToolSpecDo};
 use crate::common::pb::transfer::{TransferHeader, TransferItem};
@@ -14,6 +15,7 @@ use crate::namespace::model::{
     Namespace, NamespaceDO, NamespaceFromFlags, NamespaceParam, NamespaceRaftReq,
 };
 use crate::naming::model::actor_model::NamingRaftReq;
+use crate::naming::route_rule::{ServiceRouteRuleDO, ServiceRouteRuleParam};
 use crate::raft::db::table::TableManagerReq;
 use crate::raft::filestore::raftdata::RaftDataHandler;
 use crate::raft::store::ClientRequest;
@@ -44,6 +46,10 @@ pub(crate) fn reader_transfer_record<'a>(
             CONFIG_TREE_NAME.clone()
         } else if USER_TREE_NAME.as_str() == record_do.table_name.as_ref() {
             USER_TREE_NAME.clone()
+        } else if ROLE_TREE_NAME.as_str() == record_do.table_name.as_ref() {
+            ROLE_TREE_NAME.clone()
+        } else if API_KEY_TREE_NAME.as_str() == record_do.table_name.as_ref() {
+            API_KEY_TREE_NAME.clone()
         } else if CACHE_TREE_NAME.as_str() == record_do.table_name.as_ref() {
             CACHE_TREE_NAME.clone()
         } else if NAMESPACE_TREE_NAME.as_str() == record_do.table_name.as_ref() {
@@ -54,6 +60,8 @@ pub(crate) fn reader_transfer_record<'a>(
             MCP_SERVER_TABLE_NAME.clone()
         } else if NAMING_INSTANCE_TABLE.as_str() == record_do.table_name.as_ref() {
             NAMING_INSTANCE_TABLE.clone()
+        } else if NAMING_ROUTE_RULE_TABLE.as_str() == record_do.table_name.as_ref() {
+            NAMING_ROUTE_RULE_TABLE.clone()
         } else {
             //ignore
             EMPTY_ARC_STRING.clone()
@@ -221,7 +229,10 @@ impl TransferImportManager {
                 } else if param.config && record.table_name.as_str() == NAMESPACE_TREE_NAME.as_str()
                 {
                     Self::apply_namespace(raft, record).await?;
-                } else if (param.user && record.table_name.as_str() == USER_TREE_NAME.as_str())
+                } else if (param.user
+                    && (record.table_name.as_str() == USER_TREE_NAME.as_str()
+                        || record.table_name.as_str() == ROLE_TREE_NAME.as_str()
+                        || record.table_name.as_str() == API_KEY_TREE_NAME.as_str()))
                     || (param.cache && record.table_name.as_str() == CACHE_TREE_NAME.as_str())
                 {
                     Self::apply_table(raft, record).await?;
@@ -229,6 +240,10 @@ impl TransferImportManager {
                     && record.table_name.as_str() == NAMING_INSTANCE_TABLE.as_str()
                 {
                     Self::apply_naming_instance(raft, record).await?;
+                } else if param.naming
+                    && record.table_name.as_str() == NAMING_ROUTE_RULE_TABLE.as_str()
+                {
+                    Self::apply_naming_route_rule(raft, record).await?;
                 } else {
                     ignore += 1;
                 }
@@ -266,6 +281,7 @@ impl TransferImportManager {
             namespace_id: value.namespace_id,
             namespace_name: Some(value.namespace_name),
             r#type: Some(NamespaceFromFlags::get_db_type(value.flag)),
+            config_validate_enforce: Some(value.config_validate_enforce),
         };
         let req = ClientRequest::NamespaceReq(NamespaceRaftReq::Update(param));
         Self::send_raft_request(raft, req).await?;
@@ -352,6 +368,18 @@ impl TransferImportManager {
         Ok(())
     }
 
+    async fn apply_naming_route_rule(
+        raft: &Arc<NacosRaft>,
+        record: TransferRecordRef<'_>,
+    ) -> anyhow::Result<()> {
+        let param: ServiceRouteRuleParam = ServiceRouteRuleDO::from_bytes(&record.value)?.into();
+        let req = ClientRequest::NamingReq {
+            req: NamingRaftReq::UpdateRouteRule(param),
+        };
+        Self::send_raft_request(raft, req).await?;
+        Ok(())
+    }
+
     async fn apply_mcp_finished(raft: &Arc<NacosRaft>) -> anyhow::Result<()> {
         let req = ClientRequest::McpReq {
             req: McpManagerRaftReq::ImportFinished,
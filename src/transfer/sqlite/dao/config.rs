@@ -17,6 +17,8 @@ pub struct ConfigDO {
     pub content: Option<Arc<String>>,
     pub config_type: Option<Arc<String>>,
     pub config_desc: Option<Arc<String>>,
+    pub config_tags: Option<Arc<String>>,
+    pub encrypted_data_key: Option<Arc<String>>,
     pub last_time: Option<i64>,
 }
 
@@ -30,6 +32,8 @@ impl ConfigDO {
         s.content = get_row_arc_value(r, "content");
         s.config_type = get_row_arc_value(r, "config_type");
         s.config_desc = get_row_arc_value(r, "config_desc");
+        s.config_tags = get_row_arc_value(r, "config_tags");
+        s.encrypted_data_key = get_row_arc_value(r, "encrypted_data_key");
         s.last_time = get_row_value(r, "last_time");
         s
     }
@@ -54,8 +58,8 @@ impl ConfigSql {
 
     pub fn query_prepare(&self, param: &ConfigParam) -> (String, Vec<serde_json::Value>) {
         B::prepare(
-            B::new_sql("select id, data_id, group_id, tenant_id, content, config_type, config_desc, last_time from tb_config")
-                .push_build(&mut self.conditions(param))
+            //兼容没有config_tags、encrypted_data_key列的历史导出文件
+            B::new_sql("select * from tb_config").push_build(&mut self.conditions(param)),
         )
     }
 
@@ -90,6 +94,14 @@ impl ConfigSql {
             field_builder.push_sql("config_desc");
             value_builder.push("?", config_desc);
         }
+        if let Some(config_tags) = &record.config_tags {
+            field_builder.push_sql("config_tags");
+            value_builder.push("?", config_tags);
+        }
+        if let Some(encrypted_data_key) = &record.encrypted_data_key {
+            field_builder.push_sql("encrypted_data_key");
+            value_builder.push("?", encrypted_data_key);
+        }
         if let Some(last_time) = &record.last_time {
             field_builder.push_sql("last_time");
             value_builder.push("?", last_time);
@@ -129,6 +141,12 @@ impl ConfigSql {
         if let Some(config_desc) = &record.config_desc {
             set_builder.eq("config_desc", config_desc);
         }
+        if let Some(config_tags) = &record.config_tags {
+            set_builder.eq("config_tags", config_tags);
+        }
+        if let Some(encrypted_data_key) = &record.encrypted_data_key {
+            set_builder.eq("encrypted_data_key", encrypted_data_key);
+        }
         if let Some(last_time) = &record.last_time {
             set_builder.eq("last_time", last_time);
         }
@@ -82,6 +82,15 @@ impl From<UserDO> for UserDo {
             namespace_white_list: Default::default(),
             namespace_black_list: Default::default(),
             source: None,
+            resource_permissions: Default::default(),
+            need_change_password: None,
+            password_modified_time: None,
+            login_fail_count: None,
+            lock_until_time: None,
+            totp_secret: None,
+            totp_enable: None,
+            totp_recovery_codes: Default::default(),
+            totp_last_step: None,
         }
     }
 }
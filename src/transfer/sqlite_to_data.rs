@@ -112,6 +112,10 @@ fn build_config_record(
             None,
         );
     }
+    if let Some(tags) = &config_do.config_tags {
+        config_value.tags = ConfigUtils::parse_tags(tags);
+    }
+    config_value.set_encrypted_data_key(config_do.encrypted_data_key);
     let value_do: ConfigValueDO = config_value.into();
     let record = TransferRecordDto {
         table_name: Some(CONFIG_TREE_NAME.clone()),
@@ -136,6 +140,7 @@ fn apply_tenant(conn: &Connection, writer_actor: &Addr<TransferWriterActor>) ->
             namespace_id: item.tenant_id,
             namespace_name: item.tenant_name,
             r#type: Some(FROM_USER_VALUE.to_string()),
+            config_validate_enforce: None,
         };
         let record = TransferRecordDto {
             table_name: Some(NAMESPACE_TREE_NAME.clone()),
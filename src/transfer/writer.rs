@@ -1,7 +1,8 @@
 #![allow(clippy::suspicious_open_options)]
 use crate::common::constant::{
-    CACHE_TREE_NAME, CONFIG_TREE_NAME, EMPTY_STR, MCP_SERVER_TABLE_NAME, MCP_TOOL_SPEC_TABLE_NAME,
-    NAMESPACE_TREE_NAME, NAMING_INSTANCE_TABLE, SEQUENCE_TREE_NAME, USER_TREE_NAME,
+    API_KEY_TREE_NAME, CACHE_TREE_NAME, CONFIG_TREE_NAME, EMPTY_STR, MCP_SERVER_TABLE_NAME,
+    MCP_TOOL_SPEC_TABLE_NAME, NAMESPACE_TREE_NAME, NAMING_INSTANCE_TABLE, NAMING_ROUTE_RULE_TABLE,
+    ROLE_TREE_NAME, SEQUENCE_TREE_NAME, USER_TREE_NAME,
 };
 use crate::common::tempfile::TempFile;
 use crate::raft::filestore::raftdata::RaftDataHandler;
@@ -209,6 +210,12 @@ impl TransferWriterManager {
         writer_actor.do_send(TransferWriterRequest::AddTableNameMap(
             USER_TREE_NAME.clone(),
         ));
+        writer_actor.do_send(TransferWriterRequest::AddTableNameMap(
+            ROLE_TREE_NAME.clone(),
+        ));
+        writer_actor.do_send(TransferWriterRequest::AddTableNameMap(
+            API_KEY_TREE_NAME.clone(),
+        ));
         writer_actor.do_send(TransferWriterRequest::AddTableNameMap(
             CACHE_TREE_NAME.clone(),
         ));
@@ -221,6 +228,9 @@ impl TransferWriterManager {
         writer_actor.do_send(TransferWriterRequest::AddTableNameMap(
             NAMING_INSTANCE_TABLE.clone(),
         ));
+        writer_actor.do_send(TransferWriterRequest::AddTableNameMap(
+            NAMING_ROUTE_RULE_TABLE.clone(),
+        ));
         writer_actor.do_send(TransferWriterRequest::InitHeader);
         writer_actor
     }
@@ -0,0 +1,102 @@
+use crate::common::model::TokenSession;
+use crate::user::{UserManager, UserManagerReq, UserManagerResult};
+use actix::Addr;
+use sha2::{Digest, Sha256};
+use std::convert::TryFrom;
+use std::sync::Arc;
+
+/// API key前缀,用于和登录token区分
+pub const API_KEY_PREFIX: &str = "rnk_";
+const KEY_ID_LEN: usize = 16;
+
+pub struct ApiKeyUtils;
+
+impl ApiKeyUtils {
+    pub fn is_api_key(token: &str) -> bool {
+        token.starts_with(API_KEY_PREFIX)
+    }
+
+    ///
+    /// 生成新的API key,返回(key_id,完整key);格式: rnk_{key_id}{secret}
+    pub fn generate() -> (String, String) {
+        let key_id = uuid::Uuid::new_v4().simple().to_string()[..KEY_ID_LEN].to_owned();
+        let secret =
+            uuid::Uuid::new_v4().simple().to_string() + &uuid::Uuid::new_v4().simple().to_string();
+        let key = format!("{}{}{}", API_KEY_PREFIX, &key_id, secret);
+        (key_id, key)
+    }
+
+    pub fn parse_key_id(key: &str) -> Option<&str> {
+        let v = key.strip_prefix(API_KEY_PREFIX)?;
+        if v.len() <= KEY_ID_LEN || !v.is_char_boundary(KEY_ID_LEN) {
+            return None;
+        }
+        Some(&v[..KEY_ID_LEN])
+    }
+
+    pub fn hash(key: &str) -> String {
+        let digest = Sha256::digest(key.as_bytes());
+        digest.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    ///
+    /// 比较key与存储的hash值,比较耗时与内容无关
+    pub fn verify(key: &str, key_hash: &str) -> bool {
+        Self::verify_hash(&Self::hash(key), key_hash)
+    }
+
+    pub fn encode_used_time(used_time: u32) -> Vec<u8> {
+        used_time.to_be_bytes().to_vec()
+    }
+
+    pub fn decode_used_time(v: &[u8]) -> u32 {
+        <[u8; 4]>::try_from(v)
+            .map(u32::from_be_bytes)
+            .unwrap_or_default()
+    }
+
+    pub fn verify_hash(hash: &str, key_hash: &str) -> bool {
+        if hash.len() != key_hash.len() {
+            return false;
+        }
+        hash.bytes()
+            .zip(key_hash.bytes())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+    }
+}
+
+///
+/// 通过API key获取openapi与grpc请求的访问会话
+pub async fn get_api_key_session(
+    user_manager: &Addr<UserManager>,
+    key: &str,
+) -> anyhow::Result<Option<Arc<TokenSession>>> {
+    let req = UserManagerReq::CheckApiKey {
+        key: key.to_owned(),
+    };
+    match user_manager.send(req).await?? {
+        UserManagerResult::ApiKeySession(session) => Ok(session),
+        _ => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generate_and_verify() {
+        let (key_id, key) = ApiKeyUtils::generate();
+        assert!(ApiKeyUtils::is_api_key(&key));
+        assert_eq!(ApiKeyUtils::parse_key_id(&key), Some(key_id.as_str()));
+        let key_hash = ApiKeyUtils::hash(&key);
+        assert!(ApiKeyUtils::verify(&key, &key_hash));
+        assert!(!ApiKeyUtils::verify(&format!("{}0", &key), &key_hash));
+        assert_eq!(ApiKeyUtils::parse_key_id("rnk_123"), None);
+        assert!(!ApiKeyUtils::is_api_key("abc"));
+        let v = ApiKeyUtils::encode_used_time(1_700_000_000);
+        assert_eq!(ApiKeyUtils::decode_used_time(&v), 1_700_000_000);
+        assert_eq!(ApiKeyUtils::decode_used_time(b"bad"), 0);
+    }
+}
@@ -2,15 +2,28 @@ use crate::common::AppSysConfig;
 use actix::prelude::*;
 use anyhow::Error;
 use bean_factory::{bean, Inject};
-use std::{sync::Arc, time::Duration};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::Duration,
+};
 //use inner_mem_cache::MemCache;
 
 use self::{
-    model::{UserDo, UserDto},
-    permission::USER_ROLE_MANAGER,
+    api_key::ApiKeyUtils,
+    model::{ApiKeyDo, ApiKeyDto, RoleDo, RoleDto, TotpInfoDto, UserDo, UserDto, UserLoginStateDo},
+    password_policy::PasswordPolicy,
+    permission::{UserRoleHelper, USER_ROLE_MANAGER, USER_ROLE_VISITOR},
+    totp::{TotpCipher, TotpUtils},
+};
+use crate::common::constant::{
+    API_KEY_TREE_NAME, API_KEY_USAGE_TREE_NAME, ROLE_TREE_NAME, USER_LOGIN_STATE_TREE_NAME,
+    USER_TREE_NAME,
+};
+use crate::common::model::privilege::{
+    NamespacePrivilegeGroup, PrivilegeGroup, PrivilegeGroupOptionParam,
 };
-use crate::common::constant::USER_TREE_NAME;
-use crate::common::model::privilege::{PrivilegeGroup, PrivilegeGroupOptionParam};
+use crate::common::model::TokenSession;
 use crate::common::string_utils::StringUtils;
 use crate::raft::cache::{CacheManager, CacheUserChangeReq};
 use crate::user::model::UserSourceType;
@@ -27,8 +40,11 @@ use crate::{
 };
 
 pub mod api;
+pub mod api_key;
 pub mod model;
+pub mod password_policy;
 pub mod permission;
+pub mod totp;
 
 pub(crate) fn build_password_hash(password: &str) -> anyhow::Result<String> {
     Ok(bcrypt::hash(password, 10u32)?)
@@ -56,16 +72,23 @@ pub struct UserManager {
     raft_table_route: Option<Arc<TableRoute>>,
     table_manager: Option<Addr<TableManager>>,
     cache_manager: Option<Addr<CacheManager>>,
+    password_policy: PasswordPolicy,
+    totp_cipher: TotpCipher,
+    /// 开启OAuth2时无密码的旧用户可能来自OAuth2登录,LDAP同步不处理其中不在LDAP中的用户
+    oauth2_enable: bool,
 }
 
 impl UserManager {
-    pub fn new() -> Self {
+    pub fn new(sys_config: &AppSysConfig) -> Self {
         Self {
             //cache: MemCache::new(),
             //cache_sec: 1200,
             raft_table_route: Default::default(),
             table_manager: Default::default(),
             cache_manager: Default::default(),
+            password_policy: PasswordPolicy::new(sys_config),
+            totp_cipher: TotpCipher::new(sys_config),
+            oauth2_enable: sys_config.oauth2_enable,
         }
     }
 
@@ -93,9 +116,11 @@ impl UserManager {
                         nickname: Some(sys_config.init_admin_username.to_owned()),
                         password: Some(sys_config.init_admin_password.to_owned()),
                         roles: Some(vec![USER_ROLE_MANAGER.clone()]),
+                        need_change_password: Some(sys_config.init_admin_must_change_password),
                         ..Default::default()
                     };
-                    let user_manager_req = UserManagerReq::AddUser {
+                    // 初始管理员密码不校验密码策略
+                    let user_manager_req = UserManagerReq::InitUser {
                         user,
                         namespace_privilege_param: None,
                     };
@@ -129,7 +154,7 @@ impl UserManager {
                 namespace_privilege.blacklist_is_all = value;
             }
         }
-        let user_do = UserDo {
+        let mut user_do = UserDo {
             username: user.username.as_ref().to_owned(),
             // 新版本不存储原密码,启用后新版数据不支持降级回去使用
             password: String::new(),
@@ -158,8 +183,20 @@ impl UserManager {
                 .iter()
                 .map(|e| e.as_ref().to_owned())
                 .collect(),
+            password_modified_time: user.password.as_ref().map(|_| now),
+            need_change_password: user.need_change_password,
             source: user.source,
+            resource_permissions: Default::default(),
+            login_fail_count: None,
+            lock_until_time: None,
+            totp_secret: None,
+            totp_enable: None,
+            totp_recovery_codes: Default::default(),
+            totp_last_step: None,
         };
+        if let Some(resource_permissions) = &user.resource_permissions {
+            user_do.set_resource_permissions(resource_permissions);
+        }
         let user_data = user_do.to_bytes();
         let req = TableManagerReq::Set {
             table_name: USER_TREE_NAME.clone(),
@@ -216,9 +253,28 @@ impl UserManager {
         .await
     }
 
+    ///
+    /// 新增或修改内部用户密码时校验密码策略
+    fn check_password_policy(
+        password_policy: &PasswordPolicy,
+        user: &UserDto,
+    ) -> anyhow::Result<()> {
+        let is_inner = user
+            .source
+            .as_ref()
+            .map(|s| UserSourceType::from_name(s).unwrap_or_default())
+            .unwrap_or_default()
+            .is_inner();
+        if let (true, Some(password)) = (is_inner, &user.password) {
+            password_policy.check_password(password)?;
+        }
+        Ok(())
+    }
+
     async fn update_user(
         raft_table_route: &Option<Arc<TableRoute>>,
         cache_manager: &Option<Addr<CacheManager>>,
+        password_policy: &PasswordPolicy,
         user: UserDto,
         namespace_privilege_param: Option<PrivilegeGroupOptionParam<Arc<String>>>,
     ) -> Result<UserManagerInnerCtx, Error> {
@@ -248,10 +304,16 @@ impl UserManager {
         }
         if let Some(password) = user.password {
             if source.is_inner() && !password.is_empty() {
+                password_policy.check_password(&password)?;
                 last_user.password_hash = build_password_hash(&password).ok();
                 // 新版本不存储原密码
                 last_user.password = String::new();
+                last_user.password_modified_time = Some(now);
+                // 修改密码后默认清除必须修改密码标记,管理员可指定要求用户下次登录修改
+                last_user.need_change_password = Some(user.need_change_password.unwrap_or(false));
             }
+        } else if let Some(need_change_password) = user.need_change_password {
+            last_user.need_change_password = Some(need_change_password);
         }
         if let Some(enable) = user.enable {
             last_user.enable = enable;
@@ -262,7 +324,7 @@ impl UserManager {
             }
         }
         if let Some(roles) = user.roles {
-            if source.is_inner() && !roles.is_empty() {
+            if (source.is_inner() || source.is_service_account()) && !roles.is_empty() {
                 last_user.roles = roles.into_iter().map(|e| e.as_ref().to_owned()).collect();
             }
         }
@@ -295,6 +357,9 @@ impl UserManager {
                 .map(|e| e.as_ref().to_owned())
                 .collect();
         }
+        if let Some(resource_permissions) = &user.resource_permissions {
+            last_user.set_resource_permissions(resource_permissions);
+        }
         last_user.gmt_modified = now;
         let user_data = last_user.to_bytes();
         let req = TableManagerReq::Set {
@@ -323,13 +388,14 @@ impl UserManager {
 
     async fn check_user(
         raft_table_route: &Option<Arc<TableRoute>>,
+        password_policy: &PasswordPolicy,
         name: Arc<String>,
         password: &String,
     ) -> Result<UserManagerInnerCtx, Error> {
         if name.is_empty() || password.is_empty() {
             return Err(anyhow::anyhow!("args is empty"));
         }
-        let last_user = if let Some(raft_table_route) = &raft_table_route {
+        let mut last_user = if let Some(raft_table_route) = &raft_table_route {
             let query_req = TableManagerQueryReq::GetByArcKey {
                 table_name: USER_TREE_NAME.clone(),
                 key: name.clone(),
@@ -348,6 +414,15 @@ impl UserManager {
             .map(|s| UserSourceType::from_name(s).unwrap_or_default())
             .unwrap_or_default();
         let mut check_success = last_user.enable && source.is_inner();
+        if !check_success {
+            return Ok(UserManagerInnerCtx::CheckUserResult(name, false, last_user));
+        }
+        Self::load_login_state(raft_table_route, &mut last_user).await?;
+        let now = (now_millis() / 1000) as u32;
+        if PasswordPolicy::is_locked(&last_user, now) {
+            //锁定期间不再校验密码
+            return Ok(UserManagerInnerCtx::CheckUserResult(name, false, last_user));
+        }
         if !StringUtils::is_option_empty(&last_user.password_hash) {
             check_success = check_success
                 && verify_password_hash_option(password, &last_user.password_hash).unwrap_or(false);
@@ -363,6 +438,19 @@ impl UserManager {
             //兼容老版本数据比较,以支持平滑从老版本升级到新版本
             check_success = check_success && &last_user.password == password;
         }
+        let changed = if check_success {
+            PasswordPolicy::clear_login_fail(&mut last_user)
+        } else {
+            password_policy.record_login_fail(&mut last_user, now);
+            password_policy.lock_fail_count > 0
+        };
+        if changed {
+            Self::save_login_state(raft_table_route, &last_user).await?;
+        }
+        if check_success && password_policy.is_password_expired(&last_user, now) {
+            //密码过期只影响本次登录结果,不修改存储的数据
+            last_user.need_change_password = Some(true);
+        }
         Ok(UserManagerInnerCtx::CheckUserResult(
             name,
             check_success,
@@ -370,6 +458,315 @@ impl UserManager {
         ))
     }
 
+    async fn save_user_do(
+        raft_table_route: &Option<Arc<TableRoute>>,
+        user_do: &UserDo,
+    ) -> anyhow::Result<()> {
+        if let Some(raft_table_route) = raft_table_route {
+            let req = TableManagerReq::Set {
+                table_name: USER_TREE_NAME.clone(),
+                key: user_do.username.as_bytes().to_owned(),
+                value: user_do.to_bytes(),
+                last_seq_id: None,
+            };
+            raft_table_route.request(req).await?;
+        }
+        Ok(())
+    }
+
+    ///
+    /// 从leader读取登录状态合并到用户记录;未写入过登录状态的旧数据沿用用户记录中的值
+    async fn load_login_state(
+        raft_table_route: &Option<Arc<TableRoute>>,
+        user: &mut UserDo,
+    ) -> anyhow::Result<()> {
+        if let Some(raft_table_route) = raft_table_route {
+            let query_req = TableManagerQueryReq::GetByArcKey {
+                table_name: USER_LOGIN_STATE_TREE_NAME.clone(),
+                key: Arc::new(user.username.clone()),
+            };
+            if let TableManagerResult::Value(v) =
+                raft_table_route.get_leader_data(query_req).await?
+            {
+                UserLoginStateDo::from_bytes(&v)?.apply_to(user);
+            }
+        }
+        Ok(())
+    }
+
+    ///
+    /// 只写入登录状态,不回写用户记录,避免覆盖并发的用户信息修改
+    async fn save_login_state(
+        raft_table_route: &Option<Arc<TableRoute>>,
+        user: &UserDo,
+    ) -> anyhow::Result<()> {
+        if let Some(raft_table_route) = raft_table_route {
+            let req = TableManagerReq::Set {
+                table_name: USER_LOGIN_STATE_TREE_NAME.clone(),
+                key: user.username.as_bytes().to_owned(),
+                value: UserLoginStateDo::from_user(user).to_bytes(),
+                last_seq_id: None,
+            };
+            raft_table_route.request(req).await?;
+        }
+        Ok(())
+    }
+
+    async fn query_leader_user(
+        raft_table_route: &Option<Arc<TableRoute>>,
+        username: &Arc<String>,
+    ) -> anyhow::Result<UserDo> {
+        if let Some(raft_table_route) = &raft_table_route {
+            let query_req = TableManagerQueryReq::GetByArcKey {
+                table_name: USER_TREE_NAME.clone(),
+                key: username.clone(),
+            };
+            match raft_table_route.get_leader_data(query_req).await? {
+                TableManagerResult::Value(old_value) => Ok(UserDo::from_bytes(&old_value)?),
+                _ => Err(anyhow::anyhow!("not found user {}", username)),
+            }
+        } else {
+            Err(anyhow::anyhow!("raft_table_route is none "))
+        }
+    }
+
+    ///
+    /// 管理员解锁因登录失败被锁定的账号
+    async fn unlock_user(
+        raft_table_route: &Option<Arc<TableRoute>>,
+        username: Arc<String>,
+    ) -> anyhow::Result<UserManagerInnerCtx> {
+        let mut last_user = Self::query_leader_user(raft_table_route, &username).await?;
+        Self::load_login_state(raft_table_route, &mut last_user).await?;
+        if PasswordPolicy::clear_login_fail(&mut last_user) {
+            Self::save_login_state(raft_table_route, &last_user).await?;
+        }
+        Ok(UserManagerInnerCtx::None)
+    }
+
+    ///
+    /// 禁用LDAP中已不存在或已停用的用户,刷新其它LDAP用户的角色,并使有变化用户的会话失效
+    async fn sync_ldap_users(
+        raft_table_route: &Option<Arc<TableRoute>>,
+        table_manager: &Option<Addr<TableManager>>,
+        cache_manager: &Option<Addr<CacheManager>>,
+        users: HashMap<Arc<String>, Arc<String>>,
+        migrate_absent_legacy: bool,
+    ) -> anyhow::Result<UserManagerInnerCtx> {
+        let table_manager = if let Some(table_manager) = table_manager {
+            table_manager
+        } else {
+            return Err(anyhow::anyhow!("table_manager is none"));
+        };
+        let query_req = TableManagerQueryReq::QueryPageList {
+            table_name: USER_TREE_NAME.clone(),
+            like_key: None,
+            offset: None,
+            limit: None,
+            is_rev: false,
+        };
+        let list = match table_manager.send(query_req).await?? {
+            TableManagerResult::PageListResult(_, list) => list,
+            _ => vec![],
+        };
+        let now = (now_millis() / 1000) as u32;
+        let (mut disable_count, mut update_count, mut migrate_count) = (0, 0, 0);
+        for (_, v) in list {
+            let mut user_do = UserDo::from_bytes(&v)?;
+            let username = Arc::new(user_do.username.clone());
+            let role = users.get(&username);
+            let migrate = !user_do.is_ldap();
+            if !user_do.prepare_ldap_sync(role.is_some(), migrate_absent_legacy) {
+                continue;
+            }
+            if migrate {
+                migrate_count += 1;
+                user_do.gmt_modified = now;
+                Self::save_user_do(raft_table_route, &user_do).await?;
+            }
+            // 每个节点本地维护会话失效时间,已禁用的用户每次同步都让本节点的会话失效
+            let mut invalid_session = role.is_none();
+            if user_do.apply_ldap_sync(role) {
+                if role.is_none() {
+                    disable_count += 1;
+                } else {
+                    update_count += 1;
+                }
+                invalid_session = true;
+                user_do.gmt_modified = now;
+                Self::save_user_do(raft_table_route, &user_do).await?;
+            }
+            if let (true, Some(cache_manager)) = (invalid_session, cache_manager) {
+                cache_manager
+                    .send(CacheUserChangeReq::UserPrivilegeChange {
+                        username,
+                        change_time: now,
+                    })
+                    .await
+                    .ok();
+            }
+        }
+        if disable_count > 0 || update_count > 0 || migrate_count > 0 {
+            log::info!(
+                "ldap user sync, disabled: {}, role updated: {}, migrated: {}",
+                disable_count,
+                update_count,
+                migrate_count
+            );
+        }
+        Ok(UserManagerInnerCtx::None)
+    }
+
+    ///
+    /// 生成新的TOTP密钥,验证动态码后才正式开启两步验证
+    async fn init_totp(
+        raft_table_route: &Option<Arc<TableRoute>>,
+        totp_cipher: &TotpCipher,
+        username: Arc<String>,
+    ) -> anyhow::Result<UserManagerInnerCtx> {
+        let mut last_user = Self::query_leader_user(raft_table_route, &username).await?;
+        if !last_user.is_inner() {
+            return Err(anyhow::anyhow!(
+                "only inner user support two-factor authentication"
+            ));
+        }
+        if last_user.totp_enable.unwrap_or_default() {
+            return Err(anyhow::anyhow!(
+                "two-factor authentication is already enabled"
+            ));
+        }
+        let secret = TotpUtils::generate_secret();
+        last_user.totp_secret = Some(totp_cipher.encrypt(&secret)?);
+        last_user.totp_enable = Some(false);
+        Self::save_user_do(raft_table_route, &last_user).await?;
+        Ok(UserManagerInnerCtx::Totp(TotpInfoDto {
+            otpauth_url: Some(TotpUtils::build_otpauth_url(&username, &secret)),
+            secret: Some(secret),
+            recovery_codes: None,
+        }))
+    }
+
+    async fn enable_totp(
+        raft_table_route: &Option<Arc<TableRoute>>,
+        totp_cipher: &TotpCipher,
+        username: Arc<String>,
+        code: String,
+    ) -> anyhow::Result<UserManagerInnerCtx> {
+        let mut last_user = Self::query_leader_user(raft_table_route, &username).await?;
+        Self::load_login_state(raft_table_route, &mut last_user).await?;
+        if last_user.totp_enable.unwrap_or_default() {
+            return Err(anyhow::anyhow!(
+                "two-factor authentication is already enabled"
+            ));
+        }
+        let secret = match &last_user.totp_secret {
+            Some(v) => totp_cipher.decrypt(v)?,
+            None => return Err(anyhow::anyhow!("totp secret is not initialized")),
+        };
+        let now = now_millis() / 1000;
+        let step = TotpUtils::verify(&secret, &code, now, 0)
+            .ok_or_else(|| anyhow::anyhow!("invalid verification code"))?;
+        let (recovery_codes, recovery_code_hashes) = TotpUtils::generate_recovery_codes();
+        last_user.totp_enable = Some(true);
+        last_user.totp_last_step = Some(step);
+        last_user.totp_recovery_codes = recovery_code_hashes;
+        Self::save_user_do(raft_table_route, &last_user).await?;
+        Self::save_login_state(raft_table_route, &last_user).await?;
+        Ok(UserManagerInnerCtx::Totp(TotpInfoDto {
+            secret: None,
+            otpauth_url: None,
+            recovery_codes: Some(recovery_codes),
+        }))
+    }
+
+    ///
+    /// 关闭两步验证;code为空时表示管理员重置,不校验动态码
+    async fn disable_totp(
+        raft_table_route: &Option<Arc<TableRoute>>,
+        totp_cipher: &TotpCipher,
+        username: Arc<String>,
+        code: Option<String>,
+    ) -> anyhow::Result<UserManagerInnerCtx> {
+        let mut last_user = Self::query_leader_user(raft_table_route, &username).await?;
+        Self::load_login_state(raft_table_route, &mut last_user).await?;
+        if let Some(code) = code {
+            let now = (now_millis() / 1000) as u32;
+            if !Self::verify_totp_code(totp_cipher, &mut last_user, &code, now) {
+                return Err(anyhow::anyhow!("invalid verification code"));
+            }
+        }
+        last_user.totp_secret = None;
+        last_user.totp_enable = Some(false);
+        last_user.totp_recovery_codes = vec![];
+        last_user.totp_last_step = None;
+        Self::save_user_do(raft_table_route, &last_user).await?;
+        Self::save_login_state(raft_table_route, &last_user).await?;
+        Ok(UserManagerInnerCtx::None)
+    }
+
+    ///
+    /// 登录第二步校验动态码或恢复码,失败次数计入账号锁定
+    async fn check_totp(
+        raft_table_route: &Option<Arc<TableRoute>>,
+        totp_cipher: &TotpCipher,
+        password_policy: &PasswordPolicy,
+        username: Arc<String>,
+        code: String,
+    ) -> anyhow::Result<UserManagerInnerCtx> {
+        let mut last_user = Self::query_leader_user(raft_table_route, &username).await?;
+        Self::load_login_state(raft_table_route, &mut last_user).await?;
+        let now = (now_millis() / 1000) as u32;
+        if !last_user.enable
+            || !last_user.totp_enable.unwrap_or_default()
+            || PasswordPolicy::is_locked(&last_user, now)
+        {
+            return Ok(UserManagerInnerCtx::CheckUserResult(
+                username, false, last_user,
+            ));
+        }
+        let recovery_code_count = last_user.totp_recovery_codes.len();
+        let check_success = Self::verify_totp_code(totp_cipher, &mut last_user, &code, now);
+        if check_success {
+            PasswordPolicy::clear_login_fail(&mut last_user);
+        } else {
+            password_policy.record_login_fail(&mut last_user, now);
+        }
+        if last_user.totp_recovery_codes.len() != recovery_code_count {
+            //使用了恢复码,需要回写用户记录
+            Self::save_user_do(raft_table_route, &last_user).await?;
+        }
+        Self::save_login_state(raft_table_route, &last_user).await?;
+        if check_success && password_policy.is_password_expired(&last_user, now) {
+            last_user.need_change_password = Some(true);
+        }
+        Ok(UserManagerInnerCtx::CheckUserResult(
+            username,
+            check_success,
+            last_user,
+        ))
+    }
+
+    fn verify_totp_code(totp_cipher: &TotpCipher, user: &mut UserDo, code: &str, now: u32) -> bool {
+        let secret = match user
+            .totp_secret
+            .as_ref()
+            .and_then(|v| totp_cipher.decrypt(v).ok())
+        {
+            Some(v) => v,
+            None => return false,
+        };
+        if let Some(step) = TotpUtils::verify(
+            &secret,
+            code,
+            now as u64,
+            user.totp_last_step.unwrap_or_default(),
+        ) {
+            user.totp_last_step = Some(step);
+            return true;
+        }
+        TotpUtils::use_recovery_code(code, &mut user.totp_recovery_codes)
+    }
+
     async fn remove(
         raft_table_route: Option<Arc<TableRoute>>,
         table_manager: &Option<Addr<TableManager>>,
@@ -425,8 +822,19 @@ impl UserManager {
                 table_name: USER_TREE_NAME.clone(),
                 key: username.as_bytes().to_owned(),
             };
-            if let Some(raft_table_route) = raft_table_route {
+            if let Some(raft_table_route) = &raft_table_route {
+                raft_table_route.request(req).await.ok();
+                let req = TableManagerReq::Remove {
+                    table_name: USER_LOGIN_STATE_TREE_NAME.clone(),
+                    key: username.as_bytes().to_owned(),
+                };
                 raft_table_route.request(req).await.ok();
+                // 同时移除该用户的API key
+                for api_key in Self::query_api_key_list(table_manager, Some(&username)).await? {
+                    Self::remove_api_key(raft_table_route, &api_key.key_id)
+                        .await
+                        .ok();
+                }
             }
             if let Some(cache_manager) = &cache_manager {
                 cache_manager
@@ -482,7 +890,17 @@ impl UserManager {
                 TableManagerResult::PageListResult(size, list) => {
                     let mut user_list = Vec::with_capacity(list.len());
                     for (_, v) in list {
-                        user_list.push(UserDo::from_bytes(&v)?.into());
+                        let mut user_do = UserDo::from_bytes(&v)?;
+                        let query_req = TableManagerQueryReq::GetByArcKey {
+                            table_name: USER_LOGIN_STATE_TREE_NAME.clone(),
+                            key: Arc::new(user_do.username.clone()),
+                        };
+                        if let TableManagerResult::Value(v) =
+                            table_manager.send(query_req).await??
+                        {
+                            UserLoginStateDo::from_bytes(&v)?.apply_to(&mut user_do);
+                        }
+                        user_list.push(user_do.into());
                     }
                     Ok(UserManagerInnerCtx::UserPageResult(size, user_list))
                 }
@@ -492,11 +910,314 @@ impl UserManager {
             Ok(UserManagerInnerCtx::UserPageResult(0, vec![]))
         }
     }
-}
 
-impl Default for UserManager {
-    fn default() -> Self {
-        Self::new()
+    async fn query_role_list(
+        table_manager: &Option<Addr<TableManager>>,
+    ) -> anyhow::Result<Vec<RoleDo>> {
+        let mut role_list = vec![];
+        if let Some(table_manager) = &table_manager {
+            let query_req = TableManagerQueryReq::QueryPageList {
+                table_name: ROLE_TREE_NAME.clone(),
+                like_key: None,
+                offset: None,
+                limit: None,
+                is_rev: false,
+            };
+            if let TableManagerResult::PageListResult(_, list) =
+                table_manager.send(query_req).await??
+            {
+                for (_, v) in list {
+                    role_list.push(RoleDo::from_bytes(&v)?);
+                }
+            }
+        }
+        Ok(role_list)
+    }
+
+    async fn save_role(
+        raft_table_route: &Option<Arc<TableRoute>>,
+        role: RoleDto,
+        is_add: bool,
+    ) -> anyhow::Result<UserManagerInnerCtx> {
+        let raft_table_route = if let Some(raft_table_route) = raft_table_route {
+            raft_table_route
+        } else {
+            return Err(anyhow::anyhow!("raft_table_route is none "));
+        };
+        let query_req = TableManagerQueryReq::GetByArcKey {
+            table_name: ROLE_TREE_NAME.clone(),
+            key: role.name.clone(),
+        };
+        let last_role = match raft_table_route.get_leader_data(query_req).await? {
+            TableManagerResult::Value(old_value) => Some(RoleDo::from_bytes(&old_value)?),
+            _ => None,
+        };
+        let now = (now_millis() / 1000) as u32;
+        let role_do = match (last_role, is_add) {
+            (Some(_), true) => return Err(anyhow::anyhow!("role already exists: {}", &role.name)),
+            (None, false) => return Err(anyhow::anyhow!("not found role {}", &role.name)),
+            (Some(mut last_role), false) => {
+                if let Some(desc) = role.desc {
+                    last_role.desc = desc;
+                }
+                if let Some(modules) = role.modules {
+                    last_role.modules = modules;
+                }
+                last_role.gmt_modified = now;
+                last_role
+            }
+            (None, true) => RoleDo {
+                name: role.name.as_ref().to_owned(),
+                desc: role.desc.unwrap_or_default(),
+                modules: role.modules.unwrap_or_default(),
+                gmt_create: now,
+                gmt_modified: now,
+            },
+        };
+        UserRoleHelper::check_custom_role(&role_do.name, &role_do.modules)?;
+        let req = TableManagerReq::Set {
+            table_name: ROLE_TREE_NAME.clone(),
+            key: role_do.name.as_bytes().to_owned(),
+            value: role_do.to_bytes(),
+            last_seq_id: None,
+        };
+        raft_table_route.request(req).await?;
+        Ok(UserManagerInnerCtx::None)
+    }
+
+    async fn remove_role(
+        raft_table_route: &Option<Arc<TableRoute>>,
+        table_manager: &Option<Addr<TableManager>>,
+        name: Arc<String>,
+    ) -> anyhow::Result<UserManagerInnerCtx> {
+        if let Some(table_manager) = &table_manager {
+            let query_req = TableManagerQueryReq::QueryPageList {
+                table_name: USER_TREE_NAME.clone(),
+                like_key: None,
+                offset: None,
+                limit: None,
+                is_rev: false,
+            };
+            if let TableManagerResult::PageListResult(_, list) =
+                table_manager.send(query_req).await??
+            {
+                let in_use = list
+                    .iter()
+                    .filter_map(|(_, v)| UserDo::from_bytes(v).ok())
+                    .any(|user_do| user_do.roles.contains(name.as_ref()));
+                // 仍有用户使用该角色时，不允许删除
+                if in_use {
+                    return Err(anyhow::anyhow!("role is in use: {}", &name));
+                }
+            }
+        }
+        let req = TableManagerReq::Remove {
+            table_name: ROLE_TREE_NAME.clone(),
+            key: name.as_bytes().to_owned(),
+        };
+        if let Some(raft_table_route) = raft_table_route {
+            raft_table_route.request(req).await?;
+        }
+        Ok(UserManagerInnerCtx::None)
+    }
+
+    async fn query_api_key_list(
+        table_manager: &Addr<TableManager>,
+        username: Option<&Arc<String>>,
+    ) -> anyhow::Result<Vec<ApiKeyDo>> {
+        let mut list = vec![];
+        let query_req = TableManagerQueryReq::QueryPageList {
+            table_name: API_KEY_TREE_NAME.clone(),
+            like_key: None,
+            offset: None,
+            limit: None,
+            is_rev: false,
+        };
+        if let TableManagerResult::PageListResult(_, items) =
+            table_manager.send(query_req).await??
+        {
+            for (_, v) in items {
+                let api_key = ApiKeyDo::from_bytes(&v)?;
+                if username.is_none_or(|e| e.as_str() == api_key.username) {
+                    list.push(api_key);
+                }
+            }
+        }
+        for api_key in list.iter_mut() {
+            let used_time =
+                Self::query_api_key_used_time(table_manager, Arc::new(api_key.key_id.clone()))
+                    .await?;
+            api_key.last_used_time = api_key.last_used_time.max(used_time);
+        }
+        Ok(list)
+    }
+
+    async fn query_api_key_used_time(
+        table_manager: &Addr<TableManager>,
+        key_id: Arc<String>,
+    ) -> anyhow::Result<u32> {
+        let query_req = TableManagerQueryReq::GetByArcKey {
+            table_name: API_KEY_USAGE_TREE_NAME.clone(),
+            key: key_id,
+        };
+        match table_manager.send(query_req).await?? {
+            TableManagerResult::Value(v) => Ok(ApiKeyUtils::decode_used_time(&v)),
+            _ => Ok(0),
+        }
+    }
+
+    async fn create_api_key(
+        raft_table_route: &Option<Arc<TableRoute>>,
+        table_manager: &Option<Addr<TableManager>>,
+        param: ApiKeyDto,
+    ) -> anyhow::Result<UserManagerInnerCtx> {
+        let name = param.name.unwrap_or_default();
+        if name.is_empty() {
+            return Err(anyhow::anyhow!("api key name is empty"));
+        }
+        if let UserManagerInnerCtx::QueryUser(_, None) =
+            Self::query_user(table_manager, false, param.username.clone()).await?
+        {
+            return Err(anyhow::anyhow!("not found user {}", &param.username));
+        }
+        let now = (now_millis() / 1000) as u32;
+        let expire_time = (param.expire_time.unwrap_or_default() / 1000) as u32;
+        if expire_time > 0 && expire_time <= now {
+            return Err(anyhow::anyhow!("api key expire time is invalid"));
+        }
+        let (key_id, key) = ApiKeyUtils::generate();
+        let api_key = ApiKeyDo {
+            key_id,
+            name,
+            username: param.username.as_ref().to_owned(),
+            key_hash: ApiKeyUtils::hash(&key),
+            namespaces: param.namespaces.unwrap_or_default(),
+            write: param.write.unwrap_or_default(),
+            expire_time,
+            last_used_time: 0,
+            gmt_create: now,
+        };
+        let req = TableManagerReq::Set {
+            table_name: API_KEY_TREE_NAME.clone(),
+            key: api_key.key_id.as_bytes().to_owned(),
+            value: api_key.to_bytes(),
+            last_seq_id: None,
+        };
+        if let Some(raft_table_route) = raft_table_route {
+            raft_table_route.request(req).await?;
+        } else {
+            return Err(anyhow::anyhow!("raft_table_route is none "));
+        }
+        let mut dto: ApiKeyDto = api_key.into();
+        dto.key = Some(key);
+        Ok(UserManagerInnerCtx::ApiKey(dto))
+    }
+
+    async fn revoke_api_key(
+        raft_table_route: &Option<Arc<TableRoute>>,
+        key_id: Arc<String>,
+    ) -> anyhow::Result<UserManagerInnerCtx> {
+        if let Some(raft_table_route) = raft_table_route {
+            Self::remove_api_key(raft_table_route, &key_id).await?;
+        }
+        Ok(UserManagerInnerCtx::None)
+    }
+
+    async fn remove_api_key(raft_table_route: &TableRoute, key_id: &str) -> anyhow::Result<()> {
+        let req = TableManagerReq::Remove {
+            table_name: API_KEY_TREE_NAME.clone(),
+            key: key_id.as_bytes().to_owned(),
+        };
+        raft_table_route.request(req).await?;
+        let req = TableManagerReq::Remove {
+            table_name: API_KEY_USAGE_TREE_NAME.clone(),
+            key: key_id.as_bytes().to_owned(),
+        };
+        raft_table_route.request(req).await?;
+        Ok(())
+    }
+
+    ///
+    /// 校验API key并按key的权限范围构建访问会话;
+    /// key与用户信息从本节点的表中读取,最近使用时间每分钟最多更新一次,
+    /// 且只写入使用时间表,不回写凭证记录,避免覆盖并发的吊销操作
+    async fn check_api_key(
+        raft_table_route: &Option<Arc<TableRoute>>,
+        table_manager: &Option<Addr<TableManager>>,
+        key: String,
+    ) -> anyhow::Result<UserManagerInnerCtx> {
+        let (key_id, table_manager_addr) = match (ApiKeyUtils::parse_key_id(&key), table_manager) {
+            (Some(key_id), Some(table_manager)) => (Arc::new(key_id.to_owned()), table_manager),
+            _ => return Ok(UserManagerInnerCtx::ApiKeySession(None)),
+        };
+        let query_req = TableManagerQueryReq::GetByArcKey {
+            table_name: API_KEY_TREE_NAME.clone(),
+            key: key_id.clone(),
+        };
+        let api_key = match table_manager_addr.send(query_req).await?? {
+            TableManagerResult::Value(v) => ApiKeyDo::from_bytes(&v)?,
+            _ => return Ok(UserManagerInnerCtx::ApiKeySession(None)),
+        };
+        let now = (now_millis() / 1000) as u32;
+        if !ApiKeyUtils::verify(&key, &api_key.key_hash) || api_key.is_expired(now) {
+            return Ok(UserManagerInnerCtx::ApiKeySession(None));
+        }
+        let user: UserDto =
+            match Self::query_user(table_manager, false, Arc::new(api_key.username.clone())).await?
+            {
+                UserManagerInnerCtx::QueryUser(_, Some(user_do)) if user_do.enable => {
+                    user_do.into()
+                }
+                _ => return Ok(UserManagerInnerCtx::ApiKeySession(None)),
+            };
+        let namespace_privilege = if api_key.namespaces.is_empty() {
+            user.namespace_privilege.clone()
+        } else {
+            let user_privilege =
+                NamespacePrivilegeGroup::new(user.namespace_privilege.clone().unwrap_or_default());
+            let whitelist: HashSet<Arc<String>> = api_key
+                .namespaces
+                .iter()
+                .map(|e| Arc::new(e.to_owned()))
+                .filter(|e| user_privilege.check_permission(e))
+                .collect();
+            let mut namespace_privilege = PrivilegeGroup::empty();
+            namespace_privilege.whitelist = Some(Arc::new(whitelist));
+            Some(namespace_privilege)
+        };
+        let (roles, resource_privilege) = if api_key.write {
+            (
+                user.roles.clone().unwrap_or_default(),
+                user.build_resource_privilege(),
+            )
+        } else {
+            (
+                vec![USER_ROLE_VISITOR.clone()],
+                user.build_resource_privilege().to_read_only(),
+            )
+        };
+        let session = Arc::new(TokenSession {
+            username: user.username,
+            roles,
+            extend_infos: user.extend_info.unwrap_or_default(),
+            namespace_privilege,
+            resource_privilege,
+        });
+        let last_used_time = Self::query_api_key_used_time(table_manager_addr, key_id).await?;
+        if now >= last_used_time.max(api_key.last_used_time) + 60 {
+            if let Some(raft_table_route) = raft_table_route {
+                let req = TableManagerReq::Set {
+                    table_name: API_KEY_USAGE_TREE_NAME.clone(),
+                    key: api_key.key_id.as_bytes().to_owned(),
+                    value: ApiKeyUtils::encode_used_time(now),
+                    last_seq_id: None,
+                };
+                if let Err(e) = raft_table_route.request(req).await {
+                    log::warn!("update api key used time error,{}", e);
+                }
+            }
+        }
+        Ok(UserManagerInnerCtx::ApiKeySession(Some(session)))
     }
 }
 
@@ -574,6 +1295,28 @@ pub enum UserManagerReq {
     Remove {
         username: Arc<String>,
     },
+    UnlockUser {
+        username: Arc<String>,
+    },
+    /// 按LDAP中的有效用户(用户名->角色)同步LDAP来源的用户
+    SyncLdapUsers {
+        users: HashMap<Arc<String>, Arc<String>>,
+    },
+    InitTotp {
+        username: Arc<String>,
+    },
+    EnableTotp {
+        username: Arc<String>,
+        code: String,
+    },
+    DisableTotp {
+        username: Arc<String>,
+        code: Option<String>,
+    },
+    CheckTotp {
+        username: Arc<String>,
+        code: String,
+    },
     Query {
         name: Arc<String>,
     },
@@ -583,6 +1326,28 @@ pub enum UserManagerReq {
         limit: Option<i64>,
         is_rev: bool,
     },
+    QueryRoleList,
+    AddRole {
+        role: RoleDto,
+    },
+    UpdateRole {
+        role: RoleDto,
+    },
+    RemoveRole {
+        name: Arc<String>,
+    },
+    QueryApiKeyList {
+        username: Option<Arc<String>>,
+    },
+    CreateApiKey {
+        param: ApiKeyDto,
+    },
+    RevokeApiKey {
+        key_id: Arc<String>,
+    },
+    CheckApiKey {
+        key: String,
+    },
 }
 
 pub enum UserManagerInnerCtx {
@@ -591,6 +1356,11 @@ pub enum UserManagerInnerCtx {
     CheckUserResult(Arc<String>, bool, UserDo),
     QueryUser(Arc<String>, Option<UserDo>),
     UserPageResult(usize, Vec<UserDto>),
+    RoleList(Vec<RoleDto>),
+    ApiKey(ApiKeyDto),
+    ApiKeyList(Vec<ApiKeyDto>),
+    ApiKeySession(Option<Arc<TokenSession>>),
+    Totp(TotpInfoDto),
 }
 
 pub enum UserManagerResult {
@@ -598,6 +1368,11 @@ pub enum UserManagerResult {
     CheckUserResult(bool, UserDto),
     QueryUser(Option<UserDto>),
     UserPageResult(usize, Vec<UserDto>),
+    RoleList(Vec<RoleDto>),
+    ApiKey(ApiKeyDto),
+    ApiKeyList(Vec<ApiKeyDto>),
+    ApiKeySession(Option<Arc<TokenSession>>),
+    Totp(TotpInfoDto),
 }
 
 impl Handler<UserManagerReq> for UserManager {
@@ -607,6 +1382,9 @@ impl Handler<UserManagerReq> for UserManager {
         let raft_table_route = self.raft_table_route.clone();
         let table_manager = self.table_manager.clone();
         let cache_manager = self.cache_manager.clone();
+        let password_policy = self.password_policy.clone();
+        let totp_cipher = self.totp_cipher.clone();
+        let oauth2_enable = self.oauth2_enable;
         //let query_info_at_cache = match &msg {
         //    UserManagerReq::Query { name } => self.cache.get(name).ok().is_some(),
         //    _ => false,
@@ -618,6 +1396,7 @@ impl Handler<UserManagerReq> for UserManager {
                     user,
                     namespace_privilege_param,
                 } => {
+                    Self::check_password_policy(&password_policy, &user)?;
                     Self::add_user(
                         raft_table_route,
                         cache_manager,
@@ -645,13 +1424,46 @@ impl Handler<UserManagerReq> for UserManager {
                     Self::update_user(
                         &raft_table_route,
                         &cache_manager,
+                        &password_policy,
                         user,
                         namespace_privilege_param,
                     )
                     .await
                 }
                 UserManagerReq::CheckUser { name, password } => {
-                    Self::check_user(&raft_table_route, name, &password).await
+                    Self::check_user(&raft_table_route, &password_policy, name, &password).await
+                }
+                UserManagerReq::UnlockUser { username } => {
+                    Self::unlock_user(&raft_table_route, username).await
+                }
+                UserManagerReq::SyncLdapUsers { users } => {
+                    Self::sync_ldap_users(
+                        &raft_table_route,
+                        &table_manager,
+                        &cache_manager,
+                        users,
+                        !oauth2_enable,
+                    )
+                    .await
+                }
+                UserManagerReq::InitTotp { username } => {
+                    Self::init_totp(&raft_table_route, &totp_cipher, username).await
+                }
+                UserManagerReq::EnableTotp { username, code } => {
+                    Self::enable_totp(&raft_table_route, &totp_cipher, username, code).await
+                }
+                UserManagerReq::DisableTotp { username, code } => {
+                    Self::disable_totp(&raft_table_route, &totp_cipher, username, code).await
+                }
+                UserManagerReq::CheckTotp { username, code } => {
+                    Self::check_totp(
+                        &raft_table_route,
+                        &totp_cipher,
+                        &password_policy,
+                        username,
+                        code,
+                    )
+                    .await
                 }
                 UserManagerReq::Remove { username } => {
                     Self::remove(raft_table_route, &table_manager, &cache_manager, username).await
@@ -668,6 +1480,41 @@ impl Handler<UserManagerReq> for UserManager {
                     Self::query_user_list(&table_manager, offset, like_username, limit, is_rev)
                         .await
                 }
+                UserManagerReq::QueryRoleList => {
+                    let role_list = Self::query_role_list(&table_manager).await?;
+                    Ok(UserManagerInnerCtx::RoleList(
+                        role_list.into_iter().map(RoleDto::from).collect(),
+                    ))
+                }
+                UserManagerReq::AddRole { role } => {
+                    Self::save_role(&raft_table_route, role, true).await
+                }
+                UserManagerReq::UpdateRole { role } => {
+                    Self::save_role(&raft_table_route, role, false).await
+                }
+                UserManagerReq::RemoveRole { name } => {
+                    Self::remove_role(&raft_table_route, &table_manager, name).await
+                }
+                UserManagerReq::QueryApiKeyList { username } => {
+                    let list = match &table_manager {
+                        Some(table_manager) => {
+                            Self::query_api_key_list(table_manager, username.as_ref()).await?
+                        }
+                        None => vec![],
+                    };
+                    Ok(UserManagerInnerCtx::ApiKeyList(
+                        list.into_iter().map(ApiKeyDto::from).collect(),
+                    ))
+                }
+                UserManagerReq::CreateApiKey { param } => {
+                    Self::create_api_key(&raft_table_route, &table_manager, param).await
+                }
+                UserManagerReq::RevokeApiKey { key_id } => {
+                    Self::revoke_api_key(&raft_table_route, key_id).await
+                }
+                UserManagerReq::CheckApiKey { key } => {
+                    Self::check_api_key(&raft_table_route, &table_manager, key).await
+                }
             }
         }
         .into_actor(self)
@@ -691,6 +1538,11 @@ impl Handler<UserManagerReq> for UserManager {
                 UserManagerInnerCtx::UserPageResult(size, list) => {
                     Ok(UserManagerResult::UserPageResult(size, list))
                 }
+                UserManagerInnerCtx::RoleList(list) => Ok(UserManagerResult::RoleList(list)),
+                UserManagerInnerCtx::ApiKey(v) => Ok(UserManagerResult::ApiKey(v)),
+                UserManagerInnerCtx::ApiKeyList(list) => Ok(UserManagerResult::ApiKeyList(list)),
+                UserManagerInnerCtx::ApiKeySession(v) => Ok(UserManagerResult::ApiKeySession(v)),
+                UserManagerInnerCtx::Totp(v) => Ok(UserManagerResult::Totp(v)),
             },
         );
         Box::pin(fut)
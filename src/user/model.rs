@@ -1,4 +1,6 @@
-use crate::common::model::privilege::{PrivilegeGroup, PrivilegeGroupFlags};
+use crate::common::model::privilege::{
+    PrivilegeGroup, PrivilegeGroupFlags, ResourceAction, ResourcePermission, ResourcePrivilege,
+};
 use crate::user::permission::UserRoleHelper;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
@@ -9,6 +11,8 @@ use std::{collections::HashMap, sync::Arc};
 pub enum UserSourceType {
     Inner,
     Ldap,
+    /// 服务账号,不支持密码登录,只通过API key访问openapi与grpc接口
+    ServiceAccount,
 }
 
 impl Default for UserSourceType {
@@ -22,11 +26,16 @@ impl UserSourceType {
         matches!(self, UserSourceType::Inner)
     }
 
+    pub fn is_service_account(&self) -> bool {
+        matches!(self, UserSourceType::ServiceAccount)
+    }
+
     pub fn from_name(name: &str) -> Option<Self> {
         match name {
             "" => Some(UserSourceType::Inner),
             "INNER" => Some(UserSourceType::Inner),
             "LDAP" => Some(UserSourceType::Ldap),
+            "SERVICE_ACCOUNT" => Some(UserSourceType::ServiceAccount),
             _ => None,
         }
     }
@@ -35,6 +44,7 @@ impl UserSourceType {
         match self {
             UserSourceType::Inner => "INNER",
             UserSourceType::Ldap => "LDAP",
+            UserSourceType::ServiceAccount => "SERVICE_ACCOUNT",
         }
     }
 }
@@ -68,6 +78,105 @@ pub struct UserDo {
     pub namespace_black_list: ::prost::alloc::vec::Vec<String>,
     #[prost(string, optional, tag = "13")]
     pub source: Option<String>,
+    #[prost(message, repeated, tag = "14")]
+    pub resource_permissions: ::prost::alloc::vec::Vec<ResourcePermissionDo>,
+    /// 下次登录必须修改密码
+    #[prost(bool, optional, tag = "15")]
+    pub need_change_password: Option<bool>,
+    #[prost(uint32, optional, tag = "16")]
+    pub password_modified_time: Option<u32>,
+    /// 登录失败次数、锁定时间与TOTP时间步已改为存储在登录状态表,这里只用于兼容旧数据
+    #[prost(uint32, optional, tag = "17")]
+    pub login_fail_count: Option<u32>,
+    /// 账号锁定截止时间(秒)
+    #[prost(uint32, optional, tag = "18")]
+    pub lock_until_time: Option<u32>,
+    /// 加密后的TOTP密钥
+    #[prost(string, optional, tag = "19")]
+    pub totp_secret: Option<String>,
+    #[prost(bool, optional, tag = "20")]
+    pub totp_enable: Option<bool>,
+    /// 未使用的恢复码hash
+    #[prost(string, repeated, tag = "21")]
+    pub totp_recovery_codes: ::prost::alloc::vec::Vec<String>,
+    /// 最近一次验证通过的时间步,防止动态码重复使用
+    #[prost(uint64, optional, tag = "22")]
+    pub totp_last_step: Option<u64>,
+}
+
+///
+/// 登录状态,按用户名单独存储;登录校验只写入该记录,不回写整个用户记录
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct UserLoginStateDo {
+    #[prost(uint32, tag = "1")]
+    pub login_fail_count: u32,
+    /// 账号锁定截止时间(秒)
+    #[prost(uint32, tag = "2")]
+    pub lock_until_time: u32,
+    #[prost(uint64, tag = "3")]
+    pub totp_last_step: u64,
+}
+
+impl UserLoginStateDo {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut v = Vec::new();
+        prost::Message::encode(self, &mut v).unwrap_or_default();
+        v
+    }
+
+    pub fn from_bytes(v: &[u8]) -> anyhow::Result<Self> {
+        Ok(prost::Message::decode(v)?)
+    }
+
+    pub fn from_user(user: &UserDo) -> Self {
+        Self {
+            login_fail_count: user.login_fail_count.unwrap_or_default(),
+            lock_until_time: user.lock_until_time.unwrap_or_default(),
+            totp_last_step: user.totp_last_step.unwrap_or_default(),
+        }
+    }
+
+    ///
+    /// 用户记录中的同名字段只用于兼容旧数据,存在登录状态记录时以登录状态为准
+    pub fn apply_to(&self, user: &mut UserDo) {
+        user.login_fail_count = Some(self.login_fail_count);
+        user.lock_until_time = Some(self.lock_until_time);
+        user.totp_last_step = Some(self.totp_last_step);
+    }
+}
+
+#[derive(Clone, PartialEq, prost::Message, Serialize, Deserialize)]
+pub struct ResourcePermissionDo {
+    #[prost(string, tag = "1")]
+    pub namespace: String,
+    #[prost(string, tag = "2")]
+    pub group: String,
+    #[prost(string, tag = "3")]
+    pub resource: String,
+    #[prost(string, tag = "4")]
+    pub action: String,
+}
+
+impl From<&ResourcePermission> for ResourcePermissionDo {
+    fn from(value: &ResourcePermission) -> Self {
+        Self {
+            namespace: value.namespace.as_ref().to_owned(),
+            group: value.group.as_ref().to_owned(),
+            resource: value.resource.as_ref().to_owned(),
+            action: value.action.to_str().to_owned(),
+        }
+    }
+}
+
+impl ResourcePermissionDo {
+    pub fn to_permission(&self) -> Option<ResourcePermission> {
+        Some(ResourcePermission {
+            namespace: Arc::new(self.namespace.clone()),
+            group: Arc::new(self.group.clone()),
+            resource: Arc::new(self.resource.clone()),
+            action: ResourceAction::from_name(&self.action)?,
+        })
+    }
 }
 
 impl UserDo {
@@ -101,6 +210,87 @@ impl UserDo {
             PrivilegeGroup::all()
         }
     }
+
+    pub fn is_inner(&self) -> bool {
+        self.source
+            .as_ref()
+            .map(|s| UserSourceType::from_name(s).unwrap_or_default())
+            .unwrap_or_default()
+            .is_inner()
+    }
+
+    ///
+    /// 旧版本LDAP(及OAuth2)登录创建的用户来源为Inner且没有设置密码
+    pub fn is_legacy_external(&self) -> bool {
+        self.is_inner()
+            && self.password.is_empty()
+            && self.password_hash.as_ref().is_none_or(|v| v.is_empty())
+    }
+
+    ///
+    /// 判断用户是否参与LDAP同步,旧版本LDAP登录创建的用户在此迁移为LDAP来源;
+    /// 旧用户在LDAP中存在时迁移,不在LDAP中时只有migrate_absent为true(没有其它途径创建无密码用户)才迁移
+    pub fn prepare_ldap_sync(&mut self, in_directory: bool, migrate_absent: bool) -> bool {
+        if self.is_ldap() {
+            return true;
+        }
+        if !self.is_legacy_external() || !(in_directory || migrate_absent) {
+            return false;
+        }
+        self.source = Some(UserSourceType::Ldap.to_str().to_owned());
+        true
+    }
+
+    pub fn is_ldap(&self) -> bool {
+        self.source
+            .as_ref()
+            .map(|s| UserSourceType::from_name(s).unwrap_or_default())
+            .map(|s| matches!(s, UserSourceType::Ldap))
+            .unwrap_or_default()
+    }
+
+    ///
+    /// 按LDAP同步结果更新用户;LDAP中已不存在或已停用(role为None)时禁用用户,否则刷新角色。
+    /// LDAP用户组只映射内置角色,刷新时只替换内置角色,保留管理员额外分配的自定义角色。
+    /// 同步不会自动启用用户,被禁用的用户需要管理员手动启用。有变更时返回true
+    pub fn apply_ldap_sync(&mut self, role: Option<&Arc<String>>) -> bool {
+        match role {
+            None if self.enable => {
+                self.enable = false;
+                true
+            }
+            Some(role) => {
+                let mut roles = vec![role.as_ref().to_owned()];
+                roles.extend(
+                    self.roles
+                        .iter()
+                        .filter(|e| !UserRoleHelper::is_builtin_role(e))
+                        .cloned(),
+                );
+                if roles == self.roles {
+                    return false;
+                }
+                self.roles = roles;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn build_resource_permissions(&self) -> Vec<ResourcePermission> {
+        self.resource_permissions
+            .iter()
+            .filter_map(|e| e.to_permission())
+            .collect()
+    }
+
+    pub fn build_resource_privilege(&self) -> ResourcePrivilege {
+        ResourcePrivilege::new(self.build_resource_permissions())
+    }
+
+    pub fn set_resource_permissions(&mut self, items: &[ResourcePermission]) {
+        self.resource_permissions = items.iter().map(ResourcePermissionDo::from).collect();
+    }
 }
 
 #[derive(Clone, Serialize, Deserialize, Default)]
@@ -116,7 +306,19 @@ pub struct UserDto {
     pub roles: Option<Vec<Arc<String>>>,
     pub extend_info: Option<HashMap<String, String>>,
     pub namespace_privilege: Option<PrivilegeGroup<Arc<String>>>,
+    /// 细粒度数据权限,为空时不限制
+    pub resource_permissions: Option<Vec<ResourcePermission>>,
     pub source: Option<String>,
+    pub need_change_password: Option<bool>,
+    /// 账号锁定截止时间(毫秒),未锁定时为空
+    pub lock_until_time: Option<i64>,
+    pub totp_enable: Option<bool>,
+}
+
+impl UserDto {
+    pub fn build_resource_privilege(&self) -> ResourcePrivilege {
+        ResourcePrivilege::new(self.resource_permissions.clone().unwrap_or_default())
+    }
 }
 
 impl From<UserDo> for UserDto {
@@ -126,6 +328,7 @@ impl From<UserDo> for UserDto {
             roles.push(UserRoleHelper::get_role(role));
         }
         let namespace_privilege = Some(value.build_namespace_privilege());
+        let resource_permissions = Some(value.build_resource_permissions());
         Self {
             username: Arc::new(value.username),
             nickname: Some(value.nickname),
@@ -139,7 +342,216 @@ impl From<UserDo> for UserDto {
             roles: Some(roles),
             extend_info: Some(value.extend_info),
             namespace_privilege,
+            resource_permissions,
             source: value.source,
+            need_change_password: value.need_change_password,
+            lock_until_time: value
+                .lock_until_time
+                .filter(|e| *e > 0)
+                .map(|e| e as i64 * 1000),
+            totp_enable: value.totp_enable,
         }
     }
 }
+
+///
+/// 两步验证绑定信息,密钥与恢复码只在生成时返回一次
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TotpInfoDto {
+    pub secret: Option<String>,
+    pub otpauth_url: Option<String>,
+    pub recovery_codes: Option<Vec<String>>,
+}
+
+///
+/// 自定义角色,由已有的功能模块组合而成
+#[derive(Clone, PartialEq, prost::Message, Serialize, Deserialize)]
+pub struct RoleDo {
+    #[prost(string, tag = "1")]
+    pub name: String,
+    #[prost(string, tag = "2")]
+    pub desc: String,
+    #[prost(string, repeated, tag = "3")]
+    pub modules: ::prost::alloc::vec::Vec<String>,
+    #[prost(uint32, tag = "4")]
+    pub gmt_create: u32,
+    #[prost(uint32, tag = "5")]
+    pub gmt_modified: u32,
+}
+
+impl RoleDo {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut v = Vec::new();
+        prost::Message::encode(self, &mut v).unwrap_or_default();
+        v
+    }
+
+    pub fn from_bytes(v: &[u8]) -> anyhow::Result<Self> {
+        Ok(prost::Message::decode(v)?)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct RoleDto {
+    pub name: Arc<String>,
+    pub desc: Option<String>,
+    pub modules: Option<Vec<String>>,
+    pub gmt_create: Option<i64>,
+    pub gmt_modified: Option<i64>,
+}
+
+impl From<RoleDo> for RoleDto {
+    fn from(value: RoleDo) -> Self {
+        Self {
+            name: Arc::new(value.name),
+            desc: Some(value.desc),
+            modules: Some(value.modules),
+            gmt_create: Some(value.gmt_create as i64 * 1000),
+            gmt_modified: Some(value.gmt_modified as i64 * 1000),
+        }
+    }
+}
+
+///
+/// openapi与grpc访问使用的长期API key,只保存key的hash值
+#[derive(Clone, PartialEq, prost::Message, Serialize, Deserialize)]
+pub struct ApiKeyDo {
+    #[prost(string, tag = "1")]
+    pub key_id: String,
+    #[prost(string, tag = "2")]
+    pub name: String,
+    #[prost(string, tag = "3")]
+    pub username: String,
+    #[prost(string, tag = "4")]
+    pub key_hash: String,
+    /// 可访问的命名空间,为空时与用户命名空间权限一致
+    #[prost(string, repeated, tag = "5")]
+    pub namespaces: ::prost::alloc::vec::Vec<String>,
+    #[prost(bool, tag = "6")]
+    pub write: bool,
+    /// 过期时间(秒),0表示不过期
+    #[prost(uint32, tag = "7")]
+    pub expire_time: u32,
+    #[prost(uint32, tag = "8")]
+    pub last_used_time: u32,
+    #[prost(uint32, tag = "9")]
+    pub gmt_create: u32,
+}
+
+impl ApiKeyDo {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut v = Vec::new();
+        prost::Message::encode(self, &mut v).unwrap_or_default();
+        v
+    }
+
+    pub fn from_bytes(v: &[u8]) -> anyhow::Result<Self> {
+        Ok(prost::Message::decode(v)?)
+    }
+
+    pub fn is_expired(&self, now: u32) -> bool {
+        self.expire_time > 0 && self.expire_time <= now
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiKeyDto {
+    pub key_id: Option<Arc<String>>,
+    pub name: Option<String>,
+    pub username: Arc<String>,
+    pub namespaces: Option<Vec<String>>,
+    pub write: Option<bool>,
+    pub expire_time: Option<i64>,
+    pub last_used_time: Option<i64>,
+    pub gmt_create: Option<i64>,
+    /// 完整的key,只在创建时返回一次
+    pub key: Option<String>,
+}
+
+impl From<ApiKeyDo> for ApiKeyDto {
+    fn from(value: ApiKeyDo) -> Self {
+        Self {
+            key_id: Some(Arc::new(value.key_id)),
+            name: Some(value.name),
+            username: Arc::new(value.username),
+            namespaces: Some(value.namespaces),
+            write: Some(value.write),
+            expire_time: Some(value.expire_time as i64 * 1000),
+            last_used_time: Some(value.last_used_time as i64 * 1000),
+            gmt_create: Some(value.gmt_create as i64 * 1000),
+            key: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ldap_sync_legacy_user() {
+        let role = Arc::new("1".to_owned());
+        // 旧版本LDAP登录创建的用户
+        let mut legacy = UserDo {
+            username: "u1".to_owned(),
+            enable: true,
+            source: Some(UserSourceType::Inner.to_str().to_owned()),
+            ..Default::default()
+        };
+        assert!(legacy.prepare_ldap_sync(true, false));
+        assert!(legacy.is_ldap());
+        assert!(legacy.apply_ldap_sync(Some(&role)));
+        assert!(!legacy.apply_ldap_sync(Some(&role)));
+
+        // 刷新角色时只替换内置角色,保留自定义角色
+        legacy.roles = vec!["0".to_owned(), "ops".to_owned()];
+        assert!(legacy.apply_ldap_sync(Some(&role)));
+        assert_eq!(legacy.roles, vec!["1".to_owned(), "ops".to_owned()]);
+        assert!(!legacy.apply_ldap_sync(Some(&role)));
+
+        // 已离开的旧用户:未开启OAuth2时迁移并禁用,开启时不处理
+        let mut departed = UserDo {
+            username: "u2".to_owned(),
+            enable: true,
+            ..Default::default()
+        };
+        assert!(!departed.clone().prepare_ldap_sync(false, false));
+        assert!(departed.prepare_ldap_sync(false, true));
+        assert!(departed.apply_ldap_sync(None));
+        assert!(!departed.enable);
+
+        // 设置了密码的内部用户不参与同步
+        let mut inner = UserDo {
+            username: "admin".to_owned(),
+            enable: true,
+            password_hash: Some("hash".to_owned()),
+            ..Default::default()
+        };
+        assert!(!inner.prepare_ldap_sync(true, true));
+        assert!(inner.is_inner());
+    }
+
+    #[test]
+    fn login_state_override_user() {
+        let mut user = UserDo {
+            username: "u1".to_owned(),
+            login_fail_count: Some(2),
+            lock_until_time: Some(100),
+            ..Default::default()
+        };
+        let state = UserLoginStateDo {
+            login_fail_count: 3,
+            lock_until_time: 0,
+            totp_last_step: 10,
+        };
+        let state = UserLoginStateDo::from_bytes(&state.to_bytes()).unwrap();
+        state.apply_to(&mut user);
+        assert_eq!(user.login_fail_count, Some(3));
+        assert_eq!(user.lock_until_time, Some(0));
+        assert_eq!(user.totp_last_step, Some(10));
+        assert_eq!(UserLoginStateDo::from_user(&user), state);
+    }
+}
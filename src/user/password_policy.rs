@@ -0,0 +1,148 @@
+use crate::common::AppSysConfig;
+use crate::user::model::UserDo;
+
+const SECONDS_OF_DAY: u32 = 24 * 60 * 60;
+
+///
+/// 内部用户密码策略与登录失败锁定策略
+#[derive(Debug, Clone, Default)]
+pub struct PasswordPolicy {
+    pub min_length: u32,
+    pub require_char_types: u32,
+    pub expire_days: u32,
+    pub lock_fail_count: u32,
+    pub lock_second: u32,
+}
+
+impl PasswordPolicy {
+    pub fn new(sys_config: &AppSysConfig) -> Self {
+        Self {
+            min_length: sys_config.password_min_length,
+            require_char_types: sys_config.password_require_char_types,
+            expire_days: sys_config.password_expire_days,
+            lock_fail_count: sys_config.login_lock_fail_count,
+            lock_second: sys_config.login_lock_second,
+        }
+    }
+
+    ///
+    /// 校验密码复杂度,不满足时返回具体原因
+    pub fn check_password(&self, password: &str) -> anyhow::Result<()> {
+        if (password.chars().count() as u32) < self.min_length {
+            return Err(anyhow::anyhow!(
+                "password length must be at least {}",
+                self.min_length
+            ));
+        }
+        let char_types = Self::count_char_types(password);
+        if char_types < self.require_char_types {
+            return Err(anyhow::anyhow!(
+                "password must contain at least {} of lowercase letters, uppercase letters, digits and special characters",
+                self.require_char_types
+            ));
+        }
+        Ok(())
+    }
+
+    fn count_char_types(password: &str) -> u32 {
+        let mut flags = 0u8;
+        for c in password.chars() {
+            if c.is_ascii_lowercase() {
+                flags |= 1;
+            } else if c.is_ascii_uppercase() {
+                flags |= 2;
+            } else if c.is_ascii_digit() {
+                flags |= 4;
+            } else {
+                flags |= 8;
+            }
+        }
+        flags.count_ones()
+    }
+
+    ///
+    /// 密码是否已过期;老数据没有密码修改时间时以创建时间计算
+    pub fn is_password_expired(&self, user: &UserDo, now: u32) -> bool {
+        if self.expire_days == 0 {
+            return false;
+        }
+        let modified_time = user.password_modified_time.unwrap_or(user.gmt_create);
+        modified_time + self.expire_days * SECONDS_OF_DAY <= now
+    }
+
+    pub fn is_locked(user: &UserDo, now: u32) -> bool {
+        user.lock_until_time.unwrap_or_default() > now
+    }
+
+    ///
+    /// 记录一次登录失败,达到次数后锁定账号
+    pub fn record_login_fail(&self, user: &mut UserDo, now: u32) {
+        if self.lock_fail_count == 0 {
+            return;
+        }
+        let fail_count = user.login_fail_count.unwrap_or_default() + 1;
+        if fail_count >= self.lock_fail_count {
+            user.login_fail_count = Some(0);
+            user.lock_until_time = Some(now + self.lock_second);
+        } else {
+            user.login_fail_count = Some(fail_count);
+        }
+    }
+
+    ///
+    /// 清除登录失败计数与锁定状态,有变更时返回true
+    pub fn clear_login_fail(user: &mut UserDo) -> bool {
+        if user.login_fail_count.unwrap_or_default() == 0
+            && user.lock_until_time.unwrap_or_default() == 0
+        {
+            return false;
+        }
+        user.login_fail_count = Some(0);
+        user.lock_until_time = Some(0);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_password() {
+        let policy = PasswordPolicy {
+            min_length: 8,
+            require_char_types: 3,
+            ..Default::default()
+        };
+        assert!(policy.check_password("Ab1").is_err());
+        assert!(policy.check_password("abcdefgh1").is_err());
+        assert!(policy.check_password("Abcdefgh1").is_ok());
+        assert!(policy.check_password("abcdefg_1").is_ok());
+        assert!(PasswordPolicy::default().check_password("").is_ok());
+    }
+
+    #[test]
+    fn login_fail_lock_and_expire() {
+        let policy = PasswordPolicy {
+            expire_days: 1,
+            lock_fail_count: 2,
+            lock_second: 60,
+            ..Default::default()
+        };
+        let mut user = UserDo {
+            gmt_create: 1000,
+            ..Default::default()
+        };
+        policy.record_login_fail(&mut user, 1000);
+        assert!(!PasswordPolicy::is_locked(&user, 1000));
+        policy.record_login_fail(&mut user, 1000);
+        assert!(PasswordPolicy::is_locked(&user, 1059));
+        assert!(!PasswordPolicy::is_locked(&user, 1060));
+        assert!(PasswordPolicy::clear_login_fail(&mut user));
+        assert!(!PasswordPolicy::clear_login_fail(&mut user));
+        assert!(!policy.is_password_expired(&user, 1000 + SECONDS_OF_DAY - 1));
+        assert!(policy.is_password_expired(&user, 1000 + SECONDS_OF_DAY));
+        user.password_modified_time = Some(2000);
+        assert!(!policy.is_password_expired(&user, 1000 + SECONDS_OF_DAY));
+    }
+}
@@ -3,7 +3,11 @@
 /// 权限资源分为两类：
 /// 1）web资源，由前端控制页面是否支持访问；
 /// 2）http请求路径，由后端拦截器控制否支持请求；
-use std::{collections::HashSet, hash::Hash, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+    sync::{Arc, RwLock},
+};
 
 use crate::common::constant::{EMPTY_STR, HTTP_METHOD_ALL, HTTP_METHOD_GET, HTTP_METHOD_POST};
 
@@ -70,6 +74,7 @@ impl ModuleResource {
     }
 }
 
+#[derive(Debug)]
 pub struct GroupResource {
     pub web_resources: HashSet<&'static str>,
     pub path_resources: HashSet<PathResource>,
@@ -105,6 +110,9 @@ impl GroupResource {
 
 type R = Resource;
 
+/// 控制台解密加密配置接口
+const CONFIG_DECRYPT_PATH: &str = "/rnacos/api/console/v2/config/decrypt";
+
 lazy_static::lazy_static! {
     pub(crate) static ref USER_ROLE_MANAGER: Arc<String> =  Arc::new("0".to_string());
     pub(crate) static ref USER_ROLE_DEVELOPER: Arc<String> =  Arc::new("1".to_string());
@@ -139,6 +147,7 @@ lazy_static::lazy_static! {
         R::Path("/rnacos/api/console/login/login",HTTP_METHOD_ALL),
         R::Path("/rnacos/api/console/login/captcha",HTTP_METHOD_ALL),
         R::Path("/rnacos/api/console/login/logout",HTTP_METHOD_ALL),
+        R::Path("/rnacos/api/console/login/totp",HTTP_METHOD_ALL),
         R::Path("/rnacos/api/console/namespaces",HTTP_METHOD_GET),
         R::Path("/rnacos/api/console/user/info",HTTP_METHOD_GET),
         R::Path("/rnacos/api/console/user/web_resources",HTTP_METHOD_GET),
@@ -149,9 +158,13 @@ lazy_static::lazy_static! {
         R::Path("/rnacos/api/console/v2/login/logout",HTTP_METHOD_ALL),
         R::Path("/rnacos/api/console/v2/login/config",HTTP_METHOD_ALL),
         R::Path("/rnacos/api/console/v2/login/oauth2/login",HTTP_METHOD_ALL),
+        R::Path("/rnacos/api/console/v2/login/totp",HTTP_METHOD_ALL),
         R::Path("/rnacos/api/console/v2/user/info",HTTP_METHOD_GET),
         R::Path("/rnacos/api/console/v2/user/web_resources",HTTP_METHOD_GET),
         R::Path("/rnacos/api/console/v2/user/reset_password",HTTP_METHOD_ALL),
+        R::Path("/rnacos/api/console/v2/user/totp/init",HTTP_METHOD_POST),
+        R::Path("/rnacos/api/console/v2/user/totp/enable",HTTP_METHOD_POST),
+        R::Path("/rnacos/api/console/v2/user/totp/disable",HTTP_METHOD_POST),
         R::Path("/rnacos/api/console/v2/namespaces/list",HTTP_METHOD_GET),
 
     ]);
@@ -207,6 +220,25 @@ lazy_static::lazy_static! {
         R::Path("/rnacos/api/console/v2/user/add",HTTP_METHOD_ALL),
         R::Path("/rnacos/api/console/v2/user/update",HTTP_METHOD_ALL),
         R::Path("/rnacos/api/console/v2/user/remove",HTTP_METHOD_ALL),
+        R::Path("/rnacos/api/console/v2/user/unlock",HTTP_METHOD_ALL),
+        R::Path("/rnacos/api/console/v2/user/totp/reset",HTTP_METHOD_POST),
+        R::Path("/rnacos/api/console/v2/user/apikey/list",HTTP_METHOD_GET),
+        R::Path("/rnacos/api/console/v2/user/apikey/add",HTTP_METHOD_POST),
+        R::Path("/rnacos/api/console/v2/user/apikey/revoke",HTTP_METHOD_POST),
+        R::Path("/rnacos/api/console/v2/role/list",HTTP_METHOD_GET),
+        R::Path("/rnacos/api/console/v2/role/modules",HTTP_METHOD_GET),
+        R::Path("/rnacos/api/console/v2/role/add",HTTP_METHOD_POST),
+        R::Path("/rnacos/api/console/v2/role/update",HTTP_METHOD_POST),
+        R::Path("/rnacos/api/console/v2/role/remove",HTTP_METHOD_POST),
+    ]);
+
+    static ref M_AUDIT_VISITOR: ModuleResource = ModuleResource::new(vec![
+        //WebResource
+        R::WebResource("/manage/audit"),
+        R::WebResource("/rnacos/manage/audit"),
+        //path
+        R::Path("/rnacos/manage/audit",HTTP_METHOD_GET),
+        R::Path("/rnacos/api/console/v2/audit/list",HTTP_METHOD_GET),
     ]);
 
     static ref M_CONFIG_VISITOR: ModuleResource = ModuleResource::new(vec![
@@ -228,6 +260,8 @@ lazy_static::lazy_static! {
         R::Path("/rnacos/api/console/v2/config/download",HTTP_METHOD_GET),
         R::Path("/rnacos/api/console/v2/config/info",HTTP_METHOD_GET),
         R::Path("/rnacos/api/console/v2/config/history",HTTP_METHOD_GET),
+        R::Path("/rnacos/api/console/v2/config/history/diff",HTTP_METHOD_GET),
+        R::Path("/rnacos/api/console/v2/config/gray/info",HTTP_METHOD_GET),
     ]);
 
     static ref M_CONFIG_MANAGE: ModuleResource = ModuleResource::new(vec![
@@ -252,10 +286,22 @@ lazy_static::lazy_static! {
         R::Path("/rnacos/api/console/v2/config/download",HTTP_METHOD_GET),
         R::Path("/rnacos/api/console/v2/config/info",HTTP_METHOD_GET),
         R::Path("/rnacos/api/console/v2/config/history",HTTP_METHOD_GET),
+        R::Path("/rnacos/api/console/v2/config/history/diff",HTTP_METHOD_GET),
+        R::Path("/rnacos/api/console/v2/config/history/rollback",HTTP_METHOD_ALL),
+        R::Path(CONFIG_DECRYPT_PATH,HTTP_METHOD_GET),
         R::Path("/rnacos/api/console/v2/config/import",HTTP_METHOD_ALL),
         R::Path("/rnacos/api/console/v2/config/add",HTTP_METHOD_ALL),
         R::Path("/rnacos/api/console/v2/config/update",HTTP_METHOD_ALL),
         R::Path("/rnacos/api/console/v2/config/remove",HTTP_METHOD_ALL),
+        R::Path("/rnacos/api/console/v2/config/gray/info",HTTP_METHOD_GET),
+        R::Path("/rnacos/api/console/v2/config/gray/publish",HTTP_METHOD_ALL),
+        R::Path("/rnacos/api/console/v2/config/gray/promote",HTTP_METHOD_ALL),
+        R::Path("/rnacos/api/console/v2/config/gray/abort",HTTP_METHOD_ALL),
+        R::Path("/rnacos/api/console/v2/config/webhook/list",HTTP_METHOD_GET),
+        R::Path("/rnacos/api/console/v2/config/webhook/add",HTTP_METHOD_ALL),
+        R::Path("/rnacos/api/console/v2/config/webhook/update",HTTP_METHOD_ALL),
+        R::Path("/rnacos/api/console/v2/config/webhook/remove",HTTP_METHOD_ALL),
+        R::Path("/rnacos/api/console/v2/config/webhook/logs",HTTP_METHOD_GET),
     ]);
 
     static ref M_NAMING_VISITOR: ModuleResource = ModuleResource::new(vec![
@@ -432,12 +478,65 @@ lazy_static::lazy_static! {
         &M_CONFIG_MANAGE,
         &M_NAMING_MANAGE,
         &M_USER_MANAGE,
+        &M_AUDIT_VISITOR,
         &M_METRICS_VISITOR,
         &M_TRASFER_DATE_MANAGE,
         &M_MCP_TOOL_SPEC_MANAGE,
         &M_MCP_SERVER_MANAGE,
     ]));
 
+    /// 可用于组合自定义角色的功能模块
+    static ref MODULE_RESOURCES: Vec<(&'static str, &'static ModuleResource)> = vec![
+        ("CLUSTER_VISITOR", &*M_CLUSTER_VISITOR),
+        ("NAMESPACE_VISITOR", &*M_NAMESPACE_VISITOR),
+        ("NAMESPACE_MANAGE", &*M_NAMESPACE_MANAGE),
+        ("USER_MANAGE", &*M_USER_MANAGE),
+        ("AUDIT_VISITOR", &*M_AUDIT_VISITOR),
+        ("CONFIG_VISITOR", &*M_CONFIG_VISITOR),
+        ("CONFIG_MANAGE", &*M_CONFIG_MANAGE),
+        ("NAMING_VISITOR", &*M_NAMING_VISITOR),
+        ("NAMING_MANAGE", &*M_NAMING_MANAGE),
+        ("METRICS_VISITOR", &*M_METRICS_VISITOR),
+        ("TRANSFER_DATA_MANAGE", &*M_TRASFER_DATE_MANAGE),
+        ("MCP_TOOL_SPEC_VISITOR", &*M_MCP_TOOL_SPEC_VISITOR),
+        ("MCP_TOOL_SPEC_MANAGE", &*M_MCP_TOOL_SPEC_MANAGE),
+        ("MCP_SERVER_VISITOR", &*M_MCP_SERVER_VISITOR),
+        ("MCP_SERVER_MANAGE", &*M_MCP_SERVER_MANAGE),
+    ];
+
+    /// 控制台维护的自定义角色,由UserManager从T_ROLE表加载
+    static ref CUSTOM_ROLES: RwLock<HashMap<String, Arc<CustomRole>>> = RwLock::new(HashMap::new());
+}
+
+/// 带写权限的功能模块,包含这些模块的自定义角色允许sdk写请求
+const WRITE_MODULES: [&str; 2] = ["CONFIG_MANAGE", "NAMING_MANAGE"];
+
+///
+/// 自定义角色
+#[derive(Debug)]
+pub struct CustomRole {
+    pub name: Arc<String>,
+    pub modules: Vec<String>,
+    pub resource: GroupResource,
+    pub can_write: bool,
+}
+
+impl CustomRole {
+    pub fn new(name: Arc<String>, modules: Vec<String>) -> Self {
+        let mut module_resources: Vec<&ModuleResource> = vec![&M_BASE];
+        for (module_name, module) in MODULE_RESOURCES.iter() {
+            if modules.iter().any(|e| e == module_name) {
+                module_resources.push(module);
+            }
+        }
+        let can_write = modules.iter().any(|e| WRITE_MODULES.contains(&e.as_str()));
+        Self {
+            name,
+            modules,
+            resource: GroupResource::new(module_resources),
+            can_write,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -446,6 +545,7 @@ pub enum UserRole {
     Developer,
     Manager,
     OldConsole,
+    Custom(Arc<CustomRole>),
     None,
 }
 const MANAGER_VALUE: &str = "0";
@@ -459,7 +559,10 @@ impl UserRole {
             MANAGER_VALUE => Self::Manager,
             DEVELOPER_VALUE => Self::Developer,
             VISITOR_VALUE => Self::Visitor,
-            _ => Self::None,
+            _ => match UserRoleHelper::get_custom_role(role_value) {
+                Some(role) => Self::Custom(role),
+                None => Self::None,
+            },
         }
     }
 
@@ -468,6 +571,7 @@ impl UserRole {
             Self::Manager => MANAGER_VALUE,
             Self::Developer => DEVELOPER_VALUE,
             Self::Visitor => VISITOR_VALUE,
+            Self::Custom(role) => role.name.as_str(),
             _ => NONE_VALUE,
         }
     }
@@ -479,6 +583,7 @@ impl UserRole {
             UserRole::Manager => vec![R_MANAGER.as_ref()],
             //旧控制台使用开发者权限
             UserRole::OldConsole => vec![R_DEVELOPER.as_ref()],
+            UserRole::Custom(role) => vec![&role.resource],
             UserRole::None => vec![],
         }
     }
@@ -501,6 +606,28 @@ impl UserRole {
         false
     }
 
+    ///
+    /// sdk请求(发布配置、注册实例等)的写权限,访客只能读
+    pub fn can_write(&self) -> bool {
+        match self {
+            Self::Manager | Self::Developer | Self::OldConsole => true,
+            Self::Custom(role) => role.can_write,
+            _ => false,
+        }
+    }
+
+    ///
+    /// 是否有解密加密配置的权限,与控制台解密接口的权限一致
+    pub fn can_decrypt_config_by_roles(role_values: &Vec<Arc<String>>) -> bool {
+        Self::match_url_by_roles(role_values, CONFIG_DECRYPT_PATH, HTTP_METHOD_GET)
+    }
+
+    pub fn can_write_by_roles(role_values: &[Arc<String>]) -> bool {
+        role_values
+            .iter()
+            .any(|item| Self::new(item.as_str()).can_write())
+    }
+
     pub fn get_web_resources(&self) -> Vec<&'static str> {
         //log::info!("get_web_resources {:?}", &self);
         let resources = self.get_resources();
@@ -556,6 +683,67 @@ impl UserRoleHelper {
         Arc::new(role_value.to_owned())
     }
 
+    pub fn is_builtin_role(role_value: &str) -> bool {
+        ALL_ROLES.iter().any(|e| e.as_str() == role_value)
+    }
+
+    pub fn get_custom_role(role_value: &str) -> Option<Arc<CustomRole>> {
+        if role_value.is_empty() {
+            return None;
+        }
+        CUSTOM_ROLES
+            .read()
+            .ok()
+            .and_then(|roles| roles.get(role_value).cloned())
+    }
+
+    ///
+    /// 用最新的角色列表替换自定义角色
+    pub fn reload_custom_roles(roles: Vec<(Arc<String>, Vec<String>)>) {
+        let mut map = HashMap::with_capacity(roles.len());
+        for (name, modules) in roles {
+            map.insert(
+                name.as_ref().to_owned(),
+                Arc::new(CustomRole::new(name, modules)),
+            );
+        }
+        if let Ok(mut custom_roles) = CUSTOM_ROLES.write() {
+            *custom_roles = map;
+        }
+    }
+
+    pub fn get_module_names() -> Vec<&'static str> {
+        MODULE_RESOURCES.iter().map(|(name, _)| *name).collect()
+    }
+
+    ///
+    /// 校验自定义角色名称与功能模块
+    pub fn check_custom_role(name: &str, modules: &[String]) -> anyhow::Result<()> {
+        if name.is_empty() || name.len() > 64 {
+            return Err(anyhow::anyhow!("role name length must be between 1 and 64"));
+        }
+        if !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            return Err(anyhow::anyhow!(
+                "role name only support letters, digits, '_' and '-'"
+            ));
+        }
+        if Self::is_builtin_role(name) {
+            return Err(anyhow::anyhow!("role name is a builtin role: {}", name));
+        }
+        if modules.is_empty() {
+            return Err(anyhow::anyhow!("role modules is empty"));
+        }
+        for module in modules {
+            if !MODULE_RESOURCES.iter().any(|(e, _)| e == module) {
+                return Err(anyhow::anyhow!("unknown role module: {}", module));
+            }
+        }
+        Ok(())
+    }
+
     pub fn get_role_by_name(role_name: &str, default: Arc<String>) -> Arc<String> {
         match role_name {
             "VISITOR" => USER_ROLE_VISITOR.clone(),
@@ -566,3 +754,54 @@ impl UserRoleHelper {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::constant::HTTP_METHOD_POST;
+
+    #[test]
+    fn custom_role() {
+        let name = Arc::new("naming_viewer".to_owned());
+        assert!(UserRoleHelper::check_custom_role(&name, &["NAMING_VISITOR".to_owned()]).is_ok());
+        assert!(UserRoleHelper::check_custom_role("1", &["NAMING_VISITOR".to_owned()]).is_err());
+        assert!(UserRoleHelper::check_custom_role("r1", &["UNKNOWN".to_owned()]).is_err());
+        assert!(matches!(UserRole::new(&name), UserRole::None));
+        UserRoleHelper::reload_custom_roles(vec![(
+            name.clone(),
+            vec!["NAMING_VISITOR".to_owned()],
+        )]);
+        let roles = vec![name.clone()];
+        assert!(UserRole::match_url_by_roles(
+            &roles,
+            "/rnacos/api/console/v2/service/list",
+            HTTP_METHOD_GET
+        ));
+        assert!(!UserRole::match_url_by_roles(
+            &roles,
+            "/rnacos/api/console/v2/config/list",
+            HTTP_METHOD_GET
+        ));
+        assert!(!UserRole::match_url_by_roles(
+            &roles,
+            "/rnacos/api/console/v2/service/add",
+            HTTP_METHOD_POST
+        ));
+        assert!(!UserRole::can_write_by_roles(&roles));
+        let web_resources = UserRole::get_web_resources_by_roles(vec![name.as_str()]);
+        assert!(web_resources.contains(&"/manage/service"));
+        assert!(!web_resources.contains(&"/manage/configs"));
+        UserRoleHelper::reload_custom_roles(vec![]);
+        assert!(matches!(UserRole::new(&name), UserRole::None));
+    }
+
+    #[test]
+    fn can_decrypt_config() {
+        assert!(UserRole::can_decrypt_config_by_roles(&vec![
+            USER_ROLE_MANAGER.clone()
+        ]));
+        assert!(!UserRole::can_decrypt_config_by_roles(&vec![
+            USER_ROLE_VISITOR.clone()
+        ]));
+    }
+}
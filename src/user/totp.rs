@@ -0,0 +1,312 @@
+use crate::common::crypto_utils;
+use crate::common::AppSysConfig;
+use crate::user::api_key::ApiKeyUtils;
+use crypto::hmac::Hmac;
+use crypto::mac::Mac;
+use crypto::sha1::Sha1;
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::hkdf;
+use ring::rand::{SecureRandom, SystemRandom};
+use std::sync::Arc;
+
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+/// 与主流认证器默认参数一致: 30秒步长,6位数字
+const TIME_STEP: u64 = 30;
+const CODE_DIGITS: u32 = 6;
+/// 允许前后各一个步长的时钟偏差
+const ALLOW_SKEW_STEP: u64 = 1;
+const SECRET_LEN: usize = 20;
+/// HKDF派生TOTP加密密钥使用的salt与info
+const KEY_SALT: &[u8] = b"r-nacos-totp-cipher";
+const KEY_INFO: &[u8] = b"totp-secret-aes-256-gcm";
+pub const RECOVERY_CODE_COUNT: usize = 10;
+pub const TOTP_ISSUER: &str = "r-nacos";
+
+///
+/// 基于RFC 6238的TOTP工具
+pub struct TotpUtils;
+
+impl TotpUtils {
+    ///
+    /// 生成base32编码的随机密钥
+    pub fn generate_secret() -> String {
+        let bytes: Vec<u8> = (0..SECRET_LEN).map(|_| rand::random::<u8>()).collect();
+        Self::base32_encode(&bytes)
+    }
+
+    pub fn build_otpauth_url(username: &str, secret: &str) -> String {
+        let label = format!("{}:{}", TOTP_ISSUER, username);
+        format!(
+            "otpauth://totp/{}?secret={}&issuer={}&digits={}&period={}",
+            Self::percent_encode(&label),
+            secret,
+            TOTP_ISSUER,
+            CODE_DIGITS,
+            TIME_STEP
+        )
+    }
+
+    fn percent_encode(v: &str) -> String {
+        let mut result = String::with_capacity(v.len());
+        for b in v.bytes() {
+            if b.is_ascii_alphanumeric() || b"-._~:".contains(&b) {
+                result.push(b as char);
+            } else {
+                result.push_str(&format!("%{:02X}", b));
+            }
+        }
+        result
+    }
+
+    ///
+    /// RFC 4226 HOTP
+    pub fn hotp(key: &[u8], counter: u64) -> u32 {
+        let mut mac = Hmac::new(Sha1::new(), key);
+        mac.input(&counter.to_be_bytes());
+        let result = mac.result();
+        let hash = result.code();
+        let offset = (hash[hash.len() - 1] & 0x0f) as usize;
+        let bin = ((hash[offset] as u32 & 0x7f) << 24)
+            | ((hash[offset + 1] as u32) << 16)
+            | ((hash[offset + 2] as u32) << 8)
+            | (hash[offset + 3] as u32);
+        bin % 10u32.pow(CODE_DIGITS)
+    }
+
+    ///
+    /// 校验动态码,成功时返回匹配的时间步;last_step用于防止同一个动态码重复使用
+    pub fn verify(secret: &str, code: &str, now_second: u64, last_step: u64) -> Option<u64> {
+        let code = code.trim();
+        if code.len() != CODE_DIGITS as usize || !code.bytes().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        let code: u32 = code.parse().ok()?;
+        let key = Self::base32_decode(secret)?;
+        let current_step = now_second / TIME_STEP;
+        let start = current_step.saturating_sub(ALLOW_SKEW_STEP);
+        (start..=current_step + ALLOW_SKEW_STEP)
+            .find(|step| *step > last_step && Self::hotp(&key, *step) == code)
+    }
+
+    ///
+    /// 生成一组恢复码,返回(恢复码,恢复码hash)
+    pub fn generate_recovery_codes() -> (Vec<String>, Vec<String>) {
+        let mut codes = Vec::with_capacity(RECOVERY_CODE_COUNT);
+        let mut hashes = Vec::with_capacity(RECOVERY_CODE_COUNT);
+        for _ in 0..RECOVERY_CODE_COUNT {
+            let v: String = rand::thread_rng()
+                .sample_iter(&Alphanumeric)
+                .take(10)
+                .map(|c| char::from(c).to_ascii_lowercase())
+                .collect();
+            let code = format!("{}-{}", &v[..5], &v[5..]);
+            hashes.push(Self::hash_recovery_code(&code));
+            codes.push(code);
+        }
+        (codes, hashes)
+    }
+
+    pub fn hash_recovery_code(code: &str) -> String {
+        let code: String = code
+            .trim()
+            .chars()
+            .filter(|c| *c != '-')
+            .map(|c| c.to_ascii_lowercase())
+            .collect();
+        ApiKeyUtils::hash(&code)
+    }
+
+    ///
+    /// 使用恢复码,匹配成功时从列表中移除并返回true
+    pub fn use_recovery_code(code: &str, hashes: &mut Vec<String>) -> bool {
+        if code.trim().is_empty() {
+            return false;
+        }
+        let code_hash = Self::hash_recovery_code(code);
+        if let Some(index) = hashes
+            .iter()
+            .position(|e| ApiKeyUtils::verify_hash(&code_hash, e))
+        {
+            hashes.remove(index);
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn base32_encode(data: &[u8]) -> String {
+        let mut result = String::with_capacity((data.len() * 8).div_ceil(5));
+        let mut buffer = 0u32;
+        let mut bits = 0;
+        for b in data {
+            buffer = (buffer << 8) | *b as u32;
+            bits += 8;
+            while bits >= 5 {
+                bits -= 5;
+                result.push(BASE32_ALPHABET[((buffer >> bits) & 0x1f) as usize] as char);
+            }
+        }
+        if bits > 0 {
+            result.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+        }
+        result
+    }
+
+    pub fn base32_decode(data: &str) -> Option<Vec<u8>> {
+        let mut result = Vec::with_capacity(data.len() * 5 / 8);
+        let mut buffer = 0u32;
+        let mut bits = 0;
+        for c in data.bytes() {
+            let c = c.to_ascii_uppercase();
+            if c == b'=' || c == b' ' {
+                continue;
+            }
+            let v = BASE32_ALPHABET.iter().position(|e| *e == c)? as u32;
+            buffer = (buffer << 5) | v;
+            bits += 5;
+            if bits >= 8 {
+                bits -= 8;
+                result.push(((buffer >> bits) & 0xff) as u8);
+            }
+        }
+        Some(result)
+    }
+}
+
+///
+/// TOTP密钥加密存储;集群各节点需要使用相同的密钥材料。
+/// 密钥由HKDF-SHA256从密钥材料派生出256位,使用AES-256-GCM加密
+#[derive(Clone)]
+pub struct TotpCipher {
+    key: Option<Arc<LessSafeKey>>,
+}
+
+impl TotpCipher {
+    ///
+    /// 优先使用RNACOS_TOTP_SECRET_KEY,未设置时使用集群token派生密钥;
+    /// 两者都未设置时不支持开启两步验证
+    pub fn new(sys_config: &AppSysConfig) -> Self {
+        if !sys_config.totp_secret_key.is_empty() {
+            Self::new_by_material(sys_config.totp_secret_key.as_str())
+        } else if !sys_config.cluster_token.is_empty() {
+            Self::new_by_material(sys_config.cluster_token.as_str())
+        } else {
+            Self { key: None }
+        }
+    }
+
+    pub fn new_by_material(material: &str) -> Self {
+        let prk = hkdf::Salt::new(hkdf::HKDF_SHA256, KEY_SALT).extract(material.as_bytes());
+        let key = prk
+            .expand(&[KEY_INFO], &AES_256_GCM)
+            .map(|okm| Arc::new(LessSafeKey::new(UnboundKey::from(okm))))
+            .ok();
+        Self { key }
+    }
+
+    pub fn is_available(&self) -> bool {
+        self.key.is_some()
+    }
+
+    fn get_key(&self) -> anyhow::Result<&LessSafeKey> {
+        self.key.as_deref().ok_or_else(|| {
+            anyhow::anyhow!(
+                "two-factor authentication requires RNACOS_TOTP_SECRET_KEY or RNACOS_CLUSTER_TOKEN"
+            )
+        })
+    }
+
+    /// 输出格式: base64(nonce + 密文 + tag)
+    pub fn encrypt(&self, secret: &str) -> anyhow::Result<String> {
+        let key = self.get_key()?;
+        let mut nonce = [0u8; NONCE_LEN];
+        SystemRandom::new()
+            .fill(&mut nonce)
+            .map_err(|_| anyhow::anyhow!("generate nonce failed"))?;
+        let mut in_out = secret.as_bytes().to_vec();
+        key.seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::empty(),
+            &mut in_out,
+        )
+        .map_err(|_| anyhow::anyhow!("encrypt totp secret failed"))?;
+        let mut sealed = nonce.to_vec();
+        sealed.extend(in_out);
+        Ok(crypto_utils::encode_base64(&sealed))
+    }
+
+    pub fn decrypt(&self, sealed: &str) -> anyhow::Result<String> {
+        let key = self.get_key()?;
+        let mut sealed = crypto_utils::decode_base64(sealed)?;
+        if sealed.len() <= NONCE_LEN {
+            return Err(anyhow::anyhow!("invalid totp secret"));
+        }
+        let mut in_out = sealed.split_off(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(&sealed)
+            .map_err(|_| anyhow::anyhow!("invalid totp secret"))?;
+        let plain = key
+            .open_in_place(nonce, Aad::empty(), &mut in_out)
+            .map_err(|_| anyhow::anyhow!("decrypt totp secret failed"))?;
+        Ok(String::from_utf8(plain.to_vec())?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rfc6238_code() {
+        let key = b"12345678901234567890";
+        assert_eq!(TotpUtils::hotp(key, 59 / TIME_STEP), 287082);
+        assert_eq!(TotpUtils::hotp(key, 1111111109 / TIME_STEP), 81804);
+        let secret = TotpUtils::base32_encode(key);
+        assert_eq!(secret, "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ");
+        assert_eq!(TotpUtils::base32_decode(&secret).unwrap(), key.to_vec());
+        let step = TotpUtils::verify(&secret, "081804", 1111111109, 0).unwrap();
+        // 同一个动态码不能重复使用
+        assert!(TotpUtils::verify(&secret, "081804", 1111111109, step).is_none());
+        assert!(TotpUtils::verify(&secret, "081805", 1111111109, 0).is_none());
+        assert_eq!(
+            TotpUtils::build_otpauth_url("a b", &secret),
+            format!(
+                "otpauth://totp/r-nacos:a%20b?secret={}&issuer=r-nacos&digits=6&period=30",
+                &secret
+            )
+        );
+    }
+
+    #[test]
+    fn recovery_code_and_cipher() {
+        let (codes, mut hashes) = TotpUtils::generate_recovery_codes();
+        assert_eq!(codes.len(), RECOVERY_CODE_COUNT);
+        assert!(TotpUtils::use_recovery_code(
+            &codes[0].to_uppercase(),
+            &mut hashes
+        ));
+        assert!(!TotpUtils::use_recovery_code(&codes[0], &mut hashes));
+        assert_eq!(hashes.len(), RECOVERY_CODE_COUNT - 1);
+
+        let cipher = TotpCipher::new_by_material("test");
+        let secret = TotpUtils::generate_secret();
+        let sealed = cipher.encrypt(&secret).unwrap();
+        assert_ne!(sealed, secret);
+        assert_eq!(cipher.decrypt(&sealed).unwrap(), secret);
+        // 不同密钥材料无法解密,密文被篡改时校验失败
+        assert!(TotpCipher::new_by_material("other")
+            .decrypt(&sealed)
+            .is_err());
+        let mut tampered = crypto_utils::decode_base64(&sealed).unwrap();
+        let last = tampered.len() - 1;
+        tampered[last] ^= 1;
+        assert!(cipher
+            .decrypt(&crypto_utils::encode_base64(&tampered))
+            .is_err());
+        // 未配置密钥材料时拒绝开启
+        let empty = TotpCipher::new(&AppSysConfig::default());
+        assert!(!empty.is_available());
+        assert!(empty.encrypt(&secret).is_err());
+    }
+}